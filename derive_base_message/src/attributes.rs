@@ -0,0 +1,215 @@
+use syn::{Attribute, Error, Expr, Ident, LitStr};
+
+/// `MessageType` variants a derived struct can map to; `Unknown` carries a
+/// name and cannot be targeted.
+pub const MESSAGE_TYPE_VARIANTS: [&str; 5] = ["Ai", "Chat", "Human", "System", "Tool"];
+
+#[derive(Debug, Default, PartialEq)]
+pub struct StructAttributes {
+    pub gen_tests: bool,
+    pub message_type: Option<Ident>,
+    pub tag: Option<String>,
+    pub rename: Option<String>,
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub struct FieldAttributes {
+    pub skip_accessors: bool,
+    /// `default = "expr"`: left out of `new()` and initialized with `expr`.
+    pub default: Option<Expr>,
+}
+
+pub fn parse_field_attributes(attrs: &[Attribute]) -> Result<FieldAttributes, Error> {
+    let mut parsed = FieldAttributes::default();
+    for attr in attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("base_message"))
+    {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("skip_accessors") {
+                parsed.skip_accessors = true;
+                Ok(())
+            } else if meta.path.is_ident("default") {
+                let value: LitStr = meta.value()?.parse()?;
+                parsed.default = Some(value.parse()?);
+                Ok(())
+            } else {
+                Err(meta.error("unsupported base_message field attribute"))
+            }
+        })?;
+    }
+    Ok(parsed)
+}
+
+pub fn parse_struct_attributes(attrs: &[Attribute]) -> Result<StructAttributes, Error> {
+    let mut parsed = StructAttributes::default();
+    let mut rename_span = None;
+    for attr in attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("base_message"))
+    {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("gen_tests") {
+                parsed.gen_tests = true;
+                Ok(())
+            } else if meta.path.is_ident("message_type") {
+                let value: LitStr = meta.value()?.parse()?;
+                if !MESSAGE_TYPE_VARIANTS.contains(&value.value().as_str()) {
+                    return Err(Error::new(
+                        value.span(),
+                        format!(
+                            "unknown MessageType variant `{}`, expected one of: {}",
+                            value.value(),
+                            MESSAGE_TYPE_VARIANTS.join(", ")
+                        ),
+                    ));
+                }
+                parsed.message_type = Some(Ident::new(&value.value(), value.span()));
+                Ok(())
+            } else if meta.path.is_ident("tag") {
+                parsed.tag = Some(meta.value()?.parse::<LitStr>()?.value());
+                Ok(())
+            } else if meta.path.is_ident("rename") {
+                let value: LitStr = meta.value()?.parse()?;
+                rename_span = Some(value.span());
+                parsed.rename = Some(value.value());
+                Ok(())
+            } else {
+                Err(meta.error("unsupported base_message attribute"))
+            }
+        })?;
+    }
+    if let (Some(span), None) = (rename_span, &parsed.tag) {
+        return Err(Error::new(span, "`rename` requires `tag`"));
+    }
+    Ok(parsed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use syn::{parse_quote, DeriveInput};
+
+    #[test]
+    fn test_parse_no_attributes() {
+        let input: DeriveInput = parse_quote! {
+            struct ChatMessage {
+                base: BaseMessageFields,
+            }
+        };
+
+        let parsed = parse_struct_attributes(&input.attrs).unwrap();
+        assert_eq!(parsed, StructAttributes::default());
+    }
+
+    #[test]
+    fn test_parse_gen_tests() {
+        let input: DeriveInput = parse_quote! {
+            #[derive(Debug)]
+            #[base_message(gen_tests)]
+            struct ChatMessage {
+                base: BaseMessageFields,
+            }
+        };
+
+        let parsed = parse_struct_attributes(&input.attrs).unwrap();
+        assert!(parsed.gen_tests);
+    }
+
+    #[test]
+    fn test_parse_message_type() {
+        let input: DeriveInput = parse_quote! {
+            #[base_message(gen_tests, message_type = "Ai")]
+            struct AssistantReply {
+                base: BaseMessageFields,
+            }
+        };
+
+        let parsed = parse_struct_attributes(&input.attrs).unwrap();
+        assert!(parsed.gen_tests);
+        assert_eq!(parsed.message_type.unwrap(), "Ai");
+
+        let input: DeriveInput = parse_quote! {
+            #[base_message(message_type = "Assistant")]
+            struct AssistantReply {
+                base: BaseMessageFields,
+            }
+        };
+        let error = parse_struct_attributes(&input.attrs).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "unknown MessageType variant `Assistant`, expected one of: Ai, Chat, Human, System, Tool"
+        );
+    }
+
+    #[test]
+    fn test_parse_tag_and_rename() {
+        let input: DeriveInput = parse_quote! {
+            #[base_message(tag = "role", rename = "user")]
+            struct UserTurn {
+                base: BaseMessageFields,
+            }
+        };
+
+        let parsed = parse_struct_attributes(&input.attrs).unwrap();
+        assert_eq!(parsed.tag.as_deref(), Some("role"));
+        assert_eq!(parsed.rename.as_deref(), Some("user"));
+
+        let input: DeriveInput = parse_quote! {
+            #[base_message(rename = "user")]
+            struct UserTurn {
+                base: BaseMessageFields,
+            }
+        };
+        let error = parse_struct_attributes(&input.attrs).unwrap_err();
+        assert_eq!(error.to_string(), "`rename` requires `tag`");
+    }
+
+    #[test]
+    fn test_parse_field_attributes() {
+        let input: DeriveInput = parse_quote! {
+            struct ToolMessage {
+                #[base_message(skip_accessors)]
+                tool_call_id: String,
+                #[serde(default)]
+                attempt: u32,
+                #[base_message(readonly)]
+                status: ToolStatus,
+                #[base_message(default = "Vec::new()")]
+                tags: Vec<String>,
+                #[base_message(default = "1 +")]
+                retries: u32,
+            }
+        };
+        let fields = crate::fields::extract_fields(&input).unwrap();
+        let attrs: Vec<_> = fields.named.iter().map(|field| &field.attrs).collect();
+
+        assert!(parse_field_attributes(attrs[0]).unwrap().skip_accessors);
+        assert_eq!(
+            parse_field_attributes(attrs[1]).unwrap(),
+            FieldAttributes::default()
+        );
+        assert_eq!(
+            parse_field_attributes(attrs[2]).unwrap_err().to_string(),
+            "unsupported base_message field attribute"
+        );
+        assert_eq!(
+            parse_field_attributes(attrs[3]).unwrap().default,
+            Some(parse_quote!(Vec::new()))
+        );
+        assert!(parse_field_attributes(attrs[4]).is_err());
+    }
+
+    #[test]
+    fn test_parse_unknown_attribute() {
+        let input: DeriveInput = parse_quote! {
+            #[base_message(bogus)]
+            struct ChatMessage {
+                base: BaseMessageFields,
+            }
+        };
+
+        let error = parse_struct_attributes(&input.attrs).unwrap_err();
+        assert_eq!(error.to_string(), "unsupported base_message attribute");
+    }
+}
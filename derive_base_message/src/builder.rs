@@ -0,0 +1,113 @@
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use syn::{DeriveInput, Error};
+
+use crate::fields::{extract_fields, field_args, field_initializers};
+
+/// Emits `<Struct>Builder` and `<Struct>::builder`, which takes the same
+/// extra fields as `new`. The builder wraps a message made by `new("", ..)`
+/// and derives `Debug`, `Clone` and `PartialEq` like the one
+/// `define_message!` emits, so the message type needs them too.
+pub fn implement_builder(input: &DeriveInput) -> Result<TokenStream2, Error> {
+    let struct_name = &input.ident;
+    let vis = &input.vis;
+    let generics = &input.generics;
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let builder_name = format_ident!("{}Builder", struct_name);
+    let named_fields = extract_fields(input)?;
+    let field_args = field_args(named_fields, &["base"]);
+    let field_initializers = field_initializers(named_fields, &["base"]);
+
+    Ok(quote! {
+        #[derive(Debug, Clone, PartialEq)]
+        #vis struct #builder_name #generics #where_clause {
+            message: #struct_name #ty_generics,
+        }
+
+        impl #impl_generics #struct_name #ty_generics #where_clause {
+            pub fn builder(#(#field_args),*) -> #builder_name #ty_generics {
+                #builder_name {
+                    message: Self::new("" #(, #field_initializers)*),
+                }
+            }
+        }
+
+        impl #impl_generics #builder_name #ty_generics #where_clause {
+            pub fn content(mut self, content: impl Into<MessageContent>) -> Self {
+                self.message.base.content = content.into();
+                self
+            }
+
+            pub fn name(mut self, name: impl Into<String>) -> Self {
+                self.message.base.name = Some(name.into());
+                self
+            }
+
+            pub fn id(mut self, id: impl Into<String>) -> Self {
+                self.message.base.id = Some(id.into());
+                self
+            }
+
+            pub fn example(mut self, example: bool) -> Self {
+                self.message.base.example = example;
+                self
+            }
+
+            pub fn kwarg<V>(mut self, key: impl Into<String>, value: V) -> Self
+            where
+                Metadata: Extend<(String, V)>,
+            {
+                self.message.base.additional_kwargs.extend([(key.into(), value)]);
+                self
+            }
+
+            pub fn build(self) -> #struct_name #ty_generics {
+                self.message
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use syn::parse_quote;
+
+    #[test]
+    fn test_builder_takes_extra_fields() {
+        let input: DeriveInput = parse_quote! {
+            pub struct ChatMessage {
+                role: String,
+                base: BaseMessageFields,
+            }
+        };
+
+        let generated = implement_builder(&input).unwrap().to_string();
+
+        assert!(generated.starts_with(
+            &quote! {
+                #[derive(Debug, Clone, PartialEq)]
+                pub struct ChatMessageBuilder {
+                    message: ChatMessage,
+                }
+
+                impl ChatMessage {
+                    pub fn builder(role: String) -> ChatMessageBuilder {
+                        ChatMessageBuilder {
+                            message: Self::new("", role),
+                        }
+                    }
+                }
+            }
+            .to_string()
+        ));
+        assert!(generated.contains(
+            &quote! {
+                pub fn build(self) -> ChatMessage {
+                    self.message
+                }
+            }
+            .to_string()
+        ));
+    }
+}
@@ -1,5 +1,9 @@
-use crate::fields::{extract_fields, field_args, field_initializers};
-use crate::methods::{implement_base_getters, implement_base_setters};
+use crate::attributes::parse_struct_attributes;
+use crate::builder::implement_builder;
+use crate::fields::{extract_fields, field_args, field_initializers, field_values};
+use crate::methods::{implement_base_getters, implement_base_setters, implement_field_accessors};
+use crate::serde_impl::implement_tagged_serde;
+use crate::tests_gen::implement_generated_tests;
 use proc_macro2::TokenStream as TokenStream2;
 use quote::{format_ident, quote};
 use syn::{DeriveInput, Error, Ident};
@@ -10,16 +14,19 @@ fn has_role_field(input: &DeriveInput) -> bool {
             named_fields
                 .named
                 .iter()
-                .any(|field| field.ident.as_ref().map_or(false, |ident| ident == "role"))
+                .any(|field| field.ident.as_ref().is_some_and(|ident| ident == "role"))
         })
         .unwrap_or(false)
 }
 
-fn implement_struct_new(input: &DeriveInput) -> Result<TokenStream2, Error> {
+fn implement_struct_new(
+    input: &DeriveInput,
+    message_type_name: &Ident,
+) -> Result<TokenStream2, Error> {
     let named_fields = extract_fields(input)?;
     let field_args = field_args(named_fields, &["base"]);
     let field_initializers = field_initializers(named_fields, &["base"]);
-    let message_type_name = extract_message_type_name(input);
+    let field_values = field_values(named_fields, &["base"]);
 
     let (field_args_tokens, field_initializers_tokens) = if field_args.is_empty() {
         (quote! {}, quote! {})
@@ -29,24 +36,30 @@ fn implement_struct_new(input: &DeriveInput) -> Result<TokenStream2, Error> {
             quote! { , #(#field_initializers),* },
         )
     };
+    let field_values_tokens = if field_values.is_empty() {
+        quote! {}
+    } else {
+        quote! { , #(#field_values),* }
+    };
 
     Ok(quote! {
         pub fn new(content: &str #field_args_tokens) -> Self {
             Self::new_with_example(content, false #field_initializers_tokens)
         }
 
+        /// Like `new`, with an id from `generate_message_id()`.
+        pub fn new_with_id(content: &str #field_args_tokens) -> Self {
+            let mut message = Self::new(content #field_initializers_tokens);
+            message.base.id = Some(generate_message_id());
+            message
+        }
+
         pub fn new_with_example(content: &str, example: bool #field_args_tokens) -> Self {
+            let mut base = BaseMessageFields::new(content, MessageType::#message_type_name);
+            base.example = example;
             Self {
-                base: BaseMessageFields {
-                    content: content.to_string(),
-                    example,
-                    message_type: MessageType::#message_type_name,
-                    additional_kwargs: std::collections::HashMap::new(),
-                    response_metadata: std::collections::HashMap::new(),
-                    id: None,
-                    name: None,
-                }
-                #field_initializers_tokens
+                base
+                #field_values_tokens
             }
         }
     })
@@ -63,6 +76,7 @@ fn extract_message_type_name(input: &DeriveInput) -> Ident {
 
 fn implement_base_message(input: &DeriveInput) -> TokenStream2 {
     let struct_name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
     let getter_impl = implement_base_getters();
     let has_role = has_role_field(input);
     let role_impl = if has_role {
@@ -80,7 +94,7 @@ fn implement_base_message(input: &DeriveInput) -> TokenStream2 {
     };
 
     quote! {
-        impl BaseMessage for #struct_name {
+        impl #impl_generics BaseMessage for #struct_name #ty_generics #where_clause {
             #getter_impl
             #role_impl
         }
@@ -94,20 +108,67 @@ pub fn derive_macro(input: TokenStream2) -> TokenStream2 {
     };
 
     let struct_name = &ast.ident;
+    let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
+
+    let attributes = match parse_struct_attributes(&ast.attrs) {
+        Ok(attributes) => attributes,
+        Err(err) => return err.to_compile_error(),
+    };
 
-    let struct_new_impl = match implement_struct_new(&ast) {
+    let message_type_name = attributes
+        .message_type
+        .clone()
+        .unwrap_or_else(|| extract_message_type_name(&ast));
+
+    let struct_new_impl = match implement_struct_new(&ast, &message_type_name) {
         Ok(impl_code) => impl_code,
         Err(err) => return err.to_compile_error(),
     };
 
+    let generated_tests = if attributes.gen_tests {
+        match implement_generated_tests(&ast, &message_type_name) {
+            Ok(tests) => tests,
+            Err(err) => return err.to_compile_error(),
+        }
+    } else {
+        quote! {}
+    };
+
+    let tagged_serde = match &attributes.tag {
+        Some(tag) => match implement_tagged_serde(
+            &ast,
+            tag,
+            attributes.rename.as_deref(),
+            &message_type_name,
+        ) {
+            Ok(serde_impl) => serde_impl,
+            Err(err) => return err.to_compile_error(),
+        },
+        None => quote! {},
+    };
+
+    let field_accessors = match extract_fields(&ast).and_then(implement_field_accessors) {
+        Ok(accessors) => accessors,
+        Err(err) => return err.to_compile_error(),
+    };
+
+    let builder = match implement_builder(&ast) {
+        Ok(builder) => builder,
+        Err(err) => return err.to_compile_error(),
+    };
+
     let base_setters = implement_base_setters();
     let base_message_impl = implement_base_message(&ast);
     quote! {
-        impl #struct_name {
+        impl #impl_generics #struct_name #ty_generics #where_clause {
             #struct_new_impl
             #base_setters
+            #field_accessors
         }
         #base_message_impl
+        #builder
+        #tagged_serde
+        #generated_tests
     }
 }
 
@@ -120,7 +181,7 @@ mod tests {
     fn base_message_impl_common() -> TokenStream2 {
         quote! {
             fn content(&self) -> &str {
-                &self.base.content
+                self.base.content.as_str()
             }
 
             fn message_type(&self) -> &MessageType {
@@ -131,11 +192,11 @@ mod tests {
                 self.base.example
             }
 
-            fn additional_kwargs(&self) -> &std::collections::HashMap<String, String> {
+            fn additional_kwargs(&self) -> &Metadata {
                 &self.base.additional_kwargs
             }
 
-            fn response_metadata(&self) -> &std::collections::HashMap<String, String> {
+            fn response_metadata(&self) -> &Metadata {
                 &self.base.response_metadata
             }
 
@@ -146,13 +207,27 @@ mod tests {
             fn name(&self) -> Option<&str> {
                 self.base.name.as_deref()
             }
+
+
+            fn content_blocks(&self) -> std::borrow::Cow<'_, [ContentBlock]> {
+                self.base.content.blocks()
+            }
+
+            fn text(&self) -> std::borrow::Cow<'_, str> {
+                self.base.content.text()
+            }
         }
     }
 
     fn base_message_setters() -> TokenStream2 {
         quote! {
             pub fn set_content(&mut self, new_content: &str) {
-                self.base.content = new_content.to_string();
+                self.base.content = new_content.into();
+            }
+
+            pub fn with_content(mut self, content: impl Into<MessageContent>) -> Self {
+                self.base.content = content.into();
+                self
             }
 
             pub fn set_example(&mut self, example: bool) {
@@ -166,6 +241,32 @@ mod tests {
             pub fn set_name(&mut self, name: Option<String>) {
                 self.base.name = name;
             }
+
+            pub fn provenance(&self) -> Option<&Provenance> {
+                self.base.provenance.as_ref()
+            }
+
+            pub fn set_provenance(&mut self, provenance: Option<Provenance>) {
+                self.base.provenance = provenance;
+            }
+
+            pub fn with_provenance(mut self, provenance: Provenance) -> Self {
+                self.base.provenance = Some(provenance);
+                self
+            }
+
+            pub fn voice(&self) -> Option<&VoiceMetadata> {
+                self.base.voice.as_ref()
+            }
+
+            pub fn set_voice(&mut self, voice: Option<VoiceMetadata>) {
+                self.base.voice = voice;
+            }
+
+            pub fn with_voice(mut self, voice: VoiceMetadata) -> Self {
+                self.base.voice = Some(voice);
+                self
+            }
         }
     }
 
@@ -182,6 +283,7 @@ mod tests {
 
         let base_message_impl_common = base_message_impl_common();
         let base_message_setters = base_message_setters();
+        let builder = implement_builder(&input).unwrap();
 
         let expected = quote! {
             impl HumanMessage {
@@ -189,22 +291,27 @@ mod tests {
                     Self::new_with_example(content, false, role)
                 }
 
+                /// Like `new`, with an id from `generate_message_id()`.
+                pub fn new_with_id(content: &str, role: String) -> Self {
+                    let mut message = Self::new(content, role);
+                    message.base.id = Some(generate_message_id());
+                    message
+                }
+
                 pub fn new_with_example(content: &str, example: bool, role: String) -> Self {
+                    let mut base = BaseMessageFields::new(content, MessageType::Human);
+                    base.example = example;
                     Self {
-                        base: BaseMessageFields {
-                            content: content.to_string(),
-                            example,
-                            message_type: MessageType::Human,
-                            additional_kwargs: std::collections::HashMap::new(),
-                            response_metadata: std::collections::HashMap::new(),
-                            id: None,
-                            name: None,
-                        },
+                        base,
                         role
                     }
                 }
 
                 #base_message_setters
+
+                pub fn set_role(&mut self, role: String) {
+                    self.role = role;
+                }
             }
 
             impl BaseMessage for HumanMessage {
@@ -214,6 +321,8 @@ mod tests {
                     &self.role
                 }
             }
+
+            #builder
         };
 
         assert_eq!(generated.to_string(), expected.to_string());
@@ -231,6 +340,7 @@ mod tests {
 
         let base_message_impl_common = base_message_impl_common();
         let base_message_setters = base_message_setters();
+        let builder = implement_builder(&input).unwrap();
 
         let expected = quote! {
             impl SystemMessage {
@@ -238,17 +348,18 @@ mod tests {
                     Self::new_with_example(content, false)
                 }
 
+                /// Like `new`, with an id from `generate_message_id()`.
+                pub fn new_with_id(content: &str) -> Self {
+                    let mut message = Self::new(content);
+                    message.base.id = Some(generate_message_id());
+                    message
+                }
+
                 pub fn new_with_example(content: &str, example: bool) -> Self {
+                    let mut base = BaseMessageFields::new(content, MessageType::System);
+                    base.example = example;
                     Self {
-                        base: BaseMessageFields {
-                            content: content.to_string(),
-                            example,
-                            message_type: MessageType::System,
-                            additional_kwargs: std::collections::HashMap::new(),
-                            response_metadata: std::collections::HashMap::new(),
-                            id: None,
-                            name: None,
-                        }
+                        base
                     }
                 }
 
@@ -262,6 +373,8 @@ mod tests {
                     self.base.message_type.as_str()
                 }
             }
+
+            #builder
         };
 
         assert_eq!(generated.to_string(), expected.to_string());
@@ -282,6 +395,7 @@ mod tests {
 
         let base_message_impl_common = base_message_impl_common();
         let base_message_setters = base_message_setters();
+        let builder = implement_builder(&input).unwrap();
 
         let expected = quote! {
             impl ToolMessage {
@@ -289,17 +403,18 @@ mod tests {
                     Self::new_with_example(content, false, tool_call_id, artifact, status)
                 }
 
+                /// Like `new`, with an id from `generate_message_id()`.
+                pub fn new_with_id(content: &str, tool_call_id: String, artifact: Option<String>, status: ToolStatus) -> Self {
+                    let mut message = Self::new(content, tool_call_id, artifact, status);
+                    message.base.id = Some(generate_message_id());
+                    message
+                }
+
                 pub fn new_with_example(content: &str, example: bool, tool_call_id: String, artifact: Option<String>, status: ToolStatus) -> Self {
+                    let mut base = BaseMessageFields::new(content, MessageType::Tool);
+                    base.example = example;
                     Self {
-                        base: BaseMessageFields {
-                            content: content.to_string(),
-                            example,
-                            message_type: MessageType::Tool,
-                            additional_kwargs: std::collections::HashMap::new(),
-                            response_metadata: std::collections::HashMap::new(),
-                            id: None,
-                            name: None,
-                        },
+                        base,
                         tool_call_id,
                         artifact,
                         status
@@ -307,6 +422,30 @@ mod tests {
                 }
 
                 #base_message_setters
+
+                pub fn tool_call_id(&self) -> &String {
+                    &self.tool_call_id
+                }
+
+                pub fn set_tool_call_id(&mut self, tool_call_id: String) {
+                    self.tool_call_id = tool_call_id;
+                }
+
+                pub fn artifact(&self) -> &Option<String> {
+                    &self.artifact
+                }
+
+                pub fn set_artifact(&mut self, artifact: Option<String>) {
+                    self.artifact = artifact;
+                }
+
+                pub fn status(&self) -> &ToolStatus {
+                    &self.status
+                }
+
+                pub fn set_status(&mut self, status: ToolStatus) {
+                    self.status = status;
+                }
             }
 
             impl BaseMessage for ToolMessage {
@@ -316,8 +455,128 @@ mod tests {
                     self.base.message_type.as_str()
                 }
             }
+
+            #builder
         };
 
         assert_eq!(generated.to_string(), expected.to_string());
     }
+
+    #[test]
+    fn test_generic_struct_carries_generics() {
+        let input: DeriveInput = parse_quote! {
+            #[base_message(message_type = "Ai", tag = "role")]
+            pub struct Wrapper<T: Serialize> where T: Clone {
+                base: BaseMessageFields,
+                payload: T,
+            }
+        };
+
+        let generated = derive_macro(quote! { #input }).to_string();
+
+        assert!(generated
+            .starts_with(&quote! { impl<T: Serialize> Wrapper<T> where T: Clone }.to_string()));
+        assert!(generated
+            .contains(&quote! { pub fn new(content: &str, payload: T) -> Self }.to_string()));
+        assert!(generated.contains(
+            &quote! { impl<T: Serialize> BaseMessage for Wrapper<T> where T: Clone }.to_string()
+        ));
+        assert!(generated.contains(
+            &quote! { pub struct WrapperBuilder<T: Serialize> where T: Clone { message: Wrapper<T>, } }
+                .to_string()
+        ));
+        assert!(generated.contains(
+            &quote! { impl<'de, T: Serialize> serde::Deserialize<'de> for Wrapper<T> where T: Clone, T: serde::de::DeserializeOwned }
+                .to_string()
+        ));
+    }
+
+    #[test]
+    fn test_gen_tests_rejects_generic_struct() {
+        let input: DeriveInput = parse_quote! {
+            #[base_message(gen_tests)]
+            struct Wrapper<T> {
+                base: BaseMessageFields,
+                payload: T,
+            }
+        };
+
+        let generated = derive_macro(quote! { #input }).to_string();
+
+        assert!(generated.contains("compile_error"));
+        assert!(generated.contains("not supported on generic structs"));
+    }
+
+    #[test]
+    fn test_gen_tests_attribute_emits_test_module() {
+        let input: DeriveInput = parse_quote! {
+            #[base_message(gen_tests)]
+            struct ChatMessage {
+                role: String,
+                base: BaseMessageFields,
+            }
+        };
+
+        let generated = derive_macro(quote! { #input }).to_string();
+
+        assert!(generated.contains("mod __base_message_tests_chatmessage"));
+        assert!(generated.contains(
+            &quote! { ChatMessage::new("generated test content", Default::default()) }.to_string()
+        ));
+        assert!(generated.contains(&quote! { MessageType::Chat }.to_string()));
+    }
+
+    #[test]
+    fn test_without_gen_tests_attribute_emits_no_test_module() {
+        let input: DeriveInput = parse_quote! {
+            struct ChatMessage {
+                role: String,
+                base: BaseMessageFields,
+            }
+        };
+
+        let generated = derive_macro(quote! { #input }).to_string();
+
+        assert!(!generated.contains("__base_message_tests"));
+    }
+
+    #[test]
+    fn test_message_type_attribute_overrides_struct_name() {
+        let input: DeriveInput = parse_quote! {
+            #[base_message(message_type = "Ai")]
+            struct AssistantReply {
+                base: BaseMessageFields,
+            }
+        };
+
+        let generated = derive_macro(quote! { #input }).to_string();
+
+        assert!(generated
+            .contains(&quote! { BaseMessageFields::new(content, MessageType::Ai) }.to_string()));
+
+        let input: DeriveInput = parse_quote! {
+            #[base_message(message_type = "Bot")]
+            struct AssistantReply {
+                base: BaseMessageFields,
+            }
+        };
+        let generated = derive_macro(quote! { #input }).to_string();
+        assert!(generated.contains("compile_error"));
+        assert!(generated.contains("unknown MessageType variant `Bot`"));
+    }
+
+    #[test]
+    fn test_invalid_attribute_is_compile_error() {
+        let input: DeriveInput = parse_quote! {
+            #[base_message(unknown_option)]
+            struct ChatMessage {
+                base: BaseMessageFields,
+            }
+        };
+
+        let generated = derive_macro(quote! { #input }).to_string();
+
+        assert!(generated.contains("compile_error"));
+        assert!(generated.contains("unsupported base_message attribute"));
+    }
 }
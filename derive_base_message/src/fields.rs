@@ -1,6 +1,8 @@
 use proc_macro2::Ident;
 use quote::quote;
-use syn::{DataStruct, DeriveInput, Error, Field, FieldsNamed, Type};
+use syn::{DataStruct, DeriveInput, Error, Expr, Field, FieldsNamed, Type};
+
+use crate::attributes::parse_field_attributes;
 
 pub fn extract_fields(input: &DeriveInput) -> Result<&FieldsNamed, Error> {
     match input.data {
@@ -23,13 +25,21 @@ pub fn field_name_and_type(field: &Field) -> (&Option<Ident>, &Type) {
 
 fn is_excluded(name: &Option<Ident>, excludes: &[&str]) -> bool {
     name.as_ref()
-        .map_or(false, |n| excludes.contains(&n.to_string().as_str()))
+        .is_some_and(|n| excludes.contains(&n.to_string().as_str()))
+}
+
+// Malformed attributes are reported by `implement_field_accessors`.
+fn default_value(field: &Field) -> Option<Expr> {
+    parse_field_attributes(&field.attrs).ok()?.default
 }
 
+/// Constructor parameters: every field not excluded and without a
+/// `#[base_message(default = "..")]`.
 pub fn field_args(fields: &FieldsNamed, excludes: &[&str]) -> Vec<proc_macro2::TokenStream> {
     fields
         .named
         .iter()
+        .filter(|field| default_value(field).is_none())
         .map(field_name_and_type)
         .filter(|(name, _)| !is_excluded(name, excludes))
         .map(|(name, ty)| {
@@ -46,6 +56,7 @@ pub fn field_initializers(
     fields
         .named
         .iter()
+        .filter(|field| default_value(field).is_none())
         .map(field_name_and_type)
         .filter(|(name, _)| !is_excluded(name, excludes))
         .map(|(name, _)| {
@@ -55,6 +66,23 @@ pub fn field_initializers(
         .collect()
 }
 
+/// Struct-literal entries: parameters by shorthand, defaulted fields set to
+/// their expression.
+pub fn field_values(fields: &FieldsNamed, excludes: &[&str]) -> Vec<proc_macro2::TokenStream> {
+    fields
+        .named
+        .iter()
+        .filter(|field| !is_excluded(&field.ident, excludes))
+        .map(|field| {
+            let name = field.ident.as_ref().unwrap();
+            match default_value(field) {
+                Some(default) => quote! { #name: #default },
+                None => quote! { #name },
+            }
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -306,6 +334,35 @@ mod tests {
         assert_eq!(result.to_string(), expected.to_string());
     }
 
+    #[test]
+    fn test_defaulted_fields_leave_the_signature() {
+        let input: DeriveInput = parse_quote! {
+            struct TestStruct {
+                field1: String,
+                #[base_message(default = "vec![1, 2]")]
+                field2: Vec<u32>,
+            }
+        };
+
+        let fields = extract_fields(&input).unwrap();
+        let args = field_args(fields, &[]);
+        let initializers = field_initializers(fields, &[]);
+        let values = field_values(fields, &[]);
+
+        assert_eq!(
+            quote! { #(#args),* }.to_string(),
+            quote! { field1: String }.to_string()
+        );
+        assert_eq!(
+            quote! { #(#initializers),* }.to_string(),
+            quote! { field1 }.to_string()
+        );
+        assert_eq!(
+            quote! { #(#values),* }.to_string(),
+            quote! { field1, field2: vec![1, 2] }.to_string()
+        );
+    }
+
     #[test]
     fn test_field_initializers_with_optional_fields() {
         let input: DeriveInput = parse_quote! {
@@ -1,11 +1,15 @@
+mod attributes;
+mod builder;
 mod derive_macro;
 mod fields;
 mod methods;
+mod serde_impl;
+mod tests_gen;
 
 use derive_macro::derive_macro;
 use proc_macro::TokenStream;
 
-#[proc_macro_derive(BaseMessage)]
+#[proc_macro_derive(BaseMessage, attributes(base_message, serde))]
 pub fn derive_base_message(input: TokenStream) -> TokenStream {
     derive_macro(input.into()).into()
 }
@@ -1,10 +1,13 @@
 use proc_macro2::TokenStream as TokenStream2;
-use quote::quote;
+use quote::{format_ident, quote};
+use syn::{Error, FieldsNamed};
+
+use crate::attributes::parse_field_attributes;
 
 pub fn implement_base_getters() -> TokenStream2 {
     quote! {
         fn content(&self) -> &str {
-            &self.base.content
+            self.base.content.as_str()
         }
 
         fn message_type(&self) -> &MessageType {
@@ -15,11 +18,11 @@ pub fn implement_base_getters() -> TokenStream2 {
             self.base.example
         }
 
-        fn additional_kwargs(&self) -> &std::collections::HashMap<String, String> {
+        fn additional_kwargs(&self) -> &Metadata {
             &self.base.additional_kwargs
         }
 
-        fn response_metadata(&self) -> &std::collections::HashMap<String, String> {
+        fn response_metadata(&self) -> &Metadata {
             &self.base.response_metadata
         }
 
@@ -30,13 +33,55 @@ pub fn implement_base_getters() -> TokenStream2 {
         fn name(&self) -> Option<&str> {
             self.base.name.as_deref()
         }
+
+
+        fn content_blocks(&self) -> std::borrow::Cow<'_, [ContentBlock]> {
+            self.base.content.blocks()
+        }
+
+        fn text(&self) -> std::borrow::Cow<'_, str> {
+            self.base.content.text()
+        }
     }
 }
 
+/// A getter and `set_` method for every field other than `base`, unless
+/// the field is marked `#[base_message(skip_accessors)]`. A `role` field
+/// only gets the setter, since `BaseMessage::role` already reads it.
+pub fn implement_field_accessors(fields: &FieldsNamed) -> Result<TokenStream2, Error> {
+    let mut accessors = Vec::new();
+    for field in &fields.named {
+        let name = field.ident.as_ref().unwrap();
+        if name == "base" || parse_field_attributes(&field.attrs)?.skip_accessors {
+            continue;
+        }
+        let ty = &field.ty;
+        let setter = format_ident!("set_{}", name);
+        if name != "role" {
+            accessors.push(quote! {
+                pub fn #name(&self) -> &#ty {
+                    &self.#name
+                }
+            });
+        }
+        accessors.push(quote! {
+            pub fn #setter(&mut self, #name: #ty) {
+                self.#name = #name;
+            }
+        });
+    }
+    Ok(quote! { #(#accessors)* })
+}
+
 pub fn implement_base_setters() -> TokenStream2 {
     quote! {
         pub fn set_content(&mut self, new_content: &str) {
-            self.base.content = new_content.to_string();
+            self.base.content = new_content.into();
+        }
+
+        pub fn with_content(mut self, content: impl Into<MessageContent>) -> Self {
+            self.base.content = content.into();
+            self
         }
 
         pub fn set_example(&mut self, example: bool) {
@@ -50,6 +95,32 @@ pub fn implement_base_setters() -> TokenStream2 {
         pub fn set_name(&mut self, name: Option<String>) {
             self.base.name = name;
         }
+
+        pub fn provenance(&self) -> Option<&Provenance> {
+            self.base.provenance.as_ref()
+        }
+
+        pub fn set_provenance(&mut self, provenance: Option<Provenance>) {
+            self.base.provenance = provenance;
+        }
+
+        pub fn with_provenance(mut self, provenance: Provenance) -> Self {
+            self.base.provenance = Some(provenance);
+            self
+        }
+
+        pub fn voice(&self) -> Option<&VoiceMetadata> {
+            self.base.voice.as_ref()
+        }
+
+        pub fn set_voice(&mut self, voice: Option<VoiceMetadata>) {
+            self.base.voice = voice;
+        }
+
+        pub fn with_voice(mut self, voice: VoiceMetadata) -> Self {
+            self.base.voice = Some(voice);
+            self
+        }
     }
 }
 
@@ -65,7 +136,7 @@ mod tests {
 
         let expected = quote! {
             fn content(&self) -> &str {
-                &self.base.content
+                self.base.content.as_str()
             }
 
             fn message_type(&self) -> &MessageType {
@@ -76,11 +147,11 @@ mod tests {
                 self.base.example
             }
 
-            fn additional_kwargs(&self) -> &std::collections::HashMap<String, String> {
+            fn additional_kwargs(&self) -> &Metadata {
                 &self.base.additional_kwargs
             }
 
-            fn response_metadata(&self) -> &std::collections::HashMap<String, String> {
+            fn response_metadata(&self) -> &Metadata {
                 &self.base.response_metadata
             }
 
@@ -91,8 +162,48 @@ mod tests {
             fn name(&self) -> Option<&str> {
                 self.base.name.as_deref()
             }
+
+
+            fn content_blocks(&self) -> std::borrow::Cow<'_, [ContentBlock]> {
+                self.base.content.blocks()
+            }
+
+            fn text(&self) -> std::borrow::Cow<'_, str> {
+                self.base.content.text()
+            }
+        };
+
+        assert_eq!(generated.to_string(), expected.to_string());
+    }
+
+    #[test]
+    fn test_implement_field_accessors() {
+        let input: syn::DeriveInput = syn::parse_quote! {
+            struct ToolMessage {
+                #[base_message(skip_accessors)]
+                tool_call_id: String,
+                role: String,
+                attempt: u32,
+                base: BaseMessageFields,
+            }
         };
+        let fields = crate::fields::extract_fields(&input).unwrap();
 
+        let generated = super::implement_field_accessors(fields).unwrap();
+
+        let expected = quote! {
+            pub fn set_role(&mut self, role: String) {
+                self.role = role;
+            }
+
+            pub fn attempt(&self) -> &u32 {
+                &self.attempt
+            }
+
+            pub fn set_attempt(&mut self, attempt: u32) {
+                self.attempt = attempt;
+            }
+        };
         assert_eq!(generated.to_string(), expected.to_string());
     }
 
@@ -102,7 +213,12 @@ mod tests {
 
         let expected = quote! {
             pub fn set_content(&mut self, new_content: &str) {
-                self.base.content = new_content.to_string();
+                self.base.content = new_content.into();
+            }
+
+            pub fn with_content(mut self, content: impl Into<MessageContent>) -> Self {
+                self.base.content = content.into();
+                self
             }
 
             pub fn set_example(&mut self, example: bool) {
@@ -116,6 +232,32 @@ mod tests {
             pub fn set_name(&mut self, name: Option<String>) {
                 self.base.name = name;
             }
+
+            pub fn provenance(&self) -> Option<&Provenance> {
+                self.base.provenance.as_ref()
+            }
+
+            pub fn set_provenance(&mut self, provenance: Option<Provenance>) {
+                self.base.provenance = provenance;
+            }
+
+            pub fn with_provenance(mut self, provenance: Provenance) -> Self {
+                self.base.provenance = Some(provenance);
+                self
+            }
+
+            pub fn voice(&self) -> Option<&VoiceMetadata> {
+                self.base.voice.as_ref()
+            }
+
+            pub fn set_voice(&mut self, voice: Option<VoiceMetadata>) {
+                self.base.voice = voice;
+            }
+
+            pub fn with_voice(mut self, voice: VoiceMetadata) -> Self {
+                self.base.voice = Some(voice);
+                self
+            }
         };
 
         assert_eq!(generated.to_string(), expected.to_string());
@@ -0,0 +1,140 @@
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{parse_quote, Attribute, DeriveInput, Error, Generics, Ident, WherePredicate};
+
+use crate::fields::extract_fields;
+
+/// Emits `Serialize`/`Deserialize` impls that write `tag: rename` in place
+/// of `message_type`, flattening `base`. Field-level `#[serde(...)]`
+/// attributes are carried over; the struct must not derive serde itself.
+/// Serialization goes through a mirror of borrowed fields, so nothing is
+/// cloned. Type parameters are bounded by `Serialize` for the one impl and
+/// `DeserializeOwned` for the other.
+pub fn implement_tagged_serde(
+    input: &DeriveInput,
+    tag: &str,
+    rename: Option<&str>,
+    message_type_name: &Ident,
+) -> Result<TokenStream2, Error> {
+    let struct_name = &input.ident;
+    let generics = &input.generics;
+    let (_, ty_generics, fields_where) = generics.split_for_impl();
+    let serialize_generics = with_bounds(generics, |param| {
+        parse_quote! { #param: serde::Serialize }
+    });
+    let (serialize_impl, _, serialize_where) = serialize_generics.split_for_impl();
+    let mut borrowed_generics = generics.clone();
+    borrowed_generics.params.insert(0, parse_quote! { 'fields });
+    let mut deserialize_generics = with_bounds(generics, |param| {
+        parse_quote! { #param: serde::de::DeserializeOwned }
+    });
+    deserialize_generics.params.insert(0, parse_quote! { 'de });
+    let (deserialize_impl, _, deserialize_where) = deserialize_generics.split_for_impl();
+    let named_fields = extract_fields(input)?;
+    let rename = rename
+        .map(str::to_string)
+        .unwrap_or_else(|| message_type_name.to_string().to_lowercase());
+
+    let mut names = Vec::new();
+    let mut mirror_fields = Vec::new();
+    let mut borrowed_fields = Vec::new();
+    for field in &named_fields.named {
+        let name = field.ident.as_ref().unwrap();
+        let ty = &field.ty;
+        if name == "base" {
+            mirror_fields.push(quote! { #[serde(flatten)] base: #ty });
+            borrowed_fields.push(quote! { #[serde(flatten)] base: &'fields #ty });
+        } else {
+            let attrs: Vec<&Attribute> = field
+                .attrs
+                .iter()
+                .filter(|attr| attr.path().is_ident("serde"))
+                .collect();
+            mirror_fields.push(quote! { #(#attrs)* #name: #ty });
+            borrowed_fields.push(quote! { #(#attrs)* #name: &'fields #ty });
+        }
+        names.push(name);
+    }
+
+    Ok(quote! {
+        impl #serialize_impl serde::Serialize for #struct_name #ty_generics #serialize_where {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                #[derive(serde::Serialize)]
+                struct Fields #borrowed_generics #fields_where {
+                    #(#borrowed_fields),*
+                }
+
+                let fields = Fields {
+                    #(#names: &self.#names),*
+                };
+                serialize_tagged(&fields, #tag, #rename, serializer)
+            }
+        }
+
+        impl #deserialize_impl serde::Deserialize<'de> for #struct_name #ty_generics #deserialize_where {
+            fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                #[derive(serde::Deserialize)]
+                struct Fields #generics #fields_where {
+                    #(#mirror_fields),*
+                }
+
+                let fields: Fields #ty_generics =
+                    deserialize_tagged(deserializer, #tag, #rename, &MessageType::#message_type_name)?;
+                Ok(#struct_name {
+                    #(#names: fields.#names),*
+                })
+            }
+        }
+    })
+}
+
+/// `generics` with `bound` added for each type parameter. The mirror
+/// structs redeclare the parameters, as items inside an impl cannot name
+/// the impl's own.
+fn with_bounds(generics: &Generics, bound: impl Fn(&Ident) -> WherePredicate) -> Generics {
+    let mut generics = generics.clone();
+    let predicates: Vec<WherePredicate> = generics
+        .type_params()
+        .map(|param| bound(&param.ident))
+        .collect();
+    generics.make_where_clause().predicates.extend(predicates);
+    generics
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quote::format_ident;
+    use syn::parse_quote;
+
+    #[test]
+    fn test_tagged_serde_carries_field_attributes() {
+        let input: DeriveInput = parse_quote! {
+            struct UserTurn {
+                #[serde(rename = "userId")]
+                user_id: String,
+                base: BaseMessageFields,
+            }
+        };
+
+        let generated =
+            implement_tagged_serde(&input, "role", None, &format_ident!("Human")).unwrap();
+        let generated = generated.to_string();
+
+        assert!(
+            generated.contains(&quote! { #[serde(rename = "userId")] user_id: String }.to_string())
+        );
+        assert!(
+            generated.contains(&quote! { #[serde(flatten)] base: BaseMessageFields }.to_string())
+        );
+        assert!(generated.contains(
+            &quote! { #[serde(rename = "userId")] user_id: &'fields String }.to_string()
+        ));
+        assert!(generated.contains(&quote! { user_id: &self.user_id }.to_string()));
+        assert!(!generated.contains("clone"));
+        assert!(generated.contains(
+            &quote! { serialize_tagged(&fields, "role", "human", serializer) }.to_string()
+        ));
+        assert!(generated.contains(&quote! { &MessageType::Human }.to_string()));
+    }
+}
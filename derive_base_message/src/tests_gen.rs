@@ -0,0 +1,78 @@
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use syn::{DeriveInput, Error, Ident};
+
+use crate::fields::{extract_fields, field_args};
+
+/// Emits a `#[cfg(test)]` module covering the code generated for the struct:
+/// constructor defaults, getter/setter round-trips, and a serde round-trip.
+/// Extra fields are built with `Default::default()`, and the serde test needs
+/// `serde_json` available to the crate's tests. Generic structs are
+/// rejected: there is no type to instantiate them with.
+pub fn implement_generated_tests(
+    input: &DeriveInput,
+    message_type_name: &Ident,
+) -> Result<TokenStream2, Error> {
+    if !input.generics.params.is_empty() {
+        return Err(Error::new_spanned(
+            &input.generics,
+            "`gen_tests` is not supported on generic structs",
+        ));
+    }
+    let struct_name = &input.ident;
+    let module_name = format_ident!(
+        "__base_message_tests_{}",
+        struct_name.to_string().to_lowercase()
+    );
+    let named_fields = extract_fields(input)?;
+    let defaults: Vec<TokenStream2> = field_args(named_fields, &["base"])
+        .iter()
+        .map(|_| quote! { , Default::default() })
+        .collect();
+
+    Ok(quote! {
+        #[cfg(test)]
+        mod #module_name {
+            use super::*;
+
+            fn sample() -> #struct_name {
+                #struct_name::new("generated test content" #(#defaults)*)
+            }
+
+            #[test]
+            fn test_constructor_defaults() {
+                let message = sample();
+                assert_eq!(BaseMessage::content(&message), "generated test content");
+                assert_eq!(BaseMessage::message_type(&message), &MessageType::#message_type_name);
+                assert!(!BaseMessage::is_example(&message));
+                assert!(BaseMessage::additional_kwargs(&message).is_empty());
+                assert!(BaseMessage::response_metadata(&message).is_empty());
+                assert_eq!(BaseMessage::id(&message), None);
+                assert_eq!(BaseMessage::name(&message), None);
+            }
+
+            #[test]
+            fn test_getter_setter_round_trip() {
+                let mut message = sample();
+                message.set_content("updated content");
+                message.set_example(true);
+                message.set_id(Some("generated-id".to_string()));
+                message.set_name(Some("generated-name".to_string()));
+
+                assert_eq!(BaseMessage::content(&message), "updated content");
+                assert!(BaseMessage::is_example(&message));
+                assert_eq!(BaseMessage::id(&message), Some("generated-id"));
+                assert_eq!(BaseMessage::name(&message), Some("generated-name"));
+            }
+
+            #[test]
+            fn test_serde_round_trip() {
+                let mut message = sample();
+                message.set_id(Some("generated-id".to_string()));
+                let serialized = serde_json::to_value(&message).unwrap();
+                let deserialized: #struct_name = serde_json::from_value(serialized.clone()).unwrap();
+                assert_eq!(serde_json::to_value(&deserialized).unwrap(), serialized);
+            }
+        }
+    })
+}
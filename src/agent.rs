@@ -0,0 +1,158 @@
+use std::collections::{HashMap, VecDeque};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Conversation, MessageEnum};
+
+/// A message in transit between two agents.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AgentEnvelope {
+    pub from_agent: String,
+    pub to_agent: String,
+    pub message: MessageEnum,
+
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub correlation_id: Option<String>,
+}
+
+impl AgentEnvelope {
+    pub fn new(
+        from_agent: impl Into<String>,
+        to_agent: impl Into<String>,
+        message: impl Into<MessageEnum>,
+    ) -> Self {
+        AgentEnvelope {
+            from_agent: from_agent.into(),
+            to_agent: to_agent.into(),
+            message: message.into(),
+            correlation_id: None,
+        }
+    }
+
+    pub fn with_correlation_id(mut self, correlation_id: impl Into<String>) -> Self {
+        self.correlation_id = Some(correlation_id.into());
+        self
+    }
+
+    /// An envelope going back to the sender, in the same correlation.
+    pub fn reply(&self, message: impl Into<MessageEnum>) -> AgentEnvelope {
+        AgentEnvelope {
+            from_agent: self.to_agent.clone(),
+            to_agent: self.from_agent.clone(),
+            message: message.into(),
+            correlation_id: self.correlation_id.clone(),
+        }
+    }
+}
+
+pub trait Mailbox {
+    fn send(&mut self, envelope: AgentEnvelope);
+
+    /// The oldest undelivered envelope addressed to `agent`.
+    fn receive(&mut self, agent: &str) -> Option<AgentEnvelope>;
+
+    fn pending(&self, agent: &str) -> usize;
+}
+
+/// A [`Mailbox`] that queues per recipient and keeps every envelope it has
+/// seen, in send order, so the traffic can be saved and replayed.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct InMemoryMailbox {
+    #[serde(default)]
+    queues: HashMap<String, VecDeque<AgentEnvelope>>,
+    #[serde(default)]
+    log: Vec<AgentEnvelope>,
+}
+
+impl InMemoryMailbox {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn log(&self) -> &[AgentEnvelope] {
+        &self.log
+    }
+
+    pub fn correlated<'a>(
+        &'a self,
+        correlation_id: &'a str,
+    ) -> impl Iterator<Item = &'a AgentEnvelope> + 'a {
+        self.log
+            .iter()
+            .filter(move |envelope| envelope.correlation_id.as_deref() == Some(correlation_id))
+    }
+
+    /// The logged traffic as a conversation, with each message's `name` set
+    /// to the agent that sent it.
+    pub fn to_conversation(&self) -> Conversation {
+        self.log
+            .iter()
+            .map(|envelope| {
+                let mut message = envelope.message.clone();
+                message.base_mut().name = Some(envelope.from_agent.clone());
+                message
+            })
+            .collect()
+    }
+}
+
+impl Mailbox for InMemoryMailbox {
+    fn send(&mut self, envelope: AgentEnvelope) {
+        self.queues
+            .entry(envelope.to_agent.clone())
+            .or_default()
+            .push_back(envelope.clone());
+        self.log.push(envelope);
+    }
+
+    fn receive(&mut self, agent: &str) -> Option<AgentEnvelope> {
+        self.queues.get_mut(agent)?.pop_front()
+    }
+
+    fn pending(&self, agent: &str) -> usize {
+        self.queues.get(agent).map_or(0, VecDeque::len)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AiMessage, BaseMessage, HumanMessage};
+
+    #[test]
+    fn test_send_receive_and_reply() {
+        let mut mailbox = InMemoryMailbox::new();
+        mailbox.send(
+            AgentEnvelope::new("planner", "coder", HumanMessage::new("Write a parser"))
+                .with_correlation_id("task-1"),
+        );
+
+        assert_eq!(mailbox.pending("coder"), 1);
+        assert!(mailbox.receive("planner").is_none());
+
+        let request = mailbox.receive("coder").unwrap();
+        assert_eq!(request.message.content(), "Write a parser");
+        mailbox.send(request.reply(AiMessage::new("Done")));
+
+        let reply = mailbox.receive("planner").unwrap();
+        assert_eq!(reply.from_agent, "coder");
+        assert_eq!(reply.correlation_id.as_deref(), Some("task-1"));
+        assert_eq!(mailbox.correlated("task-1").count(), 2);
+        assert_eq!(mailbox.pending("coder"), 0);
+    }
+
+    #[test]
+    fn test_log_replays_as_conversation() {
+        let mut mailbox = InMemoryMailbox::new();
+        mailbox.send(AgentEnvelope::new("a", "b", HumanMessage::new("ping")));
+        mailbox.send(AgentEnvelope::new("b", "a", AiMessage::new("pong")));
+
+        let json = serde_json::to_string(&mailbox).unwrap();
+        let restored: InMemoryMailbox = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored, mailbox);
+
+        let conversation = restored.to_conversation();
+        let speakers: Vec<Option<&str>> = conversation.iter().map(|m| m.name()).collect();
+        assert_eq!(speakers, vec![Some("a"), Some("b")]);
+    }
+}
@@ -1,6 +1,69 @@
 use crate::prelude::*;
+use crate::{InvalidToolCall, Logprobs, ToolCall, UsageMetadata};
 
-define_message!(Ai);
+define_message!(
+    Ai {},
+    defaults {
+        /// Token log probabilities of the completion.
+        #[serde(skip_serializing_if = "Option::is_none", default)]
+        pub logprobs: Option<Logprobs>,
+
+        /// Token usage of the call that produced it.
+        #[serde(skip_serializing_if = "Option::is_none", default)]
+        pub usage_metadata: Option<UsageMetadata>,
+
+        /// Structured tool calls.
+        #[serde(skip_serializing_if = "Vec::is_empty", default)]
+        pub tool_calls: Vec<ToolCall>,
+
+        #[serde(skip_serializing_if = "Vec::is_empty", default)]
+        pub invalid_tool_calls: Vec<InvalidToolCall>,
+    },
+    tool_calls = tool_calls,
+    invalid_tool_calls = invalid_tool_calls
+);
+
+impl AiMessage {
+    pub fn logprobs(&self) -> Option<&Logprobs> {
+        self.logprobs.as_ref()
+    }
+
+    pub fn set_logprobs(&mut self, logprobs: Option<Logprobs>) {
+        self.logprobs = logprobs;
+    }
+
+    pub fn with_logprobs(mut self, logprobs: Logprobs) -> Self {
+        self.logprobs = Some(logprobs);
+        self
+    }
+
+    pub fn usage_metadata(&self) -> Option<&UsageMetadata> {
+        self.usage_metadata.as_ref()
+    }
+
+    pub fn set_usage_metadata(&mut self, usage_metadata: Option<UsageMetadata>) {
+        self.usage_metadata = usage_metadata;
+    }
+
+    pub fn with_usage_metadata(mut self, usage_metadata: UsageMetadata) -> Self {
+        self.usage_metadata = Some(usage_metadata);
+        self
+    }
+
+    pub fn set_tool_calls(&mut self, tool_calls: Vec<ToolCall>) {
+        self.tool_calls = tool_calls;
+    }
+
+    pub fn with_tool_calls(mut self, tool_calls: Vec<ToolCall>) -> Self {
+        self.tool_calls = tool_calls;
+        self
+    }
+
+    pub fn with_invalid_tool_calls(mut self, invalid_tool_calls: Vec<InvalidToolCall>) -> Self {
+        self.invalid_tool_calls = invalid_tool_calls;
+        self
+    }
+}
 
 #[cfg(test)]
 mod tests {
@@ -23,6 +86,13 @@ mod tests {
         assert_eq!(ai_message.content(), "new_content");
     }
 
+    #[test]
+    fn test_aimessage_new_with_id() {
+        let ai_message = AiMessage::new_with_id("Has an id.");
+        assert!(ai_message.id().is_some());
+        assert_eq!(ai_message.content(), "Has an id.");
+    }
+
     #[test]
     fn test_aimessage_example() {
         let mut ai_message = AiMessage::new("Example message.");
@@ -40,6 +110,36 @@ mod tests {
         assert_eq!(ai_message.name(), Some("AI Bot"));
     }
 
+    #[test]
+    fn test_aimessage_with_provenance() {
+        let mut ai_message = AiMessage::new("Traced message.")
+            .with_provenance(Provenance::new("router").with_step("classify"));
+        assert_eq!(ai_message.provenance().unwrap().component, "router");
+
+        ai_message.set_provenance(None);
+        assert!(ai_message.provenance().is_none());
+    }
+
+    #[test]
+    fn test_aimessage_with_voice() {
+        let mut ai_message =
+            AiMessage::new("Transcribed message.").with_voice(VoiceMetadata::new(1_500));
+        assert_eq!(ai_message.voice().unwrap().duration_ms, 1_500);
+
+        ai_message.set_voice(None);
+        assert!(ai_message.voice().is_none());
+    }
+
+    #[test]
+    fn test_aimessage_with_content_blocks() {
+        let ai_message = AiMessage::new("").with_content(vec![
+            ContentBlock::text("Describe this."),
+            ContentBlock::image_url("https://example.com/chart.png"),
+        ]);
+        assert_eq!(ai_message.content(), "Describe this.");
+        assert_eq!(ai_message.content_blocks().len(), 2);
+    }
+
     #[test]
     fn test_aimessage_with_additional_kwargs() {
         let mut ai_message = AiMessage::new("This is an AI message.");
@@ -48,10 +148,7 @@ mod tests {
             .additional_kwargs
             .insert("key".to_string(), "value".to_string());
 
-        assert_eq!(
-            ai_message.additional_kwargs().get("key"),
-            Some(&"value".to_string())
-        );
+        assert_eq!(ai_message.additional_kwargs().get_str("key"), Some("value"));
     }
 
     #[test]
@@ -63,8 +160,8 @@ mod tests {
             .insert("source".to_string(), "AI Model".to_string());
 
         assert_eq!(
-            ai_message.response_metadata().get("source"),
-            Some(&"AI Model".to_string())
+            ai_message.response_metadata().get_str("source"),
+            Some("AI Model")
         );
     }
 
@@ -98,7 +195,7 @@ mod tests {
     fn test_aimessage_debug_format() {
         let ai_message = AiMessage::new("Debug AI message.");
         let debug_output = format!("{:?}", ai_message);
-        let expected_debug_output = r#"AiMessage { base: BaseMessageFields { content: "Debug AI message.", example: false, message_type: Ai, additional_kwargs: {}, response_metadata: {}, id: None, name: None } }"#;
+        let expected_debug_output = r#"AiMessage { base: BaseMessageFields { content: Text("Debug AI message."), example: false, message_type: Ai, additional_kwargs: {}, response_metadata: {}, id: None, name: None, provenance: None, voice: None, pinned: false, extensions: Extensions { .. } } }"#;
         assert_eq!(debug_output, expected_debug_output);
     }
 
@@ -119,12 +216,12 @@ mod tests {
         assert_eq!(ai_message.id(), Some("AI123"));
         assert_eq!(ai_message.name(), Some("AI Assistant"));
         assert_eq!(
-            ai_message.additional_kwargs().get("task"),
-            Some(&"information retrieval".to_string())
+            ai_message.additional_kwargs().get_str("task"),
+            Some("information retrieval")
         );
         assert_eq!(
-            ai_message.response_metadata().get("model"),
-            Some(&"gpt-3".to_string())
+            ai_message.response_metadata().get_str("model"),
+            Some("gpt-3")
         );
 
         let expected_json = json!({
@@ -144,4 +241,24 @@ mod tests {
         let serialized: Value = serde_json::to_value(&ai_message).unwrap();
         assert_eq!(serialized, expected_json);
     }
+
+    #[test]
+    fn test_aimessage_builder() {
+        let ai_message = AiMessage::builder()
+            .content("Sure.")
+            .name("assistant")
+            .id("a1")
+            .kwarg("cached", true)
+            .example(true)
+            .build();
+
+        assert_eq!(ai_message.content(), "Sure.");
+        assert_eq!(ai_message.name(), Some("assistant"));
+        assert_eq!(ai_message.id(), Some("a1"));
+        assert_eq!(
+            ai_message.additional_kwargs().get_bool("cached"),
+            Some(true)
+        );
+        assert!(ai_message.is_example());
+    }
 }
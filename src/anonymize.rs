@@ -0,0 +1,365 @@
+use std::collections::HashMap;
+use std::ops::Range;
+
+use serde_json::Value;
+
+use crate::safety::{replace_spans, token_spans};
+use crate::{Conversation, MessageEnum};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EntityKind {
+    Name,
+    Email,
+    Id,
+}
+
+impl EntityKind {
+    fn label(self) -> &'static str {
+        match self {
+            EntityKind::Name => "PERSON",
+            EntityKind::Email => "EMAIL",
+            EntityKind::Id => "ID",
+        }
+    }
+}
+
+/// What [`anonymize`] replaces. `names` lists the people to pseudonymize in
+/// message content; speaker names on messages are always treated as names when
+/// `speaker_names` is set.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AnonymizationPolicy {
+    pub names: Vec<String>,
+    pub emails: bool,
+    pub speaker_names: bool,
+    pub message_ids: bool,
+}
+
+impl Default for AnonymizationPolicy {
+    fn default() -> Self {
+        AnonymizationPolicy {
+            names: Vec::new(),
+            emails: true,
+            speaker_names: true,
+            message_ids: true,
+        }
+    }
+}
+
+impl AnonymizationPolicy {
+    pub fn with_names<I, S>(mut self, names: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.names.extend(names.into_iter().map(Into::into));
+        self
+    }
+}
+
+/// Keeps the entity → placeholder mapping, so several conversations can be
+/// anonymized consistently with each other.
+#[derive(Debug, Clone, Default)]
+pub struct Anonymizer {
+    policy: AnonymizationPolicy,
+    placeholders: HashMap<(EntityKind, String), String>,
+    counters: HashMap<EntityKind, usize>,
+}
+
+impl Anonymizer {
+    pub fn new(policy: AnonymizationPolicy) -> Self {
+        Anonymizer {
+            policy,
+            ..Self::default()
+        }
+    }
+
+    pub fn policy(&self) -> &AnonymizationPolicy {
+        &self.policy
+    }
+
+    /// The placeholder assigned to `value`, if it has been seen.
+    pub fn placeholder_for(&self, kind: EntityKind, value: &str) -> Option<&str> {
+        self.placeholders
+            .get(&(kind, value.to_lowercase()))
+            .map(String::as_str)
+    }
+
+    pub fn anonymize(&mut self, conversation: &Conversation) -> Conversation {
+        let mut anonymized = conversation.clone();
+        for message in anonymized.messages_mut() {
+            self.anonymize_message(message);
+        }
+        anonymized
+    }
+
+    pub fn anonymize_message(&mut self, message: &mut MessageEnum) {
+        if let Some(ai) = message.as_ai_mut() {
+            for call in &mut ai.tool_calls {
+                self.anonymize_value(&mut call.args);
+            }
+            for call in &mut ai.invalid_tool_calls {
+                if let Some(args) = call.args.take() {
+                    call.args = Some(self.anonymize_text(&args));
+                }
+            }
+        }
+        let base = message.base_mut();
+        for text in base.content.texts_mut() {
+            *text = self.anonymize_text(text);
+        }
+        for value in base.additional_kwargs.values_mut() {
+            self.anonymize_value(value);
+        }
+
+        if self.policy.speaker_names {
+            if let Some(name) = base.name.take() {
+                base.name = Some(self.placeholder(EntityKind::Name, &name));
+            }
+        }
+        if self.policy.message_ids {
+            if let Some(id) = base.id.take() {
+                base.id = Some(self.placeholder(EntityKind::Id, &id));
+            }
+        }
+    }
+
+    pub fn anonymize_text(&mut self, text: &str) -> String {
+        let mut spans: Vec<(Range<usize>, EntityKind)> = Vec::new();
+        if self.policy.emails {
+            spans.extend(
+                token_spans(text)
+                    .into_iter()
+                    .filter(|span| is_email(&text[span.clone()]))
+                    .map(|span| (span, EntityKind::Email)),
+            );
+        }
+        for name in &self.policy.names {
+            spans.extend(
+                find_words(text, name)
+                    .into_iter()
+                    .map(|span| (span, EntityKind::Name)),
+            );
+        }
+
+        // Longest match wins where entities overlap, e.g. a name inside an email.
+        spans.sort_by_key(|(span, _)| (span.start, std::cmp::Reverse(span.end)));
+        let mut kept: Vec<(Range<usize>, EntityKind)> = Vec::new();
+        for (span, kind) in spans {
+            if kept.last().is_none_or(|(last, _)| span.start >= last.end) {
+                kept.push((span, kind));
+            }
+        }
+
+        let mut replacements: HashMap<&str, String> = HashMap::new();
+        for (span, kind) in &kept {
+            let matched = &text[span.clone()];
+            let placeholder = self.placeholder(*kind, matched);
+            replacements.insert(matched, placeholder);
+        }
+        let ranges: Vec<Range<usize>> = kept.into_iter().map(|(span, _)| span).collect();
+        replace_spans(text, &ranges, |matched| replacements[matched].clone())
+    }
+
+    /// Anonymizes every string inside `value`, e.g. tool call arguments.
+    pub fn anonymize_value(&mut self, value: &mut Value) {
+        match value {
+            Value::String(text) => *text = self.anonymize_text(text),
+            Value::Array(items) => items.iter_mut().for_each(|item| self.anonymize_value(item)),
+            Value::Object(fields) => fields
+                .values_mut()
+                .for_each(|field| self.anonymize_value(field)),
+            _ => {}
+        }
+    }
+
+    fn placeholder(&mut self, kind: EntityKind, value: &str) -> String {
+        let key = (kind, value.to_lowercase());
+        if let Some(existing) = self.placeholders.get(&key) {
+            return existing.clone();
+        }
+        let counter = self.counters.entry(kind).or_insert(0);
+        *counter += 1;
+        let placeholder = format!("{}_{}", kind.label(), counter);
+        self.placeholders.insert(key, placeholder.clone());
+        placeholder
+    }
+}
+
+pub fn anonymize(conversation: &Conversation, policy: AnonymizationPolicy) -> Conversation {
+    Anonymizer::new(policy).anonymize(conversation)
+}
+
+fn is_email(token: &str) -> bool {
+    match token.split_once('@') {
+        Some((local, domain)) => {
+            !local.is_empty()
+                && !domain.contains('@')
+                && domain
+                    .split_once('.')
+                    .is_some_and(|(host, tld)| !host.is_empty() && !tld.is_empty())
+        }
+        None => false,
+    }
+}
+
+// Case-insensitive whole-word occurrences of `needle` in `text`, as byte
+// ranges of `text`.
+fn find_words(text: &str, needle: &str) -> Vec<Range<usize>> {
+    let needle: Vec<char> = needle.chars().flat_map(char::to_lowercase).collect();
+    if needle.is_empty() {
+        return Vec::new();
+    }
+    // Lowercasing can turn one char into several (e.g. "İ"), so each
+    // lowered char remembers the original char it came from.
+    let lowered: Vec<(char, Range<usize>)> = text
+        .char_indices()
+        .flat_map(|(start, c)| {
+            let span = start..start + c.len_utf8();
+            c.to_lowercase().map(move |lower| (lower, span.clone()))
+        })
+        .collect();
+
+    let is_word = |c: Option<char>| c.is_some_and(char::is_alphanumeric);
+    let mut spans = Vec::new();
+    let mut index = 0;
+    while index + needle.len() <= lowered.len() {
+        let window = &lowered[index..index + needle.len()];
+        let span = window[0].1.start..window[needle.len() - 1].1.end;
+        let whole_chars = (index == 0 || lowered[index - 1].1 != window[0].1)
+            && lowered
+                .get(index + needle.len())
+                .is_none_or(|(_, next)| *next != window[needle.len() - 1].1);
+        if whole_chars
+            && window.iter().map(|(c, _)| *c).eq(needle.iter().copied())
+            && !is_word(text[..span.start].chars().next_back())
+            && !is_word(text[span.end..].chars().next())
+        {
+            spans.push(span);
+            index += needle.len();
+        } else {
+            index += 1;
+        }
+    }
+    spans
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AiMessage, BaseMessage, HumanMessage, ToolCall};
+    use serde_json::json;
+
+    fn conversation() -> Conversation {
+        let mut question = HumanMessage::new("Hi, I'm Alice Smith, email alice@example.com.");
+        question.set_name(Some("Alice Smith".to_string()));
+        question.set_id(Some("msg-1".to_string()));
+        let answer =
+            AiMessage::new("Hello alice smith! I'll reply to alice@example.com or bob@corp.io.");
+
+        let mut conversation = Conversation::new();
+        conversation.push(question);
+        conversation.push(answer);
+        conversation
+    }
+
+    #[test]
+    fn test_same_entity_gets_same_placeholder() {
+        let policy = AnonymizationPolicy::default().with_names(["Alice Smith"]);
+        let anonymized = anonymize(&conversation(), policy);
+
+        let messages = anonymized.messages();
+        assert_eq!(messages[0].content(), "Hi, I'm PERSON_1, email EMAIL_1.");
+        assert_eq!(
+            messages[1].content(),
+            "Hello PERSON_1! I'll reply to EMAIL_1 or EMAIL_2."
+        );
+        assert_eq!(messages[0].name(), Some("PERSON_1"));
+        assert_eq!(messages[0].id(), Some("ID_1"));
+    }
+
+    #[test]
+    fn test_mapping_is_shared_across_conversations() {
+        let mut anonymizer = Anonymizer::new(AnonymizationPolicy::default().with_names(["Alice"]));
+        anonymizer.anonymize(&conversation());
+
+        let second: Conversation = vec![HumanMessage::new("bob@corp.io says hi to Alice")]
+            .into_iter()
+            .collect();
+        let anonymized = anonymizer.anonymize(&second);
+
+        assert_eq!(
+            anonymized.messages()[0].content(),
+            "EMAIL_2 says hi to PERSON_1"
+        );
+        assert_eq!(
+            anonymizer.placeholder_for(EntityKind::Email, "BOB@corp.io"),
+            Some("EMAIL_2")
+        );
+    }
+
+    #[test]
+    fn test_policy_can_disable_entities() {
+        let policy = AnonymizationPolicy {
+            emails: false,
+            speaker_names: false,
+            message_ids: false,
+            ..AnonymizationPolicy::default()
+        };
+        let original = conversation();
+
+        assert_eq!(anonymize(&original, policy), original);
+    }
+
+    #[test]
+    fn test_names_match_whole_words_only() {
+        let mut anonymizer = Anonymizer::new(AnonymizationPolicy::default().with_names(["Al"]));
+
+        assert_eq!(
+            anonymizer.anonymize_text("Al met Alan."),
+            "PERSON_1 met Alan."
+        );
+    }
+
+    #[test]
+    fn test_names_match_outside_ascii() {
+        let mut only_alice = Anonymizer::new(AnonymizationPolicy::default().with_names(["Alice"]));
+        let mut both =
+            Anonymizer::new(AnonymizationPolicy::default().with_names(["Alice", "İlker"]));
+
+        assert_eq!(
+            only_alice.anonymize_text("Alice met İlker today"),
+            "PERSON_1 met İlker today"
+        );
+        assert_eq!(
+            both.anonymize_text("Alice met İLKER today"),
+            "PERSON_1 met PERSON_2 today"
+        );
+    }
+
+    #[test]
+    fn test_tool_call_args_and_kwargs_are_anonymized() {
+        let mut message: MessageEnum = AiMessage::new("")
+            .with_tool_calls(vec![ToolCall::new(
+                "call_1",
+                "send_email",
+                json!({"to": ["alice@example.com"], "body": "Hi Alice"}),
+            )])
+            .into();
+        message
+            .base_mut()
+            .additional_kwargs
+            .insert("reply_to", "alice@example.com");
+        let mut anonymizer = Anonymizer::new(AnonymizationPolicy::default().with_names(["Alice"]));
+
+        anonymizer.anonymize_message(&mut message);
+
+        assert_eq!(
+            message.tool_calls()[0].args,
+            json!({"to": ["EMAIL_1"], "body": "Hi PERSON_1"})
+        );
+        assert_eq!(
+            message.additional_kwargs().get_str("reply_to"),
+            Some("EMAIL_1")
+        );
+    }
+}
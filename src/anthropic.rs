@@ -0,0 +1,265 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::compat::ensure_compatible;
+use crate::{AnyMessage, BaseMessage, ContentBlock, MessageContent, ModelProfile};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AnthropicError(pub String);
+
+impl fmt::Display for AnthropicError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Cannot convert to an Anthropic request: {}", self.0)
+    }
+}
+
+impl std::error::Error for AnthropicError {}
+
+/// The conversation part of a Messages API request; callers add `model`,
+/// `max_tokens` and sampling parameters alongside it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AnthropicRequestBody {
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub system: Option<String>,
+    pub messages: Vec<AnthropicMessage>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AnthropicRole {
+    User,
+    Assistant,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AnthropicMessage {
+    pub role: AnthropicRole,
+    pub content: Vec<AnthropicContent>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AnthropicContent {
+    Text {
+        text: String,
+    },
+    Image {
+        source: AnthropicSource,
+    },
+    Document {
+        source: AnthropicSource,
+        #[serde(skip_serializing_if = "Option::is_none", default)]
+        title: Option<String>,
+    },
+    ToolUse {
+        id: String,
+        name: String,
+        input: Value,
+    },
+    ToolResult {
+        tool_use_id: String,
+        content: String,
+        #[serde(skip_serializing_if = "is_false", default)]
+        is_error: bool,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AnthropicSource {
+    Base64 { media_type: String, data: String },
+    Url { url: String },
+}
+
+fn is_false(value: &bool) -> bool {
+    !*value
+}
+
+/// Strict mode: like [`to_request`], but fails when
+/// [`check_compatibility`](crate::check_compatibility) finds anything the
+/// model described by `profile` cannot take.
+pub fn to_request_strict(
+    messages: &[AnyMessage],
+    profile: &ModelProfile,
+) -> Result<AnthropicRequestBody, AnthropicError> {
+    ensure_compatible(messages, profile).map_err(|err| AnthropicError(err.to_string()))?;
+    to_request(messages)
+}
+
+/// Builds a request body: system messages are joined into `system`, tool
+/// results become user turns, and consecutive turns with the same role are
+/// merged so user and assistant alternate.
+pub fn to_request(messages: &[AnyMessage]) -> Result<AnthropicRequestBody, AnthropicError> {
+    let mut system: Vec<String> = Vec::new();
+    let mut turns: Vec<AnthropicMessage> = Vec::new();
+    for message in messages {
+        let (role, content) =
+            match message {
+                AnyMessage::System(message) => {
+                    system.push(message.text().into_owned());
+                    continue;
+                }
+                AnyMessage::Human(message) => {
+                    (AnthropicRole::User, content_blocks(&message.base.content)?)
+                }
+                AnyMessage::Ai(message) => {
+                    let mut content = content_blocks(&message.base.content)?;
+                    content.extend(message.tool_calls().iter().map(|call| {
+                        AnthropicContent::ToolUse {
+                            id: call.id.clone(),
+                            name: call.name.clone(),
+                            input: call.args.clone(),
+                        }
+                    }));
+                    (AnthropicRole::Assistant, content)
+                }
+                AnyMessage::Tool(tool) => (
+                    AnthropicRole::User,
+                    vec![AnthropicContent::ToolResult {
+                        tool_use_id: tool.tool_call_id().to_string(),
+                        content: tool.text().into_owned(),
+                        is_error: tool.is_error(),
+                    }],
+                ),
+                AnyMessage::Chat(_) | AnyMessage::Remove(_) | AnyMessage::Unknown(_) => {
+                    return Err(AnthropicError(format!(
+                        "unsupported message type '{}'",
+                        message.role()
+                    )))
+                }
+            };
+        match turns.last_mut() {
+            Some(last) if last.role == role => last.content.extend(content),
+            _ => turns.push(AnthropicMessage { role, content }),
+        }
+    }
+    Ok(AnthropicRequestBody {
+        system: (!system.is_empty()).then(|| system.join("\n\n")),
+        messages: turns,
+    })
+}
+
+// Empty text blocks are rejected by the API, so they are dropped.
+fn content_blocks(content: &MessageContent) -> Result<Vec<AnthropicContent>, AnthropicError> {
+    content
+        .blocks()
+        .iter()
+        .filter(|block| block.as_text() != Some(""))
+        .map(|block| {
+            Ok(match block.clone() {
+                ContentBlock::Text { text } => AnthropicContent::Text { text },
+                ContentBlock::ImageUrl { url, .. } => AnthropicContent::Image {
+                    source: AnthropicSource::Url { url },
+                },
+                ContentBlock::Image { mime_type, data } => AnthropicContent::Image {
+                    source: AnthropicSource::Base64 {
+                        media_type: mime_type,
+                        data,
+                    },
+                },
+                ContentBlock::Document {
+                    mime_type,
+                    data,
+                    title,
+                } => AnthropicContent::Document {
+                    source: AnthropicSource::Base64 {
+                        media_type: mime_type,
+                        data,
+                    },
+                    title,
+                },
+                ContentBlock::Audio { .. } => {
+                    return Err(AnthropicError("audio content is not supported".to_string()))
+                }
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tool_message::ToolStatus;
+    use crate::{AiMessage, HumanMessage, SystemMessage, ToolCall, ToolMessage};
+    use serde_json::json;
+
+    #[test]
+    fn test_system_extraction_and_merging() {
+        let messages: Vec<AnyMessage> = vec![
+            SystemMessage::new("Be brief.").into(),
+            HumanMessage::new("Weather in Paris?").into(),
+            HumanMessage::new("And Rome?").into(),
+            AiMessage::new("")
+                .with_tool_calls(vec![ToolCall::new(
+                    "toolu_1",
+                    "weather",
+                    json!({"city": "Paris"}),
+                )])
+                .into(),
+            ToolMessage::new("Sunny", "toolu_1".to_string(), None, ToolStatus::Error).into(),
+            SystemMessage::new("Answer in French.").into(),
+        ];
+
+        let body = to_request(&messages).unwrap();
+
+        assert_eq!(
+            body.system.as_deref(),
+            Some("Be brief.\n\nAnswer in French.")
+        );
+        assert_eq!(body.messages.len(), 3);
+        assert_eq!(body.messages[0].content.len(), 2);
+        assert_eq!(
+            serde_json::to_value(&body.messages[1]).unwrap(),
+            json!({"role": "assistant", "content": [
+                {"type": "tool_use", "id": "toolu_1", "name": "weather", "input": {"city": "Paris"}},
+            ]})
+        );
+        assert_eq!(
+            serde_json::to_value(&body.messages[2].content[0]).unwrap(),
+            json!({"type": "tool_result", "tool_use_id": "toolu_1", "content": "Sunny", "is_error": true})
+        );
+    }
+
+    #[test]
+    fn test_content_blocks() {
+        let question = HumanMessage::new("").with_content(vec![
+            ContentBlock::text("Summarize."),
+            ContentBlock::document("application/pdf", "JVBERi0="),
+            ContentBlock::image_url("https://example.com/a.png"),
+        ]);
+
+        let body = to_request(&[question.into()]).unwrap();
+
+        let value = serde_json::to_value(&body).unwrap();
+        assert!(value.get("system").is_none());
+        assert_eq!(
+            value["messages"][0]["content"][1]["source"],
+            json!({"type": "base64", "media_type": "application/pdf", "data": "JVBERi0="})
+        );
+        assert_eq!(value["messages"][0]["content"][2]["source"]["type"], "url");
+
+        let audio =
+            HumanMessage::new("").with_content(vec![ContentBlock::audio("audio/wav", "UklG")]);
+        assert!(to_request(&[audio.into()]).is_err());
+    }
+
+    #[test]
+    fn test_strict_mode_rejects_images_for_text_only_models() {
+        let messages: Vec<AnyMessage> = vec![HumanMessage::new("")
+            .with_content(vec![
+                ContentBlock::text("What is this?"),
+                ContentBlock::image_url("https://example.com/cat.png"),
+            ])
+            .into()];
+        let text_only = ModelProfile::new("text-model", 8_000);
+
+        let error = to_request_strict(&messages, &text_only).unwrap_err();
+
+        assert!(error
+            .0
+            .contains("image block present but text-model is text-only"));
+        assert!(to_request_strict(&messages, &text_only.with_images()).is_ok());
+    }
+}
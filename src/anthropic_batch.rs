@@ -0,0 +1,332 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::anthropic::{to_request, AnthropicContent};
+use crate::{
+    AiMessage, AnthropicError, AnthropicRequestBody, AnyMessage, MessageContent, RequestOptions,
+    ToolCall, UsageMetadata,
+};
+
+/// Messages API parameters of one batch entry. `max_tokens` is required by
+/// the API; any other request option is carried in `extra`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AnthropicMessageParams {
+    pub model: String,
+    pub max_tokens: u64,
+    #[serde(flatten)]
+    pub body: AnthropicRequestBody,
+    #[serde(flatten)]
+    pub extra: BTreeMap<String, Value>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AnthropicBatchRequest {
+    pub custom_id: String,
+    pub params: AnthropicMessageParams,
+}
+
+/// Body of a `POST /v1/messages/batches` call.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AnthropicBatch {
+    pub requests: Vec<AnthropicBatchRequest>,
+}
+
+/// Builds a batch from `(custom_id, messages)` pairs with the model and
+/// parameters of `options`, which must include `max_tokens`. Ids must be
+/// unique, 1-64 characters of letters, digits, `-` and `_`.
+pub fn to_anthropic_batch<I, S, M>(
+    requests: I,
+    options: &RequestOptions,
+) -> Result<AnthropicBatch, AnthropicError>
+where
+    I: IntoIterator<Item = (S, M)>,
+    S: Into<String>,
+    M: AsRef<[AnyMessage]>,
+{
+    let mut extra = options.params.clone();
+    let max_tokens = extra
+        .remove("max_tokens")
+        .and_then(|value| value.as_u64())
+        .ok_or_else(|| AnthropicError("max_tokens is required for batches".to_string()))?;
+    let mut seen = HashSet::new();
+    let mut batch = AnthropicBatch::default();
+    for (custom_id, messages) in requests {
+        let custom_id = custom_id.into();
+        let valid = (1..=64).contains(&custom_id.len())
+            && custom_id
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+        if !valid {
+            return Err(AnthropicError(format!("invalid custom_id '{}'", custom_id)));
+        }
+        if !seen.insert(custom_id.clone()) {
+            return Err(AnthropicError(format!(
+                "duplicate custom_id '{}'",
+                custom_id
+            )));
+        }
+        batch.requests.push(AnthropicBatchRequest {
+            custom_id,
+            params: AnthropicMessageParams {
+                model: options.model.clone(),
+                max_tokens,
+                body: to_request(messages.as_ref())?,
+                extra: extra.clone(),
+            },
+        });
+    }
+    Ok(batch)
+}
+
+/// Body of a `POST /v1/messages/count_tokens` call.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AnthropicCountTokensRequest {
+    pub model: String,
+    #[serde(flatten)]
+    pub body: AnthropicRequestBody,
+}
+
+impl AnthropicCountTokensRequest {
+    pub fn new(model: impl Into<String>, messages: &[AnyMessage]) -> Result<Self, AnthropicError> {
+        Ok(AnthropicCountTokensRequest {
+            model: model.into(),
+            body: to_request(messages)?,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AnthropicTokenCount {
+    pub input_tokens: u64,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AnthropicUsage {
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub cache_creation_input_tokens: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub cache_read_input_tokens: Option<u64>,
+}
+
+impl From<AnthropicUsage> for UsageMetadata {
+    fn from(usage: AnthropicUsage) -> Self {
+        let mut metadata = UsageMetadata::new(usage.input_tokens, usage.output_tokens);
+        if let Some(tokens) = usage.cache_creation_input_tokens {
+            metadata = metadata.with_input_detail("cache_creation", tokens);
+        }
+        if let Some(tokens) = usage.cache_read_input_tokens {
+            metadata = metadata.with_input_detail("cache_read", tokens);
+        }
+        metadata
+    }
+}
+
+/// A Messages API response, as found in successful batch results.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AnthropicResponse {
+    pub id: String,
+    pub model: String,
+    pub content: Vec<AnthropicContent>,
+    #[serde(default)]
+    pub stop_reason: Option<String>,
+    #[serde(default)]
+    pub usage: Option<AnthropicUsage>,
+}
+
+impl AnthropicResponse {
+    /// Text blocks become the content and `tool_use` blocks tool calls; the
+    /// id, model and stop reason go to `response_metadata`.
+    pub fn into_ai_message(self) -> AiMessage {
+        let mut content = MessageContent::Text(String::new());
+        let mut calls = Vec::new();
+        for block in self.content {
+            match block {
+                AnthropicContent::Text { text } => content.append(MessageContent::Text(text)),
+                AnthropicContent::ToolUse { id, name, input } => {
+                    calls.push(ToolCall::new(id, name, input))
+                }
+                _ => {}
+            }
+        }
+        let mut message = AiMessage::new("")
+            .with_content(content)
+            .with_tool_calls(calls);
+        let metadata = &mut message.base.response_metadata;
+        metadata.insert("id", self.id);
+        metadata.insert("model", self.model);
+        if let Some(stop_reason) = self.stop_reason {
+            metadata.insert("stop_reason", stop_reason);
+        }
+        message.usage_metadata = self.usage.map(UsageMetadata::from);
+        message
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AnthropicBatchResult {
+    Succeeded { message: AnthropicResponse },
+    Errored { error: Value },
+    Canceled,
+    Expired,
+}
+
+/// One line of a batch results file.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AnthropicBatchResultLine {
+    pub custom_id: String,
+    pub result: AnthropicBatchResult,
+}
+
+/// A results line that did not produce a message. `custom_id` is missing
+/// only when the line itself could not be read.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AnthropicBatchItemError {
+    pub line: usize,
+    pub custom_id: Option<String>,
+    pub error: AnthropicError,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AnthropicBatchOutput {
+    pub messages: HashMap<String, AiMessage>,
+    pub errors: Vec<AnthropicBatchItemError>,
+}
+
+impl AnthropicBatchOutput {
+    pub fn is_clean(&self) -> bool {
+        self.errors.is_empty()
+    }
+}
+
+/// Parses a batch results `.jsonl` file into replies keyed by `custom_id`.
+/// Errored, canceled and expired entries and unreadable lines are collected
+/// in [`AnthropicBatchOutput::errors`].
+pub fn parse_anthropic_batch_results(input: &str) -> AnthropicBatchOutput {
+    let mut output = AnthropicBatchOutput::default();
+    for (index, line) in input.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let parsed: AnthropicBatchResultLine = match serde_json::from_str(line) {
+            Ok(parsed) => parsed,
+            Err(err) => {
+                output.errors.push(AnthropicBatchItemError {
+                    line: index + 1,
+                    custom_id: None,
+                    error: AnthropicError(format!("unreadable batch result: {}", err)),
+                });
+                continue;
+            }
+        };
+        let error = match parsed.result {
+            AnthropicBatchResult::Succeeded { message } => {
+                output
+                    .messages
+                    .insert(parsed.custom_id, message.into_ai_message());
+                continue;
+            }
+            AnthropicBatchResult::Errored { error } => format!("request errored: {}", error),
+            AnthropicBatchResult::Canceled => "request was canceled".to_string(),
+            AnthropicBatchResult::Expired => "request expired".to_string(),
+        };
+        output.errors.push(AnthropicBatchItemError {
+            line: index + 1,
+            custom_id: Some(parsed.custom_id),
+            error: AnthropicError(error),
+        });
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BaseMessage, HumanMessage, SystemMessage};
+    use serde_json::json;
+
+    #[test]
+    fn test_batch_and_count_tokens_payloads() {
+        let messages: Vec<AnyMessage> = vec![
+            SystemMessage::new("Be brief.").into(),
+            HumanMessage::new("Hi").into(),
+        ];
+        let options = RequestOptions::new("claude-sonnet-4-5")
+            .with_param("max_tokens", 256)
+            .with_param("temperature", 0.2);
+
+        let batch = to_anthropic_batch([("req-1", &messages[..])], &options).unwrap();
+
+        assert_eq!(
+            serde_json::to_value(&batch).unwrap()["requests"][0],
+            json!({
+                "custom_id": "req-1",
+                "params": {
+                    "model": "claude-sonnet-4-5",
+                    "max_tokens": 256,
+                    "temperature": 0.2,
+                    "system": "Be brief.",
+                    "messages": [{"role": "user", "content": [{"type": "text", "text": "Hi"}]}]
+                }
+            })
+        );
+        assert!(to_anthropic_batch([("req 1", &messages[..])], &options).is_err());
+        assert!(to_anthropic_batch(
+            [("a", &messages[..])],
+            &RequestOptions::new("claude-sonnet-4-5")
+        )
+        .is_err());
+
+        let count = AnthropicCountTokensRequest::new("claude-sonnet-4-5", &messages).unwrap();
+        let count = serde_json::to_value(count).unwrap();
+        assert_eq!(count["model"], "claude-sonnet-4-5");
+        assert_eq!(count["system"], "Be brief.");
+        let response: AnthropicTokenCount =
+            serde_json::from_value(json!({"input_tokens": 14})).unwrap();
+        assert_eq!(response.input_tokens, 14);
+    }
+
+    #[test]
+    fn test_parse_batch_results() {
+        let results = [
+            json!({
+                "custom_id": "req-1",
+                "result": {
+                    "type": "succeeded",
+                    "message": {
+                        "id": "msg_1",
+                        "type": "message",
+                        "role": "assistant",
+                        "model": "claude-sonnet-4-5",
+                        "content": [
+                            {"type": "text", "text": "Checking."},
+                            {"type": "tool_use", "id": "toolu_1", "name": "weather", "input": {"city": "Paris"}}
+                        ],
+                        "stop_reason": "tool_use",
+                        "usage": {"input_tokens": 20, "output_tokens": 9}
+                    }
+                }
+            })
+            .to_string(),
+            json!({"custom_id": "req-2", "result": {"type": "expired"}}).to_string(),
+        ]
+        .join("\n");
+
+        let output = parse_anthropic_batch_results(&results);
+
+        let reply = &output.messages["req-1"];
+        assert_eq!(reply.content(), "Checking.");
+        assert_eq!(reply.tool_calls()[0].name, "weather");
+        assert_eq!(
+            reply.response_metadata().get_str("stop_reason"),
+            Some("tool_use")
+        );
+        assert_eq!(reply.usage_metadata().unwrap().total_tokens, 29);
+        assert_eq!(output.errors.len(), 1);
+        assert_eq!(output.errors[0].custom_id.as_deref(), Some("req-2"));
+    }
+}
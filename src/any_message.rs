@@ -0,0 +1,125 @@
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::unknown_message::UnknownMessage;
+use crate::{
+    AiMessage, ChatMessage, HumanMessage, MessageEnum, RemoveMessage, SystemMessage, ToolMessage,
+};
+
+/// The owned holder for any built-in message struct. It is [`MessageEnum`]
+/// and serializes in the provider-style `role` format; use this module as a
+/// `#[serde(with = "messageforge::any_message")]` adapter for the `type`
+/// tagged format that keeps each struct's native serde shape.
+pub type AnyMessage = MessageEnum;
+
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum TaggedRef<'a> {
+    Ai(&'a AiMessage),
+    Human(&'a HumanMessage),
+    System(&'a SystemMessage),
+    Tool(&'a ToolMessage),
+    Chat(&'a ChatMessage),
+    Remove(&'a RemoveMessage),
+    Unknown(&'a UnknownMessage),
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum Tagged {
+    Ai(AiMessage),
+    Human(HumanMessage),
+    System(SystemMessage),
+    Tool(ToolMessage),
+    Chat(ChatMessage),
+    Remove(RemoveMessage),
+    Unknown(UnknownMessage),
+}
+
+pub fn serialize<S>(message: &MessageEnum, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    let tagged = match message {
+        MessageEnum::Ai(message) => TaggedRef::Ai(message),
+        MessageEnum::Human(message) => TaggedRef::Human(message),
+        MessageEnum::System(message) => TaggedRef::System(message),
+        MessageEnum::Tool(message) => TaggedRef::Tool(message),
+        MessageEnum::Chat(message) => TaggedRef::Chat(message),
+        MessageEnum::Remove(message) => TaggedRef::Remove(message),
+        MessageEnum::Unknown(message) => TaggedRef::Unknown(message),
+    };
+    tagged.serialize(serializer)
+}
+
+pub fn deserialize<'de, D>(deserializer: D) -> Result<MessageEnum, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Ok(match Tagged::deserialize(deserializer)? {
+        Tagged::Ai(message) => MessageEnum::Ai(message),
+        Tagged::Human(message) => MessageEnum::Human(message),
+        Tagged::System(message) => MessageEnum::System(message),
+        Tagged::Tool(message) => MessageEnum::Tool(message),
+        Tagged::Chat(message) => MessageEnum::Chat(message),
+        Tagged::Remove(message) => MessageEnum::Remove(message),
+        Tagged::Unknown(message) => MessageEnum::Unknown(message),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tool_message::ToolStatus;
+    use crate::{BaseMessage, MessageType};
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Record {
+        #[serde(with = "crate::any_message")]
+        message: AnyMessage,
+    }
+
+    fn messages() -> Vec<AnyMessage> {
+        vec![
+            SystemMessage::new("Be brief.").into(),
+            HumanMessage::new("Weather?").into(),
+            AiMessage::new("Checking.").into(),
+            ToolMessage::new("Sunny", "call_1".to_string(), None, ToolStatus::Success).into(),
+            UnknownMessage::new("critic", "Fine").into(),
+            ChatMessage::new("Agreed.", "moderator".to_string()).into(),
+            RemoveMessage::new("m1").into(),
+        ]
+    }
+
+    #[test]
+    fn test_tagged_round_trip() {
+        let records: Vec<Record> = messages()
+            .into_iter()
+            .map(|message| Record { message })
+            .collect();
+        let json = serde_json::to_string(&records).unwrap();
+        let parsed: Vec<Record> = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(parsed, records);
+        let value = serde_json::to_value(&parsed[3]).unwrap();
+        assert_eq!(value["message"]["type"], "tool");
+        assert_eq!(value["message"]["tool_call_id"], "call_1");
+        let value = serde_json::to_value(&parsed[5]).unwrap();
+        assert_eq!(value["message"]["type"], "chat");
+        assert_eq!(value["message"]["role"], "moderator");
+    }
+
+    #[test]
+    fn test_role_format_round_trip() {
+        let messages = messages();
+        assert_eq!(messages[2].message_type(), &MessageType::Ai);
+        assert_eq!(messages[4].role(), "critic");
+        assert_eq!(messages[5].message_type(), &MessageType::Chat);
+
+        let json = serde_json::to_string(&messages).unwrap();
+        assert_eq!(
+            serde_json::from_str::<Vec<AnyMessage>>(&json).unwrap(),
+            messages
+        );
+        assert_eq!(messages[6].role(), "remove");
+    }
+}
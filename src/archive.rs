@@ -0,0 +1,355 @@
+use std::fmt;
+use std::io::{self, Write};
+
+use crate::{Conversation, MessageEnum};
+
+/// Archive layout, all integers little-endian:
+///
+/// ```text
+/// "MFAR" u16:version
+/// frames:  { u32:len json-message }*
+/// index:   u64:message_count u64:offset*
+///          u64:conversation_count { u64:first u64:count u32:len session_id }*
+/// trailer: u64:index_offset "MFAR"
+/// ```
+///
+/// Only the conversation table is read up front; message offsets and frames
+/// are read on access, so the archive can be memory-mapped and scanned
+/// without loading it.
+pub const ARCHIVE_FORMAT_VERSION: u16 = 1;
+
+const MAGIC: [u8; 4] = *b"MFAR";
+const HEADER_LEN: usize = MAGIC.len() + 2;
+const TRAILER_LEN: usize = 8 + MAGIC.len();
+
+#[derive(Debug)]
+pub enum ArchiveError {
+    Io(io::Error),
+    Json(serde_json::Error),
+    Corrupt(String),
+    UnsupportedVersion(u16),
+    OutOfRange { index: usize, len: usize },
+}
+
+impl fmt::Display for ArchiveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ArchiveError::Io(err) => write!(f, "Archive I/O error: {}", err),
+            ArchiveError::Json(err) => write!(f, "Archive message is not valid JSON: {}", err),
+            ArchiveError::Corrupt(reason) => write!(f, "Corrupt archive: {}", reason),
+            ArchiveError::UnsupportedVersion(version) => write!(
+                f,
+                "Unsupported archive version {} (expected {})",
+                version, ARCHIVE_FORMAT_VERSION
+            ),
+            ArchiveError::OutOfRange { index, len } => {
+                write!(f, "Index {} is out of range for {} entries", index, len)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ArchiveError {}
+
+impl From<io::Error> for ArchiveError {
+    fn from(err: io::Error) -> Self {
+        ArchiveError::Io(err)
+    }
+}
+
+impl From<serde_json::Error> for ArchiveError {
+    fn from(err: serde_json::Error) -> Self {
+        ArchiveError::Json(err)
+    }
+}
+
+struct ConversationEntry {
+    first: usize,
+    count: usize,
+    session_id: Option<String>,
+}
+
+pub struct ArchiveWriter<W: Write> {
+    writer: W,
+    position: u64,
+    offsets: Vec<u64>,
+    conversations: Vec<ConversationEntry>,
+}
+
+impl<W: Write> ArchiveWriter<W> {
+    pub fn new(mut writer: W) -> Result<Self, ArchiveError> {
+        writer.write_all(&MAGIC)?;
+        writer.write_all(&ARCHIVE_FORMAT_VERSION.to_le_bytes())?;
+        Ok(ArchiveWriter {
+            writer,
+            position: HEADER_LEN as u64,
+            offsets: Vec::new(),
+            conversations: Vec::new(),
+        })
+    }
+
+    pub fn write_conversation(&mut self, conversation: &Conversation) -> Result<(), ArchiveError> {
+        let first = self.offsets.len();
+        for message in conversation {
+            let frame = serde_json::to_vec(message)?;
+            let len = u32::try_from(frame.len())
+                .map_err(|_| ArchiveError::Corrupt("message frame exceeds 4 GiB".to_string()))?;
+            self.offsets.push(self.position);
+            self.writer.write_all(&len.to_le_bytes())?;
+            self.writer.write_all(&frame)?;
+            self.position += 4 + frame.len() as u64;
+        }
+        self.conversations.push(ConversationEntry {
+            first,
+            count: conversation.len(),
+            session_id: conversation.session_id().map(str::to_string),
+        });
+        Ok(())
+    }
+
+    /// Writes the index and trailer and hands back the underlying writer.
+    pub fn finish(mut self) -> Result<W, ArchiveError> {
+        let index_offset = self.position;
+        let w = &mut self.writer;
+        w.write_all(&(self.offsets.len() as u64).to_le_bytes())?;
+        for offset in &self.offsets {
+            w.write_all(&offset.to_le_bytes())?;
+        }
+        w.write_all(&(self.conversations.len() as u64).to_le_bytes())?;
+        for entry in &self.conversations {
+            w.write_all(&(entry.first as u64).to_le_bytes())?;
+            w.write_all(&(entry.count as u64).to_le_bytes())?;
+            let session = entry.session_id.as_deref().unwrap_or_default().as_bytes();
+            w.write_all(&(session.len() as u32).to_le_bytes())?;
+            w.write_all(session)?;
+        }
+        w.write_all(&index_offset.to_le_bytes())?;
+        w.write_all(&MAGIC)?;
+        w.flush()?;
+        Ok(self.writer)
+    }
+}
+
+/// A read-only archive over any byte buffer: a `Vec<u8>`, a slice, or a
+/// memory map (see [`ConversationArchive::open_mmap`]).
+pub struct ConversationArchive<B: AsRef<[u8]>> {
+    bytes: B,
+    offsets_start: usize,
+    message_count: usize,
+    conversations: Vec<ConversationEntry>,
+}
+
+impl<B: AsRef<[u8]>> ConversationArchive<B> {
+    pub fn open(bytes: B) -> Result<Self, ArchiveError> {
+        let data = bytes.as_ref();
+        if data.len() < HEADER_LEN + TRAILER_LEN || data[..MAGIC.len()] != MAGIC {
+            return Err(corrupt("missing archive header"));
+        }
+        let version = u16::from_le_bytes([data[4], data[5]]);
+        if version != ARCHIVE_FORMAT_VERSION {
+            return Err(ArchiveError::UnsupportedVersion(version));
+        }
+        if data[data.len() - MAGIC.len()..] != MAGIC {
+            return Err(corrupt("missing archive trailer"));
+        }
+
+        let index_offset = read_u64(data, data.len() - TRAILER_LEN)? as usize;
+        let message_count = read_u64(data, index_offset)? as usize;
+        let offsets_start = index_offset + 8;
+        let mut cursor = message_count
+            .checked_mul(8)
+            .and_then(|len| offsets_start.checked_add(len))
+            .ok_or_else(|| corrupt("message index overflows"))?;
+
+        let conversation_count = read_u64(data, cursor)? as usize;
+        cursor += 8;
+        let mut conversations = Vec::new();
+        for _ in 0..conversation_count {
+            let first = read_u64(data, cursor)? as usize;
+            let count = read_u64(data, cursor + 8)? as usize;
+            let len = read_u32(data, cursor + 16)? as usize;
+            let session = slice(data, cursor + 20, len)?;
+            let session =
+                std::str::from_utf8(session).map_err(|_| corrupt("session id is not UTF-8"))?;
+            if first.saturating_add(count) > message_count {
+                return Err(corrupt("conversation points past the message index"));
+            }
+            conversations.push(ConversationEntry {
+                first,
+                count,
+                session_id: (!session.is_empty()).then(|| session.to_string()),
+            });
+            cursor += 20 + len;
+        }
+
+        Ok(ConversationArchive {
+            bytes,
+            offsets_start,
+            message_count,
+            conversations,
+        })
+    }
+
+    pub fn message_count(&self) -> usize {
+        self.message_count
+    }
+
+    pub fn conversation_count(&self) -> usize {
+        self.conversations.len()
+    }
+
+    pub fn session_id(&self, conversation: usize) -> Option<&str> {
+        self.conversations
+            .get(conversation)
+            .and_then(|entry| entry.session_id.as_deref())
+    }
+
+    /// The undecoded JSON frame of a message.
+    pub fn raw_message(&self, index: usize) -> Result<&[u8], ArchiveError> {
+        if index >= self.message_count {
+            return Err(ArchiveError::OutOfRange {
+                index,
+                len: self.message_count,
+            });
+        }
+        let data = self.bytes.as_ref();
+        let offset = read_u64(data, self.offsets_start + index * 8)? as usize;
+        let len = read_u32(data, offset)? as usize;
+        slice(data, offset + 4, len)
+    }
+
+    pub fn message(&self, index: usize) -> Result<MessageEnum, ArchiveError> {
+        Ok(serde_json::from_slice(self.raw_message(index)?)?)
+    }
+
+    pub fn messages(&self) -> impl Iterator<Item = Result<MessageEnum, ArchiveError>> + '_ {
+        (0..self.message_count).map(move |index| self.message(index))
+    }
+
+    pub fn conversation(&self, index: usize) -> Result<Conversation, ArchiveError> {
+        let entry = self
+            .conversations
+            .get(index)
+            .ok_or(ArchiveError::OutOfRange {
+                index,
+                len: self.conversations.len(),
+            })?;
+        let messages = (entry.first..entry.first + entry.count)
+            .map(|message| self.message(message))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut conversation = Conversation::from(messages);
+        conversation.set_session_id(entry.session_id.clone());
+        Ok(conversation)
+    }
+}
+
+#[cfg(feature = "mmap")]
+impl ConversationArchive<memmap2::Mmap> {
+    /// Maps the archive at `path` read-only.
+    ///
+    /// The file must not be modified while it is mapped; frames are read
+    /// directly from the mapping.
+    pub fn open_mmap(path: impl AsRef<std::path::Path>) -> Result<Self, ArchiveError> {
+        let file = std::fs::File::open(path)?;
+        // SAFETY: the mapping is read-only and the caller guarantees the file
+        // is not truncated or rewritten while the archive is open.
+        let map = unsafe { memmap2::Mmap::map(&file)? };
+        ConversationArchive::open(map)
+    }
+}
+
+fn corrupt(reason: &str) -> ArchiveError {
+    ArchiveError::Corrupt(reason.to_string())
+}
+
+fn slice(data: &[u8], start: usize, len: usize) -> Result<&[u8], ArchiveError> {
+    start
+        .checked_add(len)
+        .and_then(|end| data.get(start..end))
+        .ok_or_else(|| corrupt("read past end of archive"))
+}
+
+fn read_u64(data: &[u8], at: usize) -> Result<u64, ArchiveError> {
+    let bytes = slice(data, at, 8)?;
+    Ok(u64::from_le_bytes(bytes.try_into().unwrap_or_default()))
+}
+
+fn read_u32(data: &[u8], at: usize) -> Result<u32, ArchiveError> {
+    let bytes = slice(data, at, 4)?;
+    Ok(u32::from_le_bytes(bytes.try_into().unwrap_or_default()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AiMessage, BaseMessage, HumanMessage};
+
+    fn archive_bytes() -> Vec<u8> {
+        let mut first = Conversation::with_session_id("s1");
+        first.push(HumanMessage::new("Hi"));
+        first.push(AiMessage::new("Hello!"));
+        let mut second = Conversation::new();
+        second.push(HumanMessage::new("Another"));
+
+        let mut writer = ArchiveWriter::new(Vec::new()).unwrap();
+        writer.write_conversation(&first).unwrap();
+        writer.write_conversation(&second).unwrap();
+        writer.finish().unwrap()
+    }
+
+    #[test]
+    fn test_archive_round_trip() {
+        let archive = ConversationArchive::open(archive_bytes()).unwrap();
+
+        assert_eq!(archive.message_count(), 3);
+        assert_eq!(archive.conversation_count(), 2);
+        assert_eq!(archive.session_id(0), Some("s1"));
+        assert_eq!(archive.session_id(1), None);
+        assert_eq!(archive.message(1).unwrap().content(), "Hello!");
+
+        let first = archive.conversation(0).unwrap();
+        assert_eq!(first.session_id(), Some("s1"));
+        assert_eq!(first.len(), 2);
+        let contents: Vec<String> = archive
+            .messages()
+            .map(|m| m.unwrap().content().to_string())
+            .collect();
+        assert_eq!(contents, vec!["Hi", "Hello!", "Another"]);
+    }
+
+    #[test]
+    fn test_archive_rejects_bad_input() {
+        let bytes = archive_bytes();
+
+        assert!(matches!(
+            ConversationArchive::open(&bytes[..bytes.len() - 1]),
+            Err(ArchiveError::Corrupt(_))
+        ));
+        let mut wrong_version = bytes.clone();
+        wrong_version[4] = 9;
+        assert!(matches!(
+            ConversationArchive::open(wrong_version),
+            Err(ArchiveError::UnsupportedVersion(9))
+        ));
+
+        let archive = ConversationArchive::open(&bytes[..]).unwrap();
+        assert!(matches!(
+            archive.message(3),
+            Err(ArchiveError::OutOfRange { index: 3, len: 3 })
+        ));
+    }
+
+    #[cfg(feature = "mmap")]
+    #[test]
+    fn test_archive_open_mmap() {
+        let path = std::env::temp_dir().join(format!("messageforge-{}.mfar", std::process::id()));
+        std::fs::write(&path, archive_bytes()).unwrap();
+
+        let archive = ConversationArchive::open_mmap(&path).unwrap();
+        assert_eq!(archive.message(2).unwrap().content(), "Another");
+
+        drop(archive);
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,165 @@
+use async_openai::types::{
+    ChatCompletionRequestMessage, ChatCompletionResponseMessage, CompletionUsage,
+    CreateChatCompletionResponse,
+};
+use serde_json::Value;
+
+use crate::openai::{from_openai_message, to_openai_message};
+use crate::{AiMessage, AnyMessage, OpenAiError, UsageMetadata};
+
+// Both sides speak the chat-completions wire format, so conversions go
+// through it rather than mapping every async-openai variant by hand.
+
+impl TryFrom<&AnyMessage> for ChatCompletionRequestMessage {
+    type Error = OpenAiError;
+
+    /// Fails for roles async-openai has no variant for, such as custom
+    /// chat roles.
+    fn try_from(message: &AnyMessage) -> Result<Self, OpenAiError> {
+        serde_json::from_value(to_openai_message(message)?).map_err(wire_error)
+    }
+}
+
+impl TryFrom<&ChatCompletionRequestMessage> for AnyMessage {
+    type Error = OpenAiError;
+
+    fn try_from(message: &ChatCompletionRequestMessage) -> Result<Self, OpenAiError> {
+        from_openai_message(&serde_json::to_value(message).map_err(wire_error)?)
+    }
+}
+
+impl TryFrom<&ChatCompletionResponseMessage> for AiMessage {
+    type Error = OpenAiError;
+
+    fn try_from(message: &ChatCompletionResponseMessage) -> Result<Self, OpenAiError> {
+        match from_openai_message(&serde_json::to_value(message).map_err(wire_error)?)? {
+            AnyMessage::Ai(ai) => Ok(ai),
+            _ => Err(OpenAiError(
+                "completion is not an assistant message".to_string(),
+            )),
+        }
+    }
+}
+
+/// The first choice, with the completion's `id`, `model` and
+/// `finish_reason` in `response_metadata` and its usage attached.
+impl TryFrom<&CreateChatCompletionResponse> for AiMessage {
+    type Error = OpenAiError;
+
+    fn try_from(response: &CreateChatCompletionResponse) -> Result<Self, OpenAiError> {
+        let choice = response
+            .choices
+            .first()
+            .ok_or_else(|| OpenAiError("completion without choices".to_string()))?;
+        let mut ai = AiMessage::try_from(&choice.message)?;
+        let metadata = &mut ai.base.response_metadata;
+        metadata.insert("id", response.id.clone());
+        metadata.insert("model", response.model.clone());
+        if let Some(reason) = &choice.finish_reason {
+            metadata.insert(
+                "finish_reason",
+                serde_json::to_value(reason).unwrap_or(Value::Null),
+            );
+        }
+        ai.set_usage_metadata(response.usage.as_ref().map(UsageMetadata::from));
+        Ok(ai)
+    }
+}
+
+impl From<&CompletionUsage> for UsageMetadata {
+    fn from(usage: &CompletionUsage) -> Self {
+        let mut metadata =
+            UsageMetadata::new(usage.prompt_tokens.into(), usage.completion_tokens.into());
+        metadata.total_tokens = usage.total_tokens.into();
+        if let Some(details) = &usage.prompt_tokens_details {
+            for (kind, tokens) in [
+                ("cache_read", details.cached_tokens),
+                ("audio", details.audio_tokens),
+            ] {
+                if let Some(tokens) = tokens.filter(|tokens| *tokens > 0) {
+                    metadata = metadata.with_input_detail(kind, tokens.into());
+                }
+            }
+        }
+        if let Some(details) = &usage.completion_tokens_details {
+            for (kind, tokens) in [
+                ("reasoning", details.reasoning_tokens),
+                ("audio", details.audio_tokens),
+            ] {
+                if let Some(tokens) = tokens.filter(|tokens| *tokens > 0) {
+                    metadata = metadata.with_output_detail(kind, tokens.into());
+                }
+            }
+        }
+        metadata
+    }
+}
+
+fn wire_error(err: serde_json::Error) -> OpenAiError {
+    OpenAiError(format!("async-openai type mismatch: {}", err))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BaseMessage, ChatMessage, HumanMessage, ToolCall};
+    use serde_json::json;
+
+    #[test]
+    fn test_request_message_round_trip() {
+        let messages: Vec<AnyMessage> = vec![
+            HumanMessage::new("Weather?").into(),
+            AiMessage::new("")
+                .with_tool_calls(vec![ToolCall::new(
+                    "call_1",
+                    "weather",
+                    json!({"city": "Paris"}),
+                )])
+                .into(),
+        ];
+
+        for message in &messages {
+            let request = ChatCompletionRequestMessage::try_from(message).unwrap();
+            assert_eq!(&AnyMessage::try_from(&request).unwrap(), message);
+        }
+        assert!(matches!(
+            ChatCompletionRequestMessage::try_from(&messages[0]).unwrap(),
+            ChatCompletionRequestMessage::User(_)
+        ));
+        let critic: AnyMessage = ChatMessage::new("Fine", "critic".to_string()).into();
+        assert!(ChatCompletionRequestMessage::try_from(&critic).is_err());
+    }
+
+    #[test]
+    fn test_completion_response_to_ai_message() {
+        let response: CreateChatCompletionResponse = serde_json::from_value(json!({
+            "id": "chatcmpl-1",
+            "object": "chat.completion",
+            "created": 1,
+            "model": "gpt-4o",
+            "choices": [{
+                "index": 0,
+                "message": {"role": "assistant", "content": "Hi there"},
+                "finish_reason": "stop"
+            }],
+            "usage": {
+                "prompt_tokens": 9,
+                "completion_tokens": 3,
+                "total_tokens": 12,
+                "prompt_tokens_details": {"cached_tokens": 4},
+                "completion_tokens_details": {"reasoning_tokens": 0}
+            }
+        }))
+        .unwrap();
+
+        let ai = AiMessage::try_from(&response).unwrap();
+
+        assert_eq!(ai.content(), "Hi there");
+        assert_eq!(ai.response_metadata()["model"], "gpt-4o");
+        assert_eq!(ai.response_metadata()["finish_reason"], "stop");
+        assert_eq!(
+            ai.usage_metadata(),
+            Some(&UsageMetadata::new(9, 3).with_input_detail("cache_read", 4))
+        );
+    }
+}
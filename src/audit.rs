@@ -0,0 +1,209 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, PoisonError};
+
+use serde::{Deserialize, Serialize};
+
+use crate::clock::{Clock, SystemClock};
+use crate::{BaseMessage, Conversation, MessageEnum};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuditEvent {
+    pub actor: String,
+    pub session: String,
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub message_ids: Vec<String>,
+    pub message_count: usize,
+    pub timestamp_ms: u64,
+}
+
+pub trait AuditSink {
+    fn record(&self, event: AuditEvent);
+}
+
+#[derive(Debug, Default)]
+pub struct InMemoryAuditSink {
+    events: Mutex<Vec<AuditEvent>>,
+}
+
+impl InMemoryAuditSink {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn events(&self) -> Vec<AuditEvent> {
+        self.events
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    /// Exports the recorded events as JSON Lines, one event per line.
+    pub fn to_json_lines(&self) -> serde_json::Result<String> {
+        self.events
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .iter()
+            .map(|event| serde_json::to_string(event).map(|line| line + "\n"))
+            .collect()
+    }
+}
+
+impl AuditSink for InMemoryAuditSink {
+    fn record(&self, event: AuditEvent) {
+        self.events
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push(event);
+    }
+}
+
+/// Conversations keyed by session id, where every read is reported to an
+/// [`AuditSink`] along with the reader and the ids of the messages returned.
+pub struct AuditedHistory<S: AuditSink> {
+    conversations: HashMap<String, Conversation>,
+    sink: S,
+    clock: Arc<dyn Clock>,
+}
+
+impl<S: AuditSink> AuditedHistory<S> {
+    pub fn new(sink: S) -> Self {
+        AuditedHistory {
+            conversations: HashMap::new(),
+            sink,
+            clock: Arc::new(SystemClock),
+        }
+    }
+
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    pub fn sink(&self) -> &S {
+        &self.sink
+    }
+
+    pub fn insert(&mut self, session: impl Into<String>, conversation: Conversation) {
+        self.conversations.insert(session.into(), conversation);
+    }
+
+    pub fn append(&mut self, session: &str, message: impl Into<MessageEnum>) {
+        self.conversations
+            .entry(session.to_string())
+            .or_default()
+            .push(message);
+    }
+
+    pub fn read(&self, actor: &str, session: &str) -> Option<&Conversation> {
+        let conversation = self.conversations.get(session)?;
+        self.audit(actor, session, conversation.iter());
+        Some(conversation)
+    }
+
+    pub fn read_messages(&self, actor: &str, session: &str, ids: &[&str]) -> Vec<&MessageEnum> {
+        let messages: Vec<&MessageEnum> = self
+            .conversations
+            .get(session)
+            .map(|conversation| {
+                conversation
+                    .iter()
+                    .filter(|message| message.id().is_some_and(|id| ids.contains(&id)))
+                    .collect()
+            })
+            .unwrap_or_default();
+        if !messages.is_empty() {
+            self.audit(actor, session, messages.iter().copied());
+        }
+        messages
+    }
+
+    fn audit<'a>(
+        &self,
+        actor: &str,
+        session: &str,
+        messages: impl Iterator<Item = &'a MessageEnum>,
+    ) {
+        let mut message_count = 0;
+        let message_ids = messages
+            .inspect(|_| message_count += 1)
+            .filter_map(|message| message.id().map(str::to_string))
+            .collect();
+        let timestamp_ms = self.clock.now_ms();
+        self.sink.record(AuditEvent {
+            actor: actor.to_string(),
+            session: session.to_string(),
+            message_ids,
+            message_count,
+            timestamp_ms,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::MockClock;
+    use crate::{AiMessage, HumanMessage};
+
+    fn history() -> AuditedHistory<InMemoryAuditSink> {
+        let mut history = AuditedHistory::new(InMemoryAuditSink::new())
+            .with_clock(Arc::new(MockClock::new(1_700_000_000_000)));
+        let mut question = HumanMessage::new("What is my balance?");
+        question.set_id(Some("m1".to_string()));
+        let mut answer = AiMessage::new("$42.");
+        answer.set_id(Some("m2".to_string()));
+        history.append("s1", question);
+        history.append("s1", answer);
+        history
+    }
+
+    #[test]
+    fn test_read_records_event() {
+        let history = history();
+
+        let conversation = history.read("dr_smith", "s1").unwrap();
+
+        assert_eq!(conversation.len(), 2);
+        let events = history.sink().events();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].actor, "dr_smith");
+        assert_eq!(events[0].session, "s1");
+        assert_eq!(events[0].message_ids, vec!["m1", "m2"]);
+        assert_eq!(events[0].message_count, 2);
+        assert_eq!(events[0].timestamp_ms, 1_700_000_000_000);
+    }
+
+    #[test]
+    fn test_read_missing_session_is_not_audited() {
+        let history = history();
+
+        assert!(history.read("auditor", "missing").is_none());
+        assert!(history.sink().events().is_empty());
+    }
+
+    #[test]
+    fn test_read_messages_records_only_returned_ids() {
+        let history = history();
+
+        let messages = history.read_messages("support", "s1", &["m2", "m9"]);
+
+        assert_eq!(messages.len(), 1);
+        let events = history.sink().events();
+        assert_eq!(events[0].message_ids, vec!["m2"]);
+    }
+
+    #[test]
+    fn test_export_json_lines() {
+        let history = history();
+        history.read("a", "s1");
+        history.read("b", "s1");
+
+        let exported = history.sink().to_json_lines().unwrap();
+        let lines: Vec<AuditEvent> = exported
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+
+        assert_eq!(lines, history.sink().events());
+    }
+}
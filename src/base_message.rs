@@ -1,42 +1,131 @@
 use std::{
-    collections::HashMap,
+    borrow::Cow,
     fmt::{self, Debug},
 };
 
-use crate::MessageType;
+use crate::{
+    ContentBlock, Extensions, InvalidToolCall, MessageContent, MessageType, Metadata, Provenance,
+    ToolCall, VoiceMetadata,
+};
 use serde::{Deserialize, Serialize};
 
+/// Fields shared by every message type. Build it with
+/// [`BaseMessageFields::new`] and set the fields you need: new fields may be
+/// added in any release.
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[non_exhaustive]
 pub struct BaseMessageFields {
-    pub content: String,
+    pub content: MessageContent,
 
     #[serde(default)]
     pub example: bool,
 
+    #[serde(skip_serializing_if = "MessageType::is_unknown")]
     pub message_type: MessageType,
 
-    #[serde(skip_serializing_if = "HashMap::is_empty", default)]
-    pub additional_kwargs: HashMap<String, String>,
+    #[serde(skip_serializing_if = "Metadata::is_empty", default)]
+    pub additional_kwargs: Metadata,
 
-    #[serde(skip_serializing_if = "HashMap::is_empty", default)]
-    pub response_metadata: HashMap<String, String>,
+    #[serde(skip_serializing_if = "Metadata::is_empty", default)]
+    pub response_metadata: Metadata,
 
     #[serde(skip_serializing_if = "Option::is_none", default)]
     pub id: Option<String>,
 
     #[serde(skip_serializing_if = "Option::is_none", default)]
     pub name: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub provenance: Option<Provenance>,
+
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub voice: Option<VoiceMetadata>,
+
+    /// Pinned messages are never trimmed or summarized away.
+    #[serde(skip_serializing_if = "is_false", default)]
+    pub pinned: bool,
+
+    /// Runtime-only state; never serialized.
+    #[serde(skip)]
+    pub extensions: Extensions,
+}
+
+impl BaseMessageFields {
+    /// Empty metadata, no id or name, not an example and not pinned.
+    pub fn new(content: impl Into<MessageContent>, message_type: MessageType) -> Self {
+        BaseMessageFields {
+            content: content.into(),
+            example: false,
+            message_type,
+            additional_kwargs: Metadata::new(),
+            response_metadata: Metadata::new(),
+            id: None,
+            name: None,
+            provenance: None,
+            voice: None,
+            pinned: false,
+            extensions: Extensions::default(),
+        }
+    }
+}
+
+fn is_false(value: &bool) -> bool {
+    !*value
 }
 
 pub trait BaseMessage {
+    /// Plain-text content; for block content, the first text block.
     fn content(&self) -> &str;
     fn message_type(&self) -> &MessageType;
     fn role(&self) -> &str;
     fn name(&self) -> Option<&str>;
     fn is_example(&self) -> bool;
-    fn additional_kwargs(&self) -> &std::collections::HashMap<String, String>;
-    fn response_metadata(&self) -> &std::collections::HashMap<String, String>;
+    fn additional_kwargs(&self) -> &Metadata;
+    fn response_metadata(&self) -> &Metadata;
     fn id(&self) -> Option<&str>;
+
+    fn tool_calls(&self) -> &[ToolCall] {
+        &[]
+    }
+
+    fn invalid_tool_calls(&self) -> &[InvalidToolCall] {
+        &[]
+    }
+
+    fn content_blocks(&self) -> Cow<'_, [ContentBlock]> {
+        Cow::Owned(vec![ContentBlock::text(self.content())])
+    }
+
+    /// All text blocks concatenated.
+    fn text(&self) -> Cow<'_, str> {
+        Cow::Borrowed(self.content())
+    }
+
+    /// A multi-line rendering for reading in a terminal: a banner with the
+    /// role, the speaker name, the text and any tool calls.
+    fn pretty_repr(&self) -> String {
+        let mut role = self.role().to_string();
+        if let Some(initial) = role.get_mut(..1) {
+            initial.make_ascii_uppercase();
+        }
+        let mut out = format!("{:=^80}", format!(" {} Message ", role));
+        if let Some(name) = self.name() {
+            out.push_str("\nName: ");
+            out.push_str(name);
+        }
+        out.push_str("\n\n");
+        out.push_str(&self.text());
+        if !self.tool_calls().is_empty() {
+            out.push_str("\nTool Calls:");
+            for call in self.tool_calls() {
+                out.push_str(&format!(
+                    "\n  {} ({})\n    Args: {}",
+                    call.name, call.id, call.args
+                ));
+            }
+        }
+        out
+    }
 }
 
 impl Debug for dyn BaseMessage {
@@ -0,0 +1,642 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::lineage::ForkOrigin;
+use crate::tool_message::ToolStatus;
+use crate::trash::TrashedMessage;
+use crate::unknown_message::UnknownMessage;
+use crate::{
+    AiMessage, BaseMessage, BaseMessageFields, ChatMessage, ContentBlock, Conversation, Extensions,
+    HumanMessage, InvalidToolCall, Logprobs, MessageContent, MessageEnum, MessageType, Metadata,
+    Provenance, RemoveMessage, SpeechSegment, SystemMessage, TokenLogprob, ToolCall, ToolMessage,
+    TopLogprob, UsageMetadata, VoiceMetadata,
+};
+
+/// Bumped whenever the wire layout below changes; older payloads are rejected
+/// rather than misread.
+pub const BINARY_FORMAT_VERSION: u16 = 10;
+
+const MAGIC: [u8; 4] = *b"MFCV";
+const HEADER_LEN: usize = MAGIC.len() + 2;
+
+#[derive(Debug)]
+pub enum BinaryError {
+    BadMagic,
+    UnsupportedVersion(u16),
+    Encode(String),
+    Decode(String),
+}
+
+impl fmt::Display for BinaryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BinaryError::BadMagic => write!(f, "Not a messageforge binary conversation"),
+            BinaryError::UnsupportedVersion(version) => write!(
+                f,
+                "Unsupported binary format version {} (expected {})",
+                version, BINARY_FORMAT_VERSION
+            ),
+            BinaryError::Encode(err) => write!(f, "Binary encoding failed: {}", err),
+            BinaryError::Decode(err) => write!(f, "Binary decoding failed: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for BinaryError {}
+
+// Binary formats are not self-describing, so the JSON shape (flattened and
+// with skipped fields) cannot be reused; these mirrors spell every field out.
+#[derive(Serialize, Deserialize)]
+struct WireConversation {
+    session_id: Option<String>,
+    forked_from: Option<(String, String)>,
+    messages: Vec<WireMessage>,
+    trash: Vec<WireTrashed>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct WireTrashed {
+    index: u64,
+    deleted_at_ms: u64,
+    message: WireMessage,
+}
+
+#[derive(Serialize, Deserialize)]
+struct WireMessage {
+    message_type: String,
+    content: WireContent,
+    example: bool,
+    // Values are JSON text, like tool call args: neither format can carry
+    // a self-describing `Value`.
+    additional_kwargs: HashMap<String, String>,
+    response_metadata: HashMap<String, String>,
+    id: Option<String>,
+    name: Option<String>,
+    provenance: Option<WireProvenance>,
+    voice: Option<WireVoice>,
+    logprobs: Option<Vec<WireTokenLogprob>>,
+    usage_metadata: Option<WireUsage>,
+    tool_calls: Vec<WireToolCall>,
+    invalid_tool_calls: Vec<WireInvalidToolCall>,
+    pinned: bool,
+    tool: Option<WireTool>,
+    /// The free-form role of a chat message.
+    role: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+enum WireContent {
+    Text(String),
+    Blocks(Vec<WireContentBlock>),
+}
+
+// `ContentBlock` is internally tagged, which binary formats cannot decode.
+#[derive(Serialize, Deserialize)]
+enum WireContentBlock {
+    Text(String),
+    ImageUrl(String, Option<String>),
+    Image(String, String),
+    Audio(String, String),
+    Document(String, String, Option<String>),
+}
+
+impl From<&MessageContent> for WireContent {
+    fn from(content: &MessageContent) -> Self {
+        match content {
+            MessageContent::Text(text) => WireContent::Text(text.clone()),
+            MessageContent::Blocks(blocks) => WireContent::Blocks(
+                blocks
+                    .iter()
+                    .map(|block| match block.clone() {
+                        ContentBlock::Text { text } => WireContentBlock::Text(text),
+                        ContentBlock::ImageUrl { url, detail } => {
+                            WireContentBlock::ImageUrl(url, detail)
+                        }
+                        ContentBlock::Image { mime_type, data } => {
+                            WireContentBlock::Image(mime_type, data)
+                        }
+                        ContentBlock::Audio { mime_type, data } => {
+                            WireContentBlock::Audio(mime_type, data)
+                        }
+                        ContentBlock::Document {
+                            mime_type,
+                            data,
+                            title,
+                        } => WireContentBlock::Document(mime_type, data, title),
+                    })
+                    .collect(),
+            ),
+        }
+    }
+}
+
+impl From<WireContent> for MessageContent {
+    fn from(wire: WireContent) -> Self {
+        match wire {
+            WireContent::Text(text) => MessageContent::Text(text),
+            WireContent::Blocks(blocks) => MessageContent::Blocks(
+                blocks
+                    .into_iter()
+                    .map(|block| match block {
+                        WireContentBlock::Text(text) => ContentBlock::Text { text },
+                        WireContentBlock::ImageUrl(url, detail) => {
+                            ContentBlock::ImageUrl { url, detail }
+                        }
+                        WireContentBlock::Image(mime_type, data) => {
+                            ContentBlock::Image { mime_type, data }
+                        }
+                        WireContentBlock::Audio(mime_type, data) => {
+                            ContentBlock::Audio { mime_type, data }
+                        }
+                        WireContentBlock::Document(mime_type, data, title) => {
+                            ContentBlock::Document {
+                                mime_type,
+                                data,
+                                title,
+                            }
+                        }
+                    })
+                    .collect(),
+            ),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct WireProvenance {
+    component: String,
+    step: Option<String>,
+    model: Option<String>,
+    template_id: Option<String>,
+    git_sha: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct WireVoice {
+    duration_ms: u64,
+    audio_blob: Option<String>,
+    transcript_confidence: Option<f32>,
+    segments: Vec<WireSpeechSegment>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct WireSpeechSegment {
+    speaker: String,
+    start_ms: u64,
+    end_ms: u64,
+    text: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct WireTokenLogprob {
+    token: String,
+    logprob: f64,
+    top_logprobs: Vec<(String, f64)>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct WireUsage {
+    input_tokens: u64,
+    output_tokens: u64,
+    total_tokens: u64,
+    input_token_details: Vec<(String, u64)>,
+    output_token_details: Vec<(String, u64)>,
+}
+
+// Arguments travel as JSON text: `Value` needs a self-describing format.
+#[derive(Serialize, Deserialize)]
+struct WireToolCall {
+    id: String,
+    name: String,
+    args: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct WireInvalidToolCall {
+    id: Option<String>,
+    name: Option<String>,
+    args: Option<String>,
+    error: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct WireTool {
+    tool_call_id: String,
+    artifact: Option<String>,
+    status: ToolStatus,
+}
+
+impl From<&Conversation> for WireConversation {
+    fn from(conversation: &Conversation) -> Self {
+        WireConversation {
+            session_id: conversation.session_id.clone(),
+            forked_from: conversation
+                .forked_from
+                .as_ref()
+                .map(|origin| (origin.session.clone(), origin.message_id.clone())),
+            messages: conversation.iter().map(WireMessage::from).collect(),
+            trash: conversation
+                .trash
+                .iter()
+                .map(|trashed| WireTrashed {
+                    index: trashed.index as u64,
+                    deleted_at_ms: trashed.deleted_at_ms,
+                    message: WireMessage::from(&trashed.message),
+                })
+                .collect(),
+        }
+    }
+}
+
+impl From<WireConversation> for Conversation {
+    fn from(wire: WireConversation) -> Self {
+        Conversation {
+            session_id: wire.session_id,
+            forked_from: wire.forked_from.map(|(session, message_id)| ForkOrigin {
+                session,
+                message_id,
+            }),
+            messages: wire.messages.into_iter().map(MessageEnum::from).collect(),
+            trash: wire
+                .trash
+                .into_iter()
+                .map(|trashed| TrashedMessage {
+                    index: trashed.index as usize,
+                    deleted_at_ms: trashed.deleted_at_ms,
+                    message: MessageEnum::from(trashed.message),
+                })
+                .collect(),
+            extensions: Extensions::default(),
+        }
+    }
+}
+
+impl From<&MessageEnum> for WireMessage {
+    fn from(message: &MessageEnum) -> Self {
+        let base = message.base();
+        WireMessage {
+            message_type: message.type_name().to_string(),
+            content: WireContent::from(&base.content),
+            example: base.example,
+            additional_kwargs: wire_metadata(&base.additional_kwargs),
+            response_metadata: wire_metadata(&base.response_metadata),
+            id: base.id.clone(),
+            name: base.name.clone(),
+            provenance: base.provenance.as_ref().map(|provenance| WireProvenance {
+                component: provenance.component.clone(),
+                step: provenance.step.clone(),
+                model: provenance.model.clone(),
+                template_id: provenance.template_id.clone(),
+                git_sha: provenance.git_sha.clone(),
+            }),
+            voice: base.voice.as_ref().map(|voice| WireVoice {
+                duration_ms: voice.duration_ms,
+                audio_blob: voice.audio_blob.clone(),
+                transcript_confidence: voice.transcript_confidence,
+                segments: voice
+                    .segments
+                    .iter()
+                    .map(|segment| WireSpeechSegment {
+                        speaker: segment.speaker.clone(),
+                        start_ms: segment.start_ms,
+                        end_ms: segment.end_ms,
+                        text: segment.text.clone(),
+                    })
+                    .collect(),
+            }),
+            logprobs: message.logprobs().map(|logprobs| {
+                logprobs
+                    .content
+                    .iter()
+                    .map(|token| WireTokenLogprob {
+                        token: token.token.clone(),
+                        logprob: token.logprob,
+                        top_logprobs: token
+                            .top_logprobs
+                            .iter()
+                            .map(|top| (top.token.clone(), top.logprob))
+                            .collect(),
+                    })
+                    .collect()
+            }),
+            usage_metadata: message.usage_metadata().map(|usage| WireUsage {
+                input_tokens: usage.input_tokens,
+                output_tokens: usage.output_tokens,
+                total_tokens: usage.total_tokens,
+                input_token_details: usage
+                    .input_token_details
+                    .iter()
+                    .map(|(kind, tokens)| (kind.clone(), *tokens))
+                    .collect(),
+                output_token_details: usage
+                    .output_token_details
+                    .iter()
+                    .map(|(kind, tokens)| (kind.clone(), *tokens))
+                    .collect(),
+            }),
+            tool_calls: message
+                .tool_calls()
+                .iter()
+                .map(|call| WireToolCall {
+                    id: call.id.clone(),
+                    name: call.name.clone(),
+                    args: call.args.to_string(),
+                })
+                .collect(),
+            invalid_tool_calls: message
+                .invalid_tool_calls()
+                .iter()
+                .map(|call| WireInvalidToolCall {
+                    id: call.id.clone(),
+                    name: call.name.clone(),
+                    args: call.args.clone(),
+                    error: call.error.clone(),
+                })
+                .collect(),
+            pinned: base.pinned,
+            tool: message.as_tool().map(|tool| WireTool {
+                tool_call_id: tool.tool_call_id().to_string(),
+                artifact: tool.artifact().clone(),
+                status: tool.status().clone(),
+            }),
+            role: match message {
+                MessageEnum::Chat(chat) => Some(chat.role().to_string()),
+                _ => None,
+            },
+        }
+    }
+}
+
+impl From<WireMessage> for MessageEnum {
+    fn from(wire: WireMessage) -> Self {
+        let message_type = MessageType::from_name(&wire.message_type);
+        let mut base = BaseMessageFields::new(wire.content, message_type);
+        base.example = wire.example;
+        base.additional_kwargs = from_wire_metadata(wire.additional_kwargs);
+        base.response_metadata = from_wire_metadata(wire.response_metadata);
+        base.id = wire.id;
+        base.name = wire.name;
+        base.provenance = wire.provenance.map(|provenance| Provenance {
+            component: provenance.component,
+            step: provenance.step,
+            model: provenance.model,
+            template_id: provenance.template_id,
+            git_sha: provenance.git_sha,
+        });
+        base.voice = wire.voice.map(|voice| VoiceMetadata {
+            duration_ms: voice.duration_ms,
+            audio_blob: voice.audio_blob,
+            transcript_confidence: voice.transcript_confidence,
+            segments: voice
+                .segments
+                .into_iter()
+                .map(|segment| SpeechSegment {
+                    speaker: segment.speaker,
+                    start_ms: segment.start_ms,
+                    end_ms: segment.end_ms,
+                    text: segment.text,
+                })
+                .collect(),
+        });
+        let logprobs = wire.logprobs.map(|tokens| Logprobs {
+            content: tokens
+                .into_iter()
+                .map(|token| TokenLogprob {
+                    token: token.token,
+                    logprob: token.logprob,
+                    top_logprobs: token
+                        .top_logprobs
+                        .into_iter()
+                        .map(|(token, logprob)| TopLogprob { token, logprob })
+                        .collect(),
+                })
+                .collect(),
+        });
+        let usage_metadata = wire.usage_metadata.map(|usage| UsageMetadata {
+            input_tokens: usage.input_tokens,
+            output_tokens: usage.output_tokens,
+            total_tokens: usage.total_tokens,
+            input_token_details: usage.input_token_details.into_iter().collect(),
+            output_token_details: usage.output_token_details.into_iter().collect(),
+        });
+        let tool_calls = wire
+            .tool_calls
+            .into_iter()
+            .map(|call| ToolCall {
+                id: call.id,
+                name: call.name,
+                args: serde_json::from_str(&call.args).unwrap_or_default(),
+            })
+            .collect();
+        let invalid_tool_calls = wire
+            .invalid_tool_calls
+            .into_iter()
+            .map(|call| InvalidToolCall {
+                id: call.id,
+                name: call.name,
+                args: call.args,
+                error: call.error,
+            })
+            .collect();
+        base.pinned = wire.pinned;
+
+        match (message_type, wire.tool) {
+            (MessageType::Ai, _) => MessageEnum::Ai(AiMessage {
+                logprobs,
+                usage_metadata,
+                tool_calls,
+                invalid_tool_calls,
+                base,
+            }),
+            (MessageType::Human, _) => MessageEnum::Human(HumanMessage { base }),
+            (MessageType::System, _) => MessageEnum::System(SystemMessage { base }),
+            (MessageType::Tool, Some(tool)) => MessageEnum::Tool(ToolMessage::new_with_base(
+                tool.tool_call_id,
+                tool.artifact,
+                tool.status,
+                base,
+            )),
+            (MessageType::Chat, _) if wire.role.is_some() => {
+                let mut chat = ChatMessage::new("", wire.role.unwrap_or_default());
+                chat.base = base;
+                MessageEnum::Chat(chat)
+            }
+            (MessageType::Remove, _) => MessageEnum::Remove(RemoveMessage { base }),
+            // Anything this version cannot represent natively is kept verbatim.
+            (_, _) => {
+                base.message_type = MessageType::Unknown;
+                MessageEnum::Unknown(UnknownMessage {
+                    type_name: wire.message_type,
+                    base,
+                })
+            }
+        }
+    }
+}
+
+fn wire_metadata(metadata: &Metadata) -> HashMap<String, String> {
+    metadata
+        .iter()
+        .map(|(key, value)| (key.clone(), value.to_string()))
+        .collect()
+}
+
+fn from_wire_metadata(wire: HashMap<String, String>) -> Metadata {
+    wire.into_iter()
+        .map(|(key, value)| {
+            let value = serde_json::from_str(&value).unwrap_or(Value::String(value));
+            (key, value)
+        })
+        .collect()
+}
+
+fn with_header(payload: Vec<u8>) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(HEADER_LEN + payload.len());
+    bytes.extend_from_slice(&MAGIC);
+    bytes.extend_from_slice(&BINARY_FORMAT_VERSION.to_le_bytes());
+    bytes.extend_from_slice(&payload);
+    bytes
+}
+
+fn strip_header(bytes: &[u8]) -> Result<&[u8], BinaryError> {
+    if bytes.len() < HEADER_LEN || bytes[..MAGIC.len()] != MAGIC {
+        return Err(BinaryError::BadMagic);
+    }
+    let version = u16::from_le_bytes([bytes[MAGIC.len()], bytes[MAGIC.len() + 1]]);
+    if version != BINARY_FORMAT_VERSION {
+        return Err(BinaryError::UnsupportedVersion(version));
+    }
+    Ok(&bytes[HEADER_LEN..])
+}
+
+#[cfg(feature = "bincode")]
+pub fn to_bincode(conversation: &Conversation) -> Result<Vec<u8>, BinaryError> {
+    bincode::serialize(&WireConversation::from(conversation))
+        .map(with_header)
+        .map_err(|err| BinaryError::Encode(err.to_string()))
+}
+
+#[cfg(feature = "bincode")]
+pub fn from_bincode(bytes: &[u8]) -> Result<Conversation, BinaryError> {
+    bincode::deserialize::<WireConversation>(strip_header(bytes)?)
+        .map(Conversation::from)
+        .map_err(|err| BinaryError::Decode(err.to_string()))
+}
+
+#[cfg(feature = "postcard")]
+pub fn to_postcard(conversation: &Conversation) -> Result<Vec<u8>, BinaryError> {
+    postcard::to_allocvec(&WireConversation::from(conversation))
+        .map(with_header)
+        .map_err(|err| BinaryError::Encode(err.to_string()))
+}
+
+#[cfg(feature = "postcard")]
+pub fn from_postcard(bytes: &[u8]) -> Result<Conversation, BinaryError> {
+    postcard::from_bytes::<WireConversation>(strip_header(bytes)?)
+        .map(Conversation::from)
+        .map_err(|err| BinaryError::Decode(err.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn conversation() -> Conversation {
+        let mut question = HumanMessage::new("What's the weather?");
+        question.set_id(Some("m1".to_string()));
+        question
+            .base
+            .additional_kwargs
+            .insert("locale".to_string(), "en".to_string());
+        question.set_voice(Some(
+            VoiceMetadata::new(1_200)
+                .with_transcript_confidence(0.9)
+                .with_segment(SpeechSegment::new("user", 0, 1_200)),
+        ));
+        let mut token = TokenLogprob::new("Checking", -0.3);
+        token.top_logprobs.push(TopLogprob {
+            token: "Looking".to_string(),
+            logprob: -1.7,
+        });
+        let answer = AiMessage::new("Checking.")
+            .with_provenance(Provenance::new("planner").with_model("gpt-4o"))
+            .with_logprobs(Logprobs::new(vec![token]))
+            .with_usage_metadata(UsageMetadata::new(12, 3).with_input_detail("cache_read", 8))
+            .with_tool_calls(vec![ToolCall::new(
+                "call_1",
+                "weather",
+                serde_json::json!({"city": "Paris"}),
+            )]);
+        let tool = ToolMessage::new(
+            "Sunny",
+            "call_1".to_string(),
+            Some("raw".to_string()),
+            ToolStatus::Success,
+        );
+
+        let mut parent = Conversation::with_session_id("s1");
+        parent.push(question);
+        let mut conversation = parent.fork_at("s2", "m1").unwrap();
+        conversation.push(answer);
+        conversation.push(tool);
+        conversation.push(UnknownMessage::new("critic", "Looks fine"));
+        conversation.push(ChatMessage::new("Agreed.", "moderator".to_string()));
+        conversation.push(RemoveMessage::new("m1"));
+        conversation.push(HumanMessage::new("").with_content(vec![
+            ContentBlock::text("And here?"),
+            ContentBlock::image("image/png", "iVBORw0KGgo="),
+        ]));
+        let mut retracted = HumanMessage::new("Never mind");
+        retracted.set_id(Some("m9".to_string()));
+        conversation.push(retracted);
+        conversation.soft_delete("m9");
+        conversation
+    }
+
+    #[test]
+    fn test_wire_round_trip() {
+        let conversation = conversation();
+        let wire = WireConversation::from(&conversation);
+
+        assert_eq!(Conversation::from(wire), conversation);
+        assert_eq!(conversation.forked_from().unwrap().session, "s1");
+    }
+
+    #[test]
+    fn test_header_guards() {
+        assert!(matches!(strip_header(b"nope"), Err(BinaryError::BadMagic)));
+
+        let mut bytes = with_header(vec![1, 2, 3]);
+        assert_eq!(strip_header(&bytes).unwrap(), &[1, 2, 3]);
+
+        bytes[MAGIC.len()] = 99;
+        assert!(matches!(
+            strip_header(&bytes),
+            Err(BinaryError::UnsupportedVersion(99))
+        ));
+    }
+
+    #[cfg(feature = "bincode")]
+    #[test]
+    fn test_bincode_round_trip() {
+        let conversation = conversation();
+        let bytes = to_bincode(&conversation).unwrap();
+
+        assert_eq!(from_bincode(&bytes).unwrap(), conversation);
+        assert!(from_bincode(&bytes[..HEADER_LEN + 3]).is_err());
+    }
+
+    #[cfg(feature = "postcard")]
+    #[test]
+    fn test_postcard_round_trip() {
+        let conversation = conversation();
+        let bytes = to_postcard(&conversation).unwrap();
+
+        let decoded = from_postcard(&bytes).unwrap();
+        assert_eq!(decoded, conversation);
+        assert_eq!(decoded.messages()[3].type_name(), "critic");
+    }
+}
@@ -0,0 +1,66 @@
+use std::collections::HashMap;
+use std::io;
+
+use crate::conversation::{fnv1a, FNV_OFFSET_BASIS};
+
+/// Storage for payloads too large to keep inline in a message. Keys are
+/// opaque strings that messages can carry as references.
+pub trait BlobStore {
+    fn put(&mut self, data: &[u8]) -> io::Result<String>;
+
+    fn get(&self, key: &str) -> io::Result<Option<Vec<u8>>>;
+}
+
+/// Content-addressed in-process store: identical payloads share a key.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct InMemoryBlobStore {
+    blobs: HashMap<String, Vec<u8>>,
+}
+
+impl InMemoryBlobStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.blobs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.blobs.is_empty()
+    }
+}
+
+impl BlobStore for InMemoryBlobStore {
+    fn put(&mut self, data: &[u8]) -> io::Result<String> {
+        let key = format!("blob:{:016x}", fnv1a(FNV_OFFSET_BASIS, data));
+        self.blobs
+            .entry(key.clone())
+            .or_insert_with(|| data.to_vec());
+        Ok(key)
+    }
+
+    fn get(&self, key: &str) -> io::Result<Option<Vec<u8>>> {
+        Ok(self.blobs.get(key).cloned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_put_is_content_addressed() {
+        let mut store = InMemoryBlobStore::new();
+
+        let first = store.put(b"payload").unwrap();
+        let second = store.put(b"payload").unwrap();
+        let other = store.put(b"other").unwrap();
+
+        assert_eq!(first, second);
+        assert_ne!(first, other);
+        assert_eq!(store.len(), 2);
+        assert_eq!(store.get(&first).unwrap().as_deref(), Some(&b"payload"[..]));
+        assert_eq!(store.get("blob:missing").unwrap(), None);
+    }
+}
@@ -0,0 +1,237 @@
+use std::error::Error;
+#[cfg(feature = "models")]
+use std::io;
+use std::sync::Arc;
+
+use crate::chat_model::{ChatModel, ChatModelError};
+use crate::fingerprint::RequestOptions;
+#[cfg(feature = "models")]
+use crate::response_cache::{CachedResponse, ResponseCache};
+use crate::{AiMessage, MessageEnum};
+
+/// Lifecycle hooks for logging and analytics integrations. Every method
+/// does nothing by default, so a handler only implements what it observes.
+pub trait Callbacks: Send + Sync {
+    /// A message was added to a conversation or returned by a model.
+    fn on_message_created(&self, _message: &MessageEnum) {}
+
+    /// A request is about to be sent to a model.
+    fn on_before_send(&self, _messages: &[MessageEnum], _options: &RequestOptions) {}
+
+    /// A streamed text delta arrived.
+    fn on_chunk(&self, _chunk: &str) {}
+
+    fn on_error(&self, _error: &dyn Error) {}
+}
+
+/// Several handlers called in registration order.
+#[derive(Clone, Default)]
+pub struct CallbackList {
+    handlers: Vec<Arc<dyn Callbacks>>,
+}
+
+impl CallbackList {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, handler: Arc<dyn Callbacks>) {
+        self.handlers.push(handler);
+    }
+
+    pub fn with(mut self, handler: Arc<dyn Callbacks>) -> Self {
+        self.push(handler);
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.handlers.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.handlers.is_empty()
+    }
+}
+
+impl Callbacks for CallbackList {
+    fn on_message_created(&self, message: &MessageEnum) {
+        for handler in &self.handlers {
+            handler.on_message_created(message);
+        }
+    }
+
+    fn on_before_send(&self, messages: &[MessageEnum], options: &RequestOptions) {
+        for handler in &self.handlers {
+            handler.on_before_send(messages, options);
+        }
+    }
+
+    fn on_chunk(&self, chunk: &str) {
+        for handler in &self.handlers {
+            handler.on_chunk(chunk);
+        }
+    }
+
+    fn on_error(&self, error: &dyn Error) {
+        for handler in &self.handlers {
+            handler.on_error(error);
+        }
+    }
+}
+
+/// Wraps a [`ChatModel`] or, with the `models` feature, a `ResponseCache`
+/// and reports what passes through it. Models report the request, the reply and any error; caches
+/// report I/O errors.
+pub struct WithCallbacks<T> {
+    inner: T,
+    callbacks: CallbackList,
+}
+
+impl<T> WithCallbacks<T> {
+    pub fn new(inner: T, callbacks: CallbackList) -> Self {
+        WithCallbacks { inner, callbacks }
+    }
+
+    pub fn inner(&self) -> &T {
+        &self.inner
+    }
+
+    pub fn callbacks(&self) -> &CallbackList {
+        &self.callbacks
+    }
+
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+
+    fn report<R, E: Error>(&self, result: Result<R, E>) -> Result<R, E> {
+        if let Err(err) = &result {
+            self.callbacks.on_error(err);
+        }
+        result
+    }
+}
+
+impl<M: ChatModel> ChatModel for WithCallbacks<M> {
+    fn invoke(
+        &self,
+        messages: &[MessageEnum],
+        options: &RequestOptions,
+    ) -> Result<AiMessage, ChatModelError> {
+        self.callbacks.on_before_send(messages, options);
+        let reply = self.report(self.inner.invoke(messages, options))?;
+        self.callbacks
+            .on_message_created(&MessageEnum::Ai(reply.clone()));
+        Ok(reply)
+    }
+}
+
+#[cfg(feature = "models")]
+impl<C: ResponseCache> ResponseCache for WithCallbacks<C> {
+    fn get(&self, key: u64) -> io::Result<Option<CachedResponse>> {
+        self.report(self.inner.get(key))
+    }
+
+    fn put(&self, key: u64, response: &CachedResponse) -> io::Result<()> {
+        self.report(self.inner.put(key, response))
+    }
+
+    fn remove(&self, key: u64) -> io::Result<()> {
+        self.report(self.inner.remove(key))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "models")]
+    use crate::CachedChatModel;
+    use crate::{BaseMessage, Conversation, HumanMessage};
+    use std::sync::Mutex;
+
+    #[derive(Default)]
+    struct Recorder {
+        events: Mutex<Vec<String>>,
+    }
+
+    impl Recorder {
+        fn events(&self) -> Vec<String> {
+            self.events.lock().unwrap().clone()
+        }
+    }
+
+    impl Callbacks for Recorder {
+        fn on_message_created(&self, message: &MessageEnum) {
+            let event = format!("created {}", message.content());
+            self.events.lock().unwrap().push(event);
+        }
+
+        fn on_before_send(&self, messages: &[MessageEnum], options: &RequestOptions) {
+            let event = format!("send {} to {}", messages.len(), options.model);
+            self.events.lock().unwrap().push(event);
+        }
+
+        fn on_error(&self, error: &dyn Error) {
+            self.events.lock().unwrap().push(format!("error {}", error));
+        }
+    }
+
+    #[cfg(feature = "models")]
+    struct BrokenCache;
+
+    #[cfg(feature = "models")]
+    impl ResponseCache for BrokenCache {
+        fn get(&self, _key: u64) -> io::Result<Option<CachedResponse>> {
+            Ok(None)
+        }
+
+        fn put(&self, _key: u64, _response: &CachedResponse) -> io::Result<()> {
+            Err(io::Error::other("disk full"))
+        }
+
+        fn remove(&self, _key: u64) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_conversation_and_model_events() {
+        let recorder = Arc::new(Recorder::default());
+        let callbacks = CallbackList::new().with(recorder.clone());
+        let mut conversation = Conversation::new();
+        conversation.add_callbacks(recorder.clone());
+        conversation.push(HumanMessage::new("Hi"));
+
+        let model = WithCallbacks::new(
+            |_: &[MessageEnum], _: &RequestOptions| Ok(AiMessage::new("Hello")),
+            callbacks,
+        );
+        model
+            .invoke(conversation.messages(), &RequestOptions::new("gpt-4o"))
+            .unwrap();
+
+        assert_eq!(
+            recorder.events(),
+            vec!["created Hi", "send 1 to gpt-4o", "created Hello"]
+        );
+    }
+
+    #[cfg(feature = "models")]
+    #[test]
+    fn test_storage_errors_are_reported() {
+        let recorder = Arc::new(Recorder::default());
+        let callbacks = CallbackList::new().with(recorder.clone());
+        let model = CachedChatModel::new(
+            |_: &[MessageEnum], _: &RequestOptions| Ok(AiMessage::new("Hello")),
+            WithCallbacks::new(BrokenCache, callbacks),
+        );
+
+        let result = model.invoke(
+            &[HumanMessage::new("Hi").into()],
+            &RequestOptions::new("gpt-4o"),
+        );
+
+        assert!(result.is_err());
+        assert_eq!(recorder.events(), vec!["error disk full"]);
+    }
+}
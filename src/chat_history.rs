@@ -0,0 +1,230 @@
+use std::io;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::clock::{Clock, SystemClock};
+use crate::trash::{self, TrashedMessage};
+use crate::AnyMessage;
+
+/// Where an agent accumulates the messages of one conversation. Backends
+/// that persist can fail, so every call returns an `io::Result`.
+pub trait ChatHistory {
+    fn add_message(&mut self, message: AnyMessage) -> io::Result<()>;
+
+    fn add_messages(&mut self, messages: Vec<AnyMessage>) -> io::Result<()> {
+        messages
+            .into_iter()
+            .try_for_each(|message| self.add_message(message))
+    }
+
+    /// Every stored message, oldest first.
+    fn messages(&self) -> io::Result<Vec<AnyMessage>>;
+
+    fn clear(&mut self) -> io::Result<()>;
+
+    /// Swaps what [`ChatHistory::messages`] returns for `messages`, e.g.
+    /// after rewriting them in bulk. The default clears and re-adds, which
+    /// also drops soft-deleted messages.
+    fn replace_messages(&mut self, messages: Vec<AnyMessage>) -> io::Result<()> {
+        self.clear()?;
+        self.add_messages(messages)
+    }
+
+    fn len(&self) -> io::Result<usize> {
+        self.messages().map(|messages| messages.len())
+    }
+
+    fn is_empty(&self) -> io::Result<bool> {
+        self.len().map(|len| len == 0)
+    }
+
+    /// Hides the message with `id` from [`ChatHistory::messages`] until it
+    /// is restored or its retention runs out. Returns whether it was found.
+    fn soft_delete(&mut self, _id: &str) -> io::Result<bool> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "this history does not support soft delete",
+        ))
+    }
+
+    fn restore(&mut self, _id: &str) -> io::Result<bool> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "this history does not support soft delete",
+        ))
+    }
+}
+
+/// How long soft-deleted messages stay recoverable by default.
+pub const DEFAULT_TRASH_RETENTION: Duration = Duration::from_secs(30 * 24 * 60 * 60);
+
+/// A [`ChatHistory`] kept in a `Vec`. With a maximum length, adding past it
+/// evicts the oldest messages. Soft-deleted messages can be restored until
+/// the trash retention passes.
+#[derive(Debug, Clone)]
+pub struct InMemoryChatHistory {
+    messages: Vec<AnyMessage>,
+    max_len: Option<usize>,
+    trash: Vec<TrashedMessage<AnyMessage>>,
+    retention: Duration,
+    clock: Arc<dyn Clock>,
+}
+
+impl Default for InMemoryChatHistory {
+    fn default() -> Self {
+        InMemoryChatHistory {
+            messages: Vec::new(),
+            max_len: None,
+            trash: Vec::new(),
+            retention: DEFAULT_TRASH_RETENTION,
+            clock: Arc::new(SystemClock),
+        }
+    }
+}
+
+impl InMemoryChatHistory {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_max_len(max_len: usize) -> Self {
+        InMemoryChatHistory {
+            max_len: Some(max_len),
+            ..Self::default()
+        }
+    }
+
+    pub fn with_trash_retention(mut self, retention: Duration) -> Self {
+        self.retention = retention;
+        self
+    }
+
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Soft-deleted messages still within their retention.
+    pub fn trash(&self) -> &[TrashedMessage<AnyMessage>] {
+        &self.trash
+    }
+
+    fn purge_expired(&mut self) {
+        trash::purge(&mut self.trash, self.retention, self.clock.now_ms());
+    }
+
+    pub fn max_len(&self) -> Option<usize> {
+        self.max_len
+    }
+
+    pub fn as_slice(&self) -> &[AnyMessage] {
+        &self.messages
+    }
+}
+
+impl ChatHistory for InMemoryChatHistory {
+    fn add_message(&mut self, message: AnyMessage) -> io::Result<()> {
+        self.messages.push(message);
+        if let Some(max_len) = self.max_len {
+            let excess = self.messages.len().saturating_sub(max_len);
+            self.messages.drain(..excess);
+        }
+        Ok(())
+    }
+
+    fn messages(&self) -> io::Result<Vec<AnyMessage>> {
+        Ok(self.messages.clone())
+    }
+
+    fn clear(&mut self) -> io::Result<()> {
+        self.messages.clear();
+        self.trash.clear();
+        Ok(())
+    }
+
+    fn replace_messages(&mut self, messages: Vec<AnyMessage>) -> io::Result<()> {
+        self.messages.clear();
+        self.add_messages(messages)
+    }
+
+    fn len(&self) -> io::Result<usize> {
+        Ok(self.messages.len())
+    }
+
+    fn soft_delete(&mut self, id: &str) -> io::Result<bool> {
+        self.purge_expired();
+        let now_ms = self.clock.now_ms();
+        Ok(trash::soft_delete(
+            &mut self.messages,
+            &mut self.trash,
+            id,
+            now_ms,
+        ))
+    }
+
+    fn restore(&mut self, id: &str) -> io::Result<bool> {
+        self.purge_expired();
+        Ok(trash::restore(&mut self.messages, &mut self.trash, id).is_some())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::MockClock;
+    use crate::{AiMessage, BaseMessage, HumanMessage};
+
+    #[test]
+    fn test_add_and_clear() {
+        let mut history = InMemoryChatHistory::new();
+        history.add_message(HumanMessage::new("Hi").into()).unwrap();
+        history.add_message(AiMessage::new("Hello").into()).unwrap();
+
+        let messages = history.messages().unwrap();
+        assert_eq!(messages.len(), 2);
+        assert!(matches!(messages[1], AnyMessage::Ai(_)));
+        assert_eq!(history.len().unwrap(), 2);
+
+        history.clear().unwrap();
+        assert!(history.is_empty().unwrap());
+    }
+
+    #[test]
+    fn test_max_len_evicts_oldest() {
+        let mut history = InMemoryChatHistory::with_max_len(2);
+        history
+            .add_messages(vec![
+                HumanMessage::new("one").into(),
+                AiMessage::new("two").into(),
+                HumanMessage::new("three").into(),
+            ])
+            .unwrap();
+
+        let contents: Vec<&str> = history.as_slice().iter().map(|m| m.content()).collect();
+        assert_eq!(contents, vec!["two", "three"]);
+        assert_eq!(InMemoryChatHistory::with_max_len(0).max_len(), Some(0));
+    }
+
+    #[test]
+    fn test_soft_delete_within_retention() {
+        let clock = Arc::new(MockClock::new(0));
+        let mut history = InMemoryChatHistory::new()
+            .with_trash_retention(Duration::from_secs(60))
+            .with_clock(clock.clone());
+        for id in ["m1", "m2"] {
+            let mut message: AnyMessage = HumanMessage::new(id).into();
+            message.base_mut().id = Some(id.to_string());
+            history.add_message(message).unwrap();
+        }
+
+        assert!(history.soft_delete("m1").unwrap());
+        assert_eq!(history.len().unwrap(), 1);
+        assert!(history.restore("m1").unwrap());
+        assert_eq!(history.as_slice()[0].content(), "m1");
+
+        history.soft_delete("m2").unwrap();
+        clock.advance(Duration::from_secs(60));
+        assert!(!history.restore("m2").unwrap());
+        assert!(history.trash().is_empty());
+    }
+}
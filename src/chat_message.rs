@@ -1,18 +1,56 @@
 use crate::prelude::*;
-use derive_base_message::BaseMessage;
 
-#[derive(BaseMessage, Debug, Serialize, Deserialize)]
-pub struct ChatMessage {
-    role: String,
-    #[serde(flatten)]
-    base: BaseMessageFields,
+define_message!(
+    /// A message whose role is chosen at runtime, such as `"critic"` or
+    /// `"moderator"` in multi-agent setups.
+    Chat { role: String },
+    role = role
+);
+
+impl ChatMessage {
+    pub fn set_role(&mut self, role: String) {
+        self.role = role;
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use serde_json;
-    use std::collections::HashMap;
+
+    #[test]
+    fn test_chat_message_constructor_defaults() {
+        let message = ChatMessage::new("Test message", "critic".to_string());
+
+        assert_eq!(message.content(), "Test message");
+        assert_eq!(message.role(), "critic");
+        assert_eq!(message.message_type(), &MessageType::Chat);
+        assert!(!message.is_example());
+        assert!(message.additional_kwargs().is_empty());
+        assert!(message.response_metadata().is_empty());
+        assert_eq!(message.id(), None);
+        assert_eq!(message.name(), None);
+    }
+
+    #[test]
+    fn test_chat_message_setters_and_builder() {
+        let mut message = ChatMessage::new("Test message", "critic".to_string());
+        message.set_content("updated content");
+        message.set_example(true);
+        message.set_id(Some("1234".to_string()));
+        message.set_name(Some("Test Name".to_string()));
+        message.set_role("moderator".to_string());
+
+        let built = ChatMessage::builder("moderator".to_string())
+            .content("updated content")
+            .example(true)
+            .id("1234")
+            .name("Test Name")
+            .build();
+
+        assert_eq!(message.role(), "moderator");
+        assert_eq!(built, message);
+    }
 
     #[test]
     fn test_chat_message_serialization_with_empty_fields() {
@@ -30,10 +68,10 @@ mod tests {
 
     #[test]
     fn test_chat_message_serialization_with_values() {
-        let mut additional_kwargs = HashMap::new();
+        let mut additional_kwargs = Metadata::new();
         additional_kwargs.insert("key1".to_string(), "value1".to_string());
 
-        let mut response_metadata = HashMap::new();
+        let mut response_metadata = Metadata::new();
         response_metadata.insert("meta_key".to_string(), "meta_value".to_string());
 
         let mut chat_message = ChatMessage::new("Test message", "User".to_string());
@@ -91,7 +129,7 @@ mod tests {
 
     #[test]
     fn test_chat_message_serialization_with_partial_values() {
-        let mut additional_kwargs = HashMap::new();
+        let mut additional_kwargs = Metadata::new();
         additional_kwargs.insert("key2".to_string(), "value2".to_string());
 
         let mut chat_message = ChatMessage::new("Partial message", "User".to_string());
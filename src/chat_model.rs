@@ -0,0 +1,45 @@
+use std::fmt;
+use std::io;
+
+use crate::fingerprint::RequestOptions;
+use crate::{AiMessage, MessageEnum};
+
+/// Anything that turns a request into a reply: a provider client, a stub in
+/// tests, or a wrapper around another model.
+pub trait ChatModel {
+    fn invoke(
+        &self,
+        messages: &[MessageEnum],
+        options: &RequestOptions,
+    ) -> Result<AiMessage, ChatModelError>;
+}
+
+impl<F> ChatModel for F
+where
+    F: Fn(&[MessageEnum], &RequestOptions) -> Result<AiMessage, ChatModelError>,
+{
+    fn invoke(
+        &self,
+        messages: &[MessageEnum],
+        options: &RequestOptions,
+    ) -> Result<AiMessage, ChatModelError> {
+        self(messages, options)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChatModelError(pub String);
+
+impl fmt::Display for ChatModelError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Chat model error: {}", self.0)
+    }
+}
+
+impl std::error::Error for ChatModelError {}
+
+impl From<io::Error> for ChatModelError {
+    fn from(err: io::Error) -> Self {
+        ChatModelError(err.to_string())
+    }
+}
@@ -0,0 +1,70 @@
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Source of timestamps, in milliseconds since the Unix epoch. Everything
+/// that stamps, expires or retains data reads the time through a `Clock` so
+/// tests can swap in a [`MockClock`].
+pub trait Clock: fmt::Debug + Send + Sync {
+    fn now_ms(&self) -> u64;
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now_ms(&self) -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_millis() as u64)
+            .unwrap_or_default()
+    }
+}
+
+/// A clock that only moves when told to. Share it through an `Arc` to keep
+/// advancing it after handing it to the code under test.
+#[derive(Debug, Default)]
+pub struct MockClock {
+    now_ms: AtomicU64,
+}
+
+impl MockClock {
+    pub fn new(now_ms: u64) -> Self {
+        MockClock {
+            now_ms: AtomicU64::new(now_ms),
+        }
+    }
+
+    pub fn set(&self, now_ms: u64) {
+        self.now_ms.store(now_ms, Ordering::SeqCst);
+    }
+
+    pub fn advance(&self, by: Duration) {
+        self.now_ms
+            .fetch_add(by.as_millis() as u64, Ordering::SeqCst);
+    }
+}
+
+impl Clock for MockClock {
+    fn now_ms(&self) -> u64 {
+        self.now_ms.load(Ordering::SeqCst)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_mock_clock_is_shared() {
+        let mock = Arc::new(MockClock::new(1_000));
+        let clock: Arc<dyn Clock> = mock.clone();
+
+        mock.advance(Duration::from_secs(2));
+        assert_eq!(clock.now_ms(), 3_000);
+        mock.set(5);
+        assert_eq!(clock.now_ms(), 5);
+        assert!(SystemClock.now_ms() > 1_600_000_000_000);
+    }
+}
@@ -0,0 +1,188 @@
+use std::fmt;
+
+use crate::tokens::approximate_message_tokens;
+use crate::tool_pairs::requested_tool_call_ids;
+use crate::{AnyMessage, BaseMessage, ContentBlock, MessageEnum, MessageType, ModelProfile};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CompatibilityIssue {
+    SystemRoleUnsupported,
+    ToolsUnsupported,
+    ImagesUnsupported,
+    ContextExceeded { tokens: usize, max: usize },
+}
+
+/// Something in the messages the model can't take, with a hint on how to
+/// fix it. `index` points at the first offending message when there is one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompatibilityFinding {
+    pub issue: CompatibilityIssue,
+    pub index: Option<usize>,
+    pub message: String,
+}
+
+impl fmt::Display for CompatibilityFinding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.index {
+            Some(index) => write!(f, "message {}: {}", index, self.message),
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
+
+/// Every finding for a request the adapters refused in strict mode.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompatibilityError(pub Vec<CompatibilityFinding>);
+
+impl fmt::Display for CompatibilityError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let findings: Vec<String> = self.0.iter().map(ToString::to_string).collect();
+        write!(f, "Incompatible with the model: {}", findings.join("; "))
+    }
+}
+
+impl std::error::Error for CompatibilityError {}
+
+/// The strict-mode gate used by the provider adapters: fails when
+/// [`check_compatibility`] has any finding.
+pub fn ensure_compatible(
+    messages: &[AnyMessage],
+    profile: &ModelProfile,
+) -> Result<(), CompatibilityError> {
+    let findings = check_compatibility(messages, profile);
+    if findings.is_empty() {
+        Ok(())
+    } else {
+        Err(CompatibilityError(findings))
+    }
+}
+
+pub fn check_compatibility(
+    messages: &[MessageEnum],
+    profile: &ModelProfile,
+) -> Vec<CompatibilityFinding> {
+    let mut findings = Vec::new();
+
+    if !profile.supports_system_role {
+        if let Some(index) = messages
+            .iter()
+            .position(|message| message.message_type() == &MessageType::System)
+        {
+            findings.push(CompatibilityFinding {
+                issue: CompatibilityIssue::SystemRoleUnsupported,
+                index: Some(index),
+                message: format!(
+                    "system message present but {} has no system role; fold it into the first human message",
+                    profile.name
+                ),
+            });
+        }
+    }
+
+    if !profile.supports_tools {
+        if let Some(index) = messages.iter().position(|message| {
+            message.message_type() == &MessageType::Tool
+                || !requested_tool_call_ids(message).is_empty()
+        }) {
+            findings.push(CompatibilityFinding {
+                issue: CompatibilityIssue::ToolsUnsupported,
+                index: Some(index),
+                message: format!(
+                    "tool calls present but {} does not support tools; render them as text or drop them",
+                    profile.name
+                ),
+            });
+        }
+    }
+
+    if !profile.supports_images {
+        if let Some(index) = messages.iter().position(|message| {
+            message.content_blocks().iter().any(|block| {
+                matches!(
+                    block,
+                    ContentBlock::Image { .. } | ContentBlock::ImageUrl { .. }
+                )
+            })
+        }) {
+            findings.push(CompatibilityFinding {
+                issue: CompatibilityIssue::ImagesUnsupported,
+                index: Some(index),
+                message: format!(
+                    "image block present but {} is text-only; describe the image in text or drop it",
+                    profile.name
+                ),
+            });
+        }
+    }
+
+    let tokens = approximate_message_tokens(messages);
+    if tokens > profile.max_context_tokens {
+        findings.push(CompatibilityFinding {
+            issue: CompatibilityIssue::ContextExceeded {
+                tokens,
+                max: profile.max_context_tokens,
+            },
+            index: None,
+            message: format!(
+                "about {} tokens but {} takes {}; trim the history",
+                tokens, profile.name, profile.max_context_tokens
+            ),
+        });
+    }
+
+    findings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tool_message::ToolStatus;
+    use crate::{HumanMessage, SystemMessage, ToolMessage};
+
+    fn messages() -> Vec<MessageEnum> {
+        vec![
+            SystemMessage::new("Be brief.").into(),
+            HumanMessage::new("What's the weather?").into(),
+            ToolMessage::new("Sunny", "call_1".to_string(), None, ToolStatus::Success).into(),
+            HumanMessage::new("")
+                .with_content(vec![ContentBlock::image_url("https://example.com/sky.png")])
+                .into(),
+        ]
+    }
+
+    #[test]
+    fn test_capable_model_has_no_findings() {
+        let profile = ModelProfile::new("big-model", 128_000).with_images();
+        assert!(check_compatibility(&messages(), &profile).is_empty());
+    }
+
+    #[test]
+    fn test_findings_for_limited_model() {
+        let profile = ModelProfile::new("tiny-model", 5)
+            .without_system_role()
+            .without_tools();
+
+        let findings = check_compatibility(&messages(), &profile);
+
+        let issues: Vec<&CompatibilityIssue> = findings.iter().map(|f| &f.issue).collect();
+        assert_eq!(
+            issues,
+            vec![
+                &CompatibilityIssue::SystemRoleUnsupported,
+                &CompatibilityIssue::ToolsUnsupported,
+                &CompatibilityIssue::ImagesUnsupported,
+                &CompatibilityIssue::ContextExceeded { tokens: 10, max: 5 },
+            ]
+        );
+        assert_eq!(findings[1].index, Some(2));
+        assert_eq!(findings[2].index, Some(3));
+        assert!(findings[0]
+            .to_string()
+            .starts_with("message 0: system message present"));
+
+        let messages = messages();
+        let error = ensure_compatible(&messages, &profile).unwrap_err();
+        assert_eq!(error.0, findings);
+        assert!(ensure_compatible(&messages[1..2], &ModelProfile::new("tiny-model", 100)).is_ok());
+    }
+}
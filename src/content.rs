@@ -0,0 +1,268 @@
+use std::borrow::Cow;
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+/// Message content: either a plain string or a list of multimodal blocks.
+/// Serializes as a bare string or an array, so JSON written before blocks
+/// existed still deserializes as [`MessageContent::Text`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum MessageContent {
+    Text(String),
+    Blocks(Vec<ContentBlock>),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ContentBlock {
+    Text {
+        text: String,
+    },
+    ImageUrl {
+        url: String,
+        #[serde(skip_serializing_if = "Option::is_none", default)]
+        detail: Option<String>,
+    },
+    /// A base64-encoded image.
+    Image {
+        mime_type: String,
+        data: String,
+    },
+    /// Base64-encoded audio.
+    Audio {
+        mime_type: String,
+        data: String,
+    },
+    /// A base64-encoded document such as a PDF.
+    Document {
+        mime_type: String,
+        data: String,
+        #[serde(skip_serializing_if = "Option::is_none", default)]
+        title: Option<String>,
+    },
+}
+
+impl ContentBlock {
+    pub fn text(text: impl Into<String>) -> Self {
+        ContentBlock::Text { text: text.into() }
+    }
+
+    pub fn image_url(url: impl Into<String>) -> Self {
+        ContentBlock::ImageUrl {
+            url: url.into(),
+            detail: None,
+        }
+    }
+
+    pub fn image(mime_type: impl Into<String>, data: impl Into<String>) -> Self {
+        ContentBlock::Image {
+            mime_type: mime_type.into(),
+            data: data.into(),
+        }
+    }
+
+    pub fn audio(mime_type: impl Into<String>, data: impl Into<String>) -> Self {
+        ContentBlock::Audio {
+            mime_type: mime_type.into(),
+            data: data.into(),
+        }
+    }
+
+    pub fn document(mime_type: impl Into<String>, data: impl Into<String>) -> Self {
+        ContentBlock::Document {
+            mime_type: mime_type.into(),
+            data: data.into(),
+            title: None,
+        }
+    }
+
+    pub fn as_text(&self) -> Option<&str> {
+        match self {
+            ContentBlock::Text { text } => Some(text),
+            _ => None,
+        }
+    }
+}
+
+impl MessageContent {
+    /// The plain-text content. For blocks this is the first text block, or
+    /// `""` if there is none; use [`MessageContent::text`] for all of it.
+    pub fn as_str(&self) -> &str {
+        match self {
+            MessageContent::Text(text) => text,
+            MessageContent::Blocks(blocks) => {
+                blocks.iter().find_map(ContentBlock::as_text).unwrap_or("")
+            }
+        }
+    }
+
+    /// Every text block concatenated; non-text blocks are skipped.
+    pub fn text(&self) -> Cow<'_, str> {
+        match self {
+            MessageContent::Text(text) => Cow::Borrowed(text),
+            MessageContent::Blocks(blocks) => {
+                let mut texts = blocks.iter().filter_map(ContentBlock::as_text);
+                match (texts.next(), texts.next()) {
+                    (None, _) => Cow::Borrowed(""),
+                    (Some(only), None) => Cow::Borrowed(only),
+                    (Some(first), Some(second)) => {
+                        let mut joined = format!("{}{}", first, second);
+                        texts.for_each(|text| joined.push_str(text));
+                        Cow::Owned(joined)
+                    }
+                }
+            }
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        match self {
+            MessageContent::Text(text) => text.is_empty(),
+            MessageContent::Blocks(blocks) => blocks.is_empty(),
+        }
+    }
+
+    /// The content as blocks; plain text becomes a single text block.
+    pub fn blocks(&self) -> Cow<'_, [ContentBlock]> {
+        match self {
+            MessageContent::Text(text) => Cow::Owned(vec![ContentBlock::text(text.as_str())]),
+            MessageContent::Blocks(blocks) => Cow::Borrowed(blocks),
+        }
+    }
+
+    /// Mutable access to every piece of text, for transforms that rewrite
+    /// text in place and leave media untouched.
+    pub fn texts_mut(&mut self) -> Vec<&mut String> {
+        match self {
+            MessageContent::Text(text) => vec![text],
+            MessageContent::Blocks(blocks) => blocks
+                .iter_mut()
+                .filter_map(|block| match block {
+                    ContentBlock::Text { text } => Some(text),
+                    _ => None,
+                })
+                .collect(),
+        }
+    }
+
+    /// Concatenates `other` onto this content, as when joining streamed
+    /// pieces: adjacent text is joined and anything else becomes a block.
+    pub fn append(&mut self, other: MessageContent) {
+        match (&mut *self, other) {
+            (MessageContent::Text(text), MessageContent::Text(more)) => text.push_str(&more),
+            (_, other) => {
+                let mut blocks = match std::mem::take(self) {
+                    MessageContent::Text(text) if text.is_empty() => Vec::new(),
+                    content => content.blocks().into_owned(),
+                };
+                for block in other.blocks().into_owned() {
+                    match (blocks.last_mut(), block) {
+                        (Some(ContentBlock::Text { text }), ContentBlock::Text { text: more }) => {
+                            text.push_str(&more)
+                        }
+                        (_, block) => blocks.push(block),
+                    }
+                }
+                *self = MessageContent::Blocks(blocks);
+            }
+        }
+    }
+
+    pub fn is_multimodal(&self) -> bool {
+        match self {
+            MessageContent::Text(_) => false,
+            MessageContent::Blocks(blocks) => blocks
+                .iter()
+                .any(|block| !matches!(block, ContentBlock::Text { .. })),
+        }
+    }
+}
+
+impl Default for MessageContent {
+    fn default() -> Self {
+        MessageContent::Text(String::new())
+    }
+}
+
+impl fmt::Display for MessageContent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.text())
+    }
+}
+
+impl From<&str> for MessageContent {
+    fn from(text: &str) -> Self {
+        MessageContent::Text(text.to_string())
+    }
+}
+
+impl From<String> for MessageContent {
+    fn from(text: String) -> Self {
+        MessageContent::Text(text)
+    }
+}
+
+impl From<Vec<ContentBlock>> for MessageContent {
+    fn from(blocks: Vec<ContentBlock>) -> Self {
+        MessageContent::Blocks(blocks)
+    }
+}
+
+impl PartialEq<str> for MessageContent {
+    fn eq(&self, other: &str) -> bool {
+        matches!(self, MessageContent::Text(text) if text == other)
+    }
+}
+
+impl PartialEq<&str> for MessageContent {
+    fn eq(&self, other: &&str) -> bool {
+        self == *other
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BaseMessage, HumanMessage, MessageEnum};
+
+    fn photo_question() -> MessageContent {
+        vec![
+            ContentBlock::text("What is in "),
+            ContentBlock::image_url("https://example.com/cat.png"),
+            ContentBlock::text("this picture?"),
+        ]
+        .into()
+    }
+
+    #[test]
+    fn test_plain_string_content_stays_a_string() {
+        let json = r#"{"content":"Hello","message_type":"Human"}"#;
+
+        let message: HumanMessage = serde_json::from_str(json).unwrap();
+
+        assert_eq!(message.base.content, "Hello");
+        assert_eq!(
+            message.content_blocks().as_ref(),
+            [ContentBlock::text("Hello")]
+        );
+        let value = serde_json::to_value(&message).unwrap();
+        assert_eq!(value["content"], "Hello");
+    }
+
+    #[test]
+    fn test_blocks_round_trip() {
+        let message = HumanMessage::new("").with_content(photo_question());
+        let message: MessageEnum = message.into();
+
+        let value = serde_json::to_value(&message).unwrap();
+        assert_eq!(value["content"][1]["type"], "image_url");
+        let parsed: MessageEnum = serde_json::from_value(value).unwrap();
+
+        assert_eq!(parsed, message);
+        assert_eq!(parsed.content(), "What is in ");
+        assert_eq!(parsed.text(), "What is in this picture?");
+        assert_eq!(parsed.content_blocks().len(), 3);
+        assert!(parsed.base().content.is_multimodal());
+    }
+}
@@ -0,0 +1,367 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::callbacks::{CallbackList, Callbacks};
+use crate::clock::{Clock, SystemClock};
+use crate::lineage::ForkOrigin;
+use crate::token_cache::TokenCountCache;
+use crate::trash::{self, TrashedMessage};
+use crate::{BaseMessage, Extensions, MessageEnum};
+
+pub(crate) const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Conversation {
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub(crate) session_id: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub(crate) forked_from: Option<ForkOrigin>,
+
+    #[serde(default)]
+    pub(crate) messages: Vec<MessageEnum>,
+
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub(crate) trash: Vec<TrashedMessage<MessageEnum>>,
+
+    #[serde(skip)]
+    pub(crate) extensions: Extensions,
+}
+
+impl Conversation {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_session_id(session_id: impl Into<String>) -> Self {
+        Conversation {
+            session_id: Some(session_id.into()),
+            ..Self::default()
+        }
+    }
+
+    pub fn session_id(&self) -> Option<&str> {
+        self.session_id.as_deref()
+    }
+
+    pub fn set_session_id(&mut self, session_id: Option<String>) {
+        self.session_id = session_id;
+    }
+
+    pub fn forked_from(&self) -> Option<&ForkOrigin> {
+        self.forked_from.as_ref()
+    }
+
+    /// Branches this conversation at the message with `message_id`, keeping
+    /// every message up to and including it and recording where it came from.
+    pub fn fork_at(&self, session_id: impl Into<String>, message_id: &str) -> Option<Conversation> {
+        let parent_session = self.session_id.clone()?;
+        let position = self
+            .messages
+            .iter()
+            .position(|message| message.id() == Some(message_id))?;
+
+        Some(Conversation {
+            session_id: Some(session_id.into()),
+            forked_from: Some(ForkOrigin {
+                session: parent_session,
+                message_id: message_id.to_string(),
+            }),
+            messages: self.messages[..=position].to_vec(),
+            trash: Vec::new(),
+            extensions: Extensions::default(),
+        })
+    }
+
+    /// Runtime-only state for the conversation; never serialized.
+    pub fn extensions(&self) -> &Extensions {
+        &self.extensions
+    }
+
+    pub fn extensions_mut(&mut self) -> &mut Extensions {
+        &mut self.extensions
+    }
+
+    /// Registers a handler notified of every message pushed from now on.
+    /// Handlers live in [`Conversation::extensions`], so they are not
+    /// serialized or carried into forks.
+    pub fn add_callbacks(&mut self, handler: Arc<dyn Callbacks>) {
+        self.extensions
+            .get_or_insert_default::<CallbackList>()
+            .push(handler);
+    }
+
+    pub fn push(&mut self, message: impl Into<MessageEnum>) {
+        let message = message.into();
+        if let Some(callbacks) = self.extensions.get::<CallbackList>() {
+            callbacks.on_message_created(&message);
+        }
+        self.messages.push(message);
+    }
+
+    /// Moves the message with `id` to the trash, hiding it from iteration
+    /// until it is restored or purged. Returns whether it was found.
+    pub fn soft_delete(&mut self, id: &str) -> bool {
+        self.soft_delete_with(id, &SystemClock)
+    }
+
+    pub fn soft_delete_with(&mut self, id: &str, clock: &dyn Clock) -> bool {
+        let deleted = trash::soft_delete(&mut self.messages, &mut self.trash, id, clock.now_ms());
+        if let (true, Some(trashed)) = (deleted, self.trash.last()) {
+            if let Some(cache) = self.extensions.get_mut::<TokenCountCache>() {
+                cache.remove(trashed.index);
+            }
+        }
+        deleted
+    }
+
+    /// Puts a trashed message back where it was deleted from.
+    pub fn restore(&mut self, id: &str) -> bool {
+        let Some(index) = trash::restore(&mut self.messages, &mut self.trash, id) else {
+            return false;
+        };
+        if let Some(cache) = self.extensions.get_mut::<TokenCountCache>() {
+            cache.insert(index);
+        }
+        true
+    }
+
+    pub fn trash(&self) -> &[TrashedMessage<MessageEnum>] {
+        &self.trash
+    }
+
+    /// Permanently drops trashed messages deleted at least `retention` ago,
+    /// returning them.
+    pub fn purge_trash(&mut self, retention: Duration, clock: &dyn Clock) -> Vec<MessageEnum> {
+        trash::purge(&mut self.trash, retention, clock.now_ms())
+    }
+
+    pub fn messages(&self) -> &[MessageEnum] {
+        &self.messages
+    }
+
+    /// Drops cached token counts, as any message may change; prefer
+    /// [`Conversation::message_mut`] to edit a single message.
+    pub fn messages_mut(&mut self) -> &mut Vec<MessageEnum> {
+        self.extensions.remove::<TokenCountCache>();
+        &mut self.messages
+    }
+
+    pub fn message_mut(&mut self, index: usize) -> Option<&mut MessageEnum> {
+        if let Some(cache) = self.extensions.get_mut::<TokenCountCache>() {
+            cache.invalidate(index);
+        }
+        self.messages.get_mut(index)
+    }
+
+    pub fn into_messages(self) -> Vec<MessageEnum> {
+        self.messages
+    }
+
+    pub fn len(&self) -> usize {
+        self.messages.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.messages.is_empty()
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, MessageEnum> {
+        self.messages.iter()
+    }
+
+    /// Stable 64-bit fingerprint of the messages, independent of map ordering
+    /// and of the process that computed it.
+    pub fn fingerprint(&self) -> u64 {
+        self.messages
+            .iter()
+            .fold(FNV_OFFSET_BASIS, |hash, message| {
+                fnv1a(hash, &message_fingerprint(message).to_le_bytes())
+            })
+    }
+}
+
+/// Fingerprint of a single message, computed over its canonical JSON form.
+pub fn message_fingerprint(message: &MessageEnum) -> u64 {
+    // Going through `Value` sorts object keys, so `HashMap` iteration order
+    // does not leak into the hash.
+    let canonical = serde_json::to_value(message)
+        .and_then(|value| serde_json::to_vec(&value))
+        .unwrap_or_default();
+    fnv1a(FNV_OFFSET_BASIS, &canonical)
+}
+
+pub(crate) fn fnv1a(mut hash: u64, bytes: &[u8]) -> u64 {
+    for byte in bytes {
+        hash ^= u64::from(*byte);
+        hash = hash.wrapping_mul(FNV_PRIME);
+    }
+    hash
+}
+
+impl From<Vec<MessageEnum>> for Conversation {
+    fn from(messages: Vec<MessageEnum>) -> Self {
+        Conversation {
+            messages,
+            ..Self::default()
+        }
+    }
+}
+
+impl<M: Into<MessageEnum>> FromIterator<M> for Conversation {
+    fn from_iter<I: IntoIterator<Item = M>>(iter: I) -> Self {
+        Conversation {
+            messages: iter.into_iter().map(Into::into).collect(),
+            ..Self::default()
+        }
+    }
+}
+
+impl<'a> IntoIterator for &'a Conversation {
+    type Item = &'a MessageEnum;
+    type IntoIter = std::slice::Iter<'a, MessageEnum>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.messages.iter()
+    }
+}
+
+impl IntoIterator for Conversation {
+    type Item = MessageEnum;
+    type IntoIter = std::vec::IntoIter<MessageEnum>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.messages.into_iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::MockClock;
+    use crate::{AiMessage, HumanMessage, SystemMessage};
+
+    #[test]
+    fn test_conversation_push_and_access() {
+        let mut conversation = Conversation::new();
+        assert!(conversation.is_empty());
+
+        conversation.push(SystemMessage::new("Be helpful."));
+        conversation.push(HumanMessage::new("Hello"));
+        conversation.push(AiMessage::new("Hi there!"));
+
+        assert_eq!(conversation.len(), 3);
+        let contents: Vec<&str> = conversation.iter().map(|m| m.content()).collect();
+        assert_eq!(contents, vec!["Be helpful.", "Hello", "Hi there!"]);
+    }
+
+    #[test]
+    fn test_conversation_from_iterator() {
+        let conversation: Conversation = vec![HumanMessage::new("a"), HumanMessage::new("b")]
+            .into_iter()
+            .collect();
+
+        assert_eq!(conversation.len(), 2);
+        assert_eq!(conversation.messages()[1].content(), "b");
+    }
+
+    #[test]
+    fn test_conversation_serialization_round_trip() {
+        let mut conversation = Conversation::new();
+        conversation.push(HumanMessage::new("Hello"));
+        conversation.push(AiMessage::new("Hi"));
+
+        let serialized = serde_json::to_string(&conversation).unwrap();
+        let deserialized: Conversation = serde_json::from_str(&serialized).unwrap();
+
+        assert_eq!(conversation, deserialized);
+    }
+
+    #[test]
+    fn test_fork_at_records_lineage() {
+        let mut conversation = Conversation::with_session_id("main");
+        let mut question = HumanMessage::new("Tell me a joke");
+        question.set_id(Some("m1".to_string()));
+        let mut answer = AiMessage::new("Why did the chicken...");
+        answer.set_id(Some("m2".to_string()));
+        conversation.push(question);
+        conversation.push(answer);
+
+        let fork = conversation.fork_at("retry", "m1").unwrap();
+
+        assert_eq!(fork.session_id(), Some("retry"));
+        assert_eq!(fork.len(), 1);
+        assert_eq!(
+            fork.forked_from(),
+            Some(&ForkOrigin {
+                session: "main".to_string(),
+                message_id: "m1".to_string(),
+            })
+        );
+        assert!(conversation.fork_at("retry", "missing").is_none());
+        assert!(Conversation::new().fork_at("retry", "m1").is_none());
+    }
+
+    #[test]
+    fn test_soft_delete_restore_and_purge() {
+        let clock = MockClock::new(0);
+        let mut conversation = Conversation::new();
+        for (id, content) in [("m1", "a"), ("m2", "b"), ("m3", "c")] {
+            let mut message = HumanMessage::new(content);
+            message.set_id(Some(id.to_string()));
+            conversation.push(message);
+        }
+
+        assert!(conversation.soft_delete_with("m2", &clock));
+        assert!(!conversation.soft_delete("missing"));
+        let contents: Vec<&str> = conversation.iter().map(|m| m.content()).collect();
+        assert_eq!(contents, vec!["a", "c"]);
+
+        let json = serde_json::to_string(&conversation).unwrap();
+        let mut parsed: Conversation = serde_json::from_str(&json).unwrap();
+        assert!(parsed.restore("m2"));
+        assert_eq!(parsed.messages()[1].content(), "b");
+        assert!(parsed.trash().is_empty());
+
+        let thirty_days = Duration::from_secs(30 * 24 * 60 * 60);
+        clock.advance(thirty_days - Duration::from_secs(1));
+        assert!(conversation.purge_trash(thirty_days, &clock).is_empty());
+        clock.advance(Duration::from_secs(1));
+        assert_eq!(conversation.purge_trash(thirty_days, &clock).len(), 1);
+        assert!(!conversation.restore("m2"));
+    }
+
+    #[test]
+    fn test_fingerprint_is_stable_across_kwarg_ordering() {
+        let mut first = HumanMessage::new("Hello");
+        let mut second = HumanMessage::new("Hello");
+        for key in ["a", "b", "c", "d", "e"] {
+            first
+                .base
+                .additional_kwargs
+                .insert(key.to_string(), key.to_string());
+        }
+        for key in ["e", "d", "c", "b", "a"] {
+            second
+                .base
+                .additional_kwargs
+                .insert(key.to_string(), key.to_string());
+        }
+
+        let first: Conversation = vec![first].into_iter().collect();
+        let second: Conversation = vec![second].into_iter().collect();
+        assert_eq!(first.fingerprint(), second.fingerprint());
+    }
+
+    #[test]
+    fn test_fingerprint_changes_with_content() {
+        let first: Conversation = vec![HumanMessage::new("Hello")].into_iter().collect();
+        let second: Conversation = vec![HumanMessage::new("Hello!")].into_iter().collect();
+
+        assert_ne!(first.fingerprint(), second.fingerprint());
+        assert_ne!(first.fingerprint(), Conversation::new().fingerprint());
+    }
+}
@@ -0,0 +1,169 @@
+use std::marker::PhantomData;
+
+use crate::{AiMessage, Conversation, HumanMessage, MessageEnum, SystemMessage, ToolMessage};
+
+/// Nothing added yet: a system prompt or the first human turn may follow.
+#[derive(Debug, Clone, Copy)]
+pub struct Start;
+
+/// The last turn was the system prompt or an AI reply.
+#[derive(Debug, Clone, Copy)]
+pub struct AwaitingHuman;
+
+/// The last turn was a human message.
+#[derive(Debug, Clone, Copy)]
+pub struct AwaitingAi;
+
+/// The last turn was an AI message with tool calls, or a tool result.
+#[derive(Debug, Clone, Copy)]
+pub struct AwaitingToolResult;
+
+/// Builds a [`Conversation`] whose shape is checked by the compiler: an
+/// optional system prompt first, then human and AI turns alternating, with
+/// tool results only after an AI turn that called tools. Each `add_*`
+/// consumes the builder and returns one in the next state.
+///
+/// ```compile_fail
+/// use messageforge::{ConversationBuilder, HumanMessage};
+///
+/// ConversationBuilder::new()
+///     .add_human(HumanMessage::new("Hi"))
+///     .add_human(HumanMessage::new("Hello?"));
+/// ```
+///
+/// Whether an AI message actually carries tool calls is only known at
+/// runtime; use [`ConversationBuilder::add_tool_calls`] for those and
+/// [`ConversationBuilder::add_ai`] for plain replies.
+#[derive(Debug, Clone)]
+pub struct ConversationBuilder<S> {
+    conversation: Conversation,
+    state: PhantomData<S>,
+}
+
+impl<S> ConversationBuilder<S> {
+    fn push<T>(mut self, message: impl Into<MessageEnum>) -> ConversationBuilder<T> {
+        self.conversation.push(message);
+        ConversationBuilder {
+            conversation: self.conversation,
+            state: PhantomData,
+        }
+    }
+}
+
+impl ConversationBuilder<Start> {
+    pub fn new() -> Self {
+        ConversationBuilder {
+            conversation: Conversation::new(),
+            state: PhantomData,
+        }
+    }
+
+    pub fn with_session_id(session_id: impl Into<String>) -> Self {
+        ConversationBuilder {
+            conversation: Conversation::with_session_id(session_id),
+            state: PhantomData,
+        }
+    }
+
+    pub fn add_system(self, message: SystemMessage) -> ConversationBuilder<AwaitingHuman> {
+        self.push(message)
+    }
+
+    pub fn add_human(self, message: HumanMessage) -> ConversationBuilder<AwaitingAi> {
+        self.push(message)
+    }
+}
+
+impl Default for ConversationBuilder<Start> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ConversationBuilder<AwaitingHuman> {
+    pub fn add_human(self, message: HumanMessage) -> ConversationBuilder<AwaitingAi> {
+        self.push(message)
+    }
+
+    pub fn build(self) -> Conversation {
+        self.conversation
+    }
+}
+
+impl ConversationBuilder<AwaitingAi> {
+    pub fn add_ai(self, message: AiMessage) -> ConversationBuilder<AwaitingHuman> {
+        self.push(message)
+    }
+
+    pub fn add_tool_calls(self, message: AiMessage) -> ConversationBuilder<AwaitingToolResult> {
+        self.push(message)
+    }
+
+    /// Ends on a human turn, ready to send for a reply.
+    pub fn build(self) -> Conversation {
+        self.conversation
+    }
+}
+
+impl ConversationBuilder<AwaitingToolResult> {
+    pub fn add_tool(self, message: ToolMessage) -> ConversationBuilder<AwaitingToolResult> {
+        self.push(message)
+    }
+
+    pub fn add_ai(self, message: AiMessage) -> ConversationBuilder<AwaitingHuman> {
+        self.push(message)
+    }
+
+    pub fn add_tool_calls(self, message: AiMessage) -> ConversationBuilder<AwaitingToolResult> {
+        self.push(message)
+    }
+
+    /// Ends on tool results, ready to send for the AI's next step.
+    pub fn build(self) -> Conversation {
+        self.conversation
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tool_message::ToolStatus;
+    use crate::{BaseMessage, ToolCall};
+    use serde_json::json;
+
+    #[test]
+    fn test_builds_alternating_conversation() {
+        let conversation = ConversationBuilder::new()
+            .add_system(SystemMessage::new("Be brief."))
+            .add_human(HumanMessage::new("Weather?"))
+            .add_tool_calls(AiMessage::new("").with_tool_calls(vec![ToolCall::new(
+                "call_1",
+                "weather",
+                json!({"city": "Paris"}),
+            )]))
+            .add_tool(ToolMessage::new(
+                "Sunny",
+                "call_1".to_string(),
+                None,
+                ToolStatus::Success,
+            ))
+            .add_ai(AiMessage::new("It is sunny."))
+            .add_human(HumanMessage::new("Thanks"))
+            .build();
+
+        let roles: Vec<&str> = conversation.iter().map(|message| message.role()).collect();
+        assert_eq!(roles, vec!["system", "human", "ai", "tool", "ai", "human"]);
+        #[cfg(feature = "analytics")]
+        assert!(crate::lint_conversation(&conversation).is_empty());
+    }
+
+    #[test]
+    fn test_with_session_id() {
+        let conversation = ConversationBuilder::with_session_id("s1")
+            .add_human(HumanMessage::new("Hi"))
+            .build();
+
+        assert_eq!(conversation.session_id(), Some("s1"));
+        assert_eq!(conversation.len(), 1);
+    }
+}
@@ -0,0 +1,289 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Conversation, MessageEnum};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DeltaEntry {
+    pub index: usize,
+    pub message: MessageEnum,
+}
+
+/// The messages that changed between a base snapshot and a newer one, plus
+/// enough fingerprints for the receiver to check it is patching the right base.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ConversationDelta {
+    pub base_len: usize,
+    pub base_fingerprint: u64,
+    pub len: usize,
+    pub fingerprint: u64,
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub changes: Vec<DeltaEntry>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum DeltaError {
+    BaseMismatch { expected: u64, found: u64 },
+    IndexOutOfRange { index: usize, len: usize },
+    ResultMismatch { expected: u64, found: u64 },
+}
+
+impl fmt::Display for DeltaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DeltaError::BaseMismatch { expected, found } => write!(
+                f,
+                "Delta base mismatch: expected fingerprint {:016x}, found {:016x}",
+                expected, found
+            ),
+            DeltaError::IndexOutOfRange { index, len } => write!(
+                f,
+                "Delta entry index {} is out of range for length {}",
+                index, len
+            ),
+            DeltaError::ResultMismatch { expected, found } => write!(
+                f,
+                "Delta result mismatch: expected fingerprint {:016x}, found {:016x}",
+                expected, found
+            ),
+        }
+    }
+}
+
+impl std::error::Error for DeltaError {}
+
+impl ConversationDelta {
+    pub fn between(base: &Conversation, current: &Conversation) -> Self {
+        let base_messages = base.messages();
+        let changes = current
+            .iter()
+            .enumerate()
+            .filter(|(index, message)| {
+                base_messages
+                    .get(*index)
+                    .is_none_or(|previous| previous != *message)
+            })
+            .map(|(index, message)| DeltaEntry {
+                index,
+                message: message.clone(),
+            })
+            .collect();
+
+        ConversationDelta {
+            base_len: base.len(),
+            base_fingerprint: base.fingerprint(),
+            len: current.len(),
+            fingerprint: current.fingerprint(),
+            changes,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty() && self.base_len == self.len
+    }
+
+    pub fn verify_base(&self, base: &Conversation) -> Result<(), DeltaError> {
+        let found = base.fingerprint();
+        if base.len() != self.base_len || found != self.base_fingerprint {
+            return Err(DeltaError::BaseMismatch {
+                expected: self.base_fingerprint,
+                found,
+            });
+        }
+        Ok(())
+    }
+
+    pub fn verify(&self, result: &Conversation) -> Result<(), DeltaError> {
+        let found = result.fingerprint();
+        if result.len() != self.len || found != self.fingerprint {
+            return Err(DeltaError::ResultMismatch {
+                expected: self.fingerprint,
+                found,
+            });
+        }
+        Ok(())
+    }
+
+    pub fn apply(&self, base: &Conversation) -> Result<Conversation, DeltaError> {
+        self.verify_base(base)?;
+
+        let mut messages: Vec<Option<MessageEnum>> =
+            base.iter().take(self.len).cloned().map(Some).collect();
+        messages.resize(self.len, None);
+
+        for entry in &self.changes {
+            let slot = messages
+                .get_mut(entry.index)
+                .ok_or(DeltaError::IndexOutOfRange {
+                    index: entry.index,
+                    len: self.len,
+                })?;
+            *slot = Some(entry.message.clone());
+        }
+
+        // A slot left empty means the delta skipped a message the base never had.
+        let messages = messages
+            .into_iter()
+            .enumerate()
+            .map(|(index, message)| {
+                message.ok_or(DeltaError::IndexOutOfRange {
+                    index,
+                    len: self.base_len,
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut result = base.clone();
+        *result.messages_mut() = messages;
+        self.verify(&result)?;
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AiMessage, BaseMessage, HumanMessage, SystemMessage};
+
+    fn base_conversation() -> Conversation {
+        let mut conversation = Conversation::new();
+        conversation.push(SystemMessage::new("Be helpful."));
+        conversation.push(HumanMessage::new("Hello"));
+        conversation.push(AiMessage::new("Hi there!"));
+        conversation
+    }
+
+    #[test]
+    fn test_delta_only_contains_new_messages() {
+        let base = base_conversation();
+        let mut current = base.clone();
+        current.push(HumanMessage::new("How are you?"));
+        current.push(AiMessage::new("Great."));
+
+        let delta = ConversationDelta::between(&base, &current);
+
+        assert_eq!(delta.base_len, 3);
+        assert_eq!(delta.len, 5);
+        let indexes: Vec<usize> = delta.changes.iter().map(|entry| entry.index).collect();
+        assert_eq!(indexes, vec![3, 4]);
+        assert_eq!(delta.apply(&base).unwrap(), current);
+    }
+
+    #[test]
+    fn test_apply_keeps_session_fork_and_trash() {
+        let mut parent = Conversation::with_session_id("parent");
+        parent.push(HumanMessage::builder().id("m1").content("Hello").build());
+        parent.push(AiMessage::builder().id("m2").content("Hi there!").build());
+        let mut base = parent.fork_at("child", "m2").unwrap();
+        base.push(HumanMessage::builder().id("m3").content("Oops").build());
+        assert!(base.soft_delete("m3"));
+        let mut current = base.clone();
+        current.push(HumanMessage::new("How are you?"));
+
+        let result = ConversationDelta::between(&base, &current)
+            .apply(&base)
+            .unwrap();
+
+        assert_eq!(result.session_id(), Some("child"));
+        assert_eq!(result.forked_from(), base.forked_from());
+        assert_eq!(result.trash(), base.trash());
+        assert_eq!(result, current);
+    }
+
+    #[test]
+    fn test_delta_with_edited_message() {
+        let base = base_conversation();
+        let mut current = base.clone();
+        current.messages_mut()[2] = AiMessage::new("Hello! How can I help?").into();
+
+        let delta = ConversationDelta::between(&base, &current);
+
+        assert_eq!(delta.changes.len(), 1);
+        assert_eq!(delta.changes[0].index, 2);
+        assert_eq!(delta.changes[0].message.content(), "Hello! How can I help?");
+        assert_eq!(delta.apply(&base).unwrap(), current);
+    }
+
+    #[test]
+    fn test_delta_with_truncation() {
+        let base = base_conversation();
+        let mut current = base.clone();
+        current.messages_mut().truncate(1);
+
+        let delta = ConversationDelta::between(&base, &current);
+
+        assert!(delta.changes.is_empty());
+        assert!(!delta.is_empty());
+        assert_eq!(delta.apply(&base).unwrap(), current);
+    }
+
+    #[test]
+    fn test_empty_delta() {
+        let base = base_conversation();
+        let delta = ConversationDelta::between(&base, &base);
+
+        assert!(delta.is_empty());
+        assert_eq!(delta.apply(&base).unwrap(), base);
+    }
+
+    #[test]
+    fn test_delta_rejects_wrong_base() {
+        let base = base_conversation();
+        let mut current = base.clone();
+        current.push(HumanMessage::new("More"));
+        let delta = ConversationDelta::between(&base, &current);
+
+        let mut other = base.clone();
+        other.messages_mut()[1] = HumanMessage::new("Something else").into();
+
+        match delta.apply(&other) {
+            Err(DeltaError::BaseMismatch { expected, .. }) => {
+                assert_eq!(expected, base.fingerprint())
+            }
+            other => panic!("Expected a base mismatch, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_delta_detects_tampered_changes() {
+        let base = base_conversation();
+        let mut current = base.clone();
+        current.push(HumanMessage::new("Original"));
+        let mut delta = ConversationDelta::between(&base, &current);
+        delta.changes[0].message = HumanMessage::new("Tampered").into();
+
+        assert!(matches!(
+            delta.apply(&base),
+            Err(DeltaError::ResultMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn test_delta_rejects_out_of_range_index() {
+        let base = base_conversation();
+        let mut current = base.clone();
+        current.push(HumanMessage::new("New"));
+        let mut delta = ConversationDelta::between(&base, &current);
+        delta.changes[0].index = 10;
+
+        assert_eq!(
+            delta.apply(&base),
+            Err(DeltaError::IndexOutOfRange { index: 10, len: 4 })
+        );
+    }
+
+    #[test]
+    fn test_delta_serialization_round_trip() {
+        let base = base_conversation();
+        let mut current = base.clone();
+        current.push(HumanMessage::new("Next question"));
+        let delta = ConversationDelta::between(&base, &current);
+
+        let serialized = serde_json::to_string(&delta).unwrap();
+        let deserialized: ConversationDelta = serde_json::from_str(&serialized).unwrap();
+
+        assert_eq!(deserialized, delta);
+        assert_eq!(deserialized.apply(&base).unwrap(), current);
+    }
+}
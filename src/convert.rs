@@ -0,0 +1,162 @@
+use crate::{InvalidMessageTypeError, MessageEnum};
+
+/// Outcome of a batch conversion: every successful output in input order and
+/// every failure keyed by its position in the input.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BatchResult<T, E> {
+    pub ok: Vec<T>,
+    pub errors: Vec<(usize, E)>,
+}
+
+impl<T, E> Default for BatchResult<T, E> {
+    fn default() -> Self {
+        BatchResult {
+            ok: Vec::new(),
+            errors: Vec::new(),
+        }
+    }
+}
+
+impl<T, E> BatchResult<T, E> {
+    pub fn is_clean(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    pub fn total(&self) -> usize {
+        self.ok.len() + self.errors.len()
+    }
+
+    /// Turns the batch into a strict result, failing with the first error.
+    pub fn into_result(self) -> Result<Vec<T>, (usize, E)> {
+        match self.errors.into_iter().next() {
+            Some(error) => Err(error),
+            None => Ok(self.ok),
+        }
+    }
+}
+
+/// Common shape for adapters and importers that turn one input record into a
+/// value. `convert_all` keeps going past bad records; `convert_all_strict`
+/// stops at the first one.
+pub trait Converter<In> {
+    type Output;
+    type Error;
+
+    fn convert(&self, input: In) -> Result<Self::Output, Self::Error>;
+
+    fn convert_all<I>(&self, inputs: I) -> BatchResult<Self::Output, Self::Error>
+    where
+        I: IntoIterator<Item = In>,
+    {
+        let mut result = BatchResult::default();
+        for (index, input) in inputs.into_iter().enumerate() {
+            match self.convert(input) {
+                Ok(output) => result.ok.push(output),
+                Err(error) => result.errors.push((index, error)),
+            }
+        }
+        result
+    }
+
+    fn convert_all_strict<I>(&self, inputs: I) -> Result<Vec<Self::Output>, (usize, Self::Error)>
+    where
+        I: IntoIterator<Item = In>,
+    {
+        inputs
+            .into_iter()
+            .enumerate()
+            .map(|(index, input)| self.convert(input).map_err(|error| (index, error)))
+            .collect()
+    }
+}
+
+/// Converts with a plain function or closure.
+pub fn convert_all<In, T, E, I, F>(inputs: I, convert: F) -> BatchResult<T, E>
+where
+    I: IntoIterator<Item = In>,
+    F: Fn(In) -> Result<T, E>,
+{
+    FnConverter(convert).convert_all(inputs)
+}
+
+struct FnConverter<F>(F);
+
+impl<In, T, E, F: Fn(In) -> Result<T, E>> Converter<In> for FnConverter<F> {
+    type Output = T;
+    type Error = E;
+
+    fn convert(&self, input: In) -> Result<T, E> {
+        (self.0)(input)
+    }
+}
+
+/// JSON records (one serialized [`MessageEnum`] each), e.g. JSONL rows.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct JsonMessageConverter;
+
+impl<'a> Converter<&'a str> for JsonMessageConverter {
+    type Output = MessageEnum;
+    type Error = serde_json::Error;
+
+    fn convert(&self, input: &'a str) -> Result<MessageEnum, serde_json::Error> {
+        serde_json::from_str(input)
+    }
+}
+
+/// `role: content` lines as accepted by `MessageEnum::try_from`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LineMessageConverter;
+
+impl<'a> Converter<&'a str> for LineMessageConverter {
+    type Output = MessageEnum;
+    type Error = InvalidMessageTypeError;
+
+    fn convert(&self, input: &'a str) -> Result<MessageEnum, InvalidMessageTypeError> {
+        MessageEnum::try_from(input)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::BaseMessage;
+
+    #[test]
+    fn test_convert_all_collects_errors() {
+        let lines = ["human: Hi", "nonsense", "ai: Hello", "robot: beep"];
+
+        let result = LineMessageConverter.convert_all(lines);
+
+        assert_eq!(result.ok.len(), 2);
+        assert_eq!(result.ok[1].content(), "Hello");
+        let failed: Vec<usize> = result.errors.iter().map(|(index, _)| *index).collect();
+        assert_eq!(failed, vec![1, 3]);
+        assert_eq!(result.total(), 4);
+        assert!(!result.is_clean());
+    }
+
+    #[test]
+    fn test_convert_all_strict_fails_fast() {
+        let rows = [
+            r#"{"role":"human","content":"Hi","message_type":"Human"}"#,
+            r#"{"role":"human""#,
+            r#"{"role":"ai","content":"Hello","message_type":"Ai"}"#,
+        ];
+
+        let error = JsonMessageConverter.convert_all_strict(rows).unwrap_err();
+        assert_eq!(error.0, 1);
+
+        let result = JsonMessageConverter.convert_all(rows);
+        assert_eq!(result.ok.len(), 2);
+        assert_eq!(result.into_result().unwrap_err().0, 1);
+    }
+
+    #[test]
+    fn test_convert_all_with_closure() {
+        let result = convert_all(["1", "x", "3"], |s: &str| s.parse::<u32>());
+
+        assert_eq!(result.ok, vec![1, 3]);
+        assert_eq!(result.errors.len(), 1);
+        assert_eq!(result.errors[0].0, 1);
+    }
+}
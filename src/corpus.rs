@@ -0,0 +1,176 @@
+use std::borrow::Borrow;
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::tokens::approximate_tokens;
+use crate::tool_pairs::requested_tool_call_ids;
+use crate::{BaseMessage, Conversation};
+
+/// Power-of-two histogram: each bucket is keyed by its lower bound, so a
+/// value `v > 0` lands in `[2^k, 2^(k+1))` and zero has its own bucket.
+/// Constant memory however many values are added.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Histogram {
+    pub count: u64,
+    pub sum: u64,
+    pub min: Option<u64>,
+    pub max: Option<u64>,
+    pub buckets: BTreeMap<u64, u64>,
+}
+
+impl Histogram {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(&mut self, value: u64) {
+        self.count += 1;
+        self.sum += value;
+        self.min = Some(self.min.map_or(value, |min| min.min(value)));
+        self.max = Some(self.max.map_or(value, |max| max.max(value)));
+        *self.buckets.entry(bucket_floor(value)).or_default() += 1;
+    }
+
+    pub fn mean(&self) -> f64 {
+        if self.count == 0 {
+            0.0
+        } else {
+            self.sum as f64 / self.count as f64
+        }
+    }
+}
+
+fn bucket_floor(value: u64) -> u64 {
+    match value {
+        0 => 0,
+        _ => 1 << value.ilog2(),
+    }
+}
+
+/// Corpus-wide statistics. Per-conversation figures are histograms; role
+/// counts are totals over every message.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CorpusReport {
+    pub conversations: u64,
+    pub messages: u64,
+    pub roles: BTreeMap<String, u64>,
+    pub conversations_with_tools: u64,
+    pub messages_per_conversation: Histogram,
+    pub tokens_per_conversation: Histogram,
+    pub tokens_per_message: Histogram,
+    pub tool_calls_per_conversation: Histogram,
+}
+
+impl CorpusReport {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(&mut self, conversation: &Conversation) {
+        let mut tokens = 0;
+        let mut tool_calls = 0;
+        let mut uses_tools = false;
+        for message in conversation {
+            let message_tokens = approximate_tokens(message.content()) as u64;
+            tokens += message_tokens;
+            self.tokens_per_message.add(message_tokens);
+            *self
+                .roles
+                .entry(message.type_name().to_string())
+                .or_default() += 1;
+
+            let calls = requested_tool_call_ids(message).len() as u64;
+            tool_calls += calls;
+            uses_tools |= calls > 0 || message.as_tool().is_some();
+        }
+
+        self.conversations += 1;
+        self.messages += conversation.len() as u64;
+        self.conversations_with_tools += u64::from(uses_tools);
+        self.messages_per_conversation
+            .add(conversation.len() as u64);
+        self.tokens_per_conversation.add(tokens);
+        self.tool_calls_per_conversation.add(tool_calls);
+    }
+}
+
+/// Folds conversations into a [`CorpusReport`] one at a time, so a corpus
+/// streamed from disk is never held in memory.
+pub fn analyze<I>(conversations: I) -> CorpusReport
+where
+    I: IntoIterator,
+    I::Item: Borrow<Conversation>,
+{
+    let mut report = CorpusReport::new();
+    for conversation in conversations {
+        report.add(conversation.borrow());
+    }
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tool_message::ToolStatus;
+    use crate::{AiMessage, HumanMessage, MessageEnum, ToolCall, ToolMessage};
+
+    fn with_tools() -> Conversation {
+        let call = AiMessage::new("").with_tool_calls(vec![
+            ToolCall::new("a", "weather", serde_json::json!({"city": "Paris"})),
+            ToolCall::new("b", "weather", serde_json::json!({"city": "Rome"})),
+        ]);
+        vec![
+            MessageEnum::from(HumanMessage::new("Weather in Paris and Rome?")),
+            call.into(),
+            ToolMessage::new("Sunny", "a".to_string(), None, ToolStatus::Success).into(),
+            ToolMessage::new("Rainy", "b".to_string(), None, ToolStatus::Success).into(),
+        ]
+        .into_iter()
+        .collect()
+    }
+
+    fn chat() -> Conversation {
+        vec![
+            MessageEnum::from(HumanMessage::new("Hi")),
+            AiMessage::new("Hello there").into(),
+        ]
+        .into_iter()
+        .collect()
+    }
+
+    #[test]
+    fn test_histogram_buckets() {
+        let mut histogram = Histogram::new();
+        for value in [0, 1, 3, 4, 7, 8, 1000] {
+            histogram.add(value);
+        }
+
+        let buckets: Vec<(u64, u64)> = histogram.buckets.into_iter().collect();
+        assert_eq!(
+            buckets,
+            vec![(0, 1), (1, 1), (2, 1), (4, 2), (8, 1), (512, 1)]
+        );
+        assert_eq!(histogram.min, Some(0));
+        assert_eq!(histogram.max, Some(1000));
+        assert_eq!(Histogram::new().mean(), 0.0);
+    }
+
+    #[test]
+    fn test_analyze_streams_conversations() {
+        let report = analyze((0..3).map(|i| if i == 0 { with_tools() } else { chat() }));
+
+        assert_eq!(report.conversations, 3);
+        assert_eq!(report.messages, 8);
+        assert_eq!(report.roles["tool"], 2);
+        assert_eq!(report.roles["human"], 3);
+        assert_eq!(report.conversations_with_tools, 1);
+        assert_eq!(report.tool_calls_per_conversation.sum, 2);
+        assert_eq!(report.messages_per_conversation.buckets[&2], 2);
+
+        let json = serde_json::to_string(&report).unwrap();
+        let parsed: CorpusReport = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, report);
+        assert_eq!(analyze(&[chat()]).conversations, 1);
+    }
+}
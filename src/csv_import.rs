@@ -0,0 +1,314 @@
+use std::fmt;
+
+use crate::{
+    AiMessage, Conversation, HumanMessage, MessageEnum, MessageType, MessageTypeInfo, SystemMessage,
+};
+
+/// `response_metadata` key the timestamp column is written to, matching the
+/// default of [`crate::MergeStrategy`].
+pub const CSV_TIMESTAMP_KEY: &str = "timestamp";
+
+/// The file could not be imported at all, e.g. a required column is absent.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CsvImportError(pub String);
+
+impl fmt::Display for CsvImportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "CSV import failed: {}", self.0)
+    }
+}
+
+impl std::error::Error for CsvImportError {}
+
+/// A row that was skipped, with the 1-based line it starts on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CsvRowError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for CsvRowError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CsvImport {
+    /// One conversation per session, in order of first appearance.
+    pub conversations: Vec<Conversation>,
+    pub errors: Vec<CsvRowError>,
+}
+
+impl CsvImport {
+    pub fn is_clean(&self) -> bool {
+        self.errors.is_empty()
+    }
+}
+
+/// Maps the columns of a CSV or TSV export, found by header name, onto
+/// messages. Role and content columns are required; timestamp (integer
+/// milliseconds) and session columns are used when the header has them.
+/// Without a session column every row lands in a single conversation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CsvImporter {
+    delimiter: char,
+    role_column: String,
+    content_column: String,
+    timestamp_column: String,
+    session_column: String,
+}
+
+impl Default for CsvImporter {
+    fn default() -> Self {
+        CsvImporter {
+            delimiter: ',',
+            role_column: "role".to_string(),
+            content_column: "content".to_string(),
+            timestamp_column: "timestamp".to_string(),
+            session_column: "session".to_string(),
+        }
+    }
+}
+
+impl CsvImporter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn tsv() -> Self {
+        Self::default().with_delimiter('\t')
+    }
+
+    pub fn with_delimiter(mut self, delimiter: char) -> Self {
+        self.delimiter = delimiter;
+        self
+    }
+
+    pub fn with_role_column(mut self, name: impl Into<String>) -> Self {
+        self.role_column = name.into();
+        self
+    }
+
+    pub fn with_content_column(mut self, name: impl Into<String>) -> Self {
+        self.content_column = name.into();
+        self
+    }
+
+    pub fn with_timestamp_column(mut self, name: impl Into<String>) -> Self {
+        self.timestamp_column = name.into();
+        self
+    }
+
+    pub fn with_session_column(mut self, name: impl Into<String>) -> Self {
+        self.session_column = name.into();
+        self
+    }
+
+    pub fn import(&self, input: &str) -> Result<CsvImport, CsvImportError> {
+        let mut records = parse_records(input, self.delimiter);
+        let header = match records.next() {
+            Some(Ok((_, header))) => header,
+            Some(Err(error)) => return Err(CsvImportError(error.to_string())),
+            None => return Err(CsvImportError("input is empty".to_string())),
+        };
+        let find = |name: &str| header.iter().position(|column| column.trim() == name);
+        let required = |name: &str| {
+            find(name).ok_or_else(|| CsvImportError(format!("missing '{}' column", name)))
+        };
+        let role = required(&self.role_column)?;
+        let content = required(&self.content_column)?;
+        let timestamp = find(&self.timestamp_column);
+        let session = find(&self.session_column);
+
+        let mut import = CsvImport::default();
+        for record in records {
+            let (line, fields) = match record {
+                Ok(record) => record,
+                Err(error) => {
+                    import.errors.push(error);
+                    continue;
+                }
+            };
+            if fields.len() != header.len() {
+                import.errors.push(CsvRowError {
+                    line,
+                    message: format!("expected {} fields, found {}", header.len(), fields.len()),
+                });
+                continue;
+            }
+            let message = match build_message(&fields[role], &fields[content]) {
+                Ok(message) => message,
+                Err(message) => {
+                    import.errors.push(CsvRowError { line, message });
+                    continue;
+                }
+            };
+            let message = match timestamp.map(|index| fields[index].trim()) {
+                Some(value) if !value.is_empty() => match value.parse::<u64>() {
+                    Ok(ms) => with_timestamp(message, ms),
+                    Err(_) => {
+                        import.errors.push(CsvRowError {
+                            line,
+                            message: format!("invalid timestamp '{}'", value),
+                        });
+                        continue;
+                    }
+                },
+                _ => message,
+            };
+            let session_id = session.map(|index| fields[index].trim().to_string());
+            let conversation = match import
+                .conversations
+                .iter()
+                .position(|conversation| conversation.session_id() == session_id.as_deref())
+            {
+                Some(position) => &mut import.conversations[position],
+                None => {
+                    let mut conversation = Conversation::new();
+                    conversation.set_session_id(session_id);
+                    import.conversations.push(conversation);
+                    import.conversations.last_mut().expect("pushed above")
+                }
+            };
+            conversation.push(message);
+        }
+        Ok(import)
+    }
+}
+
+fn build_message(role: &str, content: &str) -> Result<MessageEnum, String> {
+    let info = MessageTypeInfo::lookup(role.trim())
+        .ok_or_else(|| format!("unknown role '{}'", role.trim()))?;
+    match info.message_type {
+        MessageType::Human => Ok(HumanMessage::new(content).into()),
+        MessageType::Ai => Ok(AiMessage::new(content).into()),
+        MessageType::System => Ok(SystemMessage::new(content).into()),
+        _ => Err(format!(
+            "role '{}' cannot be imported from a spreadsheet",
+            info.role
+        )),
+    }
+}
+
+fn with_timestamp(mut message: MessageEnum, ms: u64) -> MessageEnum {
+    message
+        .base_mut()
+        .response_metadata
+        .insert(CSV_TIMESTAMP_KEY, ms);
+    message
+}
+
+type Record = Result<(usize, Vec<String>), CsvRowError>;
+
+// RFC 4180 quoting: quoted fields may hold delimiters, newlines and `""`.
+// Each record reports the line it starts on; blank lines are skipped.
+fn parse_records(input: &str, delimiter: char) -> impl Iterator<Item = Record> + '_ {
+    let mut chars = input.chars().peekable();
+    let mut line = 1;
+    std::iter::from_fn(move || loop {
+        chars.peek()?;
+        let start = line;
+        let mut fields = Vec::new();
+        let mut field = String::new();
+        let mut quoted = false;
+        loop {
+            match chars.next() {
+                None if quoted => {
+                    return Some(Err(CsvRowError {
+                        line: start,
+                        message: "unterminated quoted field".to_string(),
+                    }))
+                }
+                None => break,
+                Some('"') if quoted => {
+                    if chars.peek() == Some(&'"') {
+                        chars.next();
+                        field.push('"');
+                    } else {
+                        quoted = false;
+                    }
+                }
+                Some('"') if field.is_empty() => quoted = true,
+                Some('\n') if !quoted => {
+                    line += 1;
+                    break;
+                }
+                Some('\r') if !quoted && chars.peek() == Some(&'\n') => {}
+                Some(c) if c == delimiter && !quoted => fields.push(std::mem::take(&mut field)),
+                Some(c) => {
+                    if c == '\n' {
+                        line += 1;
+                    }
+                    field.push(c);
+                }
+            }
+        }
+        if fields.is_empty() && field.trim().is_empty() {
+            continue;
+        }
+        fields.push(field);
+        return Some(Ok((start, fields)));
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::BaseMessage;
+
+    #[test]
+    fn test_import_groups_sessions_and_reports_bad_rows() {
+        let input = "session,role,content,timestamp\n\
+                     s1,user,\"Hello, there\",1000\n\
+                     s2,system,Be brief.,\n\
+                     s1,assistant,\"Line one\nLine \"\"two\"\"\",2000\n\
+                     s1,critic,Nope,3000\n\
+                     s2,user,Hi,yesterday\n\
+                     s2,user,too,many,fields\n";
+
+        let import = CsvImporter::new().import(input).unwrap();
+
+        assert_eq!(import.conversations.len(), 2);
+        let s1 = &import.conversations[0];
+        assert_eq!(s1.session_id(), Some("s1"));
+        assert_eq!(s1.len(), 2);
+        assert_eq!(s1.messages()[0].content(), "Hello, there");
+        assert_eq!(s1.messages()[1].content(), "Line one\nLine \"two\"");
+        assert_eq!(
+            s1.messages()[1]
+                .response_metadata()
+                .get_u64(CSV_TIMESTAMP_KEY),
+            Some(2000)
+        );
+        assert!(s1.messages()[1].as_ai().is_some());
+        assert_eq!(import.conversations[1].len(), 1);
+
+        let lines: Vec<usize> = import.errors.iter().map(|error| error.line).collect();
+        assert_eq!(lines, vec![6, 7, 8]);
+        assert_eq!(
+            import.errors[0].to_string(),
+            "line 6: unknown role 'critic'"
+        );
+    }
+
+    #[test]
+    fn test_tsv_with_custom_columns() {
+        let input = "speaker\ttext\nhuman\tHi\nai\tHello\n";
+
+        let import = CsvImporter::tsv()
+            .with_role_column("speaker")
+            .with_content_column("text")
+            .import(input)
+            .unwrap();
+
+        assert!(import.is_clean());
+        assert_eq!(import.conversations.len(), 1);
+        assert_eq!(import.conversations[0].session_id(), None);
+        assert_eq!(import.conversations[0].len(), 2);
+        assert_eq!(
+            CsvImporter::new().import("role,text\n"),
+            Err(CsvImportError("missing 'content' column".to_string()))
+        );
+    }
+}
@@ -0,0 +1,304 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
+use crate::id_generator::SplitMix64;
+use crate::{BaseMessage, Conversation};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SplitRatios {
+    pub train: f64,
+    pub val: f64,
+    pub test: f64,
+}
+
+impl Default for SplitRatios {
+    fn default() -> Self {
+        SplitRatios {
+            train: 0.8,
+            val: 0.1,
+            test: 0.1,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct InvalidSplitError(String);
+
+impl fmt::Display for InvalidSplitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid split ratios: {}", self.0)
+    }
+}
+
+impl std::error::Error for InvalidSplitError {}
+
+impl SplitRatios {
+    pub fn new(train: f64, val: f64, test: f64) -> Result<Self, InvalidSplitError> {
+        let ratios = SplitRatios { train, val, test };
+        ratios.validate()?;
+        Ok(ratios)
+    }
+
+    fn validate(&self) -> Result<(), InvalidSplitError> {
+        let parts = [self.train, self.val, self.test];
+        if parts.iter().any(|part| !part.is_finite() || *part < 0.0) {
+            return Err(InvalidSplitError(format!(
+                "{:?} contains a negative or non-finite ratio",
+                parts
+            )));
+        }
+        if parts.iter().sum::<f64>() <= 0.0 {
+            return Err(InvalidSplitError("ratios sum to zero".to_string()));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DatasetSplit {
+    pub train: Vec<Conversation>,
+    pub val: Vec<Conversation>,
+    pub test: Vec<Conversation>,
+}
+
+impl DatasetSplit {
+    pub fn len(&self) -> usize {
+        self.train.len() + self.val.len() + self.test.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Shuffles with `seed` and splits by `ratios`. The same input and seed always
+/// produce the same split.
+pub fn split_dataset(
+    conversations: Vec<Conversation>,
+    ratios: SplitRatios,
+    seed: u64,
+) -> Result<DatasetSplit, InvalidSplitError> {
+    split_stratified(conversations, ratios, seed, |_| String::new())
+}
+
+/// Like [`split_dataset`], but splits each stratum returned by `key`
+/// separately so every split keeps the same mix of strata.
+pub fn split_stratified<F>(
+    conversations: Vec<Conversation>,
+    ratios: SplitRatios,
+    seed: u64,
+    key: F,
+) -> Result<DatasetSplit, InvalidSplitError>
+where
+    F: Fn(&Conversation) -> String,
+{
+    ratios.validate()?;
+
+    let mut strata: BTreeMap<String, Vec<Conversation>> = BTreeMap::new();
+    for conversation in conversations {
+        strata
+            .entry(key(&conversation))
+            .or_default()
+            .push(conversation);
+    }
+
+    let total = ratios.train + ratios.val + ratios.test;
+    let mut rng = SplitMix64::new(seed);
+    let mut split = DatasetSplit::default();
+    for (_, mut stratum) in strata {
+        rng.shuffle(&mut stratum);
+        let len = stratum.len();
+        let train = ((len as f64) * ratios.train / total).round() as usize;
+        let val = (((len as f64) * ratios.val / total).round() as usize).min(len - train);
+
+        let test = stratum.split_off(train + val);
+        let val_part = stratum.split_off(train);
+        split.train.extend(stratum);
+        split.val.extend(val_part);
+        split.test.extend(test);
+    }
+    Ok(split)
+}
+
+/// Stratum key that buckets conversations by message count. `bounds` are the
+/// exclusive upper limits of each bucket, in increasing order.
+pub fn length_bucket(bounds: &[usize]) -> impl Fn(&Conversation) -> String + '_ {
+    move |conversation| {
+        let len = conversation.len();
+        match bounds.iter().position(|bound| len < *bound) {
+            Some(index) => format!("len<{}", bounds[index]),
+            None => format!("len>={}", bounds.last().copied().unwrap_or_default()),
+        }
+    }
+}
+
+/// Training weight per message: few-shot examples (`example == true`) get
+/// `example`, everything else gets `default`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MessageWeights {
+    pub example: f64,
+    pub default: f64,
+}
+
+impl Default for MessageWeights {
+    fn default() -> Self {
+        MessageWeights {
+            example: 0.0,
+            default: 1.0,
+        }
+    }
+}
+
+impl MessageWeights {
+    pub fn for_conversation(&self, conversation: &Conversation) -> Vec<f64> {
+        conversation
+            .iter()
+            .map(|message| {
+                if message.is_example() {
+                    self.example
+                } else {
+                    self.default
+                }
+            })
+            .collect()
+    }
+
+    pub fn total(&self, conversation: &Conversation) -> f64 {
+        self.for_conversation(conversation).iter().sum()
+    }
+}
+
+/// Draws `count` conversations with replacement, each chosen with
+/// probability proportional to its total message weight.
+pub fn weighted_sample<'a>(
+    conversations: &'a [Conversation],
+    weights: &MessageWeights,
+    count: usize,
+    seed: u64,
+) -> Vec<&'a Conversation> {
+    let cumulative: Vec<f64> = conversations
+        .iter()
+        .scan(0.0, |sum, conversation| {
+            *sum += weights.total(conversation).max(0.0);
+            Some(*sum)
+        })
+        .collect();
+    let total = cumulative.last().copied().unwrap_or_default();
+    if total <= 0.0 {
+        return Vec::new();
+    }
+
+    let mut rng = SplitMix64::new(seed);
+    (0..count)
+        .map(|_| {
+            let target = rng.next_f64() * total;
+            let index = cumulative
+                .partition_point(|sum| *sum <= target)
+                .min(conversations.len() - 1);
+            &conversations[index]
+        })
+        .collect()
+}
+
+impl SplitMix64 {
+    fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            let j = (self.next_u64() % (i as u64 + 1)) as usize;
+            items.swap(i, j);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AiMessage, HumanMessage};
+
+    fn corpus(count: usize) -> Vec<Conversation> {
+        (0..count)
+            .map(|i| {
+                let mut conversation = Conversation::new();
+                for turn in 0..(1 + i % 3) {
+                    conversation.push(HumanMessage::new(&format!("{}-{}", i, turn)));
+                }
+                conversation
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_split_is_deterministic_and_complete() {
+        let first = split_dataset(corpus(50), SplitRatios::default(), 7).unwrap();
+        let second = split_dataset(corpus(50), SplitRatios::default(), 7).unwrap();
+        let other_seed = split_dataset(corpus(50), SplitRatios::default(), 8).unwrap();
+
+        assert_eq!(first, second);
+        assert_ne!(first, other_seed);
+        assert_eq!(first.len(), 50);
+        assert_eq!(
+            (first.train.len(), first.val.len(), first.test.len()),
+            (40, 5, 5)
+        );
+    }
+
+    #[test]
+    fn test_stratified_split_keeps_length_mix() {
+        let bounds = [2, 3];
+        let split = split_stratified(
+            corpus(60),
+            SplitRatios::new(0.5, 0.0, 0.5).unwrap(),
+            1,
+            length_bucket(&bounds),
+        )
+        .unwrap();
+
+        let count =
+            |set: &[Conversation], len: usize| set.iter().filter(|c| c.len() == len).count();
+        for len in 1..=3 {
+            assert_eq!(count(&split.train, len), 10);
+            assert_eq!(count(&split.test, len), 10);
+        }
+        assert!(split.val.is_empty());
+    }
+
+    #[test]
+    fn test_invalid_ratios() {
+        assert!(SplitRatios::new(-0.1, 0.5, 0.5).is_err());
+        assert!(SplitRatios::new(0.0, 0.0, 0.0).is_err());
+    }
+
+    #[test]
+    fn test_message_weights_use_example_flag() {
+        let mut example = HumanMessage::new("shot");
+        example.set_example(true);
+        let conversation: Conversation = vec![
+            crate::MessageEnum::from(example),
+            HumanMessage::new("real").into(),
+            AiMessage::new("answer").into(),
+        ]
+        .into_iter()
+        .collect();
+
+        let weights = MessageWeights::default();
+        assert_eq!(weights.for_conversation(&conversation), vec![0.0, 1.0, 1.0]);
+        assert_eq!(weights.total(&conversation), 2.0);
+    }
+
+    #[test]
+    fn test_weighted_sample_skips_zero_weight() {
+        let mut only_examples = HumanMessage::new("shot");
+        only_examples.set_example(true);
+        let conversations = vec![
+            Conversation::from_iter(vec![only_examples]),
+            Conversation::from_iter(vec![HumanMessage::new("real")]),
+        ];
+
+        let sample = weighted_sample(&conversations, &MessageWeights::default(), 20, 3);
+        assert_eq!(sample.len(), 20);
+        assert!(sample.iter().all(|c| c.messages()[0].content() == "real"));
+    }
+}
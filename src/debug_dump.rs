@@ -0,0 +1,306 @@
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+
+use crate::{BaseMessage, Conversation, MessageEnum, Metadata};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DumpFormat {
+    Json,
+    Yaml,
+}
+
+#[derive(Debug, Clone)]
+pub struct DumpOptions {
+    pub format: DumpFormat,
+    pub indent: usize,
+    pub max_content_chars: Option<usize>,
+    pub fold_metadata: bool,
+}
+
+impl Default for DumpOptions {
+    fn default() -> Self {
+        DumpOptions {
+            format: DumpFormat::Json,
+            indent: 2,
+            max_content_chars: Some(200),
+            fold_metadata: true,
+        }
+    }
+}
+
+/// Renders an annotated dump of `conversation` for bug reports: long content
+/// is elided and, when folded, kwargs/metadata only show their keys.
+pub fn dump_debug(conversation: &Conversation, options: &DumpOptions) -> String {
+    let mut root = Map::new();
+    if let Some(session_id) = conversation.session_id() {
+        root.insert("session_id".to_string(), json!(session_id));
+    }
+    root.insert("message_count".to_string(), json!(conversation.len()));
+    root.insert(
+        "messages".to_string(),
+        Value::Array(
+            conversation
+                .iter()
+                .enumerate()
+                .map(|(index, message)| dump_message(index, message, options))
+                .collect(),
+        ),
+    );
+    let root = Value::Object(root);
+
+    match options.format {
+        DumpFormat::Json => {
+            let indent = " ".repeat(options.indent);
+            let formatter = serde_json::ser::PrettyFormatter::with_indent(indent.as_bytes());
+            let mut buffer = Vec::new();
+            let mut serializer = serde_json::Serializer::with_formatter(&mut buffer, formatter);
+            root.serialize(&mut serializer)
+                .expect("serializing a Value cannot fail");
+            String::from_utf8(buffer).expect("serde_json emits UTF-8")
+        }
+        DumpFormat::Yaml => {
+            let mut output = String::new();
+            write_yaml(&mut output, &root, 0, options.indent.max(1));
+            output
+        }
+    }
+}
+
+fn dump_message(index: usize, message: &MessageEnum, options: &DumpOptions) -> Value {
+    let mut entry = Map::new();
+    entry.insert("index".to_string(), json!(index));
+    entry.insert("role".to_string(), json!(message.role()));
+    if let Some(id) = message.id() {
+        entry.insert("id".to_string(), json!(id));
+    }
+    if let Some(name) = message.name() {
+        entry.insert("name".to_string(), json!(name));
+    }
+    if message.is_example() {
+        entry.insert("example".to_string(), json!(true));
+    }
+    if let MessageEnum::Tool(tool) = message {
+        entry.insert("tool_call_id".to_string(), json!(tool.tool_call_id()));
+        entry.insert("status".to_string(), json!(tool.status()));
+    }
+
+    let content = message.content();
+    let chars = content.chars().count();
+    entry.insert("content_chars".to_string(), json!(chars));
+    let content = match options.max_content_chars {
+        Some(max) => elide(content, max),
+        None => content.to_string(),
+    };
+    entry.insert("content".to_string(), json!(content));
+
+    for (key, map) in [
+        ("additional_kwargs", message.additional_kwargs()),
+        ("response_metadata", message.response_metadata()),
+    ] {
+        if !map.is_empty() {
+            entry.insert(key.to_string(), dump_map(map, options.fold_metadata));
+        }
+    }
+    Value::Object(entry)
+}
+
+pub(crate) fn elide(content: &str, max: usize) -> String {
+    let chars = content.chars().count();
+    if chars <= max {
+        return content.to_string();
+    }
+    let kept: String = content.chars().take(max).collect();
+    format!("{}… [+{} chars]", kept, chars - max)
+}
+
+fn dump_map(map: &Metadata, fold: bool) -> Value {
+    let mut keys: Vec<&String> = map.keys().collect();
+    keys.sort();
+    if fold {
+        let keys: Vec<&str> = keys.into_iter().map(String::as_str).collect();
+        json!(format!("<{} keys: {}>", keys.len(), keys.join(", ")))
+    } else {
+        Value::Object(
+            keys.into_iter()
+                .map(|key| (key.clone(), map[key].clone()))
+                .collect(),
+        )
+    }
+}
+
+fn write_yaml(output: &mut String, value: &Value, depth: usize, indent: usize) {
+    let pad = " ".repeat(depth * indent);
+    match value {
+        Value::Object(map) => {
+            for (key, value) in map {
+                output.push_str(&pad);
+                output.push_str(&yaml_key(key));
+                output.push(':');
+                write_yaml_child(output, value, depth, indent);
+            }
+        }
+        Value::Array(items) => {
+            for item in items {
+                output.push_str(&pad);
+                output.push('-');
+                write_yaml_child(output, item, depth, indent);
+            }
+        }
+        scalar => {
+            output.push_str(&pad);
+            output.push_str(&scalar.to_string());
+            output.push('\n');
+        }
+    }
+}
+
+fn write_yaml_child(output: &mut String, value: &Value, depth: usize, indent: usize) {
+    match value {
+        Value::Object(map) if !map.is_empty() => {
+            output.push('\n');
+            write_yaml(output, value, depth + 1, indent);
+        }
+        Value::Array(items) if !items.is_empty() => {
+            output.push('\n');
+            write_yaml(output, value, depth + 1, indent);
+        }
+        Value::Object(_) => output.push_str(" {}\n"),
+        Value::Array(_) => output.push_str(" []\n"),
+        // JSON scalars (with double-quoted strings) are valid YAML scalars.
+        scalar => {
+            output.push(' ');
+            output.push_str(&scalar.to_string());
+            output.push('\n');
+        }
+    }
+}
+
+/// Keys are written bare only when YAML would read them back as the same
+/// string; anything else is double-quoted like the values.
+fn yaml_key(key: &str) -> String {
+    let plain = key.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+        && !matches!(
+            key.to_ascii_lowercase().as_str(),
+            "true" | "false" | "null" | "yes" | "no" | "on" | "off" | "y" | "n"
+        );
+    if plain {
+        key.to_string()
+    } else {
+        Value::from(key).to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AiMessage, HumanMessage};
+
+    fn conversation() -> Conversation {
+        let mut conversation = Conversation::with_session_id("s1");
+        let mut question = HumanMessage::new("What is the weather like in Paris today?");
+        question.set_id(Some("m1".to_string()));
+        question
+            .base
+            .additional_kwargs
+            .insert("user_email".to_string(), "jane@example.com".to_string());
+        question
+            .base
+            .additional_kwargs
+            .insert("client".to_string(), "ios".to_string());
+        conversation.push(question);
+        conversation.push(AiMessage::new("Sunny."));
+        conversation
+    }
+
+    #[test]
+    fn test_dump_debug_json_folds_and_elides() {
+        let options = DumpOptions {
+            max_content_chars: Some(10),
+            ..DumpOptions::default()
+        };
+
+        let dump = dump_debug(&conversation(), &options);
+        let value: Value = serde_json::from_str(&dump).unwrap();
+
+        assert_eq!(value["session_id"], "s1");
+        assert_eq!(value["message_count"], 2);
+        assert_eq!(value["messages"][0]["content"], "What is th… [+30 chars]");
+        assert_eq!(value["messages"][0]["content_chars"], 40);
+        assert_eq!(
+            value["messages"][0]["additional_kwargs"],
+            "<2 keys: client, user_email>"
+        );
+        assert_eq!(value["messages"][1]["content"], "Sunny.");
+        assert!(!dump.contains("jane@example.com"));
+    }
+
+    #[test]
+    fn test_dump_debug_unfolded() {
+        let options = DumpOptions {
+            fold_metadata: false,
+            max_content_chars: None,
+            indent: 4,
+            ..DumpOptions::default()
+        };
+
+        let dump = dump_debug(&conversation(), &options);
+
+        assert!(dump.contains("\n    \"message_count\": 2"));
+        assert!(dump.contains("jane@example.com"));
+        assert!(dump.contains("What is the weather like in Paris today?"));
+    }
+
+    #[test]
+    fn test_dump_debug_yaml() {
+        let options = DumpOptions {
+            format: DumpFormat::Yaml,
+            ..DumpOptions::default()
+        };
+
+        let dump = dump_debug(&conversation(), &options);
+
+        let expected = r#"message_count: 2
+messages:
+  -
+    additional_kwargs: "<2 keys: client, user_email>"
+    content: "What is the weather like in Paris today?"
+    content_chars: 40
+    id: "m1"
+    index: 0
+    role: "human"
+  -
+    content: "Sunny."
+    content_chars: 6
+    index: 1
+    role: "ai"
+session_id: "s1"
+"#;
+        assert_eq!(dump, expected);
+    }
+
+    #[test]
+    fn test_yaml_quotes_unsafe_keys() {
+        let mut conversation = conversation();
+        let message = &mut conversation.messages_mut()[1];
+        for key in ["a: b", "#tag", "null", "2024", "line\nbreak"] {
+            message.base_mut().additional_kwargs.insert(key, 1);
+        }
+        let options = DumpOptions {
+            format: DumpFormat::Yaml,
+            fold_metadata: false,
+            ..DumpOptions::default()
+        };
+
+        let dump = dump_debug(&conversation, &options);
+
+        assert!(dump.contains("\n      \"#tag\": 1\n"));
+        assert!(dump.contains("\n      \"2024\": 1\n"));
+        assert!(dump.contains("\n      \"a: b\": 1\n"));
+        assert!(dump.contains("\n      \"line\\nbreak\": 1\n"));
+        assert!(dump.contains("\n      \"null\": 1\n"));
+        assert!(dump.contains("\n      client: \"ios\"\n"));
+    }
+}
@@ -1,33 +1,96 @@
-#[macro_export]
+/// `define_message!(Human)` defines `HumanMessage` with only the shared
+/// fields. Extra fields go in braces before them and are taken by `new`,
+/// `new_with_id`, `new_with_example` and `builder`, in order:
+///
+/// ```ignore
+/// define_message!(Chat { role: String }, role = role);
+/// ```
+///
+/// Fields in `defaults { .. }` start at `Default::default()` and are not
+/// constructor arguments. `role = field` makes `BaseMessage::role` read that
+/// field instead of the message type, and
+/// `tool_calls = field, invalid_tool_calls = field` backs the tool-call
+/// accessors, which are otherwise empty:
+///
+/// ```ignore
+/// define_message!(
+///     Ai {},
+///     defaults { pub tool_calls: Vec<ToolCall>, pub invalid_tool_calls: Vec<InvalidToolCall> },
+///     tool_calls = tool_calls,
+///     invalid_tool_calls = invalid_tool_calls
+/// );
+/// ```
+#[cfg_attr(feature = "macros", macro_export)]
 macro_rules! define_message {
-    (MessageType::$message_type_enum:ident) => {
-        define_message!($message_type_enum);
+    (MessageType::$message_type_enum:ident $($rest:tt)*) => {
+        define_message!($message_type_enum $($rest)*);
     };
 
-    ($message_type_enum:expr) => {
+    ($(#[$attr:meta])* $message_type_enum:ident) => {
+        define_message!($(#[$attr])* $message_type_enum {});
+    };
+
+    (@role $this:tt) => {
+        $this.base.message_type.as_str()
+    };
+
+    (@role $this:tt $field:ident) => {
+        &$this.$field
+    };
+
+    (@slice $this:tt) => {
+        &[]
+    };
+
+    (@slice $this:tt $field:ident) => {
+        &$this.$field
+    };
+
+    (
+        $(#[$attr:meta])*
+        $message_type_enum:ident {
+            $($(#[$field_attr:meta])* $field_vis:vis $field:ident: $field_ty:ty),* $(,)?
+        }
+        $(, defaults {
+            $($(#[$default_attr:meta])* $default_vis:vis $default_field:ident: $default_ty:ty),* $(,)?
+        })?
+        $(, role = $role_field:ident)?
+        $(, tool_calls = $tool_calls_field:ident, invalid_tool_calls = $invalid_field:ident)?
+        $(,)?
+    ) => {
         paste::item! {
+            $(#[$attr])*
             #[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
             pub struct [<$message_type_enum Message>] {
+                $($(#[$field_attr])* $field_vis $field: $field_ty,)*
+                $($($(#[$default_attr])* $default_vis $default_field: $default_ty,)*)?
                 #[serde(flatten)]
                 pub base: BaseMessageFields,
             }
 
             impl [<$message_type_enum Message>] {
-                pub fn new(content: &str) -> Self {
-                    Self::new_with_example(content, false)
+                pub fn new(content: &str $(, $field: $field_ty)*) -> Self {
+                    Self::new_with_example(content, false $(, $field)*)
                 }
 
-                pub fn new_with_example(content: &str, example: bool) -> Self {
+                /// Like `new`, with an id from `generate_message_id()`.
+                pub fn new_with_id(content: &str $(, $field: $field_ty)*) -> Self {
+                    let mut message = Self::new(content $(, $field)*);
+                    message.base.id = Some(generate_message_id());
+                    message
+                }
+
+                pub fn new_with_example(
+                    content: &str,
+                    example: bool
+                    $(, $field: $field_ty)*
+                ) -> Self {
+                    let mut base = BaseMessageFields::new(content, MessageType::$message_type_enum);
+                    base.example = example;
                     Self {
-                        base: BaseMessageFields {
-                            content: content.to_string(),
-                            example,
-                            message_type: MessageType::$message_type_enum,
-                            additional_kwargs: std::collections::HashMap::new(),
-                            response_metadata: std::collections::HashMap::new(),
-                            id: None,
-                            name: None,
-                        }
+                        $($field,)*
+                        $($($default_field: Default::default(),)*)?
+                        base,
                     }
                 }
 
@@ -35,11 +98,11 @@ macro_rules! define_message {
                     self.base.example
                 }
 
-                pub fn additional_kwargs(&self) -> &std::collections::HashMap<String, String> {
+                pub fn additional_kwargs(&self) -> &Metadata {
                     &self.base.additional_kwargs
                 }
 
-                pub fn response_metadata(&self) -> &std::collections::HashMap<String, String> {
+                pub fn response_metadata(&self) -> &Metadata {
                     &self.base.response_metadata
                 }
 
@@ -52,7 +115,12 @@ macro_rules! define_message {
                 }
 
                 pub fn set_content(&mut self, new_content: &str) {
-                    self.base.content = new_content.to_string();
+                    self.base.content = new_content.into();
+                }
+
+                pub fn with_content(mut self, content: impl Into<MessageContent>) -> Self {
+                    self.base.content = content.into();
+                    self
                 }
 
                 pub fn set_example(&mut self, example: bool) {
@@ -66,11 +134,84 @@ macro_rules! define_message {
                 pub fn set_name(&mut self, name: Option<String>) {
                     self.base.name = name;
                 }
+
+                pub fn provenance(&self) -> Option<&Provenance> {
+                    self.base.provenance.as_ref()
+                }
+
+                pub fn set_provenance(&mut self, provenance: Option<Provenance>) {
+                    self.base.provenance = provenance;
+                }
+
+                pub fn with_provenance(mut self, provenance: Provenance) -> Self {
+                    self.base.provenance = Some(provenance);
+                    self
+                }
+
+                pub fn voice(&self) -> Option<&VoiceMetadata> {
+                    self.base.voice.as_ref()
+                }
+
+                pub fn set_voice(&mut self, voice: Option<VoiceMetadata>) {
+                    self.base.voice = voice;
+                }
+
+                pub fn with_voice(mut self, voice: VoiceMetadata) -> Self {
+                    self.base.voice = Some(voice);
+                    self
+                }
+            }
+
+            #[derive(Debug, Clone, PartialEq)]
+            pub struct [<$message_type_enum MessageBuilder>] {
+                message: [<$message_type_enum Message>],
+            }
+
+            impl [<$message_type_enum Message>] {
+                pub fn builder($($field: $field_ty),*) -> [<$message_type_enum MessageBuilder>] {
+                    [<$message_type_enum MessageBuilder>] {
+                        message: Self::new("" $(, $field)*),
+                    }
+                }
+            }
+
+            impl [<$message_type_enum MessageBuilder>] {
+                pub fn content(mut self, content: impl Into<MessageContent>) -> Self {
+                    self.message.base.content = content.into();
+                    self
+                }
+
+                pub fn name(mut self, name: impl Into<String>) -> Self {
+                    self.message.base.name = Some(name.into());
+                    self
+                }
+
+                pub fn id(mut self, id: impl Into<String>) -> Self {
+                    self.message.base.id = Some(id.into());
+                    self
+                }
+
+                pub fn example(mut self, example: bool) -> Self {
+                    self.message.base.example = example;
+                    self
+                }
+
+                pub fn kwarg<V>(mut self, key: impl Into<String>, value: V) -> Self
+                where
+                    Metadata: Extend<(String, V)>,
+                {
+                    self.message.base.additional_kwargs.extend([(key.into(), value)]);
+                    self
+                }
+
+                pub fn build(self) -> [<$message_type_enum Message>] {
+                    self.message
+                }
             }
 
             impl BaseMessage for [<$message_type_enum Message>] {
                 fn content(&self) -> &str {
-                    &self.base.content
+                    self.base.content.as_str()
                 }
 
                 fn message_type(&self) -> &MessageType {
@@ -78,18 +219,18 @@ macro_rules! define_message {
                 }
 
                 fn role(&self) -> &str {
-                    $message_type_enum.as_str()
+                    define_message!(@role self $($role_field)?)
                 }
 
                 fn is_example(&self) -> bool {
                     self.base.example
                 }
 
-                fn additional_kwargs(&self) -> &std::collections::HashMap<String, String> {
+                fn additional_kwargs(&self) -> &Metadata {
                     &self.base.additional_kwargs
                 }
 
-                fn response_metadata(&self) -> &std::collections::HashMap<String, String> {
+                fn response_metadata(&self) -> &Metadata {
                     &self.base.response_metadata
                 }
 
@@ -100,6 +241,22 @@ macro_rules! define_message {
                 fn name(&self) -> Option<&str> {
                     self.base.name.as_deref()
                 }
+
+                fn tool_calls(&self) -> &[ToolCall] {
+                    define_message!(@slice self $($tool_calls_field)?)
+                }
+
+                fn invalid_tool_calls(&self) -> &[InvalidToolCall] {
+                    define_message!(@slice self $($invalid_field)?)
+                }
+
+                fn content_blocks(&self) -> std::borrow::Cow<'_, [ContentBlock]> {
+                    self.base.content.blocks()
+                }
+
+                fn text(&self) -> std::borrow::Cow<'_, str> {
+                    self.base.content.text()
+                }
             }
         }
     };
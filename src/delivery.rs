@@ -0,0 +1,210 @@
+use std::collections::HashMap;
+use std::sync::{Arc, PoisonError, RwLock};
+
+use serde::{Deserialize, Serialize};
+
+use crate::clock::{Clock, SystemClock};
+
+/// Delivery progress of one message to one consumer (a device, user or
+/// downstream service). Timestamps are milliseconds since the Unix epoch.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeliveryState {
+    pub consumer_id: String,
+
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub sent_at: Option<u64>,
+
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub delivered_at: Option<u64>,
+
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub read_at: Option<u64>,
+}
+
+impl DeliveryState {
+    pub fn new(consumer_id: impl Into<String>) -> Self {
+        DeliveryState {
+            consumer_id: consumer_id.into(),
+            ..Self::default()
+        }
+    }
+
+    pub fn is_read(&self) -> bool {
+        self.read_at.is_some()
+    }
+
+    /// Applies an event. Each timestamp is only set once, and later stages
+    /// fill in earlier ones that were never reported (a read message was
+    /// also delivered and sent).
+    pub fn record(&mut self, event: DeliveryEvent, at_ms: u64) {
+        let stages = [
+            (DeliveryEvent::Sent, &mut self.sent_at),
+            (DeliveryEvent::Delivered, &mut self.delivered_at),
+            (DeliveryEvent::Read, &mut self.read_at),
+        ];
+        for (stage, slot) in stages {
+            if stage <= event {
+                slot.get_or_insert(at_ms);
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeliveryEvent {
+    Sent,
+    Delivered,
+    Read,
+}
+
+/// Where delivery receipts live, keyed by message id and consumer id.
+pub trait DeliveryStore {
+    fn record(&self, message_id: &str, consumer_id: &str, event: DeliveryEvent, at_ms: u64);
+
+    fn state(&self, message_id: &str, consumer_id: &str) -> Option<DeliveryState>;
+
+    /// Every consumer's state for a message.
+    fn states(&self, message_id: &str) -> Vec<DeliveryState>;
+
+    /// Stamps the `mark_*` helpers.
+    fn clock(&self) -> &dyn Clock {
+        &SystemClock
+    }
+
+    fn mark_sent(&self, message_id: &str, consumer_id: &str) {
+        let now = self.clock().now_ms();
+        self.record(message_id, consumer_id, DeliveryEvent::Sent, now);
+    }
+
+    fn mark_delivered(&self, message_id: &str, consumer_id: &str) {
+        let now = self.clock().now_ms();
+        self.record(message_id, consumer_id, DeliveryEvent::Delivered, now);
+    }
+
+    fn mark_read(&self, message_id: &str, consumer_id: &str) {
+        let now = self.clock().now_ms();
+        self.record(message_id, consumer_id, DeliveryEvent::Read, now);
+    }
+
+    /// Ids of messages `consumer_id` has not read, out of `message_ids`.
+    fn unread<'a>(&self, message_ids: &[&'a str], consumer_id: &str) -> Vec<&'a str> {
+        message_ids
+            .iter()
+            .copied()
+            .filter(|id| {
+                !self
+                    .state(id, consumer_id)
+                    .is_some_and(|state| state.is_read())
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug)]
+pub struct InMemoryDeliveryStore {
+    inner: RwLock<HashMap<String, Vec<DeliveryState>>>,
+    clock: Arc<dyn Clock>,
+}
+
+impl Default for InMemoryDeliveryStore {
+    fn default() -> Self {
+        InMemoryDeliveryStore {
+            inner: RwLock::default(),
+            clock: Arc::new(SystemClock),
+        }
+    }
+}
+
+impl InMemoryDeliveryStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+}
+
+impl DeliveryStore for InMemoryDeliveryStore {
+    fn record(&self, message_id: &str, consumer_id: &str, event: DeliveryEvent, at_ms: u64) {
+        let mut inner = self.inner.write().unwrap_or_else(PoisonError::into_inner);
+        let states = inner.entry(message_id.to_string()).or_default();
+        let position = match states.iter().position(|s| s.consumer_id == consumer_id) {
+            Some(position) => position,
+            None => {
+                states.push(DeliveryState::new(consumer_id));
+                states.len() - 1
+            }
+        };
+        states[position].record(event, at_ms);
+    }
+
+    fn clock(&self) -> &dyn Clock {
+        self.clock.as_ref()
+    }
+
+    fn state(&self, message_id: &str, consumer_id: &str) -> Option<DeliveryState> {
+        self.inner
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(message_id)?
+            .iter()
+            .find(|state| state.consumer_id == consumer_id)
+            .cloned()
+    }
+
+    fn states(&self, message_id: &str) -> Vec<DeliveryState> {
+        self.inner
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(message_id)
+            .cloned()
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::MockClock;
+
+    #[test]
+    fn test_record_fills_earlier_stages_once() {
+        let mut state = DeliveryState::new("phone");
+
+        state.record(DeliveryEvent::Sent, 10);
+        state.record(DeliveryEvent::Read, 30);
+        state.record(DeliveryEvent::Sent, 99);
+
+        assert_eq!(state.sent_at, Some(10));
+        assert_eq!(state.delivered_at, Some(30));
+        assert_eq!(state.read_at, Some(30));
+        assert_eq!(
+            serde_json::to_value(DeliveryState::new("web")).unwrap(),
+            serde_json::json!({"consumer_id": "web"})
+        );
+    }
+
+    #[test]
+    fn test_store_tracks_consumers_separately() {
+        let clock = Arc::new(MockClock::new(1_000));
+        let store = InMemoryDeliveryStore::new().with_clock(clock.clone());
+        store.record("m1", "phone", DeliveryEvent::Delivered, 5);
+        store.mark_read("m1", "laptop");
+        store.mark_sent("m2", "phone");
+
+        assert_eq!(store.states("m1").len(), 2);
+        assert_eq!(store.state("m1", "phone").unwrap().delivered_at, Some(5));
+        assert_eq!(store.state("m1", "laptop").unwrap().read_at, Some(1_000));
+        clock.advance(std::time::Duration::from_secs(1));
+        store.mark_read("m1", "phone");
+        assert_eq!(store.state("m1", "phone").unwrap().read_at, Some(2_000));
+        assert!(store.state("m3", "phone").is_none());
+        assert_eq!(
+            store.unread(&["m1", "m2", "m3"], "laptop"),
+            vec!["m2", "m3"]
+        );
+    }
+}
@@ -0,0 +1,248 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::BaseMessage;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DiffGranularity {
+    Line,
+    Word,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "op", content = "text", rename_all = "snake_case")]
+pub enum DiffOp {
+    Equal(String),
+    Insert(String),
+    Delete(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ContentDiff {
+    pub granularity: DiffGranularity,
+    pub ops: Vec<DiffOp>,
+}
+
+impl ContentDiff {
+    pub fn is_unchanged(&self) -> bool {
+        self.ops.iter().all(|op| matches!(op, DiffOp::Equal(_)))
+    }
+
+    pub fn insertions(&self) -> usize {
+        self.ops
+            .iter()
+            .filter(|op| matches!(op, DiffOp::Insert(_)))
+            .count()
+    }
+
+    pub fn deletions(&self) -> usize {
+        self.ops
+            .iter()
+            .filter(|op| matches!(op, DiffOp::Delete(_)))
+            .count()
+    }
+}
+
+/// Line diffs render one `+`/`-`/space-prefixed line per op; word diffs
+/// render inline as `[-old-]{+new+}`.
+impl fmt::Display for ContentDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for op in &self.ops {
+            match (self.granularity, op) {
+                (DiffGranularity::Line, DiffOp::Equal(text)) => writeln!(f, "  {}", text)?,
+                (DiffGranularity::Line, DiffOp::Insert(text)) => writeln!(f, "+ {}", text)?,
+                (DiffGranularity::Line, DiffOp::Delete(text)) => writeln!(f, "- {}", text)?,
+                (DiffGranularity::Word, DiffOp::Equal(text)) => write!(f, "{}", text)?,
+                (DiffGranularity::Word, DiffOp::Insert(text)) => write!(f, "{{+{}+}}", text)?,
+                (DiffGranularity::Word, DiffOp::Delete(text)) => write!(f, "[-{}-]", text)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Line-level diff of two versions of a message's content.
+pub fn diff_content(old: &str, new: &str) -> ContentDiff {
+    ContentDiff {
+        granularity: DiffGranularity::Line,
+        ops: diff_tokens(
+            &old.lines().collect::<Vec<_>>(),
+            &new.lines().collect::<Vec<_>>(),
+        ),
+    }
+}
+
+/// Word-level diff; whitespace is kept attached to the preceding word so the
+/// ops concatenate back to the original text.
+pub fn diff_words(old: &str, new: &str) -> ContentDiff {
+    let ops = diff_tokens(&split_words(old), &split_words(new));
+    ContentDiff {
+        granularity: DiffGranularity::Word,
+        ops: merge_runs(ops),
+    }
+}
+
+pub fn diff_messages(old: &impl BaseMessage, new: &impl BaseMessage) -> ContentDiff {
+    diff_content(old.content(), new.content())
+}
+
+fn split_words(text: &str) -> Vec<&str> {
+    let mut words = Vec::new();
+    let mut start = 0;
+    let mut in_space = false;
+    for (index, c) in text.char_indices() {
+        if c.is_whitespace() {
+            in_space = true;
+        } else if in_space {
+            words.push(&text[start..index]);
+            start = index;
+            in_space = false;
+        }
+    }
+    if start < text.len() {
+        words.push(&text[start..]);
+    }
+    words
+}
+
+fn diff_tokens(old: &[&str], new: &[&str]) -> Vec<DiffOp> {
+    // Trim the shared prefix and suffix so the quadratic table only covers
+    // the region that actually changed.
+    let prefix = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let old_mid = &old[prefix..old.len() - suffix];
+    let new_mid = &new[prefix..new.len() - suffix];
+
+    let mut ops: Vec<DiffOp> = old[..prefix]
+        .iter()
+        .map(|token| DiffOp::Equal(token.to_string()))
+        .collect();
+
+    let (n, m) = (old_mid.len(), new_mid.len());
+    let mut lcs = vec![vec![0usize; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lcs[i][j] = if old_mid[i] == new_mid[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let (mut i, mut j) = (0, 0);
+    while i < n || j < m {
+        if i < n && j < m && old_mid[i] == new_mid[j] {
+            ops.push(DiffOp::Equal(old_mid[i].to_string()));
+            i += 1;
+            j += 1;
+        } else if j < m && (i == n || lcs[i][j + 1] >= lcs[i + 1][j]) {
+            ops.push(DiffOp::Insert(new_mid[j].to_string()));
+            j += 1;
+        } else {
+            ops.push(DiffOp::Delete(old_mid[i].to_string()));
+            i += 1;
+        }
+    }
+
+    ops.extend(
+        old[old.len() - suffix..]
+            .iter()
+            .map(|token| DiffOp::Equal(token.to_string())),
+    );
+    ops
+}
+
+// Deletes are emitted before inserts within a changed region, and adjacent
+// ops of the same kind are joined.
+fn merge_runs(ops: Vec<DiffOp>) -> Vec<DiffOp> {
+    let mut merged: Vec<DiffOp> = Vec::new();
+    let mut deleted = String::new();
+    let mut inserted = String::new();
+    fn flush(merged: &mut Vec<DiffOp>, deleted: &mut String, inserted: &mut String) {
+        if !deleted.is_empty() {
+            merged.push(DiffOp::Delete(std::mem::take(deleted)));
+        }
+        if !inserted.is_empty() {
+            merged.push(DiffOp::Insert(std::mem::take(inserted)));
+        }
+    }
+
+    for op in ops {
+        match op {
+            DiffOp::Delete(text) => deleted.push_str(&text),
+            DiffOp::Insert(text) => inserted.push_str(&text),
+            DiffOp::Equal(text) => {
+                flush(&mut merged, &mut deleted, &mut inserted);
+                match merged.last_mut() {
+                    Some(DiffOp::Equal(previous)) => previous.push_str(&text),
+                    _ => merged.push(DiffOp::Equal(text)),
+                }
+            }
+        }
+    }
+    flush(&mut merged, &mut deleted, &mut inserted);
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::AiMessage;
+
+    #[test]
+    fn test_line_diff() {
+        let old = "Step 1: boil water\nStep 2: add pasta\nStep 3: serve";
+        let new = "Step 1: boil water\nStep 2: add salt\nStep 3: add pasta\nStep 4: serve";
+
+        let diff = diff_content(old, new);
+
+        assert_eq!(diff.insertions(), 3);
+        assert_eq!(diff.deletions(), 2);
+        assert_eq!(diff.ops[0], DiffOp::Equal("Step 1: boil water".to_string()));
+        assert!(diff.to_string().contains("+ Step 2: add salt\n"));
+        assert!(diff.to_string().contains("- Step 3: serve\n"));
+    }
+
+    #[test]
+    fn test_word_diff_round_trips_text() {
+        let old = "The quick brown fox jumps";
+        let new = "The quick red fox leaps";
+
+        let diff = diff_words(old, new);
+
+        assert_eq!(
+            diff.to_string(),
+            "The quick [-brown -]{+red +}fox [-jumps-]{+leaps+}"
+        );
+        let rebuilt_new: String = diff
+            .ops
+            .iter()
+            .filter_map(|op| match op {
+                DiffOp::Equal(text) | DiffOp::Insert(text) => Some(text.as_str()),
+                DiffOp::Delete(_) => None,
+            })
+            .collect();
+        assert_eq!(rebuilt_new, new);
+    }
+
+    #[test]
+    fn test_diff_messages_unchanged() {
+        let old = AiMessage::new("Same\nreply");
+        let new = AiMessage::new("Same\nreply");
+
+        let diff = diff_messages(&old, &new);
+        assert!(diff.is_unchanged());
+        assert_eq!(
+            serde_json::to_value(&diff.ops[0]).unwrap(),
+            serde_json::json!({"op": "equal", "text": "Same"})
+        );
+    }
+}
@@ -0,0 +1,457 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{ChatHistory, MessageEnum};
+
+const ENVELOPE_PREFIX: &str = "enc:v1:";
+
+/// Encrypts and decrypts field values under a single key. The crate ships
+/// no cipher of its own; wrap an AEAD implementation of your choice.
+pub trait FieldCipher {
+    fn key_id(&self) -> &str;
+    fn encrypt(&self, plaintext: &[u8]) -> Vec<u8>;
+    fn decrypt(&self, ciphertext: &[u8]) -> Result<Vec<u8>, EncryptionError>;
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EncryptionError {
+    Malformed(String),
+    UnknownKey(String),
+    DecryptionFailed(String),
+    Storage(String),
+}
+
+impl fmt::Display for EncryptionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EncryptionError::Malformed(value) => write!(f, "Malformed encrypted field: {}", value),
+            EncryptionError::UnknownKey(key_id) => write!(f, "Unknown encryption key: {}", key_id),
+            EncryptionError::DecryptionFailed(reason) => write!(f, "Decryption failed: {}", reason),
+            EncryptionError::Storage(reason) => write!(f, "Storage error: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for EncryptionError {}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "field", content = "key", rename_all = "snake_case")]
+pub enum Field {
+    Content,
+    Kwarg(String),
+    Metadata(String),
+}
+
+/// Encrypted value stored in place of a field as `enc:v1:<key_id>:<hex>`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EncryptedField {
+    pub key_id: String,
+    pub ciphertext: Vec<u8>,
+}
+
+impl EncryptedField {
+    pub fn seal(plaintext: &str, cipher: &dyn FieldCipher) -> Self {
+        EncryptedField {
+            key_id: cipher.key_id().to_string(),
+            ciphertext: cipher.encrypt(plaintext.as_bytes()),
+        }
+    }
+
+    pub fn open(&self, cipher: &dyn FieldCipher) -> Result<String, EncryptionError> {
+        if cipher.key_id() != self.key_id {
+            return Err(EncryptionError::UnknownKey(self.key_id.clone()));
+        }
+        let plaintext = cipher.decrypt(&self.ciphertext)?;
+        String::from_utf8(plaintext)
+            .map_err(|err| EncryptionError::DecryptionFailed(err.to_string()))
+    }
+
+    pub fn encode(&self) -> String {
+        let hex: String = self
+            .ciphertext
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect();
+        format!("{}{}:{}", ENVELOPE_PREFIX, self.key_id, hex)
+    }
+
+    /// Parses an envelope; returns `Ok(None)` for values that are not encrypted.
+    pub fn decode(value: &str) -> Result<Option<Self>, EncryptionError> {
+        let Some(rest) = value.strip_prefix(ENVELOPE_PREFIX) else {
+            return Ok(None);
+        };
+        let malformed = || EncryptionError::Malformed(value.to_string());
+        let (key_id, hex) = rest.rsplit_once(':').ok_or_else(malformed)?;
+        if hex.len() % 2 != 0 {
+            return Err(malformed());
+        }
+        let ciphertext = (0..hex.len())
+            .step_by(2)
+            .map(|index| u8::from_str_radix(&hex[index..index + 2], 16))
+            .collect::<Result<Vec<u8>, _>>()
+            .map_err(|_| malformed())?;
+        Ok(Some(EncryptedField {
+            key_id: key_id.to_string(),
+            ciphertext,
+        }))
+    }
+}
+
+/// Kwarg listing the fields [`encrypt_fields`] sealed, so decryption and
+/// rotation never mistake plaintext that happens to look like an envelope
+/// for ciphertext.
+pub const ENCRYPTED_FIELDS_KEY: &str = "encrypted_fields";
+
+/// The fields of `message` currently sealed, from its
+/// [`ENCRYPTED_FIELDS_KEY`] kwarg.
+pub fn encrypted_fields(message: &MessageEnum) -> Result<Vec<Field>, EncryptionError> {
+    match message.base().additional_kwargs.get(ENCRYPTED_FIELDS_KEY) {
+        Some(value) => serde_json::from_value(value.clone())
+            .map_err(|_| EncryptionError::Malformed(value.to_string())),
+        None => Ok(Vec::new()),
+    }
+}
+
+/// Seals `fields` that are present and not sealed yet. Kwarg and metadata
+/// values are sealed as their JSON text so they decrypt to the same type.
+pub fn encrypt_fields(
+    message: &mut MessageEnum,
+    fields: &[Field],
+    cipher: &dyn FieldCipher,
+) -> Result<(), EncryptionError> {
+    let mut sealed = encrypted_fields(message)?;
+    let base = message.base_mut();
+    for field in fields {
+        if sealed.contains(field) || field == &Field::Kwarg(ENCRYPTED_FIELDS_KEY.to_string()) {
+            continue;
+        }
+        let value = match field {
+            Field::Content => {
+                for text in base.content.texts_mut() {
+                    *text = EncryptedField::seal(text, cipher).encode();
+                }
+                sealed.push(field.clone());
+                continue;
+            }
+            Field::Kwarg(key) => base.additional_kwargs.get_mut(key),
+            Field::Metadata(key) => base.response_metadata.get_mut(key),
+        };
+        if let Some(value) = value {
+            *value = Value::String(EncryptedField::seal(&value.to_string(), cipher).encode());
+            sealed.push(field.clone());
+        }
+    }
+    if !sealed.is_empty() {
+        base.additional_kwargs
+            .insert(ENCRYPTED_FIELDS_KEY, serde_json::json!(sealed));
+    }
+    Ok(())
+}
+
+/// Decrypts every sealed field of `message` using the matching cipher
+/// from `ciphers`. On error `message` is left as it was.
+pub fn decrypt_fields(
+    message: &mut MessageEnum,
+    ciphers: &[&dyn FieldCipher],
+) -> Result<(), EncryptionError> {
+    let fields = encrypted_fields(message)?;
+    let mut decrypted = message.clone();
+    for_each_envelope(&mut decrypted, &fields, |envelope| {
+        let field = EncryptedField::decode(envelope)?
+            .ok_or_else(|| EncryptionError::Malformed(envelope.clone()))?;
+        let cipher = ciphers
+            .iter()
+            .find(|cipher| cipher.key_id() == field.key_id)
+            .ok_or_else(|| EncryptionError::UnknownKey(field.key_id.clone()))?;
+        *envelope = field.open(*cipher)?;
+        Ok(true)
+    })?;
+
+    let base = decrypted.base_mut();
+    for field in &fields {
+        let value = match field {
+            Field::Content => continue,
+            Field::Kwarg(key) => base.additional_kwargs.get_mut(key),
+            Field::Metadata(key) => base.response_metadata.get_mut(key),
+        };
+        if let Some(value) = value {
+            let json = value.as_str().unwrap_or_default();
+            *value = serde_json::from_str(json)
+                .map_err(|err| EncryptionError::DecryptionFailed(err.to_string()))?;
+        }
+    }
+    base.additional_kwargs.remove(ENCRYPTED_FIELDS_KEY);
+    *message = decrypted;
+    Ok(())
+}
+
+/// Re-encrypts every field sealed under `old` with `new`, returning how many
+/// fields were rotated. Fields under other keys are left untouched.
+pub fn rotate_message_keys<'a>(
+    messages: impl IntoIterator<Item = &'a mut MessageEnum>,
+    old: &dyn FieldCipher,
+    new: &dyn FieldCipher,
+) -> Result<usize, EncryptionError> {
+    let mut rotated = 0;
+    for message in messages {
+        let fields = encrypted_fields(message)?;
+        let mut rewritten = message.clone();
+        rotated +=
+            for_each_envelope(
+                &mut rewritten,
+                &fields,
+                |envelope| match EncryptedField::decode(envelope)? {
+                    Some(field) if field.key_id == old.key_id() => {
+                        *envelope = EncryptedField::seal(&field.open(old)?, new).encode();
+                        Ok(true)
+                    }
+                    Some(_) => Ok(false),
+                    None => Err(EncryptionError::Malformed(envelope.clone())),
+                },
+            )?;
+        *message = rewritten;
+    }
+    Ok(rotated)
+}
+
+/// [`rotate_message_keys`] over everything `history` returns, written back
+/// with [`ChatHistory::replace_messages`] when anything changed.
+pub fn rotate_keys(
+    history: &mut dyn ChatHistory,
+    old: &dyn FieldCipher,
+    new: &dyn FieldCipher,
+) -> Result<usize, EncryptionError> {
+    let storage_error = |err: std::io::Error| EncryptionError::Storage(err.to_string());
+    let mut messages = history.messages().map_err(storage_error)?;
+    let rotated = rotate_message_keys(&mut messages, old, new)?;
+    if rotated > 0 {
+        history.replace_messages(messages).map_err(storage_error)?;
+    }
+    Ok(rotated)
+}
+
+fn for_each_envelope(
+    message: &mut MessageEnum,
+    fields: &[Field],
+    mut visit: impl FnMut(&mut String) -> Result<bool, EncryptionError>,
+) -> Result<usize, EncryptionError> {
+    let base = message.base_mut();
+    let mut changed = 0;
+    for field in fields {
+        let envelopes = match field {
+            Field::Content => base.content.texts_mut(),
+            Field::Kwarg(key) => envelope(base.additional_kwargs.get_mut(key))?,
+            Field::Metadata(key) => envelope(base.response_metadata.get_mut(key))?,
+        };
+        for value in envelopes {
+            if visit(value)? {
+                changed += 1;
+            }
+        }
+    }
+    Ok(changed)
+}
+
+fn envelope(value: Option<&mut Value>) -> Result<Vec<&mut String>, EncryptionError> {
+    match value {
+        Some(Value::String(text)) => Ok(vec![text]),
+        Some(value) => Err(EncryptionError::Malformed(value.to_string())),
+        None => Ok(Vec::new()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BaseMessage, Conversation, HumanMessage, InMemoryChatHistory};
+    use serde_json::json;
+
+    struct XorCipher {
+        key_id: String,
+        key: u8,
+    }
+
+    impl XorCipher {
+        fn new(key_id: &str, key: u8) -> Self {
+            XorCipher {
+                key_id: key_id.to_string(),
+                key,
+            }
+        }
+    }
+
+    impl FieldCipher for XorCipher {
+        fn key_id(&self) -> &str {
+            &self.key_id
+        }
+
+        fn encrypt(&self, plaintext: &[u8]) -> Vec<u8> {
+            plaintext.iter().map(|byte| byte ^ self.key).collect()
+        }
+
+        fn decrypt(&self, ciphertext: &[u8]) -> Result<Vec<u8>, EncryptionError> {
+            Ok(self.encrypt(ciphertext))
+        }
+    }
+
+    fn message_with_kwarg() -> MessageEnum {
+        let mut message = HumanMessage::new("my address is 1 Main St");
+        message
+            .base
+            .additional_kwargs
+            .insert("ssn".to_string(), "123-45-6789".to_string());
+        message
+            .base
+            .additional_kwargs
+            .insert("channel".to_string(), "web".to_string());
+        message.into()
+    }
+
+    #[test]
+    fn test_envelope_encode_decode() {
+        let cipher = XorCipher::new("k1", 0x2a);
+        let field = EncryptedField::seal("hi", &cipher);
+
+        let encoded = field.encode();
+
+        assert_eq!(encoded, "enc:v1:k1:4243");
+        assert_eq!(EncryptedField::decode(&encoded).unwrap(), Some(field));
+        assert_eq!(EncryptedField::decode("plain text").unwrap(), None);
+        assert!(matches!(
+            EncryptedField::decode("enc:v1:k1:zz"),
+            Err(EncryptionError::Malformed(_))
+        ));
+    }
+
+    #[test]
+    fn test_encrypt_and_decrypt_selected_fields() {
+        let cipher = XorCipher::new("k1", 7);
+        let mut message = message_with_kwarg();
+
+        encrypt_fields(
+            &mut message,
+            &[Field::Content, Field::Kwarg("ssn".to_string())],
+            &cipher,
+        )
+        .unwrap();
+
+        assert!(message.content().starts_with("enc:v1:k1:"));
+        assert!(message.additional_kwargs()["ssn"]
+            .as_str()
+            .unwrap()
+            .starts_with("enc:v1:k1:"));
+        assert_eq!(message.additional_kwargs()["channel"], "web");
+        assert_eq!(
+            encrypted_fields(&message).unwrap(),
+            vec![Field::Content, Field::Kwarg("ssn".to_string())]
+        );
+
+        decrypt_fields(&mut message, &[&cipher]).unwrap();
+        assert_eq!(message, message_with_kwarg());
+    }
+
+    #[test]
+    fn test_non_string_values_keep_their_type() {
+        let cipher = XorCipher::new("k1", 7);
+        let mut message = message_with_kwarg();
+        message.base_mut().additional_kwargs.insert("age", 42);
+        message
+            .base_mut()
+            .response_metadata
+            .insert("scores", json!({"toxicity": 0.1}));
+        let original = message.clone();
+
+        encrypt_fields(
+            &mut message,
+            &[
+                Field::Kwarg("age".to_string()),
+                Field::Metadata("scores".to_string()),
+            ],
+            &cipher,
+        )
+        .unwrap();
+        assert!(message.additional_kwargs()["age"].is_string());
+        decrypt_fields(&mut message, &[&cipher]).unwrap();
+
+        assert_eq!(message, original);
+        assert_eq!(message.additional_kwargs().get_i64("age"), Some(42));
+    }
+
+    #[test]
+    fn test_plaintext_that_looks_encrypted_is_left_alone() {
+        let cipher = XorCipher::new("k1", 7);
+        let mut message: MessageEnum = HumanMessage::new("enc:v1:k9:not-hex").into();
+        let original = message.clone();
+
+        decrypt_fields(&mut message, &[&cipher]).unwrap();
+        assert_eq!(message, original);
+
+        encrypt_fields(&mut message, &[Field::Content], &cipher).unwrap();
+        assert!(message.content().starts_with("enc:v1:k1:"));
+        decrypt_fields(&mut message, &[&cipher]).unwrap();
+        assert_eq!(message, original);
+    }
+
+    #[test]
+    fn test_decrypt_with_unknown_key() {
+        let mut message = message_with_kwarg();
+        encrypt_fields(&mut message, &[Field::Content], &XorCipher::new("k1", 7)).unwrap();
+        let sealed = message.clone();
+
+        let result = decrypt_fields(&mut message, &[&XorCipher::new("k2", 9)]);
+
+        assert_eq!(result, Err(EncryptionError::UnknownKey("k1".to_string())));
+        assert_eq!(message, sealed);
+    }
+
+    #[test]
+    fn test_rotate_message_keys() {
+        let old = XorCipher::new("2023", 3);
+        let new = XorCipher::new("2024", 5);
+        let other = XorCipher::new("other", 11);
+
+        let mut first = message_with_kwarg();
+        encrypt_fields(
+            &mut first,
+            &[Field::Content, Field::Kwarg("ssn".to_string())],
+            &old,
+        )
+        .unwrap();
+        let mut second = message_with_kwarg();
+        encrypt_fields(&mut second, &[Field::Content], &other).unwrap();
+        let mut conversation: Conversation = vec![first, second].into_iter().collect();
+
+        let rotated = rotate_message_keys(conversation.messages_mut(), &old, &new).unwrap();
+
+        assert_eq!(rotated, 2);
+        let messages = conversation.messages_mut();
+        assert!(messages[0].content().starts_with("enc:v1:2024:"));
+        assert!(messages[1].content().starts_with("enc:v1:other:"));
+
+        decrypt_fields(&mut messages[0], &[&new]).unwrap();
+        assert_eq!(messages[0], message_with_kwarg());
+        assert!(decrypt_fields(&mut messages[1], &[&old, &new]).is_err());
+    }
+
+    #[test]
+    fn test_rotate_keys_in_history() {
+        let old = XorCipher::new("2023", 3);
+        let new = XorCipher::new("2024", 5);
+        let mut sealed = message_with_kwarg();
+        encrypt_fields(&mut sealed, &[Field::Kwarg("ssn".to_string())], &old).unwrap();
+        let mut history = InMemoryChatHistory::new();
+        history
+            .add_messages(vec![sealed, HumanMessage::new("plain").into()])
+            .unwrap();
+
+        assert_eq!(rotate_keys(&mut history, &old, &new).unwrap(), 1);
+        assert_eq!(rotate_keys(&mut history, &old, &new).unwrap(), 0);
+
+        let mut messages = history.messages().unwrap();
+        assert_eq!(messages.len(), 2);
+        decrypt_fields(&mut messages[0], &[&new]).unwrap();
+        assert_eq!(messages[0], message_with_kwarg());
+    }
+}
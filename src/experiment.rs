@@ -0,0 +1,233 @@
+use std::collections::BTreeMap;
+
+use crate::conversation::{fnv1a, FNV_OFFSET_BASIS};
+use crate::{BaseMessage, Conversation, MessageType, SystemMessage};
+
+/// `additional_kwargs` key carrying the prompt variant a conversation ran.
+pub const PROMPT_VARIANT_KEY: &str = "prompt_variant";
+/// `additional_kwargs` key carrying an outcome label, such as `resolved`.
+pub const OUTCOME_KEY: &str = "outcome";
+
+/// Content address of a system template: the same text always gets the same
+/// id, so variants can be compared across deployments and experiments.
+pub fn prompt_variant_id(system_template: &str) -> String {
+    format!(
+        "pv:{:016x}",
+        fnv1a(FNV_OFFSET_BASIS, system_template.as_bytes())
+    )
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PromptVariant {
+    pub id: String,
+    pub system_template: String,
+}
+
+impl PromptVariant {
+    pub fn new(system_template: impl Into<String>) -> Self {
+        let system_template = system_template.into();
+        PromptVariant {
+            id: prompt_variant_id(&system_template),
+            system_template,
+        }
+    }
+}
+
+/// A prompt A/B test. Units (users, sessions) are assigned to a variant by
+/// hashing them with the experiment name, so assignment is sticky without
+/// storing it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Experiment {
+    pub name: String,
+    variants: Vec<PromptVariant>,
+}
+
+impl Experiment {
+    pub fn new(name: impl Into<String>) -> Self {
+        Experiment {
+            name: name.into(),
+            variants: Vec::new(),
+        }
+    }
+
+    /// Adds a variant; a template already in the experiment is ignored.
+    pub fn with_variant(mut self, system_template: impl Into<String>) -> Self {
+        let variant = PromptVariant::new(system_template);
+        if self.variant(&variant.id).is_none() {
+            self.variants.push(variant);
+        }
+        self
+    }
+
+    pub fn variants(&self) -> &[PromptVariant] {
+        &self.variants
+    }
+
+    pub fn variant(&self, id: &str) -> Option<&PromptVariant> {
+        self.variants.iter().find(|variant| variant.id == id)
+    }
+
+    pub fn assign(&self, unit: &str) -> Option<&PromptVariant> {
+        if self.variants.is_empty() {
+            return None;
+        }
+        let hash = fnv1a(
+            fnv1a(FNV_OFFSET_BASIS, self.name.as_bytes()),
+            unit.as_bytes(),
+        );
+        self.variants
+            .get((hash % self.variants.len() as u64) as usize)
+    }
+
+    /// A conversation opened with the system prompt of `unit`'s variant,
+    /// already tagged with the variant id.
+    pub fn start(&self, unit: &str) -> Option<Conversation> {
+        let variant = self.assign(unit)?;
+        let mut conversation = Conversation::new();
+        conversation.push(SystemMessage::new(&variant.system_template));
+        tag_variant(&mut conversation, &variant.id);
+        Some(conversation)
+    }
+}
+
+/// Stamps `variant_id` on every system message of `conversation`. Returns
+/// whether there was a system message to stamp.
+pub fn tag_variant(conversation: &mut Conversation, variant_id: &str) -> bool {
+    let mut tagged = false;
+    for message in conversation.messages_mut() {
+        if message.message_type() == &MessageType::System {
+            message
+                .base_mut()
+                .additional_kwargs
+                .insert(PROMPT_VARIANT_KEY.to_string(), variant_id.to_string());
+            tagged = true;
+        }
+    }
+    tagged
+}
+
+pub fn variant_of(conversation: &Conversation) -> Option<&str> {
+    conversation
+        .iter()
+        .find_map(|message| message.additional_kwargs().get_str(PROMPT_VARIANT_KEY))
+}
+
+/// Labels the last message of `conversation` with `outcome`. Returns false
+/// if the conversation is empty.
+pub fn record_outcome(conversation: &mut Conversation, outcome: &str) -> bool {
+    match conversation.messages_mut().last_mut() {
+        Some(message) => {
+            message
+                .base_mut()
+                .additional_kwargs
+                .insert(OUTCOME_KEY.to_string(), outcome.to_string());
+            true
+        }
+        None => false,
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct VariantOutcomes {
+    pub conversations: usize,
+    /// How many conversations carried each outcome label at least once.
+    pub outcomes: BTreeMap<String, usize>,
+}
+
+impl VariantOutcomes {
+    /// Share of the variant's conversations labelled `outcome`.
+    pub fn rate(&self, outcome: &str) -> f64 {
+        if self.conversations == 0 {
+            return 0.0;
+        }
+        self.outcomes.get(outcome).copied().unwrap_or(0) as f64 / self.conversations as f64
+    }
+}
+
+/// Groups conversations by prompt variant and counts their outcome labels.
+/// Untagged conversations are skipped.
+pub fn compare_variants<'a, I>(conversations: I) -> BTreeMap<String, VariantOutcomes>
+where
+    I: IntoIterator<Item = &'a Conversation>,
+{
+    let mut report: BTreeMap<String, VariantOutcomes> = BTreeMap::new();
+    for conversation in conversations {
+        let Some(variant) = variant_of(conversation) else {
+            continue;
+        };
+        let entry = report.entry(variant.to_string()).or_default();
+        entry.conversations += 1;
+        let mut labels: Vec<&str> = conversation
+            .iter()
+            .filter_map(|message| message.additional_kwargs().get_str(OUTCOME_KEY))
+            .collect();
+        labels.sort_unstable();
+        labels.dedup();
+        for label in labels {
+            *entry.outcomes.entry(label.to_string()).or_default() += 1;
+        }
+    }
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AiMessage, HumanMessage};
+
+    #[test]
+    fn test_assignment_is_sticky_and_content_addressed() {
+        let experiment = Experiment::new("tone")
+            .with_variant("You are terse.")
+            .with_variant("You are friendly.")
+            .with_variant("You are terse.");
+
+        assert_eq!(experiment.variants().len(), 2);
+        assert_eq!(
+            experiment.variants()[0].id,
+            prompt_variant_id("You are terse.")
+        );
+        assert_eq!(experiment.assign("user-1"), experiment.assign("user-1"));
+
+        let conversation = experiment.start("user-1").unwrap();
+        let variant = experiment.assign("user-1").unwrap();
+        assert_eq!(variant_of(&conversation), Some(variant.id.as_str()));
+        assert_eq!(
+            conversation.messages()[0].content(),
+            variant.system_template
+        );
+        assert!(Experiment::new("empty").start("user-1").is_none());
+    }
+
+    #[test]
+    fn test_compare_variants() {
+        let run = |system: &str, outcome: Option<&str>| {
+            let mut conversation = Conversation::new();
+            conversation.push(SystemMessage::new(system));
+            conversation.push(HumanMessage::new("Hi"));
+            conversation.push(AiMessage::new("Hello"));
+            tag_variant(&mut conversation, &prompt_variant_id(system));
+            if let Some(outcome) = outcome {
+                record_outcome(&mut conversation, outcome);
+            }
+            conversation
+        };
+        let mut untagged = Conversation::new();
+        untagged.push(HumanMessage::new("Hi"));
+        let conversations = vec![
+            run("A", Some("resolved")),
+            run("A", None),
+            run("B", Some("resolved")),
+            untagged,
+        ];
+
+        let report = compare_variants(&conversations);
+
+        assert_eq!(report.len(), 2);
+        let a = &report[&prompt_variant_id("A")];
+        assert_eq!(a.conversations, 2);
+        assert_eq!(a.rate("resolved"), 0.5);
+        assert_eq!(report[&prompt_variant_id("B")].rate("resolved"), 1.0);
+        assert_eq!(a.rate("escalated"), 0.0);
+    }
+}
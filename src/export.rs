@@ -0,0 +1,310 @@
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use crate::{BaseMessage, Conversation, MessageEnum};
+
+/// Longest content excerpt shown in a node label, in characters.
+const LABEL_CHARS: usize = 40;
+
+struct Node {
+    id: String,
+    label: String,
+}
+
+enum EdgeKind {
+    Next,
+    ToolResult(String),
+    Fork,
+}
+
+struct Edge {
+    from: String,
+    to: String,
+    kind: EdgeKind,
+}
+
+struct Cluster {
+    title: Option<String>,
+    nodes: Vec<Node>,
+}
+
+struct Graph {
+    clusters: Vec<Cluster>,
+    edges: Vec<Edge>,
+}
+
+/// A Mermaid flowchart of `conversation`: a node per message, solid edges
+/// in order, and dashed edges from each tool call to its result.
+pub fn mermaid(conversation: &Conversation) -> String {
+    render_mermaid(&build_graph(&[conversation]))
+}
+
+/// The [`mermaid`] diagram as Graphviz DOT.
+pub fn dot(conversation: &Conversation) -> String {
+    render_dot(&build_graph(&[conversation]))
+}
+
+/// Like [`mermaid`] for a set of branches, one subgraph per session. A
+/// forked conversation shows only the messages after its fork point,
+/// joined to the message it was forked at.
+pub fn mermaid_branches(conversations: &[&Conversation]) -> String {
+    render_mermaid(&build_graph(conversations))
+}
+
+/// [`mermaid_branches`] as Graphviz DOT.
+pub fn dot_branches(conversations: &[&Conversation]) -> String {
+    render_dot(&build_graph(conversations))
+}
+
+fn build_graph(conversations: &[&Conversation]) -> Graph {
+    let branching = conversations.len() > 1;
+    let mut graph = Graph {
+        clusters: Vec::new(),
+        edges: Vec::new(),
+    };
+    // (session, message id) -> node, for fork edges.
+    let mut by_id: HashMap<(&str, &str), String> = HashMap::new();
+    let mut forks = Vec::new();
+
+    for (c, conversation) in conversations.iter().enumerate() {
+        let session = conversation.session_id().unwrap_or_default();
+        let messages = conversation.messages();
+        let fork = conversation.forked_from().filter(|_| branching);
+        let skip = fork
+            .and_then(|origin| {
+                messages
+                    .iter()
+                    .position(|message| message.id() == Some(origin.message_id.as_str()))
+            })
+            .map_or(0, |position| position + 1);
+
+        let mut cluster = Cluster {
+            title: branching.then(|| session.to_string()),
+            nodes: Vec::new(),
+        };
+        let mut calls: HashMap<&str, (String, &str)> = HashMap::new();
+        for (index, message) in messages.iter().enumerate().skip(skip) {
+            let id = if branching {
+                format!("c{}m{}", c, index)
+            } else {
+                format!("m{}", index)
+            };
+            if let Some(previous) = cluster.nodes.last() {
+                graph.edges.push(Edge {
+                    from: previous.id.clone(),
+                    to: id.clone(),
+                    kind: EdgeKind::Next,
+                });
+            }
+            for call in message.tool_calls() {
+                calls.insert(&call.id, (id.clone(), &call.name));
+            }
+            if let Some((from, name)) = message
+                .as_tool()
+                .and_then(|tool| calls.get(tool.tool_call_id()))
+            {
+                graph.edges.push(Edge {
+                    from: from.clone(),
+                    to: id.clone(),
+                    kind: EdgeKind::ToolResult(name.to_string()),
+                });
+            }
+            if let Some(message_id) = message.id() {
+                by_id.insert((session, message_id), id.clone());
+            }
+            cluster.nodes.push(Node {
+                id,
+                label: node_label(message),
+            });
+        }
+        if let (Some(origin), Some(first)) = (fork, cluster.nodes.first()) {
+            forks.push((origin, first.id.clone()));
+        }
+        graph.clusters.push(cluster);
+    }
+
+    for (origin, to) in forks {
+        if let Some(from) = by_id.get(&(origin.session.as_str(), origin.message_id.as_str())) {
+            graph.edges.push(Edge {
+                from: from.clone(),
+                to,
+                kind: EdgeKind::Fork,
+            });
+        }
+    }
+    graph
+}
+
+fn node_label(message: &MessageEnum) -> String {
+    let text = message
+        .text()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ");
+    let mut label = format!("{}:", message.role());
+    if !text.is_empty() {
+        label.push(' ');
+        label.extend(text.chars().take(LABEL_CHARS));
+        if text.chars().count() > LABEL_CHARS {
+            label.push_str("...");
+        }
+    }
+    let calls: Vec<&str> = message
+        .tool_calls()
+        .iter()
+        .map(|call| call.name.as_str())
+        .collect();
+    if !calls.is_empty() {
+        let _ = write!(label, " [calls {}]", calls.join(", "));
+    }
+    label
+}
+
+fn render_mermaid(graph: &Graph) -> String {
+    let escape = |text: &str| text.replace('"', "#quot;");
+    let mut out = String::from("flowchart TD\n");
+    for (index, cluster) in graph.clusters.iter().enumerate() {
+        let indent = if cluster.title.is_some() { "    " } else { "" };
+        if let Some(title) = &cluster.title {
+            let _ = writeln!(out, "    subgraph c{}[\"{}\"]", index, escape(title));
+        }
+        for node in &cluster.nodes {
+            let _ = writeln!(
+                out,
+                "{}    {}[\"{}\"]",
+                indent,
+                node.id,
+                escape(&node.label)
+            );
+        }
+        if cluster.title.is_some() {
+            out.push_str("    end\n");
+        }
+    }
+    for edge in &graph.edges {
+        let _ = match &edge.kind {
+            EdgeKind::Next => writeln!(out, "    {} --> {}", edge.from, edge.to),
+            EdgeKind::ToolResult(name) => {
+                writeln!(
+                    out,
+                    "    {} -. \"{}\" .-> {}",
+                    edge.from,
+                    escape(name),
+                    edge.to
+                )
+            }
+            EdgeKind::Fork => writeln!(out, "    {} ==>|fork| {}", edge.from, edge.to),
+        };
+    }
+    out
+}
+
+fn render_dot(graph: &Graph) -> String {
+    let escape = |text: &str| text.replace('\\', "\\\\").replace('"', "\\\"");
+    let mut out = String::from("digraph conversation {\n    node [shape=box];\n");
+    for (index, cluster) in graph.clusters.iter().enumerate() {
+        let indent = if cluster.title.is_some() { "    " } else { "" };
+        if let Some(title) = &cluster.title {
+            let _ = writeln!(out, "    subgraph cluster_{} {{", index);
+            let _ = writeln!(out, "        label=\"{}\";", escape(title));
+        }
+        for node in &cluster.nodes {
+            let _ = writeln!(
+                out,
+                "{}    {} [label=\"{}\"];",
+                indent,
+                node.id,
+                escape(&node.label)
+            );
+        }
+        if cluster.title.is_some() {
+            out.push_str("    }\n");
+        }
+    }
+    for edge in &graph.edges {
+        let _ = match &edge.kind {
+            EdgeKind::Next => writeln!(out, "    {} -> {};", edge.from, edge.to),
+            EdgeKind::ToolResult(name) => writeln!(
+                out,
+                "    {} -> {} [style=dashed, label=\"{}\"];",
+                edge.from,
+                edge.to,
+                escape(name)
+            ),
+            EdgeKind::Fork => writeln!(
+                out,
+                "    {} -> {} [style=bold, label=\"fork\"];",
+                edge.from, edge.to
+            ),
+        };
+    }
+    out.push_str("}\n");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tool_message::ToolStatus;
+    use crate::{AiMessage, HumanMessage, ToolCall, ToolMessage};
+    use serde_json::json;
+
+    fn agent_run() -> Conversation {
+        let mut question = HumanMessage::new("Weather in \"Paris\"?");
+        question.set_id(Some("h1".to_string()));
+        let mut conversation = Conversation::with_session_id("main");
+        conversation.push(question);
+        conversation.push(AiMessage::new("").with_tool_calls(vec![ToolCall::new(
+            "c1",
+            "weather",
+            json!({}),
+        )]));
+        conversation.push(ToolMessage::new(
+            "Sunny",
+            "c1".to_string(),
+            None,
+            ToolStatus::Success,
+        ));
+        conversation.push(AiMessage::new("It is sunny."));
+        conversation
+    }
+
+    #[test]
+    fn test_mermaid_and_dot() {
+        let conversation = agent_run();
+
+        assert_eq!(
+            mermaid(&conversation),
+            "flowchart TD\n\
+             \x20   m0[\"human: Weather in #quot;Paris#quot;?\"]\n\
+             \x20   m1[\"ai: [calls weather]\"]\n\
+             \x20   m2[\"tool: Sunny\"]\n\
+             \x20   m3[\"ai: It is sunny.\"]\n\
+             \x20   m0 --> m1\n\
+             \x20   m1 --> m2\n\
+             \x20   m1 -. \"weather\" .-> m2\n\
+             \x20   m2 --> m3\n"
+        );
+        let dot = dot(&conversation);
+        assert!(dot.starts_with("digraph conversation {"));
+        assert!(dot.contains("m0 [label=\"human: Weather in \\\"Paris\\\"?\"];"));
+        assert!(dot.contains("m1 -> m2 [style=dashed, label=\"weather\"];"));
+    }
+
+    #[test]
+    fn test_branches() {
+        let main = agent_run();
+        let mut branch = main.fork_at("retry", "h1").unwrap();
+        branch.push(AiMessage::new("Let me check."));
+
+        let diagram = mermaid_branches(&[&main, &branch]);
+
+        assert!(diagram.contains("subgraph c1[\"retry\"]"));
+        assert!(diagram.contains("c1m1[\"ai: Let me check.\"]"));
+        assert!(!diagram.contains("c1m0"));
+        assert!(diagram.contains("c0m0 ==>|fork| c1m1"));
+        assert!(
+            dot_branches(&[&main, &branch]).contains("c0m0 -> c1m1 [style=bold, label=\"fork\"];")
+        );
+    }
+}
@@ -0,0 +1,163 @@
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::fmt;
+
+trait AnyClone: Any + Send + Sync {
+    fn clone_box(&self) -> Box<dyn AnyClone>;
+    fn as_any(&self) -> &dyn Any;
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+    fn into_any(self: Box<Self>) -> Box<dyn Any>;
+}
+
+impl<T: Clone + Send + Sync + 'static> AnyClone for T {
+    fn clone_box(&self) -> Box<dyn AnyClone> {
+        Box::new(self.clone())
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn into_any(self: Box<Self>) -> Box<dyn Any> {
+        self
+    }
+}
+
+impl Clone for Box<dyn AnyClone> {
+    fn clone(&self) -> Self {
+        (**self).clone_box()
+    }
+}
+
+/// A type map for runtime-only state, one value per type, in the spirit of
+/// `http::Extensions`. Never serialized, and ignored when comparing the
+/// conversation or message that carries it. Empty extensions do not
+/// allocate.
+#[derive(Clone, Default)]
+pub struct Extensions {
+    map: HashMap<TypeId, Box<dyn AnyClone>>,
+}
+
+impl Extensions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Stores `value`, returning the previous value of the same type.
+    pub fn insert<T: Clone + Send + Sync + 'static>(&mut self, value: T) -> Option<T> {
+        self.map
+            .insert(TypeId::of::<T>(), Box::new(value))
+            .and_then(|previous| previous.into_any().downcast().ok().map(|boxed| *boxed))
+    }
+
+    pub fn get<T: Send + Sync + 'static>(&self) -> Option<&T> {
+        self.map
+            .get(&TypeId::of::<T>())
+            .and_then(|value| (**value).as_any().downcast_ref())
+    }
+
+    pub fn get_mut<T: Send + Sync + 'static>(&mut self) -> Option<&mut T> {
+        self.map
+            .get_mut(&TypeId::of::<T>())
+            .and_then(|value| (**value).as_any_mut().downcast_mut())
+    }
+
+    pub fn get_or_insert_default<T: Clone + Default + Send + Sync + 'static>(&mut self) -> &mut T {
+        if self.get::<T>().is_none() {
+            self.insert(T::default());
+        }
+        self.get_mut().expect("inserted above")
+    }
+
+    pub fn remove<T: Send + Sync + 'static>(&mut self) -> Option<T> {
+        self.map
+            .remove(&TypeId::of::<T>())
+            .and_then(|value| value.into_any().downcast().ok().map(|boxed| *boxed))
+    }
+
+    pub fn contains<T: Send + Sync + 'static>(&self) -> bool {
+        self.get::<T>().is_some()
+    }
+
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn clear(&mut self) {
+        self.map.clear();
+    }
+
+    /// Moves every value from `other` in, replacing values of the same type.
+    pub fn extend(&mut self, other: Extensions) {
+        self.map.extend(other.map);
+    }
+}
+
+impl PartialEq for Extensions {
+    fn eq(&self, _: &Self) -> bool {
+        true
+    }
+}
+
+impl fmt::Debug for Extensions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Extensions").finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Conversation, HumanMessage, MessageEnum};
+
+    #[derive(Debug, Clone, Default, PartialEq)]
+    struct RetryCount(u32);
+
+    #[test]
+    fn test_typed_slots() {
+        let mut extensions = Extensions::new();
+        assert!(extensions.is_empty());
+
+        assert_eq!(extensions.insert(RetryCount(1)), None);
+        assert_eq!(extensions.insert("sidebar"), None);
+        assert_eq!(extensions.insert(RetryCount(2)), Some(RetryCount(1)));
+        extensions.get_or_insert_default::<RetryCount>().0 += 1;
+
+        assert_eq!(extensions.get::<RetryCount>(), Some(&RetryCount(3)));
+        assert_eq!(extensions.get::<&str>(), Some(&"sidebar"));
+        assert_eq!(extensions.len(), 2);
+        let copy = extensions.clone();
+        assert_eq!(extensions.remove::<RetryCount>(), Some(RetryCount(3)));
+        assert!(!extensions.contains::<RetryCount>());
+        assert!(copy.contains::<RetryCount>());
+    }
+
+    #[test]
+    fn test_runtime_only_on_conversation_and_message() {
+        let mut message: MessageEnum = HumanMessage::new("Hi").into();
+        message.extensions_mut().insert(RetryCount(1));
+        let mut conversation = Conversation::new();
+        conversation.extensions_mut().insert(RetryCount(7));
+        conversation.push(message);
+
+        let json = serde_json::to_string(&conversation).unwrap();
+        let parsed: Conversation = serde_json::from_str(&json).unwrap();
+
+        assert!(!json.contains("extensions"));
+        assert_eq!(parsed, conversation);
+        assert!(parsed.extensions().is_empty());
+        assert_eq!(conversation.extensions().get(), Some(&RetryCount(7)));
+        assert_eq!(
+            conversation.messages()[0].extensions().get(),
+            Some(&RetryCount(1))
+        );
+    }
+}
@@ -0,0 +1,240 @@
+use std::collections::BTreeMap;
+use std::sync::{Mutex, PoisonError};
+
+use serde::{Deserialize, Serialize};
+
+use crate::transformer::{BlockedMessage, MessageTransformer};
+use crate::{BaseMessage, Conversation, MessageEnum, MessageType, SystemMessage};
+
+pub const FACTS_HEADER: &str = "Known facts:";
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Fact {
+    pub value: String,
+
+    /// Ids of the messages the value was extracted from.
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub sources: Vec<String>,
+}
+
+/// Long-term memory: named facts, each with the messages that established it.
+/// A later value for the same key replaces the earlier one.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Facts {
+    entries: BTreeMap<String, Fact>,
+}
+
+impl Facts {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn insert(
+        &mut self,
+        key: impl Into<String>,
+        value: impl Into<String>,
+        source: Option<&str>,
+    ) {
+        let value = value.into();
+        let fact = self.entries.entry(key.into()).or_default();
+        if fact.value != value {
+            fact.value = value;
+            fact.sources.clear();
+        }
+        if let Some(source) = source {
+            if !fact.sources.iter().any(|existing| existing == source) {
+                fact.sources.push(source.to_string());
+            }
+        }
+    }
+
+    pub fn get(&self, key: &str) -> Option<&Fact> {
+        self.entries.get(key)
+    }
+
+    pub fn remove(&mut self, key: &str) -> Option<Fact> {
+        self.entries.remove(key)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Fact)> {
+        self.entries.iter().map(|(key, fact)| (key.as_str(), fact))
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// A compact, pinned system message listing every fact.
+    pub fn to_system_message(&self) -> SystemMessage {
+        let lines: Vec<String> = self
+            .iter()
+            .map(|(key, fact)| format!("- {}: {}", key, fact.value))
+            .collect();
+        let mut message = SystemMessage::new(&format!("{}\n{}", FACTS_HEADER, lines.join("\n")));
+        message.base.pinned = true;
+        message
+    }
+}
+
+/// Pulls `(key, value)` facts out of a message.
+pub trait FactExtractor {
+    fn extract(&self, message: &MessageEnum) -> Vec<(String, String)>;
+}
+
+impl<F: Fn(&MessageEnum) -> Vec<(String, String)>> FactExtractor for F {
+    fn extract(&self, message: &MessageEnum) -> Vec<(String, String)> {
+        self(message)
+    }
+}
+
+/// A pass-through [`MessageTransformer`] that records what its extractor
+/// finds in every message it sees.
+pub struct FactMemory<E> {
+    extractor: E,
+    facts: Mutex<Facts>,
+}
+
+impl<E: FactExtractor> FactMemory<E> {
+    pub fn new(extractor: E) -> Self {
+        Self::with_facts(extractor, Facts::new())
+    }
+
+    pub fn with_facts(extractor: E, facts: Facts) -> Self {
+        FactMemory {
+            extractor,
+            facts: Mutex::new(facts),
+        }
+    }
+
+    pub fn facts(&self) -> Facts {
+        self.facts
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    /// Writes the current facts into `conversation`; see [`promote_facts`].
+    pub fn promote(&self, conversation: &mut Conversation) {
+        promote_facts(conversation, &self.facts());
+    }
+}
+
+impl<E: FactExtractor> MessageTransformer for FactMemory<E> {
+    fn name(&self) -> &str {
+        "facts"
+    }
+
+    fn transform(&self, message: MessageEnum) -> Result<MessageEnum, BlockedMessage> {
+        let extracted = self.extractor.extract(&message);
+        if !extracted.is_empty() {
+            let mut facts = self.facts.lock().unwrap_or_else(PoisonError::into_inner);
+            for (key, value) in extracted {
+                facts.insert(key, value, message.id());
+            }
+        }
+        Ok(message)
+    }
+}
+
+/// Replaces the conversation's facts message, or inserts one after the
+/// leading system messages. Does nothing when `facts` is empty.
+pub fn promote_facts(conversation: &mut Conversation, facts: &Facts) {
+    if facts.is_empty() {
+        return;
+    }
+    let promoted: MessageEnum = facts.to_system_message().into();
+    let messages = conversation.messages_mut();
+    if let Some(existing) = messages
+        .iter_mut()
+        .find(|message| is_facts_message(message))
+    {
+        *existing = promoted;
+        return;
+    }
+    let position = messages
+        .iter()
+        .take_while(|message| message.message_type() == &MessageType::System)
+        .count();
+    messages.insert(position, promoted);
+}
+
+fn is_facts_message(message: &MessageEnum) -> bool {
+    message.message_type() == &MessageType::System && message.content().starts_with(FACTS_HEADER)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AiMessage, HumanMessage};
+
+    fn remember(message: &MessageEnum) -> Vec<(String, String)> {
+        message
+            .content()
+            .strip_prefix("remember ")
+            .and_then(|rest| rest.split_once('='))
+            .map(|(key, value)| vec![(key.trim().to_string(), value.trim().to_string())])
+            .unwrap_or_default()
+    }
+
+    fn with_id(content: &str, id: &str) -> MessageEnum {
+        let mut message = HumanMessage::new(content);
+        message.set_id(Some(id.to_string()));
+        message.into()
+    }
+
+    #[test]
+    fn test_facts_track_sources() {
+        let mut facts = Facts::new();
+        facts.insert("diet", "vegetarian", Some("m1"));
+        facts.insert("diet", "vegetarian", Some("m4"));
+        assert_eq!(facts.get("diet").unwrap().sources, vec!["m1", "m4"]);
+
+        facts.insert("diet", "vegan", Some("m9"));
+        assert_eq!(
+            facts.get("diet").unwrap(),
+            &Fact {
+                value: "vegan".to_string(),
+                sources: vec!["m9".to_string()],
+            }
+        );
+        assert_eq!(
+            serde_json::to_value(&facts).unwrap(),
+            serde_json::json!({"diet": {"value": "vegan", "sources": ["m9"]}})
+        );
+    }
+
+    #[test]
+    fn test_memory_transformer_and_promotion() {
+        let memory = FactMemory::new(remember);
+        let mut conversation: Conversation =
+            vec![MessageEnum::from(SystemMessage::new("Be brief."))]
+                .into_iter()
+                .collect();
+
+        for message in [
+            with_id("remember city = Lisbon", "m1"),
+            with_id("remember budget = 500", "m2"),
+            AiMessage::new("Noted.").into(),
+        ] {
+            conversation.push(memory.transform(message).unwrap());
+        }
+        memory.promote(&mut conversation);
+        let message = memory.transform(with_id("remember city = Porto", "m5"));
+        conversation.push(message.unwrap());
+        memory.promote(&mut conversation);
+
+        assert_eq!(conversation.len(), 6);
+        let promoted = &conversation.messages()[1];
+        assert_eq!(
+            promoted.content(),
+            "Known facts:\n- budget: 500\n- city: Porto"
+        );
+        assert!(promoted.is_pinned());
+        assert_eq!(memory.facts().get("city").unwrap().sources, vec!["m5"]);
+    }
+}
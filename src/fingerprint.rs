@@ -0,0 +1,202 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::conversation::{fnv1a, FNV_OFFSET_BASIS};
+use crate::{BaseMessage, MessageEnum};
+
+/// The model and sampling parameters of a request. Parameters are kept
+/// sorted so insertion order never changes the fingerprint.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RequestOptions {
+    pub model: String,
+
+    #[serde(skip_serializing_if = "BTreeMap::is_empty", default)]
+    pub params: BTreeMap<String, Value>,
+}
+
+impl RequestOptions {
+    pub fn new(model: impl Into<String>) -> Self {
+        RequestOptions {
+            model: model.into(),
+            params: BTreeMap::new(),
+        }
+    }
+
+    pub fn with_param(mut self, key: impl Into<String>, value: impl Into<Value>) -> Self {
+        self.params.insert(key.into(), value.into());
+        self
+    }
+}
+
+/// Stable hash of what a model actually sees: each message's role, content
+/// blocks, name, kwargs, tool calls and tool call id, plus the model and
+/// parameters. Ids, response metadata, provenance and other bookkeeping are
+/// left out, so two requests that differ only in those share a fingerprint.
+pub fn request_fingerprint(messages: &[MessageEnum], options: &RequestOptions) -> u64 {
+    fingerprint_of(&canonical_request(messages, options))
+}
+
+pub(crate) fn fingerprint_of(canonical_request: &str) -> u64 {
+    fnv1a(FNV_OFFSET_BASIS, canonical_request.as_bytes())
+}
+
+/// The JSON that [`request_fingerprint`] hashes. Stores keep it next to a
+/// reply so a fingerprint collision reads as a miss, not a wrong answer.
+pub fn canonical_request(messages: &[MessageEnum], options: &RequestOptions) -> String {
+    let messages: Vec<Value> = messages.iter().map(canonical_message).collect();
+    let request = json!({
+        "messages": messages,
+        "model": options.model,
+        "params": options.params,
+    });
+    // Objects inside `Value` keep their keys sorted.
+    request.to_string()
+}
+
+fn canonical_message(message: &MessageEnum) -> Value {
+    let mut canonical = json!({
+        "role": message.role(),
+        "content": message.content_blocks(),
+    });
+    if let Some(name) = message.name() {
+        canonical["name"] = json!(name);
+    }
+    if !message.additional_kwargs().is_empty() {
+        canonical["additional_kwargs"] = json!(message.additional_kwargs());
+    }
+    if !message.tool_calls().is_empty() {
+        canonical["tool_calls"] = json!(message.tool_calls());
+    }
+    if !message.invalid_tool_calls().is_empty() {
+        canonical["invalid_tool_calls"] = json!(message.invalid_tool_calls());
+    }
+    if let Some(tool) = message.as_tool() {
+        canonical["tool_call_id"] = json!(tool.tool_call_id());
+    }
+    canonical
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        AiMessage, ChatMessage, ContentBlock, HumanMessage, InvalidToolCall, SystemMessage,
+        ToolCall,
+    };
+
+    fn request() -> Vec<MessageEnum> {
+        vec![
+            SystemMessage::new("Be brief.").into(),
+            HumanMessage::new("What is 2 + 2?").into(),
+        ]
+    }
+
+    #[test]
+    fn test_bookkeeping_does_not_change_fingerprint() {
+        let options = RequestOptions::new("gpt-4o")
+            .with_param("temperature", 0.0)
+            .with_param("max_tokens", 64);
+        let reordered = RequestOptions::new("gpt-4o")
+            .with_param("max_tokens", 64)
+            .with_param("temperature", 0.0);
+
+        let mut annotated = request();
+        annotated[1].base_mut().id = Some("m1".to_string());
+        annotated[1]
+            .base_mut()
+            .response_metadata
+            .insert("timestamp".to_string(), "1700000000".to_string());
+        annotated[1].set_pinned(true);
+
+        assert_eq!(
+            request_fingerprint(&request(), &options),
+            request_fingerprint(&annotated, &reordered)
+        );
+    }
+
+    #[test]
+    fn test_relevant_changes_change_fingerprint() {
+        let options = RequestOptions::new("gpt-4o").with_param("temperature", 0.0);
+        let baseline = request_fingerprint(&request(), &options);
+
+        let mut longer = request();
+        longer.push(AiMessage::new("4").into());
+        let mut renamed = request();
+        renamed[1].base_mut().name = Some("alice".to_string());
+        let warmer = RequestOptions::new("gpt-4o").with_param("temperature", 0.7);
+
+        for fingerprint in [
+            request_fingerprint(&longer, &options),
+            request_fingerprint(&renamed, &options),
+            request_fingerprint(&request(), &warmer),
+            request_fingerprint(&request(), &RequestOptions::new("gpt-4o-mini")),
+        ] {
+            assert_ne!(fingerprint, baseline);
+        }
+    }
+
+    #[test]
+    fn test_chat_roles_change_fingerprint() {
+        let options = RequestOptions::new("gpt-4o");
+        let chat = |role: &str| {
+            let messages: Vec<MessageEnum> =
+                vec![ChatMessage::new("Ship it.", role.to_string()).into()];
+            request_fingerprint(&messages, &options)
+        };
+
+        assert_ne!(chat("reviewer"), chat("author"));
+        assert_eq!(chat("reviewer"), chat("reviewer"));
+    }
+
+    #[test]
+    fn test_blocks_and_tool_calls_change_fingerprint() {
+        let options = RequestOptions::new("gpt-4o");
+        let with_blocks = |blocks: Vec<ContentBlock>| {
+            let mut messages = request();
+            messages[1].base_mut().content = blocks.into();
+            request_fingerprint(&messages, &options)
+        };
+        let with_call = |args: Value| {
+            let mut messages = request();
+            messages.push(
+                AiMessage::new("")
+                    .with_tool_calls(vec![ToolCall::new("call_1", "add", args)])
+                    .into(),
+            );
+            request_fingerprint(&messages, &options)
+        };
+        let with_invalid = |args: &str| {
+            let mut messages = request();
+            messages.push(
+                AiMessage::new("")
+                    .with_invalid_tool_calls(vec![InvalidToolCall {
+                        args: Some(args.to_string()),
+                        ..InvalidToolCall::default()
+                    }])
+                    .into(),
+            );
+            request_fingerprint(&messages, &options)
+        };
+        let question = || ContentBlock::text("What is 2 + 2?");
+
+        assert_ne!(
+            with_blocks(vec![question(), ContentBlock::image_url("https://a.png")]),
+            with_blocks(vec![question(), ContentBlock::image_url("https://b.png")])
+        );
+        assert_ne!(
+            with_blocks(vec![question(), ContentBlock::text("Show work.")]),
+            with_blocks(vec![question(), ContentBlock::text("No work.")])
+        );
+        assert_ne!(
+            with_call(json!({"a": 2, "b": 2})),
+            with_call(json!({"a": 2, "b": 3}))
+        );
+        assert_ne!(with_invalid("{\"a\": 2"), with_invalid("{\"a\": 3"));
+        assert_eq!(
+            with_blocks(vec![question()]),
+            request_fingerprint(&request(), &options)
+        );
+    }
+}
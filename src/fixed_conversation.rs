@@ -0,0 +1,181 @@
+use std::fmt;
+
+use crate::{BaseMessage, Conversation, MessageEnum, MessageType};
+
+/// Every slot holds a system or pinned message; carries the rejected message.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConversationFullError(pub Box<MessageEnum>);
+
+impl ConversationFullError {
+    pub fn into_message(self) -> MessageEnum {
+        *self.0
+    }
+}
+
+impl fmt::Display for ConversationFullError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Conversation is full of system or pinned messages; nothing can be evicted"
+        )
+    }
+}
+
+impl std::error::Error for ConversationFullError {}
+
+/// A conversation holding at most `N` messages in a fixed ring of slots, so
+/// the container never allocates after construction. When full, pushing
+/// evicts the oldest message that is neither a system message nor pinned.
+#[derive(Debug, Clone)]
+pub struct FixedConversation<const N: usize> {
+    slots: [Option<MessageEnum>; N],
+    head: usize,
+    len: usize,
+}
+
+impl<const N: usize> FixedConversation<N> {
+    pub fn new() -> Self {
+        FixedConversation {
+            slots: [const { None }; N],
+            head: 0,
+            len: 0,
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        N
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.len == N
+    }
+
+    fn slot(&self, index: usize) -> usize {
+        (self.head + index) % N
+    }
+
+    pub fn get(&self, index: usize) -> Option<&MessageEnum> {
+        if index >= self.len {
+            return None;
+        }
+        self.slots[self.slot(index)].as_ref()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &MessageEnum> + '_ {
+        (0..self.len).filter_map(|index| self.get(index))
+    }
+
+    /// Appends `message`, returning whatever was evicted to make room.
+    pub fn push(
+        &mut self,
+        message: impl Into<MessageEnum>,
+    ) -> Result<Option<MessageEnum>, ConversationFullError> {
+        let message = message.into();
+        let evicted = if self.is_full() {
+            let Some(victim) = (0..self.len).find(|&index| self.is_evictable(index)) else {
+                return Err(ConversationFullError(Box::new(message)));
+            };
+            Some(self.remove(victim))
+        } else {
+            None
+        };
+        let tail = self.slot(self.len);
+        self.slots[tail] = Some(message);
+        self.len += 1;
+        Ok(evicted)
+    }
+
+    fn is_evictable(&self, index: usize) -> bool {
+        self.get(index).is_some_and(|message| {
+            message.message_type() != &MessageType::System && !message.is_pinned()
+        })
+    }
+
+    // Evicting the head just advances it; anything later shifts the
+    // messages after `index` back one slot to close the gap.
+    fn remove(&mut self, index: usize) -> MessageEnum {
+        let removed = self.slots[self.slot(index)].take();
+        if index == 0 {
+            self.head = self.slot(1);
+            self.len -= 1;
+            return removed.expect("occupied slot");
+        }
+        for position in index..self.len - 1 {
+            let next = self.slots[self.slot(position + 1)].take();
+            let current = self.slot(position);
+            self.slots[current] = next;
+        }
+        self.len -= 1;
+        removed.expect("occupied slot")
+    }
+
+    pub fn clear(&mut self) {
+        self.slots.iter_mut().for_each(|slot| *slot = None);
+        self.head = 0;
+        self.len = 0;
+    }
+
+    pub fn to_conversation(&self) -> Conversation {
+        self.iter().cloned().collect()
+    }
+}
+
+impl<const N: usize> Default for FixedConversation<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AiMessage, HumanMessage, SystemMessage};
+
+    fn contents<const N: usize>(conversation: &FixedConversation<N>) -> Vec<&str> {
+        conversation
+            .iter()
+            .map(|message| message.content())
+            .collect()
+    }
+
+    #[test]
+    fn test_evicts_oldest_non_system() {
+        let mut conversation = FixedConversation::<3>::new();
+        conversation.push(SystemMessage::new("rules")).unwrap();
+        conversation.push(HumanMessage::new("one")).unwrap();
+        conversation.push(AiMessage::new("two")).unwrap();
+
+        let evicted = conversation.push(HumanMessage::new("three")).unwrap();
+
+        assert_eq!(evicted.unwrap().content(), "one");
+        assert_eq!(contents(&conversation), vec!["rules", "two", "three"]);
+        conversation.push(AiMessage::new("four")).unwrap();
+        assert_eq!(contents(&conversation), vec!["rules", "three", "four"]);
+        assert_eq!(conversation.to_conversation().len(), 3);
+    }
+
+    #[test]
+    fn test_pinned_and_system_are_kept() {
+        let mut pinned: MessageEnum = HumanMessage::new("remember me").into();
+        pinned.set_pinned(true);
+        let mut conversation = FixedConversation::<2>::new();
+        conversation.push(SystemMessage::new("rules")).unwrap();
+        conversation.push(pinned).unwrap();
+
+        let rejected = conversation.push(HumanMessage::new("hi")).unwrap_err();
+
+        assert_eq!(rejected.into_message().content(), "hi");
+        assert_eq!(contents(&conversation), vec!["rules", "remember me"]);
+        assert!(FixedConversation::<0>::new()
+            .push(HumanMessage::new("x"))
+            .is_err());
+    }
+}
@@ -0,0 +1,207 @@
+use std::collections::VecDeque;
+use std::fmt;
+
+use serde_json::{json, Value};
+
+use super::is_turn;
+use crate::tool_message::ToolStatus;
+use crate::{
+    AiMessage, AnyMessage, BaseMessage, ChatMessage, HumanMessage, SystemMessage, ToolCall,
+    ToolMessage,
+};
+
+pub const IM_START: &str = "<|im_start|>";
+pub const IM_END: &str = "<|im_end|>";
+const TOOL_CALL_START: &str = "<tool_call>";
+const TOOL_CALL_END: &str = "</tool_call>";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChatMlError(pub String);
+
+impl fmt::Display for ChatMlError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid ChatML: {}", self.0)
+    }
+}
+
+impl std::error::Error for ChatMlError {}
+
+/// Renders `messages` as `<|im_start|>role\ncontent<|im_end|>` turns. Only
+/// text is kept; tool calls go in the assistant turn as Hermes-style
+/// `<tool_call>{"name":..,"arguments":..}</tool_call>` blocks. With
+/// `add_generation_prompt` an open assistant turn is appended for the
+/// model to complete.
+pub fn render(messages: &[AnyMessage], add_generation_prompt: bool) -> String {
+    let mut out = String::new();
+    for message in messages.iter().filter(|message| is_turn(message)) {
+        let mut content = message.text().into_owned();
+        for call in message.tool_calls() {
+            if !content.is_empty() {
+                content.push('\n');
+            }
+            content.push_str(&format!(
+                "{}\n{}\n{}",
+                TOOL_CALL_START,
+                json!({"name": call.name, "arguments": call.args}),
+                TOOL_CALL_END
+            ));
+        }
+        out.push_str(&format!(
+            "{}{}\n{}{}\n",
+            IM_START,
+            role(message),
+            content,
+            IM_END
+        ));
+    }
+    if add_generation_prompt {
+        out.push_str(IM_START);
+        out.push_str("assistant\n");
+    }
+    out
+}
+
+/// Reads ChatML text back into messages. Tool calls get ids `call_0`,
+/// `call_1`, ... in order, and each `tool` turn answers the oldest call
+/// not yet answered. A trailing empty generation prompt is ignored.
+pub fn parse(text: &str) -> Result<Vec<AnyMessage>, ChatMlError> {
+    let mut messages = Vec::new();
+    let mut next_call = 0;
+    let mut pending: VecDeque<String> = VecDeque::new();
+    let mut rest = text.trim_start();
+    while !rest.is_empty() {
+        let turn = rest
+            .strip_prefix(IM_START)
+            .ok_or_else(|| ChatMlError(format!("expected {} at {:?}", IM_START, head(rest))))?;
+        let (role, body) = turn
+            .split_once('\n')
+            .ok_or_else(|| ChatMlError("turn has no role line".to_string()))?;
+        let role = role.trim();
+        let Some((content, after)) = body.split_once(IM_END) else {
+            if role == "assistant" && body.trim().is_empty() {
+                break;
+            }
+            return Err(ChatMlError(format!("unterminated {} turn", role)));
+        };
+        rest = after.trim_start();
+
+        let message: AnyMessage = match role {
+            "system" => SystemMessage::new(content).into(),
+            "user" => HumanMessage::new(content).into(),
+            "assistant" => {
+                let (content, calls) = split_tool_calls(content, &mut next_call)?;
+                pending.extend(calls.iter().map(|call| call.id.clone()));
+                AiMessage::new(&content).with_tool_calls(calls).into()
+            }
+            "tool" => {
+                let id = pending
+                    .pop_front()
+                    .ok_or_else(|| ChatMlError("tool turn without a tool call".to_string()))?;
+                ToolMessage::new(content, id, None, ToolStatus::Success).into()
+            }
+            "" => return Err(ChatMlError("turn has an empty role".to_string())),
+            other => ChatMessage::new(content, other.to_string()).into(),
+        };
+        messages.push(message);
+    }
+    Ok(messages)
+}
+
+fn role(message: &AnyMessage) -> &str {
+    match message {
+        AnyMessage::Human(_) => "user",
+        AnyMessage::Ai(_) => "assistant",
+        AnyMessage::System(_) => "system",
+        AnyMessage::Tool(_) => "tool",
+        other => other.role(),
+    }
+}
+
+fn split_tool_calls(
+    content: &str,
+    next_call: &mut usize,
+) -> Result<(String, Vec<ToolCall>), ChatMlError> {
+    let mut text = String::new();
+    let mut calls = Vec::new();
+    let mut rest = content;
+    while let Some(start) = rest.find(TOOL_CALL_START) {
+        text.push_str(&rest[..start]);
+        let after = &rest[start + TOOL_CALL_START.len()..];
+        let end = after
+            .find(TOOL_CALL_END)
+            .ok_or_else(|| ChatMlError(format!("unterminated {}", TOOL_CALL_START)))?;
+        let call: Value = serde_json::from_str(after[..end].trim())
+            .map_err(|err| ChatMlError(format!("bad tool call: {}", err)))?;
+        let name = call["name"]
+            .as_str()
+            .ok_or_else(|| ChatMlError("tool call has no name".to_string()))?;
+        calls.push(ToolCall::new(
+            format!("call_{}", next_call),
+            name,
+            call.get("arguments").cloned().unwrap_or(Value::Null),
+        ));
+        *next_call += 1;
+        rest = &after[end + TOOL_CALL_END.len()..];
+    }
+    text.push_str(rest);
+    Ok((text.trim().to_string(), calls))
+}
+
+fn head(text: &str) -> String {
+    text.chars().take(20).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn conversation() -> Vec<AnyMessage> {
+        vec![
+            SystemMessage::new("Be brief.").into(),
+            HumanMessage::new("Weather in Paris?").into(),
+            AiMessage::new("")
+                .with_tool_calls(vec![ToolCall::new(
+                    "call_0",
+                    "weather",
+                    json!({"city": "Paris"}),
+                )])
+                .into(),
+            ToolMessage::new("Sunny", "call_0".to_string(), None, ToolStatus::Success).into(),
+            AiMessage::new("It is sunny.").into(),
+            ChatMessage::new("Approved.", "critic".to_string()).into(),
+        ]
+    }
+
+    #[test]
+    fn test_render_and_parse_round_trip() {
+        let text = render(&conversation(), true);
+
+        assert!(text.starts_with(
+            "<|im_start|>system\nBe brief.<|im_end|>\n\
+             <|im_start|>user\nWeather in Paris?<|im_end|>\n\
+             <|im_start|>assistant\n<tool_call>\n\
+             {\"arguments\":{\"city\":\"Paris\"},\"name\":\"weather\"}\n</tool_call><|im_end|>\n\
+             <|im_start|>tool\nSunny<|im_end|>\n"
+        ));
+        assert!(text.ends_with("<|im_start|>critic\nApproved.<|im_end|>\n<|im_start|>assistant\n"));
+        assert_eq!(parse(&text).unwrap(), conversation());
+
+        let mut with_removal = conversation();
+        with_removal.insert(2, crate::RemoveMessage::new("m1").into());
+        assert_eq!(render(&with_removal, true), text);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            parse("hello").unwrap_err(),
+            ChatMlError("expected <|im_start|> at \"hello\"".to_string())
+        );
+        assert_eq!(
+            parse("<|im_start|>user\nHi").unwrap_err(),
+            ChatMlError("unterminated user turn".to_string())
+        );
+        assert!(parse("<|im_start|>tool\nSunny<|im_end|>").is_err());
+        assert!(parse("<|im_start|>assistant\n<tool_call>{}</tool_call><|im_end|>").is_err());
+    }
+}
@@ -0,0 +1,183 @@
+use serde_json::json;
+
+use super::is_turn;
+use crate::{AnyMessage, BaseMessage};
+
+/// Instruct prompt layouts. Rendered prompts start with the BOS token, so
+/// tokenize them with special tokens enabled and without adding another.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LlamaTemplate {
+    /// `<s>[INST] <<SYS>>\n..\n<</SYS>>\n\nuser [/INST] assistant </s>`
+    Llama2,
+    /// `<|start_header_id|>role<|end_header_id|>\n\ncontent<|eot_id|>`
+    Llama3,
+    /// `<s>[INST] user [/INST]assistant</s>`, with the system prompt
+    /// leading the first user turn.
+    Mistral,
+}
+
+/// Renders `messages` for `template`. Llama 3 gives tool results their own
+/// `ipython` turns; the `[INST]` formats have no tool role, so results and
+/// custom roles join the surrounding user turn. Tool calls are written as
+/// `{"name":..,"parameters":..}` JSON lines. `add_generation_prompt` opens
+/// an assistant header for Llama 3; the `[INST]` formats already end open
+/// after a user turn.
+pub fn render(
+    messages: &[AnyMessage],
+    template: LlamaTemplate,
+    add_generation_prompt: bool,
+) -> String {
+    match template {
+        LlamaTemplate::Llama3 => render_llama3(messages, add_generation_prompt),
+        LlamaTemplate::Llama2 | LlamaTemplate::Mistral => render_inst(messages, template),
+    }
+}
+
+fn render_llama3(messages: &[AnyMessage], add_generation_prompt: bool) -> String {
+    let mut out = String::from("<|begin_of_text|>");
+    for message in messages.iter().filter(|message| is_turn(message)) {
+        let role = match message {
+            AnyMessage::Human(_) => "user",
+            AnyMessage::Ai(_) => "assistant",
+            AnyMessage::System(_) => "system",
+            AnyMessage::Tool(_) => "ipython",
+            other => other.role(),
+        };
+        out.push_str(&format!(
+            "<|start_header_id|>{}<|end_header_id|>\n\n{}<|eot_id|>",
+            role,
+            content(message)
+        ));
+    }
+    if add_generation_prompt {
+        out.push_str("<|start_header_id|>assistant<|end_header_id|>\n\n");
+    }
+    out
+}
+
+fn render_inst(messages: &[AnyMessage], template: LlamaTemplate) -> String {
+    let mut system: Vec<String> = Vec::new();
+    let mut user: Vec<String> = Vec::new();
+    let mut out = String::new();
+    let mut first = true;
+    for message in messages.iter().filter(|message| is_turn(message)) {
+        match message {
+            AnyMessage::System(_) => system.push(content(message)),
+            AnyMessage::Ai(_) => {
+                out.push_str(&inst_turn(template, &mut system, &mut user, first));
+                first = false;
+                match template {
+                    LlamaTemplate::Llama2 => out.push_str(&format!(" {} </s>", content(message))),
+                    _ => out.push_str(&format!("{}</s>", content(message))),
+                }
+            }
+            _ => user.push(content(message)),
+        }
+    }
+    if !user.is_empty() || !system.is_empty() {
+        out.push_str(&inst_turn(template, &mut system, &mut user, first));
+    }
+    out
+}
+
+/// One `[INST] .. [/INST]` block from the pending user text, folding in
+/// any pending system prompt.
+fn inst_turn(
+    template: LlamaTemplate,
+    system: &mut Vec<String>,
+    user: &mut Vec<String>,
+    first: bool,
+) -> String {
+    let mut text = user.join("\n\n");
+    if !system.is_empty() {
+        let prompt = system.join("\n\n");
+        text = match template {
+            LlamaTemplate::Llama2 => format!("<<SYS>>\n{}\n<</SYS>>\n\n{}", prompt, text),
+            _ if text.is_empty() => prompt,
+            _ => format!("{}\n\n{}", prompt, text),
+        };
+    }
+    system.clear();
+    user.clear();
+    let bos = if first || template == LlamaTemplate::Llama2 {
+        "<s>"
+    } else {
+        ""
+    };
+    format!("{}[INST] {} [/INST]", bos, text)
+}
+
+fn content(message: &AnyMessage) -> String {
+    let mut text = message.text().into_owned();
+    for call in message.tool_calls() {
+        if !text.is_empty() {
+            text.push('\n');
+        }
+        text.push_str(&json!({"name": call.name, "parameters": call.args}).to_string());
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tool_message::ToolStatus;
+    use crate::{AiMessage, HumanMessage, RemoveMessage, SystemMessage, ToolCall, ToolMessage};
+
+    fn conversation() -> Vec<AnyMessage> {
+        vec![
+            SystemMessage::new("Be brief.").into(),
+            HumanMessage::new("Hi").into(),
+            AiMessage::new("Hello!").into(),
+            HumanMessage::new("Bye").into(),
+        ]
+    }
+
+    #[test]
+    fn test_render_inst_templates() {
+        assert_eq!(
+            render(&conversation(), LlamaTemplate::Llama2, true),
+            "<s>[INST] <<SYS>>\nBe brief.\n<</SYS>>\n\nHi [/INST] Hello! </s>\
+             <s>[INST] Bye [/INST]"
+        );
+        assert_eq!(
+            render(&conversation(), LlamaTemplate::Mistral, true),
+            "<s>[INST] Be brief.\n\nHi [/INST]Hello!</s>[INST] Bye [/INST]"
+        );
+    }
+
+    #[test]
+    fn test_render_llama3_with_tools() {
+        let messages: Vec<AnyMessage> = vec![
+            HumanMessage::new("Weather?").into(),
+            AiMessage::new("")
+                .with_tool_calls(vec![ToolCall::new(
+                    "call_1",
+                    "weather",
+                    json!({"city": "Paris"}),
+                )])
+                .into(),
+            ToolMessage::new("Sunny", "call_1".to_string(), None, ToolStatus::Success).into(),
+        ];
+
+        assert_eq!(
+            render(&messages, LlamaTemplate::Llama3, true),
+            "<|begin_of_text|>\
+             <|start_header_id|>user<|end_header_id|>\n\nWeather?<|eot_id|>\
+             <|start_header_id|>assistant<|end_header_id|>\n\n\
+             {\"name\":\"weather\",\"parameters\":{\"city\":\"Paris\"}}<|eot_id|>\
+             <|start_header_id|>ipython<|end_header_id|>\n\nSunny<|eot_id|>\
+             <|start_header_id|>assistant<|end_header_id|>\n\n"
+        );
+        assert!(render(&messages, LlamaTemplate::Mistral, false).ends_with("[INST] Sunny [/INST]"));
+
+        let mut with_removal = messages.clone();
+        with_removal.push(RemoveMessage::new("m1").into());
+        for template in [LlamaTemplate::Llama3, LlamaTemplate::Mistral] {
+            assert_eq!(
+                render(&with_removal, template, true),
+                render(&messages, template, true)
+            );
+        }
+    }
+}
@@ -0,0 +1,13 @@
+//! Raw prompt strings for local models, as opposed to provider request
+//! payloads.
+
+pub mod chatml;
+pub mod llama;
+
+use crate::AnyMessage;
+
+/// [`RemoveMessage`](crate::RemoveMessage) sentinels edit the history and
+/// are never rendered as turns.
+pub(crate) fn is_turn(message: &AnyMessage) -> bool {
+    !matches!(message, AnyMessage::Remove(_))
+}
@@ -0,0 +1,500 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::compat::ensure_compatible;
+use crate::tool_message::ToolStatus;
+use crate::{
+    AiMessage, AnyMessage, BaseMessage, ContentBlock, HumanMessage, MessageContent, ModelProfile,
+    ToolCall, ToolMessage, UsageMetadata,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GeminiError(pub String);
+
+impl fmt::Display for GeminiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Cannot convert Gemini content: {}", self.0)
+    }
+}
+
+impl std::error::Error for GeminiError {}
+
+/// The conversation part of a `generateContent` request; callers add
+/// `generation_config`, `tools` and so on alongside it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GeminiRequestBody {
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        default,
+        alias = "systemInstruction"
+    )]
+    pub system_instruction: Option<GeminiContent>,
+    pub contents: Vec<GeminiContent>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GeminiRole {
+    User,
+    Model,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GeminiContent {
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub role: Option<GeminiRole>,
+    #[serde(default)]
+    pub parts: Vec<GeminiPart>,
+}
+
+/// One part of a content; serialized as Gemini's single-key objects such as
+/// `{"text": ..}` or `{"inline_data": ..}`. Responses use camelCase keys,
+/// which are accepted too.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GeminiPart {
+    Text(String),
+    #[serde(alias = "inlineData")]
+    InlineData(GeminiBlob),
+    #[serde(alias = "fileData")]
+    FileData(GeminiFileData),
+    #[serde(alias = "functionCall")]
+    FunctionCall(GeminiFunctionCall),
+    #[serde(alias = "functionResponse")]
+    FunctionResponse(GeminiFunctionResponse),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GeminiBlob {
+    #[serde(alias = "mimeType")]
+    pub mime_type: String,
+    pub data: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GeminiFileData {
+    #[serde(skip_serializing_if = "Option::is_none", default, alias = "mimeType")]
+    pub mime_type: Option<String>,
+    #[serde(alias = "fileUri")]
+    pub file_uri: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GeminiFunctionCall {
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub id: Option<String>,
+    pub name: String,
+    #[serde(default)]
+    pub args: Value,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GeminiFunctionResponse {
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub id: Option<String>,
+    pub name: String,
+    pub response: Value,
+}
+
+/// Strict mode: like [`to_gemini_request`], but fails when
+/// [`check_compatibility`](crate::check_compatibility) finds anything the
+/// model described by `profile` cannot take.
+pub fn to_gemini_request_strict(
+    messages: &[AnyMessage],
+    profile: &ModelProfile,
+) -> Result<GeminiRequestBody, GeminiError> {
+    ensure_compatible(messages, profile).map_err(|err| GeminiError(err.to_string()))?;
+    to_gemini_request(messages)
+}
+
+/// Builds a request body: system messages are hoisted into
+/// `system_instruction`, Ai messages become `model` turns, tool results
+/// become `function_response` parts of a user turn, and consecutive turns
+/// with the same role are merged.
+pub fn to_gemini_request(messages: &[AnyMessage]) -> Result<GeminiRequestBody, GeminiError> {
+    let mut system: Vec<GeminiPart> = Vec::new();
+    let mut contents: Vec<GeminiContent> = Vec::new();
+    let mut call_names: HashMap<&str, &str> = HashMap::new();
+    for message in messages {
+        let (role, parts) = match message {
+            AnyMessage::System(message) => {
+                system.extend(parts_from_content(&message.base.content));
+                continue;
+            }
+            AnyMessage::Human(message) => {
+                (GeminiRole::User, parts_from_content(&message.base.content))
+            }
+            AnyMessage::Ai(message) => {
+                let mut parts = parts_from_content(&message.base.content);
+                for call in message.tool_calls() {
+                    call_names.insert(&call.id, &call.name);
+                    parts.push(GeminiPart::FunctionCall(GeminiFunctionCall {
+                        id: Some(call.id.clone()),
+                        name: call.name.clone(),
+                        args: call.args.clone(),
+                    }));
+                }
+                (GeminiRole::Model, parts)
+            }
+            AnyMessage::Tool(tool) => {
+                let name = tool
+                    .name()
+                    .or_else(|| call_names.get(tool.tool_call_id()).copied())
+                    .ok_or_else(|| {
+                        GeminiError(format!(
+                            "no function name for tool result '{}'",
+                            tool.tool_call_id()
+                        ))
+                    })?;
+                let key = if tool.is_error() { "error" } else { "content" };
+                (
+                    GeminiRole::User,
+                    vec![GeminiPart::FunctionResponse(GeminiFunctionResponse {
+                        id: Some(tool.tool_call_id().to_string()),
+                        name: name.to_string(),
+                        response: json!({ key: tool.text() }),
+                    })],
+                )
+            }
+            AnyMessage::Chat(_) | AnyMessage::Remove(_) | AnyMessage::Unknown(_) => {
+                return Err(GeminiError(format!(
+                    "unsupported message type '{}'",
+                    message.role()
+                )))
+            }
+        };
+        match contents.last_mut() {
+            Some(last) if last.role == Some(role) => last.parts.extend(parts),
+            _ => contents.push(GeminiContent {
+                role: Some(role),
+                parts,
+            }),
+        }
+    }
+    Ok(GeminiRequestBody {
+        system_instruction: (!system.is_empty()).then_some(GeminiContent {
+            role: None,
+            parts: system,
+        }),
+        contents,
+    })
+}
+
+// Gemini rejects empty text parts, so they are dropped.
+fn parts_from_content(content: &MessageContent) -> Vec<GeminiPart> {
+    content
+        .blocks()
+        .iter()
+        .filter(|block| block.as_text() != Some(""))
+        .map(|block| match block.clone() {
+            ContentBlock::Text { text } => GeminiPart::Text(text),
+            ContentBlock::ImageUrl { url, .. } => GeminiPart::FileData(GeminiFileData {
+                mime_type: None,
+                file_uri: url,
+            }),
+            ContentBlock::Image { mime_type, data }
+            | ContentBlock::Audio { mime_type, data }
+            | ContentBlock::Document {
+                mime_type, data, ..
+            } => GeminiPart::InlineData(GeminiBlob { mime_type, data }),
+        })
+        .collect()
+}
+
+/// Converts a content back into messages. A `model` turn becomes one
+/// [`AiMessage`]; a user turn becomes a [`HumanMessage`] for its media and
+/// text, followed by a [`ToolMessage`] per function response. Calls without
+/// an id are numbered `{name}_{index}`.
+pub fn from_gemini_content(content: &GeminiContent) -> Result<Vec<AnyMessage>, GeminiError> {
+    let mut body = MessageContent::default();
+    let mut calls = Vec::new();
+    let mut results = Vec::new();
+    for part in &content.parts {
+        match part {
+            GeminiPart::Text(text) => body.append(MessageContent::Text(text.clone())),
+            GeminiPart::InlineData(GeminiBlob { mime_type, data }) => {
+                let block = match mime_type.split('/').next() {
+                    Some("image") => ContentBlock::image(mime_type, data),
+                    Some("audio") => ContentBlock::audio(mime_type, data),
+                    _ => ContentBlock::document(mime_type, data),
+                };
+                body.append(MessageContent::Blocks(vec![block]));
+            }
+            GeminiPart::FileData(file) => {
+                body.append(MessageContent::Blocks(vec![ContentBlock::image_url(
+                    &file.file_uri,
+                )]))
+            }
+            GeminiPart::FunctionCall(call) => {
+                let id = call
+                    .id
+                    .clone()
+                    .unwrap_or_else(|| format!("{}_{}", call.name, calls.len()));
+                calls.push(ToolCall::new(id, &call.name, call.args.clone()));
+            }
+            GeminiPart::FunctionResponse(response) => {
+                let (status, output) = match response.response.get("error") {
+                    Some(error) => (ToolStatus::Error, error),
+                    None => (
+                        ToolStatus::Success,
+                        response
+                            .response
+                            .get("content")
+                            .unwrap_or(&response.response),
+                    ),
+                };
+                let output = match output {
+                    Value::String(text) => text.clone(),
+                    other => other.to_string(),
+                };
+                let id = response.id.clone().unwrap_or_else(|| response.name.clone());
+                let mut tool = ToolMessage::new(&output, id, None, status);
+                tool.set_name(Some(response.name.clone()));
+                results.push(tool.into());
+            }
+        }
+    }
+
+    let mut messages: Vec<AnyMessage> = Vec::new();
+    match content.role {
+        Some(GeminiRole::Model) => {
+            if !results.is_empty() {
+                return Err(GeminiError("function response in a model turn".to_string()));
+            }
+            messages.push(
+                AiMessage::new("")
+                    .with_content(body)
+                    .with_tool_calls(calls)
+                    .into(),
+            );
+        }
+        Some(GeminiRole::User) | None => {
+            if !calls.is_empty() {
+                return Err(GeminiError("function call in a user turn".to_string()));
+            }
+            if !body.is_empty() {
+                messages.push(HumanMessage::new("").with_content(body).into());
+            }
+            messages.extend(results);
+        }
+    }
+    Ok(messages)
+}
+
+/// A `generateContent` response.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GeminiResponse {
+    #[serde(default)]
+    pub candidates: Vec<GeminiCandidate>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        default,
+        alias = "usageMetadata"
+    )]
+    pub usage_metadata: Option<GeminiUsageMetadata>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        default,
+        alias = "modelVersion"
+    )]
+    pub model_version: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GeminiCandidate {
+    pub content: GeminiContent,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        default,
+        alias = "finishReason"
+    )]
+    pub finish_reason: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GeminiUsageMetadata {
+    #[serde(default, alias = "promptTokenCount")]
+    pub prompt_token_count: u64,
+    #[serde(default, alias = "candidatesTokenCount")]
+    pub candidates_token_count: u64,
+    #[serde(default, alias = "totalTokenCount")]
+    pub total_token_count: u64,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        default,
+        alias = "cachedContentTokenCount"
+    )]
+    pub cached_content_token_count: Option<u64>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        default,
+        alias = "thoughtsTokenCount"
+    )]
+    pub thoughts_token_count: Option<u64>,
+}
+
+impl From<&GeminiUsageMetadata> for UsageMetadata {
+    fn from(usage: &GeminiUsageMetadata) -> Self {
+        let mut metadata =
+            UsageMetadata::new(usage.prompt_token_count, usage.candidates_token_count);
+        // Thoughts are billed as output but not counted in candidates.
+        if let Some(tokens) = usage.thoughts_token_count {
+            metadata.output_tokens += tokens;
+            metadata = metadata.with_output_detail("reasoning", tokens);
+        }
+        if let Some(tokens) = usage.cached_content_token_count {
+            metadata = metadata.with_input_detail("cache_read", tokens);
+        }
+        metadata.total_tokens = match usage.total_token_count {
+            0 => metadata.input_tokens + metadata.output_tokens,
+            total => total,
+        };
+        metadata
+    }
+}
+
+/// The reply in the first candidate, with its usage, and `finish_reason`
+/// and `model_version` kept in `response_metadata`.
+pub fn from_gemini_response(response: &GeminiResponse) -> Result<AiMessage, GeminiError> {
+    let candidate = response
+        .candidates
+        .first()
+        .ok_or_else(|| GeminiError("response without candidates".to_string()))?;
+    let content = GeminiContent {
+        role: Some(GeminiRole::Model),
+        parts: candidate.content.parts.clone(),
+    };
+    let Some(AnyMessage::Ai(mut reply)) = from_gemini_content(&content)?.pop() else {
+        return Err(GeminiError("candidate without a model turn".to_string()));
+    };
+    let metadata = &mut reply.base.response_metadata;
+    if let Some(reason) = &candidate.finish_reason {
+        metadata.insert("finish_reason", reason.clone());
+    }
+    if let Some(version) = &response.model_version {
+        metadata.insert("model_version", version.clone());
+    }
+    reply.usage_metadata = response.usage_metadata.as_ref().map(UsageMetadata::from);
+    Ok(reply)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SystemMessage;
+
+    #[test]
+    fn test_to_gemini_request() {
+        let weather = ToolCall::new("call_1", "weather", json!({"city": "Paris"}));
+        let messages: Vec<AnyMessage> = vec![
+            SystemMessage::new("Be brief.").into(),
+            HumanMessage::new("")
+                .with_content(vec![
+                    ContentBlock::text("Where is this?"),
+                    ContentBlock::image("image/png", "iVBORw0KGgo="),
+                ])
+                .into(),
+            AiMessage::new("")
+                .with_tool_calls(vec![weather.clone()])
+                .into(),
+            ToolMessage::new("Sunny", "call_1".to_string(), None, ToolStatus::Success).into(),
+            AiMessage::new("Paris, and it is sunny.").into(),
+        ];
+
+        let body = to_gemini_request(&messages).unwrap();
+        let value = serde_json::to_value(&body).unwrap();
+
+        assert_eq!(
+            value["system_instruction"],
+            json!({"parts": [{"text": "Be brief."}]})
+        );
+        assert_eq!(
+            value["contents"][0]["parts"][1],
+            json!({"inline_data": {"mime_type": "image/png", "data": "iVBORw0KGgo="}})
+        );
+        assert_eq!(value["contents"][1]["role"], "model");
+        assert_eq!(
+            value["contents"][1]["parts"][0]["function_call"],
+            json!({"id": "call_1", "name": "weather", "args": {"city": "Paris"}})
+        );
+        assert_eq!(
+            value["contents"][2]["parts"][0]["function_response"],
+            json!({"id": "call_1", "name": "weather", "response": {"content": "Sunny"}})
+        );
+        assert_eq!(value["contents"].as_array().unwrap().len(), 4);
+
+        let round_trip: Vec<AnyMessage> = body
+            .contents
+            .iter()
+            .flat_map(|content| from_gemini_content(content).unwrap())
+            .collect();
+        assert_eq!(round_trip[0], messages[1]);
+        assert_eq!(round_trip[1], messages[2]);
+        assert_eq!(round_trip[2].content(), "Sunny");
+        assert_eq!(round_trip[2].name(), Some("weather"));
+
+        let orphan = ToolMessage::new("Sunny", "call_9".to_string(), None, ToolStatus::Success);
+        assert!(to_gemini_request(&[orphan.into()]).is_err());
+    }
+
+    #[test]
+    fn test_from_gemini_response() {
+        let response: GeminiResponse = serde_json::from_value(json!({
+            "candidates": [{
+                "content": {
+                    "role": "model",
+                    "parts": [
+                        {"text": "Checking."},
+                        {"functionCall": {"name": "weather", "args": {"city": "Rome"}}}
+                    ]
+                },
+                "finishReason": "STOP"
+            }],
+            "usageMetadata": {
+                "promptTokenCount": 12,
+                "candidatesTokenCount": 5,
+                "thoughtsTokenCount": 3,
+                "totalTokenCount": 20
+            },
+            "modelVersion": "gemini-2.0-flash"
+        }))
+        .unwrap();
+
+        let reply = from_gemini_response(&response).unwrap();
+
+        assert_eq!(reply.content(), "Checking.");
+        assert_eq!(reply.tool_calls()[0].id, "weather_0");
+        assert_eq!(reply.tool_calls()[0].args, json!({"city": "Rome"}));
+        assert_eq!(
+            reply.response_metadata().get_str("finish_reason"),
+            Some("STOP")
+        );
+        let usage = reply.usage_metadata().unwrap();
+        assert_eq!((usage.input_tokens, usage.output_tokens), (12, 8));
+        assert_eq!(usage.total_tokens, 20);
+        assert_eq!(usage.output_token_details["reasoning"], 3);
+        let empty: GeminiResponse = serde_json::from_value(json!({"candidates": []})).unwrap();
+        assert!(from_gemini_response(&empty).is_err());
+    }
+
+    #[test]
+    fn test_strict_mode_rejects_images_for_text_only_models() {
+        let messages: Vec<AnyMessage> = vec![HumanMessage::new("")
+            .with_content(vec![
+                ContentBlock::text("What is this?"),
+                ContentBlock::image_url("https://example.com/cat.png"),
+            ])
+            .into()];
+        let text_only = ModelProfile::new("text-model", 8_000);
+
+        let error = to_gemini_request_strict(&messages, &text_only).unwrap_err();
+
+        assert!(error
+            .0
+            .contains("image block present but text-model is text-only"));
+        assert!(to_gemini_request_strict(&messages, &text_only.with_images()).is_ok());
+    }
+}
@@ -23,6 +23,13 @@ mod tests {
         assert_eq!(human_message.content(), "new_content");
     }
 
+    #[test]
+    fn test_humanmessage_new_with_id() {
+        let human_message = HumanMessage::new_with_id("Has an id.");
+        assert!(human_message.id().is_some());
+        assert_eq!(human_message.content(), "Has an id.");
+    }
+
     #[test]
     fn test_humanmessage_example() {
         let mut human_message = HumanMessage::new("Example human message.");
@@ -40,6 +47,36 @@ mod tests {
         assert_eq!(human_message.name(), Some("User123"));
     }
 
+    #[test]
+    fn test_humanmessage_with_provenance() {
+        let mut human_message = HumanMessage::new("Traced message.")
+            .with_provenance(Provenance::new("router").with_step("classify"));
+        assert_eq!(human_message.provenance().unwrap().component, "router");
+
+        human_message.set_provenance(None);
+        assert!(human_message.provenance().is_none());
+    }
+
+    #[test]
+    fn test_humanmessage_with_voice() {
+        let mut human_message =
+            HumanMessage::new("Transcribed message.").with_voice(VoiceMetadata::new(1_500));
+        assert_eq!(human_message.voice().unwrap().duration_ms, 1_500);
+
+        human_message.set_voice(None);
+        assert!(human_message.voice().is_none());
+    }
+
+    #[test]
+    fn test_humanmessage_with_content_blocks() {
+        let human_message = HumanMessage::new("").with_content(vec![
+            ContentBlock::text("Describe this."),
+            ContentBlock::image_url("https://example.com/chart.png"),
+        ]);
+        assert_eq!(human_message.content(), "Describe this.");
+        assert_eq!(human_message.content_blocks().len(), 2);
+    }
+
     #[test]
     fn test_humanmessage_with_additional_kwargs() {
         let mut human_message = HumanMessage::new("This is a human message.");
@@ -49,8 +86,8 @@ mod tests {
             .insert("mood".to_string(), "curious".to_string());
 
         assert_eq!(
-            human_message.additional_kwargs().get("mood"),
-            Some(&"curious".to_string())
+            human_message.additional_kwargs().get_str("mood"),
+            Some("curious")
         );
     }
 
@@ -63,8 +100,8 @@ mod tests {
             .insert("source".to_string(), "User".to_string());
 
         assert_eq!(
-            human_message.response_metadata().get("source"),
-            Some(&"User".to_string())
+            human_message.response_metadata().get_str("source"),
+            Some("User")
         );
     }
 
@@ -98,7 +135,7 @@ mod tests {
     fn test_humanmessage_debug_format() {
         let human_message = HumanMessage::new("Debug human message.");
         let debug_output = format!("{:?}", human_message);
-        let expected_debug_output = r#"HumanMessage { base: BaseMessageFields { content: "Debug human message.", example: false, message_type: Human, additional_kwargs: {}, response_metadata: {}, id: None, name: None } }"#;
+        let expected_debug_output = r#"HumanMessage { base: BaseMessageFields { content: Text("Debug human message."), example: false, message_type: Human, additional_kwargs: {}, response_metadata: {}, id: None, name: None, provenance: None, voice: None, pinned: false, extensions: Extensions { .. } } }"#;
         assert_eq!(debug_output, expected_debug_output);
     }
 
@@ -119,12 +156,12 @@ mod tests {
         assert_eq!(human_message.id(), Some("HUM123"));
         assert_eq!(human_message.name(), Some("User123"));
         assert_eq!(
-            human_message.additional_kwargs().get("intent"),
-            Some(&"query".to_string())
+            human_message.additional_kwargs().get_str("intent"),
+            Some("query")
         );
         assert_eq!(
-            human_message.response_metadata().get("platform"),
-            Some(&"mobile".to_string())
+            human_message.response_metadata().get_str("platform"),
+            Some("mobile")
         );
 
         let expected_json = json!({
@@ -144,4 +181,29 @@ mod tests {
         let serialized: Value = serde_json::to_value(&human_message).unwrap();
         assert_eq!(serialized, expected_json);
     }
+
+    #[test]
+    fn test_humanmessage_builder() {
+        let human_message = HumanMessage::builder()
+            .content("Hello")
+            .name("alice")
+            .id("h1")
+            .kwarg("channel", "web")
+            .kwarg("attempt", 2)
+            .example(true)
+            .build();
+
+        assert_eq!(human_message.content(), "Hello");
+        assert_eq!(human_message.name(), Some("alice"));
+        assert_eq!(human_message.id(), Some("h1"));
+        assert_eq!(
+            human_message.additional_kwargs().get_str("channel"),
+            Some("web")
+        );
+        assert_eq!(
+            human_message.additional_kwargs().get_u64("attempt"),
+            Some(2)
+        );
+        assert!(human_message.is_example());
+    }
 }
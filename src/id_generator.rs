@@ -0,0 +1,181 @@
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, PoisonError, RwLock};
+
+/// Source of message ids for the generated `new_with_id()` constructors.
+pub trait IdGenerator: Send + Sync {
+    fn generate_id(&self) -> String;
+}
+
+impl<F> IdGenerator for F
+where
+    F: Fn() -> String + Send + Sync,
+{
+    fn generate_id(&self) -> String {
+        self()
+    }
+}
+
+/// Random (version 4) UUIDs such as `67e55044-10b1-426f-9247-bb680e5fe0c8`.
+#[cfg(feature = "uuid")]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct UuidV4Generator;
+
+#[cfg(feature = "uuid")]
+impl IdGenerator for UuidV4Generator {
+    fn generate_id(&self) -> String {
+        uuid::Uuid::new_v4().to_string()
+    }
+}
+
+/// `prefix-1`, `prefix-2`, ...; unique only within one generator, which
+/// makes ids predictable in tests and fixtures.
+pub struct SequentialIdGenerator {
+    prefix: String,
+    next: AtomicU64,
+}
+
+impl SequentialIdGenerator {
+    pub fn new(prefix: impl Into<String>) -> Self {
+        SequentialIdGenerator {
+            prefix: prefix.into(),
+            next: AtomicU64::new(1),
+        }
+    }
+}
+
+impl fmt::Debug for SequentialIdGenerator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SequentialIdGenerator")
+            .field("prefix", &self.prefix)
+            .field("next", &self.next.load(Ordering::Relaxed))
+            .finish()
+    }
+}
+
+impl IdGenerator for SequentialIdGenerator {
+    fn generate_id(&self) -> String {
+        format!(
+            "{}-{}",
+            self.prefix,
+            self.next.fetch_add(1, Ordering::Relaxed)
+        )
+    }
+}
+
+static ID_GENERATOR: RwLock<Option<Arc<dyn IdGenerator>>> = RwLock::new(None);
+
+/// Replaces the process-wide generator used by `new_with_id()`.
+pub fn set_id_generator(generator: Arc<dyn IdGenerator>) {
+    *ID_GENERATOR.write().unwrap_or_else(PoisonError::into_inner) = Some(generator);
+}
+
+/// Goes back to the default generator.
+pub fn reset_id_generator() {
+    *ID_GENERATOR.write().unwrap_or_else(PoisonError::into_inner) = None;
+}
+
+/// A fresh id from the generator set with [`set_id_generator`]. The default
+/// is `UuidV4Generator` with the `uuid` feature, and otherwise a
+/// UUID-shaped id from a time-seeded pseudo-random source.
+pub fn generate_message_id() -> String {
+    let generator = ID_GENERATOR
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .clone();
+    match generator {
+        Some(generator) => generator.generate_id(),
+        None => default_id(),
+    }
+}
+
+#[cfg(feature = "uuid")]
+fn default_id() -> String {
+    UuidV4Generator.generate_id()
+}
+
+#[cfg(not(feature = "uuid"))]
+fn default_id() -> String {
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_nanos() as u64);
+    let seed = nanos
+        ^ COUNTER.fetch_add(1, Ordering::Relaxed).rotate_left(32)
+        ^ (u64::from(std::process::id()) << 16);
+    let mut rng = SplitMix64::new(seed);
+    let (high, low) = (rng.next_u64(), rng.next_u64());
+    // Set the version (4) and variant bits so the id reads as a UUIDv4.
+    let high = (high & 0xffff_ffff_ffff_0fff) | 0x4000;
+    let low = (low & 0x3fff_ffff_ffff_ffff) | 0x8000_0000_0000_0000;
+    format!(
+        "{:08x}-{:04x}-{:04x}-{:04x}-{:012x}",
+        high >> 32,
+        (high >> 16) & 0xffff,
+        high & 0xffff,
+        low >> 48,
+        low & 0xffff_ffff_ffff
+    )
+}
+
+// Small self-contained PRNG so seeded ids and dataset splits are reproducible across platforms and
+// crate versions.
+#[cfg(any(not(feature = "uuid"), feature = "analytics", feature = "models"))]
+pub(crate) struct SplitMix64(u64);
+
+#[cfg(any(not(feature = "uuid"), feature = "analytics", feature = "models"))]
+impl SplitMix64 {
+    pub(crate) fn new(seed: u64) -> Self {
+        SplitMix64(seed)
+    }
+
+    pub(crate) fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+    use crate::{BaseMessage, HumanMessage};
+
+    fn is_uuid_v4(id: &str) -> bool {
+        let groups: Vec<&str> = id.split('-').collect();
+        groups.iter().map(|group| group.len()).eq([8, 4, 4, 4, 12])
+            && id.chars().all(|c| c == '-' || c.is_ascii_hexdigit())
+            && groups[2].starts_with('4')
+            && groups[3].starts_with(['8', '9', 'a', 'b'])
+    }
+
+    #[test]
+    fn test_default_ids_are_unique_uuids() {
+        let ids: HashSet<String> = (0..1_000).map(|_| default_id()).collect();
+
+        assert_eq!(ids.len(), 1_000);
+        assert!(ids.iter().all(|id| is_uuid_v4(id)));
+    }
+
+    #[test]
+    fn test_new_with_id_uses_the_configured_generator() {
+        set_id_generator(Arc::new(SequentialIdGenerator::new("msg")));
+        let first = HumanMessage::new_with_id("Hi");
+        let second = HumanMessage::new_with_id("Again");
+        set_id_generator(Arc::new(|| "fixed".to_string()));
+        let third = HumanMessage::new_with_id("Bye");
+        reset_id_generator();
+
+        assert_eq!(first.id(), Some("msg-1"));
+        assert_eq!(second.id(), Some("msg-2"));
+        assert_eq!(third.id(), Some("fixed"));
+        assert_eq!(second.content(), "Again");
+        assert!(is_uuid_v4(&generate_message_id()));
+    }
+}
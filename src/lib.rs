@@ -2,12 +2,20 @@ pub mod message_type;
 pub use message_type::InvalidMessageTypeError;
 pub use message_type::MessageType;
 
+pub mod message_type_info;
+pub use message_type_info::MessageTypeInfo;
+
+pub mod provenance;
+pub use provenance::Provenance;
+
 pub mod base_message;
 pub use base_message::BaseMessage;
 pub use base_message::BaseMessageFields;
 
+#[macro_use]
 pub mod define_message;
 pub mod prelude;
+#[cfg(feature = "derive")]
 pub use derive_base_message;
 
 pub mod ai_message;
@@ -23,7 +31,422 @@ pub mod system_message;
 pub use system_message::SystemMessage;
 
 pub mod tool_message;
-pub use tool_message::ToolMessage;
+pub use tool_message::{ToolMessage, ToolStatus};
+
+pub mod unknown_message;
+pub use unknown_message::UnknownMessage;
+
+pub mod remove_message;
+pub use remove_message::{apply_message_ops, RemoveMessage, REMOVE_ALL_MESSAGES};
 
 pub mod message_enum;
 pub use message_enum::MessageEnum;
+
+pub mod conversation;
+pub use conversation::Conversation;
+
+pub mod lineage;
+pub use lineage::{lineage_tree, ForkOrigin, LineageNode};
+
+#[cfg(feature = "history")]
+pub mod conversation_delta;
+#[cfg(feature = "history")]
+pub use conversation_delta::{ConversationDelta, DeltaError};
+
+pub mod transformer;
+pub use transformer::{apply_transformers, BlockedMessage, MessageTransformer};
+
+#[cfg(feature = "safety")]
+pub mod safety;
+#[cfg(feature = "safety")]
+pub use safety::{FilterAction, ProfanityFilter, SecretFilter};
+
+#[cfg(feature = "safety")]
+pub mod secrets;
+#[cfg(feature = "safety")]
+pub use secrets::{InMemorySecretStore, SecretKind, SecretStore, SecretVault};
+
+#[cfg(feature = "safety")]
+pub mod encryption;
+#[cfg(feature = "safety")]
+pub use encryption::{EncryptedField, EncryptionError, FieldCipher};
+
+#[cfg(feature = "history")]
+pub mod audit;
+#[cfg(feature = "history")]
+pub use audit::{AuditEvent, AuditSink, AuditedHistory, InMemoryAuditSink};
+
+pub mod debug_dump;
+pub use debug_dump::{dump_debug, DumpFormat, DumpOptions};
+
+#[cfg(feature = "history")]
+pub mod limits;
+#[cfg(feature = "history")]
+pub use limits::{LimitError, LimitPolicy, LimitViolation, Limits};
+
+#[cfg(feature = "analytics")]
+pub mod lint;
+#[cfg(feature = "analytics")]
+pub use lint::{apply_fixes, lint_conversation, LintKind, LintOptions, LintWarning};
+
+#[cfg(feature = "safety")]
+pub mod anonymize;
+#[cfg(feature = "safety")]
+pub use anonymize::{anonymize, AnonymizationPolicy, Anonymizer, EntityKind};
+
+#[cfg(feature = "analytics")]
+pub mod dataset;
+#[cfg(feature = "analytics")]
+pub use dataset::{
+    split_dataset, split_stratified, DatasetSplit, InvalidSplitError, MessageWeights, SplitRatios,
+};
+
+#[cfg(feature = "analytics")]
+pub mod metrics;
+#[cfg(feature = "analytics")]
+pub use metrics::{conversation_metrics, ConversationMetrics};
+
+#[cfg(feature = "safety")]
+pub mod logging_view;
+#[cfg(feature = "safety")]
+pub use logging_view::LoggingView;
+
+pub mod message_ref;
+pub use message_ref::{ConversationRef, MessageRef};
+
+#[cfg(feature = "context")]
+pub mod prompt_pool;
+#[cfg(feature = "context")]
+pub use prompt_pool::{PoolStats, PooledSystemMessage, PromptPool};
+
+#[cfg(feature = "history")]
+pub mod archive;
+#[cfg(feature = "history")]
+pub use archive::{ArchiveError, ArchiveWriter, ConversationArchive};
+
+#[cfg(any(feature = "bincode", feature = "postcard"))]
+pub mod binary;
+#[cfg(any(feature = "bincode", feature = "postcard"))]
+pub use binary::{BinaryError, BINARY_FORMAT_VERSION};
+
+#[cfg(feature = "formats")]
+pub mod convert;
+#[cfg(feature = "formats")]
+pub use convert::{
+    convert_all, BatchResult, Converter, JsonMessageConverter, LineMessageConverter,
+};
+
+#[cfg(feature = "analytics")]
+pub mod diff;
+#[cfg(feature = "analytics")]
+pub use diff::{diff_content, diff_messages, diff_words, ContentDiff, DiffGranularity, DiffOp};
+
+#[cfg(feature = "query")]
+pub mod query;
+#[cfg(feature = "query")]
+pub use query::{query, Query, QueryError, QueryField};
+
+#[cfg(feature = "history")]
+pub mod versioned;
+#[cfg(feature = "history")]
+pub use versioned::{ConversationEvent, ConversationOp, VersionedConversation};
+
+#[cfg(feature = "history")]
+pub mod merge;
+#[cfg(feature = "history")]
+pub use merge::{merge, MergeStrategy, TieBreak};
+
+#[cfg(feature = "context")]
+pub mod persona;
+#[cfg(feature = "context")]
+pub use persona::{apply_persona, persona_of, Persona, PERSONA_KWARG};
+
+#[cfg(feature = "history")]
+pub mod agent;
+#[cfg(feature = "history")]
+pub use agent::{AgentEnvelope, InMemoryMailbox, Mailbox};
+
+#[cfg(feature = "history")]
+pub mod turn;
+#[cfg(feature = "history")]
+pub use turn::{TurnError, TurnState, TurnStateMachine};
+
+#[cfg(feature = "history")]
+pub mod policy;
+#[cfg(feature = "history")]
+pub use policy::{ConversationPolicy, PolicyViolation};
+
+pub mod blob;
+pub use blob::{BlobStore, InMemoryBlobStore};
+
+#[cfg(feature = "history")]
+pub mod spillover;
+#[cfg(feature = "history")]
+pub use spillover::{SpilledToolOutput, SpilloverMode, ToolOutputPolicy};
+
+pub mod tool_pairs;
+pub use tool_pairs::{repair_tool_pairs, RepairReport, UnansweredCalls};
+
+pub mod window;
+pub use window::{message_groups, window_messages, window_messages_by};
+
+pub mod tokens;
+pub use tokens::{
+    approximate_message_tokens, approximate_tokens, estimate_tokens, CalibratedTokenCounter,
+    CalibrationTable, Script, ScriptCalibration, TokenEstimate,
+};
+
+pub mod compat;
+pub use compat::{
+    check_compatibility, ensure_compatible, CompatibilityError, CompatibilityFinding,
+    CompatibilityIssue,
+};
+
+pub mod model_profile;
+pub use model_profile::{ModelProfile, ModelRegistry, Pricing};
+
+#[cfg(feature = "context")]
+pub mod packing;
+#[cfg(feature = "context")]
+pub use packing::{
+    pack_context, pack_context_with, ContextSection, PackOptions, PackReport, PackedContext,
+    SectionAllocation,
+};
+
+#[cfg(feature = "context")]
+pub mod summarize;
+#[cfg(feature = "context")]
+pub use summarize::{summarize_history, SUMMARY_PREFIX};
+
+#[cfg(feature = "context")]
+pub mod facts;
+#[cfg(feature = "context")]
+pub use facts::{promote_facts, Fact, FactExtractor, FactMemory, Facts, FACTS_HEADER};
+
+#[cfg(feature = "history")]
+pub mod delivery;
+#[cfg(feature = "history")]
+pub use delivery::{DeliveryEvent, DeliveryState, DeliveryStore, InMemoryDeliveryStore};
+
+#[cfg(feature = "history")]
+pub mod signal;
+#[cfg(feature = "history")]
+pub use signal::{ConversationSignal, ConversationUpdate};
+
+pub mod voice;
+pub use voice::{SpeechSegment, VoiceMetadata};
+
+pub mod logprobs;
+pub use logprobs::{Logprobs, TokenLogprob, TopLogprob};
+
+pub mod fingerprint;
+pub use fingerprint::{canonical_request, request_fingerprint, RequestOptions};
+
+pub mod chat_model;
+pub use chat_model::{ChatModel, ChatModelError};
+
+#[cfg(feature = "models")]
+pub mod response_cache;
+#[cfg(feature = "models")]
+pub use response_cache::{
+    CachedChatModel, CachedResponse, FileResponseCache, InMemoryResponseCache, ResponseCache,
+};
+
+#[cfg(feature = "models")]
+pub mod simulation;
+#[cfg(feature = "models")]
+pub use simulation::{
+    run_simulation, run_simulation_with, AdversarialUser, CorpusUser, ScriptedUser, SimulatedUser,
+};
+
+pub mod any_message;
+pub use any_message::AnyMessage;
+
+#[cfg(feature = "models")]
+pub mod replay;
+#[cfg(feature = "models")]
+pub use replay::{RecordingChatModel, ReplayChatModel};
+
+#[cfg(feature = "analytics")]
+pub mod corpus;
+#[cfg(feature = "analytics")]
+pub use corpus::{CorpusReport, Histogram};
+
+pub mod tool_call;
+pub use tool_call::{InvalidToolCall, ToolCall};
+
+#[cfg(feature = "history")]
+pub mod sort;
+#[cfg(feature = "history")]
+pub use sort::{sort_messages, MessageOrdering, SortReport};
+
+pub mod clock;
+pub use clock::{Clock, MockClock, SystemClock};
+
+pub mod content;
+pub use content::{ContentBlock, MessageContent};
+
+pub mod serde_tag;
+pub use serde_tag::{deserialize_tagged, serialize_tagged};
+
+#[cfg(feature = "history")]
+pub mod fixed_conversation;
+#[cfg(feature = "history")]
+pub use fixed_conversation::{ConversationFullError, FixedConversation};
+
+#[cfg(feature = "providers-openai")]
+pub mod openai;
+#[cfg(feature = "providers-openai")]
+pub use openai::{
+    from_openai_messages, to_openai_messages, to_openai_messages_strict, OpenAiError,
+};
+
+#[cfg(feature = "providers-anthropic")]
+pub mod anthropic;
+#[cfg(feature = "providers-anthropic")]
+pub use anthropic::{AnthropicError, AnthropicRequestBody};
+
+pub mod extensions;
+pub use extensions::Extensions;
+
+pub mod callbacks;
+pub use callbacks::{CallbackList, Callbacks, WithCallbacks};
+
+pub mod utils;
+pub use utils::{
+    filter_messages, merge_message_runs, merge_message_runs_with, split_long_message,
+    trim_messages, trim_messages_with, ApproximateTokenCounter, MessageFilter, SplitStrategy,
+    TokenCounter, TrimStrategy, SPLIT_PART_KEY, SPLIT_TOTAL_KEY,
+};
+
+pub mod chat_history;
+pub use chat_history::{ChatHistory, InMemoryChatHistory, DEFAULT_TRASH_RETENTION};
+
+pub mod stamper;
+pub use stamper::Stamper;
+
+#[cfg(feature = "streaming")]
+pub mod message_chunk;
+#[cfg(feature = "streaming")]
+pub use message_chunk::{AiMessageChunk, MessageChunk, ToolCallChunk};
+
+#[cfg(feature = "history")]
+pub mod tenant;
+#[cfg(feature = "history")]
+pub use tenant::{TenantScopedHistory, TenantUsage, TENANT_ID_KEY};
+
+pub mod trash;
+pub use trash::TrashedMessage;
+
+#[cfg(feature = "formats")]
+pub mod csv_import;
+#[cfg(feature = "formats")]
+pub use csv_import::{CsvImport, CsvImportError, CsvImporter, CsvRowError, CSV_TIMESTAMP_KEY};
+
+#[cfg(feature = "context")]
+pub mod experiment;
+#[cfg(feature = "context")]
+pub use experiment::{
+    compare_variants, prompt_variant_id, record_outcome, tag_variant, variant_of, Experiment,
+    PromptVariant, VariantOutcomes, OUTCOME_KEY, PROMPT_VARIANT_KEY,
+};
+
+#[cfg(feature = "templates")]
+pub mod prompts;
+#[cfg(feature = "templates")]
+pub use prompts::{
+    bind_messages, BindMode, ChatPromptTemplate, MessageTemplate, MessagesPlaceholder, PromptError,
+    PromptRole, PromptTemplate, PromptValue, PromptVariables,
+};
+
+pub mod metadata;
+pub use metadata::Metadata;
+
+#[cfg(feature = "providers-openai")]
+pub mod openai_batch;
+#[cfg(feature = "providers-openai")]
+pub use openai_batch::{
+    parse_batch_output, to_batch_request, write_batch_file, BatchItemError, BatchOutput,
+    BATCH_CHAT_COMPLETIONS_URL,
+};
+
+#[cfg(feature = "providers-anthropic")]
+pub mod anthropic_batch;
+#[cfg(feature = "providers-anthropic")]
+pub use anthropic_batch::{
+    parse_anthropic_batch_results, to_anthropic_batch, AnthropicBatch, AnthropicBatchItemError,
+    AnthropicBatchOutput, AnthropicBatchRequest, AnthropicBatchResult, AnthropicBatchResultLine,
+    AnthropicCountTokensRequest, AnthropicMessageParams, AnthropicResponse, AnthropicTokenCount,
+    AnthropicUsage,
+};
+
+#[cfg(feature = "providers-gemini")]
+pub mod gemini;
+#[cfg(feature = "providers-gemini")]
+pub use gemini::{
+    from_gemini_content, from_gemini_response, to_gemini_request, to_gemini_request_strict,
+    GeminiContent, GeminiError, GeminiPart, GeminiRequestBody, GeminiResponse, GeminiRole,
+    GeminiUsageMetadata,
+};
+
+#[cfg(feature = "formats")]
+pub mod serde_registry;
+#[cfg(feature = "formats")]
+pub use serde_registry::{SerdeRegistry, SerdeRegistryError, CUSTOM_RECORD_TYPE};
+
+#[cfg(feature = "formats")]
+pub mod export;
+#[cfg(feature = "formats")]
+pub use export::{dot, dot_branches, mermaid, mermaid_branches};
+
+pub mod usage;
+pub use usage::UsageMetadata;
+
+#[cfg(feature = "share")]
+pub mod share;
+#[cfg(feature = "share")]
+pub use share::{
+    RedactionProfile, ShareCodec, ShareError, SharedConversation, SHARE_FORMAT_VERSION,
+};
+
+#[cfg(feature = "storage-sqlite")]
+pub mod sqlite_chat_history;
+#[cfg(feature = "storage-sqlite")]
+pub use sqlite_chat_history::SqliteChatHistory;
+
+#[cfg(feature = "storage-redis")]
+pub mod redis_response_cache;
+#[cfg(feature = "storage-redis")]
+pub use redis_response_cache::RedisResponseCache;
+
+pub mod token_cache;
+
+#[cfg(feature = "formats")]
+pub mod transcript;
+#[cfg(feature = "formats")]
+pub use transcript::{render_transcript, TranscriptStyle};
+
+#[cfg(feature = "formats")]
+pub mod formats;
+
+#[cfg(feature = "analytics")]
+pub mod inspect;
+#[cfg(feature = "analytics")]
+pub use inspect::{inspect, Finding, FindingKind, Inspection, Inspector};
+
+#[cfg(feature = "history")]
+pub mod conversation_builder;
+#[cfg(feature = "history")]
+pub use conversation_builder::{
+    AwaitingAi, AwaitingHuman, AwaitingToolResult, ConversationBuilder, Start,
+};
+
+#[cfg(feature = "async-openai")]
+pub mod async_openai_interop;
+
+pub mod id_generator;
+#[cfg(feature = "uuid")]
+pub use id_generator::UuidV4Generator;
+pub use id_generator::{
+    generate_message_id, reset_id_generator, set_id_generator, IdGenerator, SequentialIdGenerator,
+};
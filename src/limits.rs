@@ -0,0 +1,425 @@
+use std::fmt;
+
+use crate::{Conversation, MessageEnum};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LimitPolicy {
+    #[default]
+    Reject,
+    Truncate,
+    Warn,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LimitViolation {
+    ContentTooLarge { bytes: usize, max: usize },
+    TooManyKwargs { entries: usize, max: usize },
+    TooManyMessages { count: usize, max: usize },
+    PayloadTooLarge { bytes: usize, max: usize },
+}
+
+impl fmt::Display for LimitViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LimitViolation::ContentTooLarge { bytes, max } => {
+                write!(f, "Content is {} bytes, limit is {}", bytes, max)
+            }
+            LimitViolation::TooManyKwargs { entries, max } => {
+                write!(
+                    f,
+                    "Message has {} kwargs entries, limit is {}",
+                    entries, max
+                )
+            }
+            LimitViolation::TooManyMessages { count, max } => {
+                write!(f, "Conversation has {} messages, limit is {}", count, max)
+            }
+            LimitViolation::PayloadTooLarge { bytes, max } => {
+                write!(f, "Payload is {} bytes, limit is {}", bytes, max)
+            }
+        }
+    }
+}
+
+impl std::error::Error for LimitViolation {}
+
+#[derive(Debug)]
+pub enum LimitError {
+    Json(serde_json::Error),
+    Violation(LimitViolation),
+}
+
+impl fmt::Display for LimitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LimitError::Json(err) => write!(f, "Invalid conversation JSON: {}", err),
+            LimitError::Violation(violation) => write!(f, "Limit exceeded: {}", violation),
+        }
+    }
+}
+
+impl std::error::Error for LimitError {}
+
+impl From<LimitViolation> for LimitError {
+    fn from(violation: LimitViolation) -> Self {
+        LimitError::Violation(violation)
+    }
+}
+
+/// Size limits for messages and conversations. `None` disables a limit.
+/// Under [`LimitPolicy::Reject`] the first violation is an error; otherwise
+/// violations are returned so the caller can log them. `max_json_bytes` is
+/// checked before parsing and always rejects, since a payload can't be
+/// truncated before it is read.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Limits {
+    pub max_content_bytes: Option<usize>,
+    pub max_kwargs_entries: Option<usize>,
+    pub max_messages: Option<usize>,
+    pub max_json_bytes: Option<usize>,
+    pub policy: LimitPolicy,
+}
+
+impl Limits {
+    /// Enforces the limits on a freshly built message, e.g.
+    /// `limits.checked(HumanMessage::builder().content(text).build())`.
+    pub fn checked(
+        &self,
+        message: impl Into<MessageEnum>,
+    ) -> Result<(MessageEnum, Vec<LimitViolation>), LimitViolation> {
+        let mut message = message.into();
+        let violations = self.enforce_message(&mut message)?;
+        Ok((message, violations))
+    }
+
+    pub fn check_message(&self, message: &MessageEnum) -> Vec<LimitViolation> {
+        let base = message.base();
+        let mut violations = Vec::new();
+        if let Some(max) = self.max_content_bytes {
+            let bytes = base.content.text().len();
+            if bytes > max {
+                violations.push(LimitViolation::ContentTooLarge { bytes, max });
+            }
+        }
+        if let Some(max) = self.max_kwargs_entries {
+            if base.additional_kwargs.len() > max {
+                violations.push(LimitViolation::TooManyKwargs {
+                    entries: base.additional_kwargs.len(),
+                    max,
+                });
+            }
+        }
+        violations
+    }
+
+    pub fn enforce_message(
+        &self,
+        message: &mut MessageEnum,
+    ) -> Result<Vec<LimitViolation>, LimitViolation> {
+        let violations = self.check_message(message);
+        self.resolve(violations, |violation| match violation {
+            LimitViolation::ContentTooLarge { max, .. } => {
+                let mut budget = *max;
+                for text in message.base_mut().content.texts_mut() {
+                    let mut end = budget.min(text.len());
+                    while !text.is_char_boundary(end) {
+                        end -= 1;
+                    }
+                    text.truncate(end);
+                    budget -= text.len();
+                }
+            }
+            LimitViolation::TooManyKwargs { max, .. } => {
+                let kwargs = &mut message.base_mut().additional_kwargs;
+                let mut keys: Vec<String> = kwargs.keys().cloned().collect();
+                keys.sort();
+                for key in keys.into_iter().skip(*max) {
+                    kwargs.remove(&key);
+                }
+            }
+            LimitViolation::TooManyMessages { .. } | LimitViolation::PayloadTooLarge { .. } => {}
+        })
+    }
+
+    /// Checks every message plus the message count. Truncation keeps pinned
+    /// messages and otherwise the most recent ones.
+    pub fn enforce_conversation(
+        &self,
+        conversation: &mut Conversation,
+    ) -> Result<Vec<LimitViolation>, LimitViolation> {
+        let mut violations = Vec::new();
+        for message in conversation.messages_mut() {
+            violations.extend(self.enforce_message(message)?);
+        }
+        violations.extend(self.enforce_message_count(conversation)?);
+        Ok(violations)
+    }
+
+    pub fn from_json(&self, json: &str) -> Result<(Conversation, Vec<LimitViolation>), LimitError> {
+        if let Some(max) = self.max_json_bytes {
+            if json.len() > max {
+                return Err(LimitViolation::PayloadTooLarge {
+                    bytes: json.len(),
+                    max,
+                }
+                .into());
+            }
+        }
+        let mut conversation: Conversation =
+            serde_json::from_str(json).map_err(LimitError::Json)?;
+        let violations = self.enforce_conversation(&mut conversation)?;
+        Ok((conversation, violations))
+    }
+
+    fn enforce_message_count(
+        &self,
+        conversation: &mut Conversation,
+    ) -> Result<Vec<LimitViolation>, LimitViolation> {
+        let violations = match self.max_messages {
+            Some(max) if conversation.len() > max => vec![LimitViolation::TooManyMessages {
+                count: conversation.len(),
+                max,
+            }],
+            _ => Vec::new(),
+        };
+        self.resolve(violations, |violation| {
+            if let LimitViolation::TooManyMessages { count, max } = violation {
+                // Drop the oldest unpinned messages.
+                let mut excess = count - max;
+                conversation.messages_mut().retain(|message| {
+                    if excess > 0 && !message.is_pinned() {
+                        excess -= 1;
+                        return false;
+                    }
+                    true
+                });
+            }
+        })
+    }
+
+    fn resolve(
+        &self,
+        violations: Vec<LimitViolation>,
+        mut truncate: impl FnMut(&LimitViolation),
+    ) -> Result<Vec<LimitViolation>, LimitViolation> {
+        match self.policy {
+            LimitPolicy::Reject => match violations.into_iter().next() {
+                Some(violation) => Err(violation),
+                None => Ok(Vec::new()),
+            },
+            LimitPolicy::Truncate => {
+                violations.iter().for_each(&mut truncate);
+                Ok(violations)
+            }
+            LimitPolicy::Warn => Ok(violations),
+        }
+    }
+}
+
+impl Conversation {
+    /// Appends `message` after enforcing `limits` on it and on the new length.
+    pub fn push_checked(
+        &mut self,
+        message: impl Into<MessageEnum>,
+        limits: &Limits,
+    ) -> Result<Vec<LimitViolation>, LimitViolation> {
+        let mut message = message.into();
+        let mut violations = limits.enforce_message(&mut message)?;
+        if limits.policy == LimitPolicy::Reject
+            && limits.max_messages.is_some_and(|max| self.len() >= max)
+        {
+            return Err(LimitViolation::TooManyMessages {
+                count: self.len() + 1,
+                max: limits.max_messages.unwrap_or_default(),
+            });
+        }
+        self.push(message);
+        violations.extend(limits.enforce_message_count(self)?);
+        Ok(violations)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BaseMessage, HumanMessage};
+
+    fn limits(policy: LimitPolicy) -> Limits {
+        Limits {
+            max_content_bytes: Some(8),
+            max_kwargs_entries: Some(1),
+            max_messages: Some(2),
+            max_json_bytes: None,
+            policy,
+        }
+    }
+
+    fn oversized() -> MessageEnum {
+        let mut message = HumanMessage::new("héllo wörld");
+        message
+            .base
+            .additional_kwargs
+            .insert("a".to_string(), "1".to_string());
+        message
+            .base
+            .additional_kwargs
+            .insert("b".to_string(), "2".to_string());
+        message.into()
+    }
+
+    #[test]
+    fn test_reject_policy() {
+        let mut message = oversized();
+
+        let result = limits(LimitPolicy::Reject).enforce_message(&mut message);
+
+        assert_eq!(
+            result,
+            Err(LimitViolation::ContentTooLarge { bytes: 13, max: 8 })
+        );
+        assert_eq!(message, oversized());
+    }
+
+    #[test]
+    fn test_truncate_policy() {
+        let mut message = oversized();
+
+        let violations = limits(LimitPolicy::Truncate)
+            .enforce_message(&mut message)
+            .unwrap();
+
+        assert_eq!(violations.len(), 2);
+        assert_eq!(message.content(), "héllo w");
+        assert_eq!(message.additional_kwargs().len(), 1);
+        assert!(message.additional_kwargs().contains_key("a"));
+    }
+
+    #[test]
+    fn test_warn_policy() {
+        let mut message = oversized();
+
+        let violations = limits(LimitPolicy::Warn)
+            .enforce_message(&mut message)
+            .unwrap();
+
+        assert_eq!(violations.len(), 2);
+        assert_eq!(message, oversized());
+    }
+
+    #[test]
+    fn test_truncate_keeps_pinned_messages() {
+        let mut pinned: MessageEnum = HumanMessage::new("keep me").into();
+        pinned.set_pinned(true);
+        let mut conversation: Conversation = vec![
+            pinned,
+            HumanMessage::new("a").into(),
+            HumanMessage::new("b").into(),
+            HumanMessage::new("c").into(),
+        ]
+        .into_iter()
+        .collect();
+        let limits = Limits {
+            max_messages: Some(2),
+            policy: LimitPolicy::Truncate,
+            ..Limits::default()
+        };
+
+        limits.enforce_conversation(&mut conversation).unwrap();
+
+        let contents: Vec<&str> = conversation.iter().map(|m| m.content()).collect();
+        assert_eq!(contents, vec!["keep me", "c"]);
+    }
+
+    #[test]
+    fn test_push_checked() {
+        let mut conversation = Conversation::new();
+        let reject = limits(LimitPolicy::Reject);
+        conversation
+            .push_checked(HumanMessage::new("one"), &reject)
+            .unwrap();
+        conversation
+            .push_checked(HumanMessage::new("two"), &reject)
+            .unwrap();
+
+        let result = conversation.push_checked(HumanMessage::new("three"), &reject);
+        assert_eq!(
+            result,
+            Err(LimitViolation::TooManyMessages { count: 3, max: 2 })
+        );
+        assert_eq!(conversation.len(), 2);
+
+        let truncate = limits(LimitPolicy::Truncate);
+        conversation
+            .push_checked(HumanMessage::new("three"), &truncate)
+            .unwrap();
+        let contents: Vec<&str> = conversation.iter().map(|m| m.content()).collect();
+        assert_eq!(contents, vec!["two", "three"]);
+    }
+
+    #[test]
+    fn test_from_json_with_limits() {
+        let conversation: Conversation = vec![
+            HumanMessage::new("one"),
+            HumanMessage::new("two"),
+            HumanMessage::new("three"),
+        ]
+        .into_iter()
+        .collect();
+        let json = serde_json::to_string(&conversation).unwrap();
+
+        let error = limits(LimitPolicy::Reject).from_json(&json).unwrap_err();
+        assert!(matches!(
+            error,
+            LimitError::Violation(LimitViolation::TooManyMessages { count: 3, max: 2 })
+        ));
+
+        let (conversation, violations) = limits(LimitPolicy::Truncate).from_json(&json).unwrap();
+        assert_eq!(violations.len(), 1);
+        assert_eq!(conversation.len(), 2);
+
+        let (conversation, violations) = limits(LimitPolicy::Warn).from_json(&json).unwrap();
+        assert_eq!(violations.len(), 1);
+        assert_eq!(conversation.len(), 3);
+
+        assert!(matches!(
+            Limits::default().from_json("not json"),
+            Err(LimitError::Json(_))
+        ));
+    }
+
+    #[test]
+    fn test_oversized_json_is_rejected_before_parsing() {
+        let limits = Limits {
+            max_json_bytes: Some(16),
+            policy: LimitPolicy::Warn,
+            ..Limits::default()
+        };
+        let payload = format!("[{}", "[".repeat(64));
+
+        let error = limits.from_json(&payload).unwrap_err();
+
+        assert!(matches!(
+            error,
+            LimitError::Violation(LimitViolation::PayloadTooLarge { bytes: 65, max: 16 })
+        ));
+        assert!(matches!(limits.from_json("[]"), Ok((_, violations)) if violations.is_empty()));
+    }
+
+    #[test]
+    fn test_checked_construction() {
+        let text = "héllo wörld";
+
+        assert_eq!(
+            limits(LimitPolicy::Reject)
+                .checked(HumanMessage::builder().content(text).build())
+                .unwrap_err(),
+            LimitViolation::ContentTooLarge { bytes: 13, max: 8 }
+        );
+
+        let (message, violations) = limits(LimitPolicy::Truncate)
+            .checked(HumanMessage::builder().content(text).build())
+            .unwrap();
+        assert_eq!(violations.len(), 1);
+        assert_eq!(message.content(), "héllo w");
+    }
+}
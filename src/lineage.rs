@@ -0,0 +1,165 @@
+use std::collections::HashSet;
+
+use serde::{Deserialize, Serialize};
+
+use crate::Conversation;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ForkOrigin {
+    pub session: String,
+    pub message_id: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LineageNode {
+    pub session: String,
+
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub forked_at: Option<String>,
+
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub children: Vec<LineageNode>,
+}
+
+/// Rebuilds the branch tree that `session` belongs to, rooted at its oldest
+/// known ancestor. Returns `None` if `session` is not in `store`.
+pub fn lineage_tree<'a>(
+    store: impl IntoIterator<Item = &'a Conversation>,
+    session: &str,
+) -> Option<LineageNode> {
+    let conversations: Vec<&Conversation> = store
+        .into_iter()
+        .filter(|conversation| conversation.session_id().is_some())
+        .collect();
+    let find = |id: &str| {
+        conversations
+            .iter()
+            .find(|conversation| conversation.session_id() == Some(id))
+            .copied()
+    };
+
+    let mut root = find(session)?;
+    let mut visited = HashSet::from([session.to_string()]);
+    while let Some(parent) = root.forked_from().and_then(|origin| find(&origin.session)) {
+        let parent_id = parent.session_id().unwrap_or_default().to_string();
+        if !visited.insert(parent_id) {
+            break;
+        }
+        root = parent;
+    }
+
+    let mut visited = HashSet::new();
+    Some(build_node(root, &conversations, &mut visited))
+}
+
+fn build_node(
+    conversation: &Conversation,
+    conversations: &[&Conversation],
+    visited: &mut HashSet<String>,
+) -> LineageNode {
+    let session = conversation.session_id().unwrap_or_default().to_string();
+    visited.insert(session.clone());
+
+    let children = conversations
+        .iter()
+        .filter(|child| {
+            child
+                .forked_from()
+                .is_some_and(|origin| origin.session == session)
+        })
+        .filter_map(|child| {
+            let child_session = child.session_id().unwrap_or_default();
+            if visited.contains(child_session) {
+                None
+            } else {
+                Some(build_node(child, conversations, visited))
+            }
+        })
+        .collect();
+
+    LineageNode {
+        session,
+        forked_at: conversation
+            .forked_from()
+            .map(|origin| origin.message_id.clone()),
+        children,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AiMessage, HumanMessage};
+
+    fn conversation_with_ids(session: &str, ids: &[&str]) -> Conversation {
+        let mut conversation = Conversation::with_session_id(session);
+        for (index, id) in ids.iter().enumerate() {
+            if index % 2 == 0 {
+                let mut message = HumanMessage::new("question");
+                message.set_id(Some(id.to_string()));
+                conversation.push(message);
+            } else {
+                let mut message = AiMessage::new("answer");
+                message.set_id(Some(id.to_string()));
+                conversation.push(message);
+            }
+        }
+        conversation
+    }
+
+    #[test]
+    fn test_lineage_tree_from_any_branch() {
+        let main = conversation_with_ids("main", &["m1", "m2", "m3", "m4"]);
+        let retry = main.fork_at("retry", "m1").unwrap();
+        let edit = main.fork_at("edit", "m3").unwrap();
+        let nested = edit.fork_at("nested", "m1").unwrap();
+        let unrelated = conversation_with_ids("other", &["x1"]);
+        let store = vec![main, retry, edit, nested, unrelated];
+
+        let tree = lineage_tree(&store, "nested").unwrap();
+
+        assert_eq!(tree.session, "main");
+        assert_eq!(tree.forked_at, None);
+        let children: Vec<(&str, Option<&str>)> = tree
+            .children
+            .iter()
+            .map(|child| (child.session.as_str(), child.forked_at.as_deref()))
+            .collect();
+        assert_eq!(children, vec![("retry", Some("m1")), ("edit", Some("m3"))]);
+        assert_eq!(tree.children[1].children[0].session, "nested");
+    }
+
+    #[test]
+    fn test_lineage_tree_missing_session() {
+        let store = vec![conversation_with_ids("main", &["m1"])];
+        assert!(lineage_tree(&store, "unknown").is_none());
+    }
+
+    #[test]
+    fn test_lineage_tree_with_missing_parent() {
+        let main = conversation_with_ids("main", &["m1", "m2"]);
+        let retry = main.fork_at("retry", "m1").unwrap();
+        let store = vec![retry];
+
+        let tree = lineage_tree(&store, "retry").unwrap();
+
+        assert_eq!(tree.session, "retry");
+        assert_eq!(tree.forked_at.as_deref(), Some("m1"));
+        assert!(tree.children.is_empty());
+    }
+
+    #[test]
+    fn test_lineage_tree_serialization() {
+        let main = conversation_with_ids("main", &["m1", "m2"]);
+        let retry = main.fork_at("retry", "m1").unwrap();
+        let store = vec![main, retry];
+
+        let tree = lineage_tree(&store, "main").unwrap();
+        let serialized = serde_json::to_string(&tree).unwrap();
+
+        assert_eq!(
+            serialized,
+            r#"{"session":"main","children":[{"session":"retry","forked_at":"m1"}]}"#
+        );
+    }
+}
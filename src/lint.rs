@@ -0,0 +1,277 @@
+use std::collections::HashSet;
+use std::fmt;
+
+use crate::{BaseMessage, Conversation, MessageEnum, MessageType};
+
+pub type LintFix = Box<dyn Fn(&mut Conversation) + Send + Sync>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LintKind {
+    OrphanToolResult,
+    EmptySystemPrompt,
+    DuplicateId,
+    HugeHistory,
+}
+
+/// A problem found by [`lint_conversation`]. `index` points at the offending
+/// message when there is one; `fix`, if present, repairs it in place.
+pub struct LintWarning {
+    pub kind: LintKind,
+    pub index: Option<usize>,
+    pub message: String,
+    fix: Option<LintFix>,
+}
+
+impl LintWarning {
+    pub fn new(kind: LintKind, index: Option<usize>, message: impl Into<String>) -> Self {
+        LintWarning {
+            kind,
+            index,
+            message: message.into(),
+            fix: None,
+        }
+    }
+
+    pub fn with_fix(mut self, fix: impl Fn(&mut Conversation) + Send + Sync + 'static) -> Self {
+        self.fix = Some(Box::new(fix));
+        self
+    }
+
+    pub fn has_fix(&self) -> bool {
+        self.fix.is_some()
+    }
+
+    /// Applies the fix, returning `false` if the warning has none.
+    pub fn apply_fix(&self, conversation: &mut Conversation) -> bool {
+        match &self.fix {
+            Some(fix) => {
+                fix(conversation);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+impl fmt::Debug for LintWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LintWarning")
+            .field("kind", &self.kind)
+            .field("index", &self.index)
+            .field("message", &self.message)
+            .field("has_fix", &self.has_fix())
+            .finish()
+    }
+}
+
+impl fmt::Display for LintWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.index {
+            Some(index) => write!(f, "message {}: {}", index, self.message),
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LintOptions {
+    pub max_messages: usize,
+}
+
+impl Default for LintOptions {
+    fn default() -> Self {
+        LintOptions { max_messages: 100 }
+    }
+}
+
+pub fn lint_conversation(conversation: &Conversation) -> Vec<LintWarning> {
+    lint_conversation_with(conversation, &LintOptions::default())
+}
+
+pub fn lint_conversation_with(
+    conversation: &Conversation,
+    options: &LintOptions,
+) -> Vec<LintWarning> {
+    let mut warnings = Vec::new();
+    let mut seen_ids = HashSet::new();
+    let mut ai_in_turn = false;
+
+    for (index, message) in conversation.iter().enumerate() {
+        match message.message_type() {
+            MessageType::Human => ai_in_turn = false,
+            MessageType::Ai => ai_in_turn = true,
+            MessageType::System if message.content().trim().is_empty() => {
+                warnings.push(
+                    LintWarning::new(
+                        LintKind::EmptySystemPrompt,
+                        Some(index),
+                        "System prompt is empty",
+                    )
+                    .with_fix(move |conversation| {
+                        conversation.messages_mut().remove(index);
+                    }),
+                );
+            }
+            // Without a preceding AI message in the same turn there is no
+            // call this result could be answering.
+            MessageType::Tool if !ai_in_turn => {
+                warnings.push(
+                    LintWarning::new(
+                        LintKind::OrphanToolResult,
+                        Some(index),
+                        "Tool result has no preceding AI message",
+                    )
+                    .with_fix(move |conversation| {
+                        conversation.messages_mut().remove(index);
+                    }),
+                );
+            }
+            _ => {}
+        }
+
+        if let Some(id) = message.id() {
+            if !seen_ids.insert(id) {
+                let renamed = format!("{}-{}", id, index);
+                warnings.push(
+                    LintWarning::new(
+                        LintKind::DuplicateId,
+                        Some(index),
+                        format!("Duplicate message id '{}'", id),
+                    )
+                    .with_fix(move |conversation| {
+                        if let Some(message) = conversation.messages_mut().get_mut(index) {
+                            message.base_mut().id = Some(renamed.clone());
+                        }
+                    }),
+                );
+            }
+        }
+    }
+
+    if conversation.len() > options.max_messages {
+        let max = options.max_messages;
+        warnings.push(
+            LintWarning::new(
+                LintKind::HugeHistory,
+                None,
+                format!(
+                    "Conversation has {} messages, more than {}; consider trimming",
+                    conversation.len(),
+                    max
+                ),
+            )
+            .with_fix(move |conversation| trim_to(conversation.messages_mut(), max)),
+        );
+    }
+
+    warnings
+}
+
+/// Applies every available fix, back to front so that index-based fixes stay
+/// valid while earlier messages are removed. Returns the number applied.
+pub fn apply_fixes(conversation: &mut Conversation, warnings: &[LintWarning]) -> usize {
+    let mut ordered: Vec<&LintWarning> = warnings.iter().filter(|w| w.has_fix()).collect();
+    // History trimming has no index and must run after the per-message fixes.
+    ordered.sort_by_key(|warning| (warning.index.is_none(), std::cmp::Reverse(warning.index)));
+
+    ordered
+        .into_iter()
+        .filter(|warning| warning.apply_fix(conversation))
+        .count()
+}
+
+// Keeps leading system messages and the most recent messages up to `max`.
+fn trim_to(messages: &mut Vec<MessageEnum>, max: usize) {
+    let system = messages
+        .iter()
+        .take_while(|message| matches!(message, MessageEnum::System(_)))
+        .count()
+        .min(max);
+    let excess = messages.len().saturating_sub(max);
+    if excess > 0 {
+        messages.drain(system..system + excess);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tool_message::ToolStatus;
+    use crate::{AiMessage, HumanMessage, SystemMessage, ToolMessage};
+
+    fn tool_result() -> ToolMessage {
+        ToolMessage::new("42", "call_1".to_string(), None, ToolStatus::Success)
+    }
+
+    fn kinds(warnings: &[LintWarning]) -> Vec<LintKind> {
+        warnings.iter().map(|warning| warning.kind).collect()
+    }
+
+    #[test]
+    fn test_clean_conversation_has_no_warnings() {
+        let mut conversation = Conversation::new();
+        conversation.push(SystemMessage::new("Be helpful."));
+        conversation.push(HumanMessage::new("What is 6 * 7?"));
+        conversation.push(AiMessage::new("Let me calculate."));
+        conversation.push(tool_result());
+
+        assert!(lint_conversation(&conversation).is_empty());
+    }
+
+    #[test]
+    fn test_orphan_tool_and_empty_system_prompt() {
+        let mut conversation = Conversation::new();
+        conversation.push(SystemMessage::new("  "));
+        conversation.push(HumanMessage::new("Hi"));
+        conversation.push(tool_result());
+
+        let warnings = lint_conversation(&conversation);
+        assert_eq!(
+            kinds(&warnings),
+            vec![LintKind::EmptySystemPrompt, LintKind::OrphanToolResult]
+        );
+
+        assert_eq!(apply_fixes(&mut conversation, &warnings), 2);
+        assert_eq!(conversation.len(), 1);
+        assert_eq!(conversation.messages()[0].content(), "Hi");
+        assert!(lint_conversation(&conversation).is_empty());
+    }
+
+    #[test]
+    fn test_duplicate_ids_are_renamed() {
+        let mut first = HumanMessage::new("a");
+        first.set_id(Some("m1".to_string()));
+        let mut second = AiMessage::new("b");
+        second.set_id(Some("m1".to_string()));
+        let mut conversation = Conversation::new();
+        conversation.push(first);
+        conversation.push(second);
+
+        let warnings = lint_conversation(&conversation);
+        assert_eq!(kinds(&warnings), vec![LintKind::DuplicateId]);
+        assert_eq!(
+            warnings[0].to_string(),
+            "message 1: Duplicate message id 'm1'"
+        );
+
+        assert!(warnings[0].apply_fix(&mut conversation));
+        assert_eq!(conversation.messages()[1].id(), Some("m1-1"));
+    }
+
+    #[test]
+    fn test_huge_history_keeps_system_prompt() {
+        let mut conversation = Conversation::new();
+        conversation.push(SystemMessage::new("Be brief."));
+        for i in 0..10 {
+            conversation.push(HumanMessage::new(&i.to_string()));
+        }
+
+        let options = LintOptions { max_messages: 4 };
+        let warnings = lint_conversation_with(&conversation, &options);
+        assert_eq!(kinds(&warnings), vec![LintKind::HugeHistory]);
+
+        apply_fixes(&mut conversation, &warnings);
+        let contents: Vec<&str> = conversation.iter().map(|m| m.content()).collect();
+        assert_eq!(contents, vec!["Be brief.", "7", "8", "9"]);
+    }
+}
@@ -0,0 +1,139 @@
+use serde::ser::{SerializeMap, SerializeSeq};
+use serde::{Serialize, Serializer};
+
+use crate::debug_dump::elide;
+use crate::safety::REDACTED;
+use crate::{Conversation, MessageEnum};
+
+/// Serializes a conversation for log sinks: content is cut to `max_len`
+/// characters and the values of masked kwargs/metadata keys are redacted.
+/// The conversation itself is left untouched.
+#[derive(Debug, Clone)]
+pub struct LoggingView<'a> {
+    conversation: &'a Conversation,
+    max_len: usize,
+    masked_keys: Vec<String>,
+}
+
+impl<'a> LoggingView<'a> {
+    pub fn new(conversation: &'a Conversation, max_len: usize) -> Self {
+        LoggingView {
+            conversation,
+            max_len,
+            masked_keys: Vec::new(),
+        }
+    }
+
+    pub fn with_masked_key(mut self, key: impl Into<String>) -> Self {
+        self.masked_keys.push(key.into());
+        self
+    }
+
+    pub fn with_masked_keys<I, S>(mut self, keys: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.masked_keys.extend(keys.into_iter().map(Into::into));
+        self
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap_or_default()
+    }
+
+    fn loggable(&self, message: &MessageEnum) -> MessageEnum {
+        let mut message = message.clone();
+        let base = message.base_mut();
+        for text in base.content.texts_mut() {
+            *text = elide(text, self.max_len);
+        }
+        for map in [&mut base.additional_kwargs, &mut base.response_metadata] {
+            for key in &self.masked_keys {
+                if let Some(value) = map.get_mut(key) {
+                    *value = REDACTED.into();
+                }
+            }
+        }
+        message
+    }
+}
+
+struct LoggedMessages<'v, 'a>(&'v LoggingView<'a>);
+
+impl Serialize for LoggedMessages<'_, '_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let view = self.0;
+        let mut seq = serializer.serialize_seq(Some(view.conversation.len()))?;
+        // One message is copied at a time, never the whole conversation.
+        for message in view.conversation {
+            seq.serialize_element(&view.loggable(message))?;
+        }
+        seq.end()
+    }
+}
+
+impl Serialize for LoggingView<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(None)?;
+        if let Some(session_id) = self.conversation.session_id() {
+            map.serialize_entry("session_id", session_id)?;
+        }
+        map.serialize_entry("messages", &LoggedMessages(self))?;
+        map.end()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AiMessage, BaseMessage, HumanMessage};
+
+    fn conversation() -> Conversation {
+        let mut question = HumanMessage::new("Please summarise this very long document for me");
+        question
+            .base
+            .additional_kwargs
+            .insert("api_key".to_string(), "sk-123".to_string());
+        question
+            .base
+            .additional_kwargs
+            .insert("locale".to_string(), "en".to_string());
+
+        let mut conversation = Conversation::with_session_id("s1");
+        conversation.push(question);
+        conversation.push(AiMessage::new("Sure."));
+        conversation
+    }
+
+    #[test]
+    fn test_logging_view_truncates_and_masks() {
+        let conversation = conversation();
+        let view = LoggingView::new(&conversation, 10).with_masked_key("api_key");
+
+        let value: serde_json::Value = serde_json::from_str(&view.to_json()).unwrap();
+
+        assert_eq!(value["session_id"], "s1");
+        assert_eq!(value["messages"][0]["content"], "Please sum… [+37 chars]");
+        assert_eq!(
+            value["messages"][0]["additional_kwargs"]["api_key"],
+            REDACTED
+        );
+        assert_eq!(value["messages"][0]["additional_kwargs"]["locale"], "en");
+        assert_eq!(value["messages"][1]["content"], "Sure.");
+    }
+
+    #[test]
+    fn test_logging_view_leaves_conversation_untouched() {
+        let conversation = conversation();
+        let _ = LoggingView::new(&conversation, 3)
+            .with_masked_keys(["api_key"])
+            .to_json();
+
+        assert_eq!(
+            conversation.messages()[0].additional_kwargs()["api_key"],
+            "sk-123"
+        );
+        assert!(conversation.messages()[0].content().ends_with("for me"));
+    }
+}
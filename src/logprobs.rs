@@ -0,0 +1,132 @@
+use serde::{Deserialize, Serialize};
+
+/// Per-token log probabilities of a generated reply. The serialized shape
+/// matches the `logprobs` object of OpenAI-compatible chat completions.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Logprobs {
+    #[serde(default)]
+    pub content: Vec<TokenLogprob>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TokenLogprob {
+    pub token: String,
+    pub logprob: f64,
+
+    /// The most likely alternatives at this position, best first.
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub top_logprobs: Vec<TopLogprob>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TopLogprob {
+    pub token: String,
+    pub logprob: f64,
+}
+
+impl TokenLogprob {
+    pub fn new(token: impl Into<String>, logprob: f64) -> Self {
+        TokenLogprob {
+            token: token.into(),
+            logprob,
+            top_logprobs: Vec::new(),
+        }
+    }
+
+    pub fn probability(&self) -> f64 {
+        self.logprob.exp()
+    }
+}
+
+impl Logprobs {
+    pub fn new(content: Vec<TokenLogprob>) -> Self {
+        Logprobs { content }
+    }
+
+    /// Reads a choice's `logprobs` object from an OpenAI-compatible
+    /// response. Extra fields such as `bytes` are ignored; returns `None`
+    /// for `null` or a malformed value.
+    pub fn from_openai(value: &serde_json::Value) -> Option<Self> {
+        serde_json::from_value(value.clone()).ok()
+    }
+
+    pub fn len(&self) -> usize {
+        self.content.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.content.is_empty()
+    }
+
+    pub fn tokens(&self) -> impl Iterator<Item = &str> {
+        self.content.iter().map(|token| token.token.as_str())
+    }
+
+    /// Log probability of the whole sequence.
+    pub fn total_logprob(&self) -> f64 {
+        self.content.iter().map(|token| token.logprob).sum()
+    }
+
+    /// `exp(-mean logprob)`; 1.0 for an empty sequence.
+    pub fn perplexity(&self) -> f64 {
+        if self.content.is_empty() {
+            return 1.0;
+        }
+        (-self.total_logprob() / self.content.len() as f64).exp()
+    }
+
+    /// The least likely token, a common hallucination signal.
+    pub fn least_likely(&self) -> Option<&TokenLogprob> {
+        self.content
+            .iter()
+            .min_by(|a, b| a.logprob.total_cmp(&b.logprob))
+    }
+
+    /// Appends the tokens of a later streamed chunk.
+    pub fn append(&mut self, other: Logprobs) {
+        self.content.extend(other.content);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AiMessage, MessageEnum};
+
+    #[test]
+    fn test_from_openai_choice() {
+        let choice = serde_json::json!({
+            "logprobs": {
+                "content": [
+                    {"token": "Hel", "logprob": -0.01, "bytes": [72, 101, 108],
+                     "top_logprobs": [{"token": "Hel", "logprob": -0.01, "bytes": null},
+                                      {"token": "Hi", "logprob": -4.6, "bytes": null}]},
+                    {"token": "lo", "logprob": -0.5, "bytes": [108, 111], "top_logprobs": []}
+                ],
+                "refusal": null
+            }
+        });
+
+        let logprobs = Logprobs::from_openai(&choice["logprobs"]).unwrap();
+
+        assert_eq!(logprobs.tokens().collect::<Vec<_>>(), vec!["Hel", "lo"]);
+        assert_eq!(logprobs.content[0].top_logprobs[1].token, "Hi");
+        assert_eq!(logprobs.least_likely().unwrap().token, "lo");
+        assert!((logprobs.total_logprob() + 0.51).abs() < 1e-9);
+        assert!(Logprobs::from_openai(&serde_json::Value::Null).is_none());
+    }
+
+    #[test]
+    fn test_logprobs_on_ai_message() {
+        let mut first = Logprobs::new(vec![TokenLogprob::new("Hi", -0.2)]);
+        first.append(Logprobs::new(vec![TokenLogprob::new("!", -0.2)]));
+        assert!((first.perplexity() - 0.2f64.exp()).abs() < 1e-9);
+
+        let message: MessageEnum = AiMessage::new("Hi!").with_logprobs(first.clone()).into();
+        let json = serde_json::to_string(&message).unwrap();
+        let parsed: MessageEnum = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(parsed.logprobs(), Some(&first));
+        assert_eq!(Logprobs::default().perplexity(), 1.0);
+    }
+}
@@ -0,0 +1,193 @@
+use std::collections::HashSet;
+
+use crate::conversation::message_fingerprint;
+use crate::metadata::parse_u64;
+use crate::trash::TrashedMessage;
+use crate::{BaseMessage, Conversation, MessageEnum};
+
+/// Which side goes first when two messages can't be ordered by timestamp.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TieBreak {
+    #[default]
+    LeftFirst,
+    RightFirst,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MergeStrategy {
+    /// `response_metadata` key holding a millisecond timestamp.
+    pub timestamp_key: String,
+    pub tie_break: TieBreak,
+    /// Drop messages whose id (or, without an id, whose content fingerprint)
+    /// already appeared earlier in the merged history.
+    pub dedupe: bool,
+}
+
+impl Default for MergeStrategy {
+    fn default() -> Self {
+        MergeStrategy {
+            timestamp_key: "timestamp".to_string(),
+            tie_break: TieBreak::LeftFirst,
+            dedupe: true,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Hash)]
+enum MessageKey {
+    Id(String),
+    Fingerprint(u64),
+}
+
+/// Reconciles two copies of a conversation that were appended to
+/// independently. Each side keeps its own relative order; between sides,
+/// messages are interleaved by timestamp and otherwise by `tie_break`.
+pub fn merge(left: &Conversation, right: &Conversation, strategy: MergeStrategy) -> Conversation {
+    let timestamp = |message: &MessageEnum| {
+        message
+            .response_metadata()
+            .get(&strategy.timestamp_key)
+            .and_then(parse_u64)
+    };
+
+    let (mut i, mut j) = (0, 0);
+    let (left_messages, right_messages) = (left.messages(), right.messages());
+    let mut ordered = Vec::with_capacity(left_messages.len() + right_messages.len());
+    while i < left_messages.len() && j < right_messages.len() {
+        let take_left = match (timestamp(&left_messages[i]), timestamp(&right_messages[j])) {
+            (Some(a), Some(b)) if a != b => a < b,
+            _ => strategy.tie_break == TieBreak::LeftFirst,
+        };
+        if take_left {
+            ordered.push(&left_messages[i]);
+            i += 1;
+        } else {
+            ordered.push(&right_messages[j]);
+            j += 1;
+        }
+    }
+    ordered.extend(&left_messages[i..]);
+    ordered.extend(&right_messages[j..]);
+
+    let mut seen = HashSet::new();
+    let messages = ordered
+        .into_iter()
+        .filter(|message| {
+            if !strategy.dedupe {
+                return true;
+            }
+            let key = match message.id() {
+                Some(id) => MessageKey::Id(id.to_string()),
+                None => MessageKey::Fingerprint(message_fingerprint(message)),
+            };
+            seen.insert(key)
+        })
+        .cloned()
+        .collect();
+
+    Conversation {
+        session_id: left.session_id.clone().or_else(|| right.session_id.clone()),
+        forked_from: left
+            .forked_from
+            .clone()
+            .or_else(|| right.forked_from.clone()),
+        messages,
+        trash: merged_trash(left, right),
+        extensions: left.extensions.clone(),
+    }
+}
+
+// A message trashed on both sides is kept once, as the left side saw it.
+fn merged_trash(left: &Conversation, right: &Conversation) -> Vec<TrashedMessage<MessageEnum>> {
+    let mut trash = left.trash.clone();
+    for trashed in &right.trash {
+        let duplicate = trashed.message.id().is_some_and(|id| {
+            left.trash
+                .iter()
+                .any(|other| other.message.id() == Some(id))
+        });
+        if !duplicate {
+            trash.push(trashed.clone());
+        }
+    }
+    trash
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AiMessage, HumanMessage};
+
+    fn stamped(message: impl Into<MessageEnum>, id: &str, at: u64) -> MessageEnum {
+        let mut message = message.into();
+        let base = message.base_mut();
+        base.id = Some(id.to_string());
+        base.response_metadata
+            .insert("timestamp".to_string(), at.to_string());
+        message
+    }
+
+    fn contents(conversation: &Conversation) -> Vec<&str> {
+        conversation
+            .iter()
+            .map(|message| message.content())
+            .collect()
+    }
+
+    #[test]
+    fn test_merge_interleaves_by_timestamp_and_dedupes_ids() {
+        let shared = stamped(HumanMessage::new("Hi"), "m1", 10);
+        let phone: Conversation = vec![
+            shared.clone(),
+            stamped(HumanMessage::new("from phone"), "p1", 30),
+        ]
+        .into_iter()
+        .collect();
+        let laptop: Conversation = vec![
+            shared,
+            stamped(HumanMessage::new("from laptop"), "l1", 20),
+            stamped(AiMessage::new("reply"), "l2", 40),
+        ]
+        .into_iter()
+        .collect();
+
+        let merged = merge(&phone, &laptop, MergeStrategy::default());
+
+        assert_eq!(
+            contents(&merged),
+            vec!["Hi", "from laptop", "from phone", "reply"]
+        );
+    }
+
+    #[test]
+    fn test_tie_break_without_timestamps() {
+        let left: Conversation = vec![HumanMessage::new("a")].into_iter().collect();
+        let right: Conversation = vec![HumanMessage::new("b")].into_iter().collect();
+
+        let merged = merge(&left, &right, MergeStrategy::default());
+        assert_eq!(contents(&merged), vec!["a", "b"]);
+
+        let strategy = MergeStrategy {
+            tie_break: TieBreak::RightFirst,
+            ..MergeStrategy::default()
+        };
+        assert_eq!(contents(&merge(&left, &right, strategy)), vec!["b", "a"]);
+    }
+
+    #[test]
+    fn test_dedupe_by_fingerprint_can_be_disabled() {
+        let mut left = Conversation::with_session_id("s1");
+        left.push(HumanMessage::new("same"));
+        let right: Conversation = vec![HumanMessage::new("same")].into_iter().collect();
+
+        let merged = merge(&left, &right, MergeStrategy::default());
+        assert_eq!(merged.len(), 1);
+        assert_eq!(merged.session_id(), Some("s1"));
+
+        let strategy = MergeStrategy {
+            dedupe: false,
+            ..MergeStrategy::default()
+        };
+        assert_eq!(merge(&left, &right, strategy).len(), 2);
+    }
+}
@@ -0,0 +1,296 @@
+use std::ops::{Add, AddAssign};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{
+    AiMessage, InvalidToolCall, Logprobs, MessageContent, Metadata, ToolCall, UsageMetadata,
+};
+
+/// A partial message from a stream. Chunks concatenate with `+`/`+=` and
+/// the concatenation of a whole stream finalizes into a full message.
+pub trait MessageChunk: Sized + AddAssign {
+    type Message;
+
+    fn into_message(self) -> Self::Message;
+
+    /// Concatenates every chunk in order; `None` for an empty stream.
+    fn concat(chunks: impl IntoIterator<Item = Self>) -> Option<Self> {
+        chunks.into_iter().reduce(|mut joined, chunk| {
+            joined += chunk;
+            joined
+        })
+    }
+}
+
+/// A fragment of a streamed tool call. Providers send the id and name once
+/// and the JSON arguments in pieces; fragments with the same `index` belong
+/// to the same call.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ToolCallChunk {
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub index: Option<usize>,
+
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub id: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub name: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub args: Option<String>,
+}
+
+impl ToolCallChunk {
+    pub fn new(index: usize) -> Self {
+        ToolCallChunk {
+            index: Some(index),
+            ..Self::default()
+        }
+    }
+
+    pub fn with_id(mut self, id: impl Into<String>) -> Self {
+        self.id = Some(id.into());
+        self
+    }
+
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    pub fn with_args(mut self, args: impl Into<String>) -> Self {
+        self.args = Some(args.into());
+        self
+    }
+
+    fn merge(&mut self, other: ToolCallChunk) {
+        if self.id.is_none() {
+            self.id = other.id;
+        }
+        if self.name.is_none() {
+            self.name = other.name;
+        }
+        if let Some(more) = other.args {
+            self.args.get_or_insert_with(String::new).push_str(&more);
+        }
+    }
+
+    /// A [`ToolCall`] when the id, name and arguments are complete.
+    fn finish(self) -> Result<ToolCall, InvalidToolCall> {
+        let args = self.args.as_deref().unwrap_or("");
+        let parsed = if args.trim().is_empty() {
+            Ok(Value::Object(Default::default()))
+        } else {
+            serde_json::from_str(args)
+        };
+        match (self.id, self.name, parsed) {
+            (Some(id), Some(name), Ok(args)) => Ok(ToolCall::new(id, name, args)),
+            (id, name, parsed) => Err(InvalidToolCall {
+                id,
+                name,
+                args: self.args,
+                error: Some(match parsed {
+                    Err(err) => err.to_string(),
+                    Ok(_) => "tool call without id or name".to_string(),
+                }),
+            }),
+        }
+    }
+}
+
+/// A streamed piece of an [`AiMessage`]. Concatenation joins content and
+/// tool-call argument fragments; later `response_metadata` values replace
+/// earlier ones, so the final chunk's finish reason wins.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AiMessageChunk {
+    #[serde(default)]
+    pub content: MessageContent,
+
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub id: Option<String>,
+
+    #[serde(skip_serializing_if = "Metadata::is_empty", default)]
+    pub response_metadata: Metadata,
+
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub tool_call_chunks: Vec<ToolCallChunk>,
+
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub logprobs: Option<Logprobs>,
+
+    /// Providers report usage on one chunk, usually the last; partial
+    /// reports are summed.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub usage_metadata: Option<UsageMetadata>,
+}
+
+impl AiMessageChunk {
+    pub fn new(content: impl Into<MessageContent>) -> Self {
+        AiMessageChunk {
+            content: content.into(),
+            ..Self::default()
+        }
+    }
+
+    pub fn with_tool_call_chunk(mut self, chunk: ToolCallChunk) -> Self {
+        self.tool_call_chunks.push(chunk);
+        self
+    }
+
+    pub fn with_response_metadata(
+        mut self,
+        key: impl Into<String>,
+        value: impl Into<Value>,
+    ) -> Self {
+        self.response_metadata.insert(key, value);
+        self
+    }
+}
+
+impl AddAssign for AiMessageChunk {
+    fn add_assign(&mut self, other: AiMessageChunk) {
+        self.content.append(other.content);
+        if self.id.is_none() {
+            self.id = other.id;
+        }
+        self.response_metadata.extend(other.response_metadata);
+        for chunk in other.tool_call_chunks {
+            let existing = chunk.index.and_then(|index| {
+                self.tool_call_chunks
+                    .iter_mut()
+                    .find(|existing| existing.index == Some(index))
+            });
+            match existing {
+                Some(existing) => existing.merge(chunk),
+                None => self.tool_call_chunks.push(chunk),
+            }
+        }
+        match (&mut self.logprobs, other.logprobs) {
+            (Some(logprobs), Some(more)) => logprobs.append(more),
+            (logprobs @ None, more) => *logprobs = more,
+            (Some(_), None) => {}
+        }
+        match (&mut self.usage_metadata, other.usage_metadata) {
+            (Some(usage), Some(more)) => *usage += more,
+            (usage @ None, more) => *usage = more,
+            (Some(_), None) => {}
+        }
+    }
+}
+
+impl Add for AiMessageChunk {
+    type Output = AiMessageChunk;
+
+    fn add(mut self, other: AiMessageChunk) -> AiMessageChunk {
+        self += other;
+        self
+    }
+}
+
+impl MessageChunk for AiMessageChunk {
+    type Message = AiMessage;
+
+    /// Parses each tool call's joined arguments; calls that are incomplete or
+    /// not valid JSON become [`InvalidToolCall`]s.
+    fn into_message(self) -> AiMessage {
+        let mut tool_calls = Vec::new();
+        let mut invalid_tool_calls = Vec::new();
+        for chunk in self.tool_call_chunks {
+            match chunk.finish() {
+                Ok(call) => tool_calls.push(call),
+                Err(invalid) => invalid_tool_calls.push(invalid),
+            }
+        }
+        let mut message = AiMessage::new("")
+            .with_content(self.content)
+            .with_tool_calls(tool_calls)
+            .with_invalid_tool_calls(invalid_tool_calls);
+        message.base.id = self.id;
+        message.base.response_metadata = self.response_metadata;
+        message.logprobs = self.logprobs;
+        message.usage_metadata = self.usage_metadata;
+        message
+    }
+}
+
+impl From<AiMessageChunk> for AiMessage {
+    fn from(chunk: AiMessageChunk) -> Self {
+        chunk.into_message()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BaseMessage, ContentBlock, TokenLogprob};
+    use serde_json::json;
+
+    #[test]
+    fn test_concatenate_stream() {
+        let stream = vec![
+            AiMessageChunk::new("Let me ")
+                .with_response_metadata("model", "gpt-4o")
+                .with_tool_call_chunk(ToolCallChunk::new(0).with_id("call_1").with_name("weather")),
+            AiMessageChunk::new("check.")
+                .with_tool_call_chunk(ToolCallChunk::new(0).with_args("{\"city\":"))
+                .with_tool_call_chunk(ToolCallChunk::new(1).with_id("call_2").with_name("time")),
+            AiMessageChunk::new("")
+                .with_tool_call_chunk(ToolCallChunk::new(0).with_args(" \"Paris\"}"))
+                .with_tool_call_chunk(ToolCallChunk::new(1).with_args("{oops"))
+                .with_response_metadata("finish_reason", "tool_calls"),
+        ];
+
+        let message = AiMessageChunk::concat(stream).unwrap().into_message();
+
+        assert_eq!(message.content(), "Let me check.");
+        assert_eq!(
+            message.tool_calls(),
+            &[ToolCall::new("call_1", "weather", json!({"city": "Paris"}))]
+        );
+        assert_eq!(
+            message.invalid_tool_calls()[0].args.as_deref(),
+            Some("{oops")
+        );
+        assert_eq!(message.response_metadata()["model"], "gpt-4o");
+        assert_eq!(message.response_metadata()["finish_reason"], "tool_calls");
+        assert!(AiMessageChunk::concat(Vec::new()).is_none());
+    }
+
+    #[test]
+    fn test_add_merges_blocks_and_logprobs() {
+        let mut first = AiMessageChunk::new("A picture: ");
+        first.logprobs = Some(Logprobs::new(vec![TokenLogprob::new("A", -0.1)]));
+        let mut second = AiMessageChunk::new(vec![
+            ContentBlock::text("here"),
+            ContentBlock::image_url("https://example.com/a.png"),
+        ]);
+        second.logprobs = Some(Logprobs::new(vec![TokenLogprob::new("here", -0.2)]));
+
+        let message: AiMessage = (first + second).into();
+
+        assert_eq!(
+            message.base.content,
+            MessageContent::Blocks(vec![
+                ContentBlock::text("A picture: here"),
+                ContentBlock::image_url("https://example.com/a.png"),
+            ])
+        );
+        assert_eq!(message.logprobs().unwrap().content.len(), 2);
+    }
+
+    #[test]
+    fn test_add_sums_usage_metadata() {
+        let mut first = AiMessageChunk::new("Hi");
+        first.usage_metadata = Some(UsageMetadata::new(10, 1));
+        let mut last = AiMessageChunk::new("!");
+        last.usage_metadata = Some(UsageMetadata::new(0, 1).with_output_detail("reasoning", 4));
+
+        let message: AiMessage = (first + AiMessageChunk::new(" there") + last).into();
+
+        let usage = message.usage_metadata().unwrap();
+        assert_eq!((usage.input_tokens, usage.output_tokens), (10, 2));
+        assert_eq!(usage.total_tokens, 12);
+        assert_eq!(usage.output_token_details["reasoning"], 4);
+    }
+}
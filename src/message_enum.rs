@@ -1,20 +1,28 @@
+use std::borrow::Cow;
 use std::collections::HashMap;
 use std::fmt;
 
 use crate::tool_message::ToolStatus;
+use crate::unknown_message::UnknownMessage;
 use crate::{
-    AiMessage, BaseMessageFields, HumanMessage, InvalidMessageTypeError, SystemMessage, ToolMessage,
+    AiMessage, BaseMessageFields, ChatMessage, HumanMessage, InvalidMessageTypeError,
+    RemoveMessage, SystemMessage, ToolMessage,
 };
-use crate::{BaseMessage, MessageType};
-use serde::{Deserialize, Deserializer, Serialize};
+use crate::{
+    BaseMessage, ContentBlock, Extensions, InvalidToolCall, Logprobs, MessageContent, MessageType,
+    Metadata, Provenance, ToolCall, UsageMetadata, VoiceMetadata,
+};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
-#[derive(Serialize, Clone, PartialEq)]
-#[serde(tag = "role", rename_all = "lowercase")]
+#[derive(Clone, PartialEq)]
 pub enum MessageEnum {
     Ai(AiMessage),
     Human(HumanMessage),
     System(SystemMessage),
     Tool(ToolMessage),
+    Chat(ChatMessage),
+    Remove(RemoveMessage),
+    Unknown(UnknownMessage),
 }
 
 impl MessageEnum {
@@ -34,6 +42,14 @@ impl MessageEnum {
         }
     }
 
+    pub fn as_ai_mut(&mut self) -> Option<&mut AiMessage> {
+        if let MessageEnum::Ai(ref mut message) = self {
+            Some(message)
+        } else {
+            None
+        }
+    }
+
     pub fn as_system(&self) -> Option<&SystemMessage> {
         if let MessageEnum::System(ref message) = self {
             Some(message)
@@ -50,6 +66,71 @@ impl MessageEnum {
         }
     }
 
+    pub fn base(&self) -> &BaseMessageFields {
+        match self {
+            MessageEnum::Ai(message) => &message.base,
+            MessageEnum::Human(message) => &message.base,
+            MessageEnum::System(message) => &message.base,
+            MessageEnum::Tool(message) => &message.base,
+            MessageEnum::Chat(message) => &message.base,
+            MessageEnum::Remove(message) => &message.base,
+            MessageEnum::Unknown(message) => &message.base,
+        }
+    }
+
+    pub fn base_mut(&mut self) -> &mut BaseMessageFields {
+        match self {
+            MessageEnum::Ai(message) => &mut message.base,
+            MessageEnum::Human(message) => &mut message.base,
+            MessageEnum::System(message) => &mut message.base,
+            MessageEnum::Tool(message) => &mut message.base,
+            MessageEnum::Chat(message) => &mut message.base,
+            MessageEnum::Remove(message) => &mut message.base,
+            MessageEnum::Unknown(message) => &mut message.base,
+        }
+    }
+
+    pub fn set_content(&mut self, new_content: &str) {
+        self.base_mut().content = new_content.into();
+    }
+
+    pub fn extensions(&self) -> &Extensions {
+        &self.base().extensions
+    }
+
+    pub fn extensions_mut(&mut self) -> &mut Extensions {
+        &mut self.base_mut().extensions
+    }
+
+    pub fn is_pinned(&self) -> bool {
+        self.base().pinned
+    }
+
+    pub fn set_pinned(&mut self, pinned: bool) {
+        self.base_mut().pinned = pinned;
+    }
+
+    pub fn voice(&self) -> Option<&VoiceMetadata> {
+        self.base().voice.as_ref()
+    }
+
+    /// The message type's name; for an unknown type, the name it was
+    /// written with.
+    pub fn type_name(&self) -> &str {
+        match self {
+            MessageEnum::Unknown(message) => message.type_name(),
+            message => message.message_type().as_str(),
+        }
+    }
+
+    pub fn logprobs(&self) -> Option<&Logprobs> {
+        self.as_ai().and_then(AiMessage::logprobs)
+    }
+
+    pub fn usage_metadata(&self) -> Option<&UsageMetadata> {
+        self.as_ai().and_then(AiMessage::usage_metadata)
+    }
+
     pub fn human_from(input: &str) -> Result<HumanMessage, InvalidMessageTypeError> {
         match MessageEnum::try_from(input)? {
             MessageEnum::Human(human_message) => Ok(human_message),
@@ -125,6 +206,9 @@ impl BaseMessage for MessageEnum {
             MessageEnum::Human(message) => message.content(),
             MessageEnum::System(message) => message.content(),
             MessageEnum::Tool(message) => message.content(),
+            MessageEnum::Chat(message) => message.content(),
+            MessageEnum::Remove(message) => message.content(),
+            MessageEnum::Unknown(message) => message.content(),
         }
     }
 
@@ -134,6 +218,9 @@ impl BaseMessage for MessageEnum {
             MessageEnum::Human(message) => message.message_type(),
             MessageEnum::System(message) => message.message_type(),
             MessageEnum::Tool(message) => message.message_type(),
+            MessageEnum::Chat(message) => message.message_type(),
+            MessageEnum::Remove(message) => message.message_type(),
+            MessageEnum::Unknown(message) => message.message_type(),
         }
     }
 
@@ -143,6 +230,9 @@ impl BaseMessage for MessageEnum {
             MessageEnum::Human(_) => "human",
             MessageEnum::System(_) => "system",
             MessageEnum::Tool(_) => "tool",
+            MessageEnum::Chat(message) => message.role(),
+            MessageEnum::Remove(message) => message.role(),
+            MessageEnum::Unknown(message) => message.role(),
         }
     }
 
@@ -152,6 +242,9 @@ impl BaseMessage for MessageEnum {
             MessageEnum::Human(message) => message.name(),
             MessageEnum::System(message) => message.name(),
             MessageEnum::Tool(message) => message.name(),
+            MessageEnum::Chat(message) => message.name(),
+            MessageEnum::Remove(message) => message.name(),
+            MessageEnum::Unknown(message) => message.name(),
         }
     }
 
@@ -161,24 +254,33 @@ impl BaseMessage for MessageEnum {
             MessageEnum::Human(message) => message.is_example(),
             MessageEnum::System(message) => message.is_example(),
             MessageEnum::Tool(message) => message.is_example(),
+            MessageEnum::Chat(message) => message.is_example(),
+            MessageEnum::Remove(message) => message.is_example(),
+            MessageEnum::Unknown(message) => message.is_example(),
         }
     }
 
-    fn additional_kwargs(&self) -> &HashMap<String, String> {
+    fn additional_kwargs(&self) -> &Metadata {
         match self {
             MessageEnum::Ai(message) => message.additional_kwargs(),
             MessageEnum::Human(message) => message.additional_kwargs(),
             MessageEnum::System(message) => message.additional_kwargs(),
             MessageEnum::Tool(message) => message.additional_kwargs(),
+            MessageEnum::Chat(message) => message.additional_kwargs(),
+            MessageEnum::Remove(message) => message.additional_kwargs(),
+            MessageEnum::Unknown(message) => message.additional_kwargs(),
         }
     }
 
-    fn response_metadata(&self) -> &HashMap<String, String> {
+    fn response_metadata(&self) -> &Metadata {
         match self {
             MessageEnum::Ai(message) => message.response_metadata(),
             MessageEnum::Human(message) => message.response_metadata(),
             MessageEnum::System(message) => message.response_metadata(),
             MessageEnum::Tool(message) => message.response_metadata(),
+            MessageEnum::Chat(message) => message.response_metadata(),
+            MessageEnum::Remove(message) => message.response_metadata(),
+            MessageEnum::Unknown(message) => message.response_metadata(),
         }
     }
 
@@ -188,8 +290,43 @@ impl BaseMessage for MessageEnum {
             MessageEnum::Human(message) => message.id(),
             MessageEnum::System(message) => message.id(),
             MessageEnum::Tool(message) => message.id(),
+            MessageEnum::Chat(message) => message.id(),
+            MessageEnum::Remove(message) => message.id(),
+            MessageEnum::Unknown(message) => message.id(),
         }
     }
+
+    fn tool_calls(&self) -> &[ToolCall] {
+        match self {
+            MessageEnum::Ai(message) => message.tool_calls(),
+            MessageEnum::Human(message) => message.tool_calls(),
+            MessageEnum::System(message) => message.tool_calls(),
+            MessageEnum::Tool(message) => message.tool_calls(),
+            MessageEnum::Chat(message) => message.tool_calls(),
+            MessageEnum::Remove(message) => message.tool_calls(),
+            MessageEnum::Unknown(message) => message.tool_calls(),
+        }
+    }
+
+    fn invalid_tool_calls(&self) -> &[InvalidToolCall] {
+        match self {
+            MessageEnum::Ai(message) => message.invalid_tool_calls(),
+            MessageEnum::Human(message) => message.invalid_tool_calls(),
+            MessageEnum::System(message) => message.invalid_tool_calls(),
+            MessageEnum::Tool(message) => message.invalid_tool_calls(),
+            MessageEnum::Chat(message) => message.invalid_tool_calls(),
+            MessageEnum::Remove(message) => message.invalid_tool_calls(),
+            MessageEnum::Unknown(message) => message.invalid_tool_calls(),
+        }
+    }
+
+    fn content_blocks(&self) -> Cow<'_, [ContentBlock]> {
+        self.base().content.blocks()
+    }
+
+    fn text(&self) -> Cow<'_, str> {
+        self.base().content.text()
+    }
 }
 
 impl fmt::Debug for MessageEnum {
@@ -199,6 +336,9 @@ impl fmt::Debug for MessageEnum {
             MessageEnum::Human(message) => write!(f, "HumanMessage({:?})", message),
             MessageEnum::System(message) => write!(f, "SystemMessage({:?})", message),
             MessageEnum::Tool(message) => write!(f, "ToolMessage({:?})", message),
+            MessageEnum::Chat(message) => write!(f, "ChatMessage({:?})", message),
+            MessageEnum::Remove(message) => write!(f, "RemoveMessage({:?})", message),
+            MessageEnum::Unknown(message) => write!(f, "UnknownMessage({:?})", message),
         }
     }
 }
@@ -227,6 +367,50 @@ impl From<ToolMessage> for MessageEnum {
     }
 }
 
+impl From<ChatMessage> for MessageEnum {
+    fn from(message: ChatMessage) -> Self {
+        MessageEnum::Chat(message)
+    }
+}
+
+impl From<RemoveMessage> for MessageEnum {
+    fn from(message: RemoveMessage) -> Self {
+        MessageEnum::Remove(message)
+    }
+}
+
+impl From<UnknownMessage> for MessageEnum {
+    fn from(message: UnknownMessage) -> Self {
+        MessageEnum::Unknown(message)
+    }
+}
+
+impl Serialize for MessageEnum {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        #[derive(Serialize)]
+        struct Tagged<'a, T> {
+            role: &'a str,
+            #[serde(flatten)]
+            message: &'a T,
+        }
+
+        let role = self.role();
+        match self {
+            MessageEnum::Ai(message) => Tagged { role, message }.serialize(serializer),
+            MessageEnum::Human(message) => Tagged { role, message }.serialize(serializer),
+            MessageEnum::System(message) => Tagged { role, message }.serialize(serializer),
+            MessageEnum::Tool(message) => Tagged { role, message }.serialize(serializer),
+            // A chat message already carries its own `role`.
+            MessageEnum::Chat(message) => message.serialize(serializer),
+            MessageEnum::Remove(message) => Tagged { role, message }.serialize(serializer),
+            MessageEnum::Unknown(message) => Tagged { role, message }.serialize(serializer),
+        }
+    }
+}
+
 impl<'de> Deserialize<'de> for MessageEnum {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
@@ -235,17 +419,31 @@ impl<'de> Deserialize<'de> for MessageEnum {
         #[derive(Deserialize)]
         struct TempMessage {
             role: String,
-            content: String,
+            content: MessageContent,
             #[serde(default)]
             example: bool,
             #[serde(default)]
-            additional_kwargs: HashMap<String, String>,
+            additional_kwargs: Metadata,
             #[serde(default)]
-            response_metadata: HashMap<String, String>,
+            response_metadata: Metadata,
             #[serde(default)]
             id: Option<String>,
             #[serde(default)]
             name: Option<String>,
+            #[serde(default)]
+            provenance: Option<Provenance>,
+            #[serde(default)]
+            voice: Option<VoiceMetadata>,
+            #[serde(default)]
+            logprobs: Option<Logprobs>,
+            #[serde(default)]
+            usage_metadata: Option<UsageMetadata>,
+            #[serde(default)]
+            tool_calls: Vec<ToolCall>,
+            #[serde(default)]
+            invalid_tool_calls: Vec<InvalidToolCall>,
+            #[serde(default)]
+            pinned: bool,
 
             // ToolMessage specific fields
             #[serde(default)]
@@ -254,24 +452,39 @@ impl<'de> Deserialize<'de> for MessageEnum {
             artifact: Option<String>,
             #[serde(default)]
             status: Option<ToolStatus>,
+
+            #[serde(flatten)]
+            extra: HashMap<String, serde_json::Value>,
         }
 
         let temp = TempMessage::deserialize(deserializer)?;
-        let message_type =
-            MessageType::try_from(temp.role.as_str()).map_err(serde::de::Error::custom)?;
-
-        let base = BaseMessageFields {
-            content: temp.content,
-            example: temp.example,
-            additional_kwargs: temp.additional_kwargs,
-            response_metadata: temp.response_metadata,
-            id: temp.id,
-            name: temp.name,
-            message_type,
+        // A chat message's role is free-form, so its `message_type` tells it
+        // apart from an unknown role.
+        let is_chat = temp.extra.get("message_type") == Some(&serde_json::json!("Chat"));
+        let message_type = if is_chat {
+            MessageType::Chat
+        } else {
+            MessageType::from_name(&temp.role)
         };
 
+        let mut base = BaseMessageFields::new(temp.content, message_type);
+        base.example = temp.example;
+        base.additional_kwargs = temp.additional_kwargs;
+        base.response_metadata = temp.response_metadata;
+        base.id = temp.id;
+        base.name = temp.name;
+        base.provenance = temp.provenance;
+        base.voice = temp.voice;
+        base.pinned = temp.pinned;
+
         match message_type {
-            MessageType::Ai => Ok(MessageEnum::Ai(AiMessage { base })),
+            MessageType::Ai => Ok(MessageEnum::Ai(AiMessage {
+                logprobs: temp.logprobs,
+                usage_metadata: temp.usage_metadata,
+                tool_calls: temp.tool_calls,
+                invalid_tool_calls: temp.invalid_tool_calls,
+                base,
+            })),
             MessageType::Human => Ok(MessageEnum::Human(HumanMessage { base })),
             MessageType::System => Ok(MessageEnum::System(SystemMessage { base })),
             MessageType::Tool => {
@@ -288,7 +501,42 @@ impl<'de> Deserialize<'de> for MessageEnum {
                     base,
                 )))
             }
-            _ => Err(serde::de::Error::custom("Unsupported message type")),
+            MessageType::Chat => {
+                let mut chat = ChatMessage::new("", temp.role);
+                chat.base = base;
+                Ok(MessageEnum::Chat(chat))
+            }
+            MessageType::Remove => Ok(MessageEnum::Remove(RemoveMessage { base })),
+            MessageType::Unknown => {
+                // Keep fields we do not understand so they survive a round trip
+                // through an older reader.
+                for (key, value) in temp.extra {
+                    if key == "message_type" {
+                        continue;
+                    }
+                    base.additional_kwargs.entry(key).or_insert(value);
+                }
+                Ok(MessageEnum::Unknown(UnknownMessage {
+                    type_name: temp.role,
+                    base,
+                }))
+            }
+        }
+    }
+}
+
+impl MessageEnum {
+    /// Strict deserializer for `#[serde(deserialize_with = "...")]` that
+    /// rejects message types this version does not know about.
+    pub fn deserialize_strict<'de, D>(deserializer: D) -> Result<MessageEnum, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        match MessageEnum::deserialize(deserializer)? {
+            MessageEnum::Unknown(message) => Err(serde::de::Error::custom(
+                InvalidMessageTypeError::new(format!("Invalid message type: {}", message.role())),
+            )),
+            message => Ok(message),
         }
     }
 }
@@ -330,17 +578,7 @@ mod tests {
 
     #[test]
     fn test_message_enum_serialization_ai_message() {
-        let ai_message = AiMessage {
-            base: BaseMessageFields {
-                content: "Hello from AI.".to_string(),
-                example: false,
-                message_type: MessageType::Ai,
-                additional_kwargs: HashMap::new(),
-                response_metadata: HashMap::new(),
-                id: None,
-                name: None,
-            },
-        };
+        let ai_message = AiMessage::new("Hello from AI.");
 
         let message_enum = MessageEnum::Ai(ai_message);
 
@@ -359,15 +597,7 @@ mod tests {
     #[test]
     fn test_message_enum_serialization_human_message() {
         let human_message = HumanMessage {
-            base: BaseMessageFields {
-                content: "Hello from Human.".to_string(),
-                example: false,
-                message_type: MessageType::Human,
-                additional_kwargs: HashMap::new(),
-                response_metadata: HashMap::new(),
-                id: None,
-                name: None,
-            },
+            base: BaseMessageFields::new("Hello from Human.", MessageType::Human),
         };
 
         let message_enum = MessageEnum::Human(human_message);
@@ -387,15 +617,7 @@ mod tests {
     #[test]
     fn test_message_enum_serialization_system_message() {
         let system_message = SystemMessage {
-            base: BaseMessageFields {
-                content: "This is a system message.".to_string(),
-                example: false,
-                message_type: MessageType::System,
-                additional_kwargs: HashMap::new(),
-                response_metadata: HashMap::new(),
-                id: None,
-                name: None,
-            },
+            base: BaseMessageFields::new("This is a system message.", MessageType::System),
         };
 
         let message_enum = MessageEnum::System(system_message);
@@ -414,15 +636,7 @@ mod tests {
 
     #[test]
     fn test_message_enum_serialization_tool_message() {
-        let base = BaseMessageFields {
-            content: "Tool message content".to_string(),
-            example: false,
-            message_type: MessageType::Tool,
-            additional_kwargs: HashMap::new(),
-            response_metadata: HashMap::new(),
-            id: None,
-            name: None,
-        };
+        let base = BaseMessageFields::new("Tool message content", MessageType::Tool);
 
         let tool_message = ToolMessage::new_with_base(
             "tool_call_001".to_string(),
@@ -576,7 +790,7 @@ mod tests {
         let message_enum = MessageEnum::System(system_message);
 
         let debug_output = format!("{:?}", message_enum);
-        let expected_debug_output = r#"SystemMessage(SystemMessage { base: BaseMessageFields { content: "System message.", example: false, message_type: System, additional_kwargs: {}, response_metadata: {}, id: None, name: None } })"#;
+        let expected_debug_output = r#"SystemMessage(SystemMessage { base: BaseMessageFields { content: Text("System message."), example: false, message_type: System, additional_kwargs: {}, response_metadata: {}, id: None, name: None, provenance: None, voice: None, pinned: false, extensions: Extensions { .. } } })"#;
         assert_eq!(debug_output, expected_debug_output);
     }
 
@@ -767,17 +981,7 @@ mod tests {
 
     #[test]
     fn test_message_enum_serialization_with_message_type() {
-        let ai_message = AiMessage {
-            base: BaseMessageFields {
-                content: "Hello from AI.".to_string(),
-                example: false,
-                additional_kwargs: HashMap::new(),
-                response_metadata: HashMap::new(),
-                id: None,
-                name: None,
-                message_type: MessageType::Ai,
-            },
-        };
+        let ai_message = AiMessage::new("Hello from AI.");
 
         let message_enum = MessageEnum::Ai(ai_message);
 
@@ -796,15 +1000,7 @@ mod tests {
     #[test]
     fn test_as_human() {
         let human_message = HumanMessage {
-            base: BaseMessageFields {
-                content: "Hello from Human.".to_string(),
-                example: false,
-                message_type: MessageType::Human,
-                additional_kwargs: HashMap::new(),
-                response_metadata: HashMap::new(),
-                id: None,
-                name: None,
-            },
+            base: BaseMessageFields::new("Hello from Human.", MessageType::Human),
         };
 
         let message_enum = MessageEnum::Human(human_message.clone());
@@ -819,17 +1015,7 @@ mod tests {
 
     #[test]
     fn test_as_ai() {
-        let ai_message = AiMessage {
-            base: BaseMessageFields {
-                content: "Hello from AI.".to_string(),
-                example: false,
-                message_type: MessageType::Ai,
-                additional_kwargs: HashMap::new(),
-                response_metadata: HashMap::new(),
-                id: None,
-                name: None,
-            },
-        };
+        let ai_message = AiMessage::new("Hello from AI.");
 
         let message_enum = MessageEnum::Ai(ai_message.clone());
 
@@ -844,15 +1030,7 @@ mod tests {
     #[test]
     fn test_as_system() {
         let system_message = SystemMessage {
-            base: BaseMessageFields {
-                content: "This is a system message.".to_string(),
-                example: false,
-                message_type: MessageType::System,
-                additional_kwargs: HashMap::new(),
-                response_metadata: HashMap::new(),
-                id: None,
-                name: None,
-            },
+            base: BaseMessageFields::new("This is a system message.", MessageType::System),
         };
 
         let message_enum = MessageEnum::System(system_message.clone());
@@ -898,40 +1076,14 @@ mod tests {
     #[test]
     fn test_mixed_message_enum() {
         let human_message = HumanMessage {
-            base: BaseMessageFields {
-                content: "Hello from Human.".to_string(),
-                example: false,
-                message_type: MessageType::Human,
-                additional_kwargs: HashMap::new(),
-                response_metadata: HashMap::new(),
-                id: None,
-                name: None,
-            },
+            base: BaseMessageFields::new("Hello from Human.", MessageType::Human),
         };
 
         let system_message = SystemMessage {
-            base: BaseMessageFields {
-                content: "System message.".to_string(),
-                example: false,
-                message_type: MessageType::System,
-                additional_kwargs: HashMap::new(),
-                response_metadata: HashMap::new(),
-                id: None,
-                name: None,
-            },
+            base: BaseMessageFields::new("System message.", MessageType::System),
         };
 
-        let ai_message = AiMessage {
-            base: BaseMessageFields {
-                content: "Hello from AI.".to_string(),
-                example: false,
-                message_type: MessageType::Ai,
-                additional_kwargs: HashMap::new(),
-                response_metadata: HashMap::new(),
-                id: None,
-                name: None,
-            },
-        };
+        let ai_message = AiMessage::new("Hello from AI.");
 
         let tool_message = ToolMessage::new(
             "Tool message content",
@@ -1254,4 +1406,77 @@ mod tests {
 
         assert_eq!(messages.len(), 0);
     }
+
+    #[test]
+    fn test_deserialize_unknown_role_preserves_payload() {
+        let json_data = json!({
+            "role": "critic",
+            "content": "Needs more detail.",
+            "message_type": "Critic",
+            "id": "c1",
+            "severity": "high",
+            "scores": [1, 2]
+        });
+
+        let message: MessageEnum = serde_json::from_value(json_data).unwrap();
+
+        assert_eq!(message.role(), "critic");
+        assert_eq!(message.message_type(), &MessageType::Unknown);
+        assert_eq!(message.type_name(), "critic");
+        assert_eq!(message.content(), "Needs more detail.");
+        assert_eq!(message.id(), Some("c1"));
+        assert_eq!(message.additional_kwargs()["severity"], "high");
+        assert_eq!(message.additional_kwargs()["scores"], json!([1, 2]));
+    }
+
+    #[test]
+    fn test_unknown_message_round_trip() {
+        let message = MessageEnum::Unknown(UnknownMessage::new("critic", "Looks good."));
+
+        let serialized = serde_json::to_value(&message).unwrap();
+        assert_eq!(
+            serialized,
+            json!({
+                "role": "critic",
+                "content": "Looks good.",
+                "example": false,
+                "message_type": "critic"
+            })
+        );
+
+        let deserialized: MessageEnum = serde_json::from_value(serialized).unwrap();
+        assert_eq!(deserialized, message);
+    }
+
+    #[test]
+    fn test_deserialize_strict_rejects_unknown_role() {
+        #[derive(Deserialize)]
+        struct Envelope {
+            #[serde(deserialize_with = "MessageEnum::deserialize_strict")]
+            message: MessageEnum,
+        }
+
+        let known = json!({"message": {"role": "human", "content": "hi"}});
+        let envelope: Envelope = serde_json::from_value(known).unwrap();
+        assert_eq!(envelope.message.content(), "hi");
+
+        let unknown = json!({"message": {"role": "critic", "content": "hi"}});
+        let error = serde_json::from_value::<Envelope>(unknown).err().unwrap();
+        assert!(error.to_string().contains("Invalid message type: critic"));
+    }
+
+    #[test]
+    fn test_pinned_round_trip() {
+        let mut message = MessageEnum::Human(HumanMessage::new("Remember: no nuts."));
+        message.set_pinned(true);
+
+        let serialized = serde_json::to_value(&message).unwrap();
+        assert_eq!(serialized["pinned"], json!(true));
+
+        let deserialized: MessageEnum = serde_json::from_value(serialized).unwrap();
+        assert!(deserialized.is_pinned());
+
+        let unpinned = serde_json::to_value(MessageEnum::Human(HumanMessage::new("hi"))).unwrap();
+        assert!(unpinned.get("pinned").is_none());
+    }
 }
@@ -0,0 +1,277 @@
+use serde::{Serialize, Serializer};
+
+use crate::unknown_message::UnknownMessage;
+use crate::{
+    AiMessage, BaseMessage, BaseMessageFields, ChatMessage, Conversation, HumanMessage,
+    MessageEnum, MessageType, Metadata, RemoveMessage, SystemMessage, ToolMessage,
+};
+
+/// A borrowed message. Serializes exactly like the owned [`MessageEnum`], so
+/// adapters can build requests from several sources without cloning.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MessageRef<'a> {
+    Ai(&'a AiMessage),
+    Human(&'a HumanMessage),
+    System(&'a SystemMessage),
+    Tool(&'a ToolMessage),
+    Chat(&'a ChatMessage),
+    Remove(&'a RemoveMessage),
+    Unknown(&'a UnknownMessage),
+}
+
+impl<'a> MessageRef<'a> {
+    pub fn base(&self) -> &'a BaseMessageFields {
+        match *self {
+            MessageRef::Ai(message) => &message.base,
+            MessageRef::Human(message) => &message.base,
+            MessageRef::System(message) => &message.base,
+            MessageRef::Tool(message) => &message.base,
+            MessageRef::Chat(message) => &message.base,
+            MessageRef::Remove(message) => &message.base,
+            MessageRef::Unknown(message) => &message.base,
+        }
+    }
+
+    pub fn to_owned_message(&self) -> MessageEnum {
+        match *self {
+            MessageRef::Ai(message) => MessageEnum::Ai(message.clone()),
+            MessageRef::Human(message) => MessageEnum::Human(message.clone()),
+            MessageRef::System(message) => MessageEnum::System(message.clone()),
+            MessageRef::Tool(message) => MessageEnum::Tool(message.clone()),
+            MessageRef::Chat(message) => MessageEnum::Chat(message.clone()),
+            MessageRef::Remove(message) => MessageEnum::Remove(message.clone()),
+            MessageRef::Unknown(message) => MessageEnum::Unknown(message.clone()),
+        }
+    }
+}
+
+impl BaseMessage for MessageRef<'_> {
+    fn content(&self) -> &str {
+        self.base().content.as_str()
+    }
+
+    fn message_type(&self) -> &MessageType {
+        &self.base().message_type
+    }
+
+    fn role(&self) -> &str {
+        match *self {
+            MessageRef::Chat(message) => message.role(),
+            _ => self.base().message_type.as_str(),
+        }
+    }
+
+    fn name(&self) -> Option<&str> {
+        self.base().name.as_deref()
+    }
+
+    fn is_example(&self) -> bool {
+        self.base().example
+    }
+
+    fn additional_kwargs(&self) -> &Metadata {
+        &self.base().additional_kwargs
+    }
+
+    fn response_metadata(&self) -> &Metadata {
+        &self.base().response_metadata
+    }
+
+    fn id(&self) -> Option<&str> {
+        self.base().id.as_deref()
+    }
+}
+
+impl<'a> From<&'a MessageEnum> for MessageRef<'a> {
+    fn from(message: &'a MessageEnum) -> Self {
+        match message {
+            MessageEnum::Ai(message) => MessageRef::Ai(message),
+            MessageEnum::Human(message) => MessageRef::Human(message),
+            MessageEnum::System(message) => MessageRef::System(message),
+            MessageEnum::Tool(message) => MessageRef::Tool(message),
+            MessageEnum::Chat(message) => MessageRef::Chat(message),
+            MessageEnum::Remove(message) => MessageRef::Remove(message),
+            MessageEnum::Unknown(message) => MessageRef::Unknown(message),
+        }
+    }
+}
+
+impl<'a> From<&'a AiMessage> for MessageRef<'a> {
+    fn from(message: &'a AiMessage) -> Self {
+        MessageRef::Ai(message)
+    }
+}
+
+impl<'a> From<&'a HumanMessage> for MessageRef<'a> {
+    fn from(message: &'a HumanMessage) -> Self {
+        MessageRef::Human(message)
+    }
+}
+
+impl<'a> From<&'a SystemMessage> for MessageRef<'a> {
+    fn from(message: &'a SystemMessage) -> Self {
+        MessageRef::System(message)
+    }
+}
+
+impl<'a> From<&'a ToolMessage> for MessageRef<'a> {
+    fn from(message: &'a ToolMessage) -> Self {
+        MessageRef::Tool(message)
+    }
+}
+
+impl<'a> From<&'a ChatMessage> for MessageRef<'a> {
+    fn from(message: &'a ChatMessage) -> Self {
+        MessageRef::Chat(message)
+    }
+}
+
+impl<'a> From<&'a RemoveMessage> for MessageRef<'a> {
+    fn from(message: &'a RemoveMessage) -> Self {
+        MessageRef::Remove(message)
+    }
+}
+
+impl<'a> From<&'a UnknownMessage> for MessageRef<'a> {
+    fn from(message: &'a UnknownMessage) -> Self {
+        MessageRef::Unknown(message)
+    }
+}
+
+impl Serialize for MessageRef<'_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        #[derive(Serialize)]
+        struct Tagged<'a, T> {
+            role: &'a str,
+            #[serde(flatten)]
+            message: &'a T,
+        }
+
+        let role = self.role();
+        match *self {
+            MessageRef::Ai(message) => Tagged { role, message }.serialize(serializer),
+            MessageRef::Human(message) => Tagged { role, message }.serialize(serializer),
+            MessageRef::System(message) => Tagged { role, message }.serialize(serializer),
+            MessageRef::Tool(message) => Tagged { role, message }.serialize(serializer),
+            MessageRef::Chat(message) => message.serialize(serializer),
+            MessageRef::Remove(message) => Tagged { role, message }.serialize(serializer),
+            MessageRef::Unknown(message) => Tagged { role, message }.serialize(serializer),
+        }
+    }
+}
+
+/// An ordered list of borrowed messages, e.g. stored history followed by the
+/// turn being sent.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(transparent)]
+pub struct ConversationRef<'a> {
+    messages: Vec<MessageRef<'a>>,
+}
+
+impl<'a> ConversationRef<'a> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, message: impl Into<MessageRef<'a>>) {
+        self.messages.push(message.into());
+    }
+
+    pub fn extend_from(&mut self, messages: impl IntoIterator<Item = &'a MessageEnum>) {
+        self.messages
+            .extend(messages.into_iter().map(MessageRef::from));
+    }
+
+    pub fn messages(&self) -> &[MessageRef<'a>] {
+        &self.messages
+    }
+
+    pub fn len(&self) -> usize {
+        self.messages.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.messages.is_empty()
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, MessageRef<'a>> {
+        self.messages.iter()
+    }
+
+    pub fn to_conversation(&self) -> Conversation {
+        self.messages
+            .iter()
+            .map(MessageRef::to_owned_message)
+            .collect()
+    }
+}
+
+impl<'a> From<&'a Conversation> for ConversationRef<'a> {
+    fn from(conversation: &'a Conversation) -> Self {
+        let mut view = ConversationRef::new();
+        view.extend_from(conversation);
+        view
+    }
+}
+
+impl<'a, M: Into<MessageRef<'a>>> FromIterator<M> for ConversationRef<'a> {
+    fn from_iter<I: IntoIterator<Item = M>>(iter: I) -> Self {
+        ConversationRef {
+            messages: iter.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl<'a, 'b> IntoIterator for &'b ConversationRef<'a> {
+    type Item = &'b MessageRef<'a>;
+    type IntoIter = std::slice::Iter<'b, MessageRef<'a>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.messages.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_message_ref_serializes_like_owned() {
+        let message: MessageEnum = AiMessage::new("Hello").into();
+        let borrowed = MessageRef::from(&message);
+
+        assert_eq!(
+            serde_json::to_value(borrowed).unwrap(),
+            serde_json::to_value(&message).unwrap()
+        );
+        assert_eq!(borrowed.role(), message.role());
+        assert_eq!(borrowed.to_owned_message(), message);
+    }
+
+    #[test]
+    fn test_conversation_ref_combines_sources() {
+        let mut history = Conversation::new();
+        history.push(SystemMessage::new("Be brief."));
+        history.push(HumanMessage::new("Hi"));
+        history.push(AiMessage::new("Hello"));
+        let next_turn = HumanMessage::new("What's new?");
+
+        let mut request = ConversationRef::from(&history);
+        request.push(&next_turn);
+
+        assert_eq!(request.len(), 4);
+        let contents: Vec<&str> = request.iter().map(|m| m.content()).collect();
+        assert_eq!(contents, vec!["Be brief.", "Hi", "Hello", "What's new?"]);
+
+        let mut owned = history.clone();
+        owned.push(next_turn.clone());
+        assert_eq!(request.to_conversation(), owned);
+        assert_eq!(
+            serde_json::to_value(&request).unwrap(),
+            serde_json::to_value(owned.messages()).unwrap()
+        );
+    }
+}
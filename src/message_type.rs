@@ -1,13 +1,20 @@
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use std::fmt;
 
-#[derive(Debug, PartialEq, Eq, Serialize, Deserialize, Clone, Copy)]
+use crate::message_type_info::{MessageTypeInfo, MESSAGE_TYPES};
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub enum MessageType {
     Ai,
     Chat,
     Human,
     System,
     Tool,
+    /// A [`crate::RemoveMessage`] deletion sentinel.
+    Remove,
+    /// A type written by a newer or foreign producer; the name it was
+    /// written with is kept on [`crate::UnknownMessage`].
+    Unknown,
 }
 
 impl MessageType {
@@ -18,8 +25,62 @@ impl MessageType {
             MessageType::Human => "human",
             MessageType::System => "system",
             MessageType::Tool => "tool",
+            MessageType::Remove => "remove",
+            MessageType::Unknown => "unknown",
         }
     }
+
+    pub fn is_unknown(&self) -> bool {
+        matches!(self, MessageType::Unknown)
+    }
+
+    fn from_serde_tag(tag: &str) -> Option<MessageType> {
+        MESSAGE_TYPES
+            .iter()
+            .find(|info| info.serde_tag == tag)
+            .map(|info| info.message_type)
+    }
+
+    /// Lenient counterpart of `TryFrom<&str>`: unrecognised names become
+    /// `MessageType::Unknown` instead of an error.
+    pub fn from_name(name: &str) -> MessageType {
+        MessageType::try_from(name).unwrap_or(MessageType::Unknown)
+    }
+
+    /// Strict deserializer for `#[serde(deserialize_with = "...")]`, rejecting
+    /// types this version does not know about.
+    pub fn deserialize_strict<'de, D>(deserializer: D) -> Result<MessageType, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let tag = String::deserialize(deserializer)?;
+        MessageType::from_serde_tag(&tag).ok_or_else(|| {
+            serde::de::Error::custom(InvalidMessageTypeError::new(format!(
+                "Invalid message type: {}",
+                tag
+            )))
+        })
+    }
+}
+
+impl Serialize for MessageType {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(self.info().map_or("Unknown", |info| info.serde_tag))
+    }
+}
+
+/// Names this version does not know deserialize to `MessageType::Unknown`.
+impl<'de> Deserialize<'de> for MessageType {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let tag = String::deserialize(deserializer)?;
+        Ok(MessageType::from_serde_tag(&tag).unwrap_or(MessageType::Unknown))
+    }
 }
 
 #[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
@@ -43,16 +104,58 @@ impl TryFrom<&str> for MessageType {
     type Error = InvalidMessageTypeError;
 
     fn try_from(s: &str) -> Result<MessageType, InvalidMessageTypeError> {
-        match s {
-            "human" | "Human" | "HumanMessage" => Ok(MessageType::Human),
-            "ai" | "Ai" | "AiMessage" => Ok(MessageType::Ai),
-            "system" | "System" | "SystemMessage" => Ok(MessageType::System),
-            "chat" | "Chat" | "ChatMessage" => Ok(MessageType::Chat),
-            "tool" | "Tool" | "ToolMessage" => Ok(MessageType::Tool),
-            _ => Err(InvalidMessageTypeError::new(format!(
-                "Invalid message type: {}",
-                s
-            ))),
+        MessageTypeInfo::lookup(s)
+            .map(|info| info.message_type)
+            .ok_or_else(|| InvalidMessageTypeError::new(format!("Invalid message type: {}", s)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unknown_message_type() {
+        let message_type: MessageType = serde_json::from_str(r#""Critic""#).unwrap();
+
+        assert_eq!(message_type, MessageType::Unknown);
+        assert_eq!(message_type.as_str(), "unknown");
+        assert!(message_type.is_unknown());
+        assert_eq!(
+            serde_json::to_string(&message_type).unwrap(),
+            r#""Unknown""#
+        );
+    }
+
+    #[test]
+    fn test_known_message_type_serde_unchanged() {
+        let message_type: MessageType = serde_json::from_str(r#""Human""#).unwrap();
+
+        assert_eq!(message_type, MessageType::Human);
+        assert_eq!(serde_json::to_string(&MessageType::Ai).unwrap(), r#""Ai""#);
+    }
+
+    #[test]
+    fn test_from_name_and_try_from() {
+        assert_eq!(MessageType::from_name("AiMessage"), MessageType::Ai);
+        assert_eq!(MessageType::from_name("critic"), MessageType::Unknown);
+        assert!(MessageType::try_from("critic").is_err());
+    }
+
+    #[test]
+    fn test_deserialize_strict() {
+        #[derive(Deserialize)]
+        struct Strict {
+            #[serde(deserialize_with = "MessageType::deserialize_strict")]
+            message_type: MessageType,
         }
+
+        let ok: Strict = serde_json::from_str(r#"{"message_type":"Tool"}"#).unwrap();
+        assert_eq!(ok.message_type, MessageType::Tool);
+
+        let error = serde_json::from_str::<Strict>(r#"{"message_type":"Critic"}"#)
+            .err()
+            .unwrap();
+        assert!(error.to_string().contains("Critic"));
     }
 }
@@ -0,0 +1,139 @@
+use crate::MessageType;
+
+/// Static facts about a built-in message type, so adapters and UIs can look
+/// them up instead of keeping their own per-type tables.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MessageTypeInfo {
+    pub message_type: MessageType,
+    pub display_name: &'static str,
+    pub role: &'static str,
+    pub serde_tag: &'static str,
+    pub aliases: &'static [&'static str],
+    pub can_carry_tool_calls: bool,
+}
+
+pub static MESSAGE_TYPES: [MessageTypeInfo; 6] = [
+    MessageTypeInfo {
+        message_type: MessageType::Ai,
+        display_name: "AI",
+        role: "ai",
+        serde_tag: "Ai",
+        aliases: &["ai", "Ai", "AiMessage", "assistant"],
+        can_carry_tool_calls: true,
+    },
+    MessageTypeInfo {
+        message_type: MessageType::Chat,
+        display_name: "Chat",
+        role: "chat",
+        serde_tag: "Chat",
+        aliases: &["chat", "Chat", "ChatMessage"],
+        can_carry_tool_calls: false,
+    },
+    MessageTypeInfo {
+        message_type: MessageType::Human,
+        display_name: "Human",
+        role: "human",
+        serde_tag: "Human",
+        aliases: &["human", "Human", "HumanMessage", "user"],
+        can_carry_tool_calls: false,
+    },
+    MessageTypeInfo {
+        message_type: MessageType::System,
+        display_name: "System",
+        role: "system",
+        serde_tag: "System",
+        aliases: &["system", "System", "SystemMessage"],
+        can_carry_tool_calls: false,
+    },
+    MessageTypeInfo {
+        message_type: MessageType::Tool,
+        display_name: "Tool",
+        role: "tool",
+        serde_tag: "Tool",
+        aliases: &["tool", "Tool", "ToolMessage"],
+        can_carry_tool_calls: false,
+    },
+    MessageTypeInfo {
+        message_type: MessageType::Remove,
+        display_name: "Remove",
+        role: "remove",
+        serde_tag: "Remove",
+        aliases: &["remove", "Remove", "RemoveMessage"],
+        can_carry_tool_calls: false,
+    },
+];
+
+impl MessageTypeInfo {
+    pub fn all() -> &'static [MessageTypeInfo] {
+        &MESSAGE_TYPES
+    }
+
+    pub fn lookup(alias: &str) -> Option<&'static MessageTypeInfo> {
+        MESSAGE_TYPES
+            .iter()
+            .find(|info| info.aliases.contains(&alias))
+    }
+}
+
+impl MessageType {
+    /// Registry entry for this type; `None` for `MessageType::Unknown`.
+    pub fn info(&self) -> Option<&'static MessageTypeInfo> {
+        MESSAGE_TYPES.iter().find(|info| &info.message_type == self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_every_known_type_has_info() {
+        for message_type in [
+            MessageType::Ai,
+            MessageType::Chat,
+            MessageType::Human,
+            MessageType::System,
+            MessageType::Tool,
+        ] {
+            let info = message_type.info().unwrap();
+            assert_eq!(info.message_type, message_type);
+            assert_eq!(info.role, message_type.as_str());
+            assert_eq!(
+                serde_json::to_value(message_type).unwrap(),
+                serde_json::json!(info.serde_tag)
+            );
+        }
+        assert!(MessageType::Unknown.info().is_none());
+    }
+
+    #[test]
+    fn test_lookup_by_alias() {
+        assert_eq!(
+            MessageTypeInfo::lookup("assistant").unwrap().message_type,
+            MessageType::Ai
+        );
+        assert_eq!(
+            MessageTypeInfo::lookup("HumanMessage")
+                .unwrap()
+                .display_name,
+            "Human"
+        );
+        assert!(MessageTypeInfo::lookup("critic").is_none());
+    }
+
+    #[test]
+    fn test_tool_call_capability() {
+        let carriers: Vec<&str> = MessageTypeInfo::all()
+            .iter()
+            .filter(|info| info.can_carry_tool_calls)
+            .map(|info| info.role)
+            .collect();
+        assert_eq!(carriers, vec!["ai"]);
+    }
+
+    #[test]
+    fn test_try_from_uses_registry_aliases() {
+        assert_eq!(MessageType::try_from("user").unwrap(), MessageType::Human);
+        assert_eq!(MessageType::try_from("Tool").unwrap(), MessageType::Tool);
+    }
+}
@@ -0,0 +1,163 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// The `additional_kwargs` and `response_metadata` map of a message. Values
+/// are JSON, so nested provider payloads are stored as-is; the typed
+/// getters return `None` when a key is absent or holds another type.
+#[derive(Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Metadata(HashMap<String, Value>);
+
+impl Metadata {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Inserts anything convertible to JSON, so plain strings, numbers and
+    /// bools need no wrapping.
+    pub fn insert(&mut self, key: impl Into<String>, value: impl Into<Value>) -> Option<Value> {
+        self.0.insert(key.into(), value.into())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn get_str(&self, key: &str) -> Option<&str> {
+        self.0.get(key).and_then(Value::as_str)
+    }
+
+    pub fn get_bool(&self, key: &str) -> Option<bool> {
+        self.0.get(key).and_then(Value::as_bool)
+    }
+
+    pub fn get_i64(&self, key: &str) -> Option<i64> {
+        self.0.get(key).and_then(Value::as_i64)
+    }
+
+    pub fn get_u64(&self, key: &str) -> Option<u64> {
+        self.0.get(key).and_then(Value::as_u64)
+    }
+
+    pub fn get_f64(&self, key: &str) -> Option<f64> {
+        self.0.get(key).and_then(Value::as_f64)
+    }
+
+    pub fn into_inner(self) -> HashMap<String, Value> {
+        self.0
+    }
+}
+
+// Timestamps and counters written before values were typed are numeric
+// strings; readers that order or count accept either form.
+#[cfg(feature = "history")]
+pub(crate) fn parse_u64(value: &Value) -> Option<u64> {
+    value
+        .as_u64()
+        .or_else(|| value.as_str()?.trim().parse().ok())
+}
+
+// Prints like the bare map so message debug output stays compact.
+impl fmt::Debug for Metadata {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl Deref for Metadata {
+    type Target = HashMap<String, Value>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl DerefMut for Metadata {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl From<HashMap<String, Value>> for Metadata {
+    fn from(map: HashMap<String, Value>) -> Self {
+        Metadata(map)
+    }
+}
+
+impl<K: Into<String>, V: Into<Value>> FromIterator<(K, V)> for Metadata {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        Metadata(
+            iter.into_iter()
+                .map(|(key, value)| (key.into(), value.into()))
+                .collect(),
+        )
+    }
+}
+
+impl<K: Into<String>, V: Into<Value>> Extend<(K, V)> for Metadata {
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        self.0.extend(
+            iter.into_iter()
+                .map(|(key, value)| (key.into(), value.into())),
+        );
+    }
+}
+
+impl IntoIterator for Metadata {
+    type Item = (String, Value);
+    type IntoIter = std::collections::hash_map::IntoIter<String, Value>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a Metadata {
+    type Item = (&'a String, &'a Value);
+    type IntoIter = std::collections::hash_map::Iter<'a, String, Value>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_typed_getters() {
+        let mut metadata = Metadata::new();
+        metadata.insert("model", "gpt-4o");
+        metadata.insert("cached", true);
+        metadata.insert("tokens", 42);
+        metadata.insert("temperature", 0.5);
+        metadata.insert("raw", json!({"id": "chatcmpl-1", "choices": []}));
+
+        assert_eq!(metadata.get_str("model"), Some("gpt-4o"));
+        assert_eq!(metadata.get_bool("cached"), Some(true));
+        assert_eq!(metadata.get_i64("tokens"), Some(42));
+        assert_eq!(metadata.get_u64("tokens"), Some(42));
+        assert_eq!(metadata.get_f64("temperature"), Some(0.5));
+        assert_eq!(metadata.get_str("tokens"), None);
+        assert_eq!(metadata["raw"]["id"], "chatcmpl-1");
+        assert_eq!(metadata.get_bool("missing"), None);
+    }
+
+    #[test]
+    fn test_serializes_as_plain_object() {
+        let metadata: Metadata = [("a", json!(1)), ("b", json!({"nested": [true]}))]
+            .into_iter()
+            .collect();
+
+        let value = serde_json::to_value(&metadata).unwrap();
+
+        assert_eq!(value, json!({"a": 1, "b": {"nested": [true]}}));
+        assert_eq!(serde_json::from_value::<Metadata>(value).unwrap(), metadata);
+    }
+}
@@ -0,0 +1,212 @@
+use std::collections::HashSet;
+
+use serde::{Deserialize, Serialize};
+
+use crate::tool_message::ToolStatus;
+use crate::{BaseMessage, Conversation, MessageEnum};
+
+const REFUSAL_PHRASES: &[&str] = &[
+    "i can't help with",
+    "i cannot help with",
+    "i can't assist with",
+    "i cannot assist with",
+    "i'm unable to",
+    "i am unable to",
+    "i won't be able to",
+    "i'm sorry, but i can't",
+    "i'm sorry, but i cannot",
+    "as an ai",
+];
+
+/// Heuristic quality metrics for one conversation, flat so it can be written
+/// straight to a dashboard table. Rates are `0.0` when there is nothing to
+/// measure.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ConversationMetrics {
+    pub message_count: usize,
+    pub prompt_count: usize,
+    pub response_count: usize,
+    pub tool_result_count: usize,
+    pub prompt_chars: usize,
+    pub response_chars: usize,
+    pub response_prompt_ratio: f64,
+    pub repetition_score: f64,
+    pub refusal_rate: f64,
+    pub tool_failure_rate: f64,
+}
+
+impl ConversationMetrics {
+    pub fn compute(conversation: &Conversation) -> Self {
+        let mut metrics = ConversationMetrics {
+            message_count: conversation.len(),
+            ..Self::default()
+        };
+        let mut refusals = 0;
+        let mut tool_failures = 0;
+        let mut responses = Vec::new();
+
+        for message in conversation {
+            match message {
+                MessageEnum::Human(_) => {
+                    metrics.prompt_count += 1;
+                    metrics.prompt_chars += message.content().chars().count();
+                }
+                MessageEnum::Ai(_) => {
+                    metrics.response_count += 1;
+                    metrics.response_chars += message.content().chars().count();
+                    if is_refusal(message.content()) {
+                        refusals += 1;
+                    }
+                    responses.push(message.content());
+                }
+                MessageEnum::Tool(tool) => {
+                    metrics.tool_result_count += 1;
+                    if tool.status() == &ToolStatus::Error {
+                        tool_failures += 1;
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        metrics.response_prompt_ratio = ratio(metrics.response_chars, metrics.prompt_chars);
+        metrics.repetition_score = repetition_score(&responses);
+        metrics.refusal_rate = ratio(refusals, metrics.response_count);
+        metrics.tool_failure_rate = ratio(tool_failures, metrics.tool_result_count);
+        metrics
+    }
+
+    /// Column names matching [`ConversationMetrics::values`].
+    pub fn columns() -> &'static [&'static str] {
+        &[
+            "message_count",
+            "prompt_count",
+            "response_count",
+            "tool_result_count",
+            "prompt_chars",
+            "response_chars",
+            "response_prompt_ratio",
+            "repetition_score",
+            "refusal_rate",
+            "tool_failure_rate",
+        ]
+    }
+
+    pub fn values(&self) -> Vec<f64> {
+        vec![
+            self.message_count as f64,
+            self.prompt_count as f64,
+            self.response_count as f64,
+            self.tool_result_count as f64,
+            self.prompt_chars as f64,
+            self.response_chars as f64,
+            self.response_prompt_ratio,
+            self.repetition_score,
+            self.refusal_rate,
+            self.tool_failure_rate,
+        ]
+    }
+}
+
+pub fn conversation_metrics(conversation: &Conversation) -> ConversationMetrics {
+    ConversationMetrics::compute(conversation)
+}
+
+pub fn is_refusal(content: &str) -> bool {
+    let content = content.to_lowercase().replace('\u{2019}', "'");
+    REFUSAL_PHRASES
+        .iter()
+        .any(|phrase| content.contains(phrase))
+}
+
+// Share of word trigrams in the responses that already appeared earlier.
+fn repetition_score(responses: &[&str]) -> f64 {
+    let mut seen = HashSet::new();
+    let mut total = 0;
+    let mut repeated = 0;
+    for response in responses {
+        let words: Vec<String> = response
+            .split_whitespace()
+            .map(|word| word.to_lowercase())
+            .collect();
+        for trigram in words.windows(3) {
+            total += 1;
+            if !seen.insert(trigram.to_vec()) {
+                repeated += 1;
+            }
+        }
+    }
+    ratio(repeated, total)
+}
+
+fn ratio(numerator: usize, denominator: usize) -> f64 {
+    if denominator == 0 {
+        0.0
+    } else {
+        numerator as f64 / denominator as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AiMessage, HumanMessage, ToolMessage};
+
+    #[test]
+    fn test_metrics_for_simple_conversation() {
+        let mut conversation = Conversation::new();
+        conversation.push(HumanMessage::new("abcd"));
+        conversation.push(AiMessage::new("abcdefgh"));
+        conversation.push(HumanMessage::new("Write malware"));
+        conversation.push(AiMessage::new("I'm sorry, but I can't help with that."));
+
+        let metrics = conversation_metrics(&conversation);
+
+        assert_eq!(metrics.message_count, 4);
+        assert_eq!(metrics.prompt_count, 2);
+        assert_eq!(metrics.response_count, 2);
+        assert_eq!(metrics.refusal_rate, 0.5);
+        assert_eq!(metrics.tool_failure_rate, 0.0);
+        assert_eq!(
+            metrics.response_prompt_ratio,
+            (8 + 38) as f64 / (4 + 13) as f64
+        );
+    }
+
+    #[test]
+    fn test_repetition_and_tool_failures() {
+        let mut conversation = Conversation::new();
+        conversation.push(AiMessage::new("the answer is yes"));
+        conversation.push(AiMessage::new("the answer is yes"));
+        conversation.push(ToolMessage::new(
+            "boom",
+            "call_1".to_string(),
+            None,
+            ToolStatus::Error,
+        ));
+        conversation.push(ToolMessage::new(
+            "ok",
+            "call_2".to_string(),
+            None,
+            ToolStatus::Success,
+        ));
+
+        let metrics = conversation_metrics(&conversation);
+
+        assert_eq!(metrics.repetition_score, 0.5);
+        assert_eq!(metrics.tool_failure_rate, 0.5);
+        assert_eq!(metrics.refusal_rate, 0.0);
+    }
+
+    #[test]
+    fn test_flat_record_export() {
+        let metrics = conversation_metrics(&Conversation::new());
+
+        assert_eq!(metrics, ConversationMetrics::default());
+        assert_eq!(ConversationMetrics::columns().len(), metrics.values().len());
+        let json = serde_json::to_value(&metrics).unwrap();
+        for column in ConversationMetrics::columns() {
+            assert!(json.get(column).is_some(), "missing {}", column);
+        }
+    }
+}
@@ -0,0 +1,207 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::tokens::approximate_tokens;
+use crate::window::window_messages_by;
+use crate::{BaseMessage, MessageEnum};
+
+const BUILTIN_PROFILES: &str = include_str!("model_profiles.json");
+
+/// USD per million tokens.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Pricing {
+    pub input_per_million: f64,
+    pub output_per_million: f64,
+}
+
+/// What a target model accepts and costs. Fields left out of JSON take the
+/// values from [`ModelProfile::default`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ModelProfile {
+    pub name: String,
+    pub max_context_tokens: usize,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tokenizer: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pricing: Option<Pricing>,
+
+    pub supports_system_role: bool,
+    pub supports_tools: bool,
+    pub supports_images: bool,
+}
+
+impl Default for ModelProfile {
+    fn default() -> Self {
+        ModelProfile {
+            name: String::new(),
+            max_context_tokens: 0,
+            tokenizer: None,
+            pricing: None,
+            supports_system_role: true,
+            supports_tools: true,
+            supports_images: false,
+        }
+    }
+}
+
+impl ModelProfile {
+    pub fn new(name: impl Into<String>, max_context_tokens: usize) -> Self {
+        ModelProfile {
+            name: name.into(),
+            max_context_tokens,
+            ..Self::default()
+        }
+    }
+
+    pub fn without_system_role(mut self) -> Self {
+        self.supports_system_role = false;
+        self
+    }
+
+    pub fn without_tools(mut self) -> Self {
+        self.supports_tools = false;
+        self
+    }
+
+    pub fn with_images(mut self) -> Self {
+        self.supports_images = true;
+        self
+    }
+
+    pub fn with_pricing(mut self, input_per_million: f64, output_per_million: f64) -> Self {
+        self.pricing = Some(Pricing {
+            input_per_million,
+            output_per_million,
+        });
+        self
+    }
+
+    /// Cost in USD, or `None` when the profile has no pricing.
+    pub fn estimate_cost(&self, input_tokens: usize, output_tokens: usize) -> Option<f64> {
+        let pricing = self.pricing?;
+        Some(
+            (input_tokens as f64 * pricing.input_per_million
+                + output_tokens as f64 * pricing.output_per_million)
+                / 1_000_000.0,
+        )
+    }
+
+    /// The most recent messages that fit the context window while leaving
+    /// `reserved_output` tokens for the reply.
+    pub fn window(&self, messages: &[MessageEnum], reserved_output: usize) -> Vec<MessageEnum> {
+        let budget = self.max_context_tokens.saturating_sub(reserved_output);
+        window_messages_by(messages, budget, |message| {
+            approximate_tokens(message.content())
+        })
+    }
+}
+
+/// Model profiles by name, starting from the built-in table and overridable
+/// from JSON at runtime.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ModelRegistry {
+    profiles: HashMap<String, ModelProfile>,
+}
+
+impl ModelRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn builtin() -> Self {
+        let mut registry = Self::new();
+        registry
+            .load_json(BUILTIN_PROFILES)
+            .expect("built-in model profiles are valid JSON");
+        registry
+    }
+
+    /// Adds or replaces profiles from a JSON array of profiles.
+    pub fn load_json(&mut self, json: &str) -> Result<(), serde_json::Error> {
+        let profiles: Vec<ModelProfile> = serde_json::from_str(json)?;
+        for profile in profiles {
+            self.insert(profile);
+        }
+        Ok(())
+    }
+
+    pub fn insert(&mut self, profile: ModelProfile) {
+        self.profiles.insert(profile.name.clone(), profile);
+    }
+
+    /// Looks `model` up by exact name, then by the longest registered name
+    /// it starts with, so dated snapshots like `gpt-4o-2024-08-06` resolve.
+    pub fn get(&self, model: &str) -> Option<&ModelProfile> {
+        self.profiles.get(model).or_else(|| {
+            self.profiles
+                .iter()
+                .filter(|(name, _)| model.starts_with(name.as_str()))
+                .max_by_key(|(name, _)| name.len())
+                .map(|(_, profile)| profile)
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.profiles.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.profiles.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{HumanMessage, SystemMessage};
+
+    #[test]
+    fn test_builtin_lookup_by_prefix() {
+        let registry = ModelRegistry::builtin();
+
+        assert_eq!(registry.get("gpt-4o").unwrap().max_context_tokens, 128_000);
+        assert_eq!(
+            registry.get("gpt-4o-mini-2024-07-18").unwrap().name,
+            "gpt-4o-mini"
+        );
+        assert!(!registry.get("llama-3-8b-instruct").unwrap().supports_tools);
+        assert!(registry.get("unknown-model").is_none());
+    }
+
+    #[test]
+    fn test_json_overrides() {
+        let mut registry = ModelRegistry::builtin();
+        let count = registry.len();
+
+        registry
+            .load_json(r#"[{"name": "gpt-4o", "max_context_tokens": 64000}, {"name": "local", "max_context_tokens": 4096, "supports_system_role": false}]"#)
+            .unwrap();
+
+        let gpt = registry.get("gpt-4o").unwrap();
+        assert_eq!(gpt.max_context_tokens, 64_000);
+        assert!(gpt.pricing.is_none());
+        assert!(!registry.get("local").unwrap().supports_system_role);
+        assert_eq!(registry.len(), count + 1);
+        assert!(registry.load_json("{").is_err());
+    }
+
+    #[test]
+    fn test_cost_and_window() {
+        let profile = ModelProfile::new("test", 10).with_pricing(2.0, 8.0);
+        assert_eq!(profile.estimate_cost(500_000, 250_000), Some(3.0));
+        assert_eq!(ModelProfile::new("free", 10).estimate_cost(1, 1), None);
+
+        let messages: Vec<MessageEnum> = vec![
+            SystemMessage::new("sys").into(),
+            HumanMessage::new("a".repeat(16).as_str()).into(),
+            HumanMessage::new("b".repeat(16).as_str()).into(),
+        ];
+        let window = profile.window(&messages, 4);
+        assert_eq!(window.len(), 2);
+        assert_eq!(window[1].content(), "b".repeat(16));
+    }
+}
@@ -0,0 +1,399 @@
+use std::fmt;
+
+use serde_json::{json, Map, Value};
+
+use crate::compat::ensure_compatible;
+use crate::tool_message::ToolStatus;
+use crate::unknown_message::UnknownMessage;
+use crate::{
+    AiMessage, AnyMessage, BaseMessage, ContentBlock, HumanMessage, InvalidToolCall,
+    MessageContent, ModelProfile, SystemMessage, ToolCall, ToolMessage,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OpenAiError(pub String);
+
+impl fmt::Display for OpenAiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid OpenAI message: {}", self.0)
+    }
+}
+
+impl std::error::Error for OpenAiError {}
+
+/// Converts messages to the chat-completions `messages` array. Unknown
+/// message types keep their type name as the role (e.g. `developer`).
+/// [`RemoveMessage`](crate::RemoveMessage) sentinels edit the history
+/// rather than being part of it, so they are skipped.
+pub fn to_openai_messages(messages: &[AnyMessage]) -> Vec<Value> {
+    messages
+        .iter()
+        .filter(|message| !matches!(message, AnyMessage::Remove(_)))
+        .map(wire_message)
+        .collect()
+}
+
+/// Strict mode: like [`to_openai_messages`], but fails when
+/// [`check_compatibility`](crate::check_compatibility) finds anything the
+/// model described by `profile` cannot take.
+pub fn to_openai_messages_strict(
+    messages: &[AnyMessage],
+    profile: &ModelProfile,
+) -> Result<Vec<Value>, OpenAiError> {
+    ensure_compatible(messages, profile).map_err(|err| OpenAiError(err.to_string()))?;
+    Ok(to_openai_messages(messages))
+}
+
+/// Fails for a [`RemoveMessage`](crate::RemoveMessage), which has no wire
+/// form.
+pub fn to_openai_message(message: &AnyMessage) -> Result<Value, OpenAiError> {
+    match message {
+        AnyMessage::Remove(_) => Err(OpenAiError(
+            "a remove sentinel is not a chat message".to_string(),
+        )),
+        _ => Ok(wire_message(message)),
+    }
+}
+
+fn wire_message(message: &AnyMessage) -> Value {
+    let mut object = Map::new();
+    let role = match message {
+        AnyMessage::Ai(_) => "assistant",
+        AnyMessage::Human(_) => "user",
+        AnyMessage::System(_) => "system",
+        AnyMessage::Tool(_) => "tool",
+        other => other.role(),
+    };
+    object.insert("role".to_string(), json!(role));
+
+    match message {
+        AnyMessage::Ai(ai) => {
+            let calls = ai.tool_calls();
+            let content = &ai.base.content;
+            let content = if calls.is_empty() || !content.text().is_empty() {
+                content_to_openai(content)
+            } else {
+                Value::Null
+            };
+            object.insert("content".to_string(), content);
+            if !calls.is_empty() {
+                let calls = calls
+                    .iter()
+                    .map(|call| {
+                        json!({
+                            "id": call.id,
+                            "type": "function",
+                            "function": {"name": call.name, "arguments": call.args.to_string()},
+                        })
+                    })
+                    .collect();
+                object.insert("tool_calls".to_string(), Value::Array(calls));
+            }
+        }
+        AnyMessage::Tool(tool) => {
+            object.insert("tool_call_id".to_string(), json!(tool.tool_call_id()));
+            object.insert("content".to_string(), content_to_openai(&tool.base.content));
+        }
+        _ => {
+            let content = content_to_openai(&message.base().content);
+            object.insert("content".to_string(), content);
+        }
+    }
+
+    // Tool results have no `name` field in the API.
+    if let (Some(name), false) = (message.name(), matches!(message, AnyMessage::Tool(_))) {
+        object.insert("name".to_string(), json!(name));
+    }
+    Value::Object(object)
+}
+
+fn content_to_openai(content: &MessageContent) -> Value {
+    let blocks = match content {
+        MessageContent::Text(text) => return json!(text),
+        MessageContent::Blocks(blocks) => blocks,
+    };
+    let parts = blocks
+        .iter()
+        .map(|block| match block {
+            ContentBlock::Text { text } => json!({"type": "text", "text": text}),
+            ContentBlock::ImageUrl { url, detail } => {
+                let mut image_url = json!({"url": url});
+                if let Some(detail) = detail {
+                    image_url["detail"] = json!(detail);
+                }
+                json!({"type": "image_url", "image_url": image_url})
+            }
+            ContentBlock::Image { mime_type, data } => json!({
+                "type": "image_url",
+                "image_url": {"url": data_url(mime_type, data)},
+            }),
+            ContentBlock::Audio { mime_type, data } => json!({
+                "type": "input_audio",
+                "input_audio": {"data": data, "format": audio_format(mime_type)},
+            }),
+            ContentBlock::Document {
+                mime_type,
+                data,
+                title,
+            } => {
+                let mut file = json!({"file_data": data_url(mime_type, data)});
+                if let Some(title) = title {
+                    file["filename"] = json!(title);
+                }
+                json!({"type": "file", "file": file})
+            }
+        })
+        .collect();
+    Value::Array(parts)
+}
+
+fn data_url(mime_type: &str, data: &str) -> String {
+    format!("data:{};base64,{}", mime_type, data)
+}
+
+fn parse_data_url(url: &str) -> Option<(&str, &str)> {
+    url.strip_prefix("data:")?.split_once(";base64,")
+}
+
+fn audio_format(mime_type: &str) -> &str {
+    match mime_type {
+        "audio/mpeg" | "audio/mp3" => "mp3",
+        other => other.strip_prefix("audio/").unwrap_or(other),
+    }
+}
+
+/// Parses a chat-completions `messages` array. `developer` messages become
+/// [`SystemMessage`]s; tool calls whose arguments are not valid JSON are
+/// kept as [`InvalidToolCall`]s.
+pub fn from_openai_messages(messages: &[Value]) -> Result<Vec<AnyMessage>, OpenAiError> {
+    messages.iter().map(from_openai_message).collect()
+}
+
+pub fn from_openai_message(message: &Value) -> Result<AnyMessage, OpenAiError> {
+    let role = message
+        .get("role")
+        .and_then(Value::as_str)
+        .ok_or_else(|| OpenAiError("missing role".to_string()))?;
+    let content = content_from_openai(message.get("content").unwrap_or(&Value::Null))?;
+    let name = message
+        .get("name")
+        .and_then(Value::as_str)
+        .map(str::to_string);
+
+    let mut parsed: AnyMessage = match role {
+        "system" | "developer" => SystemMessage::new("").with_content(content).into(),
+        "user" => HumanMessage::new("").with_content(content).into(),
+        "assistant" => {
+            let (calls, invalid) = tool_calls_from_openai(message)?;
+            AiMessage::new("")
+                .with_content(content)
+                .with_tool_calls(calls)
+                .with_invalid_tool_calls(invalid)
+                .into()
+        }
+        "tool" => {
+            let tool_call_id = message
+                .get("tool_call_id")
+                .and_then(Value::as_str)
+                .ok_or_else(|| OpenAiError("tool message without tool_call_id".to_string()))?;
+            ToolMessage::new("", tool_call_id.to_string(), None, ToolStatus::Success)
+                .with_content(content)
+                .into()
+        }
+        other => {
+            let mut unknown = UnknownMessage::new(other, "");
+            unknown.base.content = content;
+            unknown.into()
+        }
+    };
+    parsed.base_mut().name = name;
+    Ok(parsed)
+}
+
+fn content_from_openai(content: &Value) -> Result<MessageContent, OpenAiError> {
+    let parts = match content {
+        Value::Null => return Ok(MessageContent::default()),
+        Value::String(text) => return Ok(MessageContent::Text(text.clone())),
+        Value::Array(parts) => parts,
+        other => return Err(OpenAiError(format!("unsupported content {}", other))),
+    };
+    parts
+        .iter()
+        .map(content_part_from_openai)
+        .collect::<Result<Vec<_>, _>>()
+        .map(MessageContent::Blocks)
+}
+
+fn content_part_from_openai(part: &Value) -> Result<ContentBlock, OpenAiError> {
+    let field = |path: &[&str]| {
+        path.iter()
+            .try_fold(part, |value, key| value.get(key))
+            .and_then(Value::as_str)
+    };
+    let missing = |what: &str| OpenAiError(format!("content part without {}", what));
+    match part.get("type").and_then(Value::as_str) {
+        Some("text") => Ok(ContentBlock::text(
+            field(&["text"]).ok_or_else(|| missing("text"))?,
+        )),
+        Some("image_url") => {
+            let url = field(&["image_url", "url"]).ok_or_else(|| missing("image_url.url"))?;
+            Ok(match parse_data_url(url) {
+                Some((mime_type, data)) => ContentBlock::image(mime_type, data),
+                None => ContentBlock::ImageUrl {
+                    url: url.to_string(),
+                    detail: field(&["image_url", "detail"]).map(str::to_string),
+                },
+            })
+        }
+        Some("input_audio") => {
+            let data = field(&["input_audio", "data"]).ok_or_else(|| missing("audio data"))?;
+            let format = field(&["input_audio", "format"]).unwrap_or("wav");
+            let mime_type = match format {
+                "mp3" => "audio/mpeg".to_string(),
+                other => format!("audio/{}", other),
+            };
+            Ok(ContentBlock::audio(mime_type, data))
+        }
+        Some("file") => {
+            let file_data = field(&["file", "file_data"]).ok_or_else(|| missing("file_data"))?;
+            let (mime_type, data) = parse_data_url(file_data)
+                .ok_or_else(|| OpenAiError("file_data is not a base64 data URL".to_string()))?;
+            Ok(ContentBlock::Document {
+                mime_type: mime_type.to_string(),
+                data: data.to_string(),
+                title: field(&["file", "filename"]).map(str::to_string),
+            })
+        }
+        other => Err(OpenAiError(format!(
+            "unsupported content part type {:?}",
+            other
+        ))),
+    }
+}
+
+fn tool_calls_from_openai(
+    message: &Value,
+) -> Result<(Vec<ToolCall>, Vec<InvalidToolCall>), OpenAiError> {
+    let mut calls = Vec::new();
+    let mut invalid = Vec::new();
+    let Some(raw_calls) = message.get("tool_calls").and_then(Value::as_array) else {
+        return Ok((calls, invalid));
+    };
+    for raw in raw_calls {
+        let id = raw.get("id").and_then(Value::as_str).map(str::to_string);
+        let function = raw.get("function");
+        let name = function
+            .and_then(|function| function.get("name"))
+            .and_then(Value::as_str)
+            .map(str::to_string);
+        let arguments = function
+            .and_then(|function| function.get("arguments"))
+            .and_then(Value::as_str)
+            .unwrap_or("{}");
+        match (id, name, serde_json::from_str::<Value>(arguments)) {
+            (Some(id), Some(name), Ok(args)) => calls.push(ToolCall::new(id, name, args)),
+            (id, name, parsed) => invalid.push(InvalidToolCall {
+                id,
+                name,
+                args: Some(arguments.to_string()),
+                error: Some(match parsed {
+                    Err(err) => err.to_string(),
+                    Ok(_) => "tool call without id or name".to_string(),
+                }),
+            }),
+        }
+    }
+    Ok((calls, invalid))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn conversation() -> Vec<AnyMessage> {
+        let mut user = HumanMessage::new("").with_content(vec![
+            ContentBlock::text("What does this say?"),
+            ContentBlock::image("image/png", "iVBORw0KGgo="),
+        ]);
+        user.set_name(Some("alice".to_string()));
+        vec![
+            SystemMessage::new("Be brief.").into(),
+            user.into(),
+            AiMessage::new("")
+                .with_tool_calls(vec![ToolCall::new("call_1", "ocr", json!({"lang": "en"}))])
+                .into(),
+            ToolMessage::new("Hello", "call_1".to_string(), None, ToolStatus::Success).into(),
+            AiMessage::new("It says hello.").into(),
+        ]
+    }
+
+    #[test]
+    fn test_to_openai_shapes() {
+        let wire = to_openai_messages(&conversation());
+
+        assert_eq!(wire[0], json!({"role": "system", "content": "Be brief."}));
+        assert_eq!(wire[1]["name"], "alice");
+        assert_eq!(
+            wire[1]["content"][1],
+            json!({"type": "image_url", "image_url": {"url": "data:image/png;base64,iVBORw0KGgo="}})
+        );
+        assert_eq!(wire[2]["content"], Value::Null);
+        assert_eq!(
+            wire[2]["tool_calls"][0],
+            json!({
+                "id": "call_1",
+                "type": "function",
+                "function": {"name": "ocr", "arguments": "{\"lang\":\"en\"}"},
+            })
+        );
+        assert_eq!(
+            wire[3],
+            json!({"role": "tool", "tool_call_id": "call_1", "content": "Hello"})
+        );
+    }
+
+    #[test]
+    fn test_round_trip_and_invalid_calls() {
+        let wire = to_openai_messages(&conversation());
+        assert_eq!(from_openai_messages(&wire).unwrap(), conversation());
+
+        let mut with_removal = conversation();
+        with_removal.push(crate::RemoveMessage::new("m1").into());
+        assert_eq!(to_openai_messages(&with_removal), wire);
+        assert!(to_openai_message(&with_removal[5]).is_err());
+
+        let broken = json!({
+            "role": "assistant",
+            "content": null,
+            "tool_calls": [{"id": "c", "type": "function", "function": {"name": "f", "arguments": "{oops"}}],
+        });
+        let parsed = from_openai_message(&broken).unwrap();
+        assert!(parsed.tool_calls().is_empty());
+        assert_eq!(
+            parsed.invalid_tool_calls()[0].args.as_deref(),
+            Some("{oops")
+        );
+
+        let developer = from_openai_message(&json!({"role": "developer", "content": "x"}));
+        assert!(matches!(developer, Ok(AnyMessage::System(_))));
+        assert!(from_openai_message(&json!({"content": "x"})).is_err());
+    }
+
+    #[test]
+    fn test_strict_mode_rejects_images_for_text_only_models() {
+        let messages: Vec<AnyMessage> = vec![HumanMessage::new("")
+            .with_content(vec![
+                ContentBlock::text("What is this?"),
+                ContentBlock::image_url("https://example.com/cat.png"),
+            ])
+            .into()];
+        let text_only = ModelProfile::new("text-model", 8_000);
+
+        let error = to_openai_messages_strict(&messages, &text_only).unwrap_err();
+
+        assert!(error
+            .0
+            .contains("image block present but text-model is text-only"));
+        assert!(to_openai_messages_strict(&messages, &text_only.with_images()).is_ok());
+    }
+}
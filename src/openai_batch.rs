@@ -0,0 +1,264 @@
+use std::collections::{HashMap, HashSet};
+use std::io::{self, Write};
+
+use serde_json::{json, Value};
+
+use crate::openai::{from_openai_message, to_openai_messages};
+use crate::{AiMessage, AnyMessage, OpenAiError, RequestOptions};
+
+/// The endpoint every generated batch line targets.
+pub const BATCH_CHAT_COMPLETIONS_URL: &str = "/v1/chat/completions";
+
+/// One line of a Batch API input file: a chat-completions request body for
+/// `messages`, with the model and parameters from `options`.
+pub fn to_batch_request(
+    custom_id: &str,
+    messages: &[AnyMessage],
+    options: &RequestOptions,
+) -> Value {
+    let mut body = json!({
+        "model": options.model,
+        "messages": to_openai_messages(messages),
+    });
+    for (key, value) in &options.params {
+        body[key] = value.clone();
+    }
+    json!({
+        "custom_id": custom_id,
+        "method": "POST",
+        "url": BATCH_CHAT_COMPLETIONS_URL,
+        "body": body,
+    })
+}
+
+/// Writes a `.jsonl` batch input file, one request per `(custom_id,
+/// messages)` pair, and returns how many lines were written. The Batch API
+/// rejects files with repeated ids, so a duplicate fails with
+/// `InvalidInput` before anything after it is written.
+pub fn write_batch_file<W, I, S, M>(
+    mut writer: W,
+    requests: I,
+    options: &RequestOptions,
+) -> io::Result<usize>
+where
+    W: Write,
+    I: IntoIterator<Item = (S, M)>,
+    S: AsRef<str>,
+    M: AsRef<[AnyMessage]>,
+{
+    let mut seen = HashSet::new();
+    for (custom_id, messages) in requests {
+        let custom_id = custom_id.as_ref();
+        if !seen.insert(custom_id.to_string()) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("duplicate custom_id '{}'", custom_id),
+            ));
+        }
+        let line = to_batch_request(custom_id, messages.as_ref(), options);
+        serde_json::to_writer(&mut writer, &line)?;
+        writer.write_all(b"\n")?;
+    }
+    Ok(seen.len())
+}
+
+/// A batch output line that did not produce a message. `custom_id` is
+/// missing only when the line itself could not be read.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BatchItemError {
+    pub line: usize,
+    pub custom_id: Option<String>,
+    pub error: OpenAiError,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BatchOutput {
+    pub messages: HashMap<String, AiMessage>,
+    pub errors: Vec<BatchItemError>,
+}
+
+impl BatchOutput {
+    pub fn is_clean(&self) -> bool {
+        self.errors.is_empty()
+    }
+}
+
+/// Parses a Batch API output file into replies keyed by `custom_id`. The
+/// completion's `id`, `model`, `finish_reason` and `usage` are kept in
+/// `response_metadata`; failed requests and unreadable lines are collected
+/// in [`BatchOutput::errors`] instead of aborting the parse.
+pub fn parse_batch_output(input: &str) -> BatchOutput {
+    let mut output = BatchOutput::default();
+    for (index, line) in input.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let record: Value = match serde_json::from_str(line) {
+            Ok(record) => record,
+            Err(err) => {
+                output.errors.push(BatchItemError {
+                    line: index + 1,
+                    custom_id: None,
+                    error: OpenAiError(format!("unreadable batch line: {}", err)),
+                });
+                continue;
+            }
+        };
+        let custom_id = record
+            .get("custom_id")
+            .and_then(Value::as_str)
+            .map(str::to_string);
+        match (custom_id, parse_batch_record(&record)) {
+            (Some(custom_id), Ok(message)) => {
+                output.messages.insert(custom_id, message);
+            }
+            (custom_id, result) => output.errors.push(BatchItemError {
+                line: index + 1,
+                custom_id,
+                error: result
+                    .err()
+                    .unwrap_or_else(|| OpenAiError("batch line without custom_id".to_string())),
+            }),
+        }
+    }
+    output
+}
+
+fn parse_batch_record(record: &Value) -> Result<AiMessage, OpenAiError> {
+    if let Some(error) = record.get("error").filter(|error| !error.is_null()) {
+        return Err(OpenAiError(format!("request failed: {}", error)));
+    }
+    let response = record
+        .get("response")
+        .ok_or_else(|| OpenAiError("batch line without response".to_string()))?;
+    let status = response.get("status_code").and_then(Value::as_u64);
+    let body = response.get("body").unwrap_or(&Value::Null);
+    if status != Some(200) {
+        return Err(OpenAiError(format!(
+            "request failed with status {:?}: {}",
+            status,
+            body.get("error").unwrap_or(body)
+        )));
+    }
+    let choice = body
+        .get("choices")
+        .and_then(|choices| choices.get(0))
+        .ok_or_else(|| OpenAiError("completion without choices".to_string()))?;
+    let message = choice
+        .get("message")
+        .ok_or_else(|| OpenAiError("choice without message".to_string()))?;
+    let AnyMessage::Ai(mut ai) = from_openai_message(message)? else {
+        return Err(OpenAiError(
+            "completion is not an assistant message".to_string(),
+        ));
+    };
+    let metadata = &mut ai.base.response_metadata;
+    for key in ["id", "model", "usage"] {
+        if let Some(value) = body.get(key) {
+            metadata.insert(key, value.clone());
+        }
+    }
+    if let Some(reason) = choice
+        .get("finish_reason")
+        .filter(|reason| !reason.is_null())
+    {
+        metadata.insert("finish_reason", reason.clone());
+    }
+    Ok(ai)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BaseMessage, HumanMessage, SystemMessage};
+
+    #[test]
+    fn test_write_batch_file() {
+        let first: Vec<AnyMessage> = vec![
+            SystemMessage::new("Be brief.").into(),
+            HumanMessage::new("Hi").into(),
+        ];
+        let second: Vec<AnyMessage> = vec![HumanMessage::new("Bye").into()];
+        let options = RequestOptions::new("gpt-4o-mini").with_param("max_tokens", 64);
+        let mut file = Vec::new();
+
+        let written = write_batch_file(
+            &mut file,
+            [("req-1", first.clone()), ("req-2", second)],
+            &options,
+        )
+        .unwrap();
+
+        assert_eq!(written, 2);
+        let lines: Vec<Value> = String::from_utf8(file)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines[0]["custom_id"], "req-1");
+        assert_eq!(lines[0]["url"], BATCH_CHAT_COMPLETIONS_URL);
+        assert_eq!(lines[0]["body"]["model"], "gpt-4o-mini");
+        assert_eq!(lines[0]["body"]["max_tokens"], 64);
+        assert_eq!(lines[0]["body"]["messages"][1]["role"], "user");
+
+        let duplicate = write_batch_file(
+            Vec::new(),
+            [("req-1", &first[..]), ("req-1", &first[..])],
+            &options,
+        );
+        assert_eq!(duplicate.unwrap_err().kind(), io::ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn test_parse_batch_output() {
+        let output = [
+            json!({
+                "id": "batch_req_1",
+                "custom_id": "req-1",
+                "response": {
+                    "status_code": 200,
+                    "body": {
+                        "id": "chatcmpl-1",
+                        "model": "gpt-4o-mini",
+                        "choices": [{
+                            "index": 0,
+                            "message": {"role": "assistant", "content": "Hello!"},
+                            "finish_reason": "stop"
+                        }],
+                        "usage": {"prompt_tokens": 9, "completion_tokens": 2, "total_tokens": 11}
+                    }
+                },
+                "error": null
+            })
+            .to_string(),
+            json!({
+                "id": "batch_req_2",
+                "custom_id": "req-2",
+                "response": {
+                    "status_code": 400,
+                    "body": {"error": {"message": "bad request"}}
+                },
+                "error": null
+            })
+            .to_string(),
+            "{not json".to_string(),
+        ]
+        .join("\n");
+
+        let parsed = parse_batch_output(&output);
+
+        let reply = &parsed.messages["req-1"];
+        assert_eq!(reply.content(), "Hello!");
+        assert_eq!(
+            reply.response_metadata().get_str("finish_reason"),
+            Some("stop")
+        );
+        assert_eq!(reply.response_metadata()["usage"]["total_tokens"], 11);
+
+        assert_eq!(parsed.errors.len(), 2);
+        assert_eq!(parsed.errors[0].custom_id.as_deref(), Some("req-2"));
+        assert!(parsed.errors[0].error.0.contains("bad request"));
+        assert_eq!(parsed.errors[1].line, 3);
+        assert_eq!(parsed.errors[1].custom_id, None);
+    }
+}
@@ -0,0 +1,278 @@
+use crate::tokens::{approximate_message_tokens, approximate_tokens};
+use crate::window::window_messages_by;
+use crate::{BaseMessage, HumanMessage, MessageEnum, SystemMessage};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ContextSection {
+    System,
+    Documents,
+    History,
+    Input,
+}
+
+/// How [`pack_context_with`] makes room. The system prompt and new input are
+/// always kept; the elastic sections in `shrink_order` give up tokens first
+/// to last, so the last one listed is filled first.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PackOptions {
+    pub shrink_order: Vec<ContextSection>,
+    pub documents_header: String,
+}
+
+impl Default for PackOptions {
+    fn default() -> Self {
+        PackOptions {
+            shrink_order: vec![ContextSection::Documents, ContextSection::History],
+            documents_header: "Relevant documents:".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SectionAllocation {
+    pub section: ContextSection,
+    pub requested_tokens: usize,
+    pub allocated_tokens: usize,
+    /// Documents or history messages left out.
+    pub dropped: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PackReport {
+    pub budget: usize,
+    pub sections: Vec<SectionAllocation>,
+}
+
+impl PackReport {
+    pub fn used_tokens(&self) -> usize {
+        self.sections.iter().map(|s| s.allocated_tokens).sum()
+    }
+
+    /// True when the system prompt and input alone exceed the budget.
+    pub fn is_over_budget(&self) -> bool {
+        self.used_tokens() > self.budget
+    }
+
+    pub fn section(&self, section: ContextSection) -> Option<&SectionAllocation> {
+        self.sections.iter().find(|s| s.section == section)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PackedContext {
+    pub messages: Vec<MessageEnum>,
+    pub report: PackReport,
+}
+
+pub fn pack_context(
+    system: Option<&SystemMessage>,
+    history: &[MessageEnum],
+    retrieved_docs: &[String],
+    new_input: &HumanMessage,
+    budget: usize,
+) -> PackedContext {
+    pack_context_with(
+        system,
+        history,
+        retrieved_docs,
+        new_input,
+        budget,
+        &PackOptions::default(),
+    )
+}
+
+/// Packs the sections into messages ordered system, documents, history,
+/// input, within `budget` approximate tokens. Documents are assumed to be
+/// ranked, so the lowest-ranked are dropped first; history keeps its most
+/// recent messages without splitting tool call groups.
+pub fn pack_context_with(
+    system: Option<&SystemMessage>,
+    history: &[MessageEnum],
+    retrieved_docs: &[String],
+    new_input: &HumanMessage,
+    budget: usize,
+    options: &PackOptions,
+) -> PackedContext {
+    let system_tokens = system.map_or(0, |system| approximate_tokens(system.content()));
+    let input_tokens = approximate_tokens(new_input.content());
+    let mut remaining = budget.saturating_sub(system_tokens + input_tokens);
+
+    let mut documents: Vec<&String> = Vec::new();
+    let mut kept_history: Vec<MessageEnum> = Vec::new();
+    let mut documents_tokens = 0;
+    let mut history_tokens = 0;
+
+    for section in options.shrink_order.iter().rev() {
+        match section {
+            ContextSection::Documents => {
+                for doc in retrieved_docs {
+                    let cost = approximate_tokens(doc);
+                    if cost > remaining {
+                        break;
+                    }
+                    remaining -= cost;
+                    documents_tokens += cost;
+                    documents.push(doc);
+                }
+            }
+            ContextSection::History => {
+                kept_history = window_messages_by(history, remaining, |message| {
+                    approximate_tokens(message.content())
+                });
+                history_tokens = approximate_message_tokens(&kept_history);
+                remaining = remaining.saturating_sub(history_tokens);
+            }
+            ContextSection::System | ContextSection::Input => {}
+        }
+    }
+
+    let mut messages: Vec<MessageEnum> = Vec::new();
+    messages.extend(system.cloned().map(MessageEnum::from));
+    if !documents.is_empty() {
+        let body: Vec<&str> = documents.iter().map(|doc| doc.as_str()).collect();
+        messages.push(
+            SystemMessage::new(&format!(
+                "{}\n\n{}",
+                options.documents_header,
+                body.join("\n\n")
+            ))
+            .into(),
+        );
+    }
+    messages.extend(kept_history.iter().cloned());
+    messages.push(new_input.clone().into());
+
+    let sections = vec![
+        SectionAllocation {
+            section: ContextSection::System,
+            requested_tokens: system_tokens,
+            allocated_tokens: system_tokens,
+            dropped: 0,
+        },
+        SectionAllocation {
+            section: ContextSection::Documents,
+            requested_tokens: retrieved_docs
+                .iter()
+                .map(|doc| approximate_tokens(doc))
+                .sum(),
+            allocated_tokens: documents_tokens,
+            dropped: retrieved_docs.len() - documents.len(),
+        },
+        SectionAllocation {
+            section: ContextSection::History,
+            requested_tokens: approximate_message_tokens(history),
+            allocated_tokens: history_tokens,
+            dropped: history.len() - kept_history.len(),
+        },
+        SectionAllocation {
+            section: ContextSection::Input,
+            requested_tokens: input_tokens,
+            allocated_tokens: input_tokens,
+            dropped: 0,
+        },
+    ];
+
+    PackedContext {
+        messages,
+        report: PackReport { budget, sections },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::AiMessage;
+
+    // Every string here is a multiple of four characters, so each costs
+    // exactly len / 4 tokens.
+    fn history() -> Vec<MessageEnum> {
+        vec![
+            HumanMessage::new("old question").into(),
+            AiMessage::new("old answer..").into(),
+            HumanMessage::new("new question").into(),
+            AiMessage::new("new answer..").into(),
+        ]
+    }
+
+    fn docs() -> Vec<String> {
+        vec!["doc one.".to_string(), "doc two.".to_string()]
+    }
+
+    #[test]
+    fn test_everything_fits() {
+        let system = SystemMessage::new("sys.");
+        let input = HumanMessage::new("now?");
+
+        let packed = pack_context(Some(&system), &history(), &docs(), &input, 100);
+
+        assert_eq!(packed.messages.len(), 7);
+        assert!(packed.messages[1]
+            .content()
+            .starts_with("Relevant documents:\n\ndoc one."));
+        assert_eq!(packed.report.used_tokens(), 1 + 4 + 12 + 1);
+        assert!(!packed.report.is_over_budget());
+    }
+
+    #[test]
+    fn test_documents_shrink_before_history() {
+        let input = HumanMessage::new("now?");
+
+        // 1 for input, 12 for history, leaving 2: one document.
+        let packed = pack_context(None, &history(), &docs(), &input, 15);
+
+        let documents = packed.report.section(ContextSection::Documents).unwrap();
+        assert_eq!(documents.dropped, 1);
+        assert_eq!(documents.allocated_tokens, 2);
+        assert_eq!(
+            packed
+                .report
+                .section(ContextSection::History)
+                .unwrap()
+                .dropped,
+            0
+        );
+
+        // 1 for input, history gets 7 of 12: its two latest messages.
+        let packed = pack_context(None, &history(), &docs(), &input, 8);
+        let history_allocation = packed.report.section(ContextSection::History).unwrap();
+        assert_eq!(history_allocation.dropped, 2);
+        assert_eq!(
+            packed
+                .report
+                .section(ContextSection::Documents)
+                .unwrap()
+                .dropped,
+            2
+        );
+        assert_eq!(packed.messages[0].content(), "new question");
+    }
+
+    #[test]
+    fn test_custom_shrink_order() {
+        let input = HumanMessage::new("now?");
+        let options = PackOptions {
+            shrink_order: vec![ContextSection::History, ContextSection::Documents],
+            ..PackOptions::default()
+        };
+
+        let packed = pack_context_with(None, &history(), &docs(), &input, 8, &options);
+
+        assert_eq!(
+            packed
+                .report
+                .section(ContextSection::Documents)
+                .unwrap()
+                .dropped,
+            0
+        );
+        assert_eq!(
+            packed
+                .report
+                .section(ContextSection::History)
+                .unwrap()
+                .dropped,
+            3
+        );
+        assert_eq!(packed.report.used_tokens(), 1 + 4 + 3);
+    }
+}
@@ -0,0 +1,129 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{BaseMessage, Conversation, MessageEnum, MessageType, SystemMessage};
+
+/// `additional_kwargs` key used to tag Ai messages with the persona that
+/// produced them.
+pub const PERSONA_KWARG: &str = "persona";
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Persona {
+    pub name: String,
+    pub system_prompt: String,
+
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub style_hints: Vec<String>,
+}
+
+impl Persona {
+    pub fn new(name: impl Into<String>, system_prompt: impl Into<String>) -> Self {
+        Persona {
+            name: name.into(),
+            system_prompt: system_prompt.into(),
+            style_hints: Vec::new(),
+        }
+    }
+
+    pub fn with_style_hint(mut self, hint: impl Into<String>) -> Self {
+        self.style_hints.push(hint.into());
+        self
+    }
+
+    /// The system prompt followed by the style hints as a bullet list.
+    pub fn render_system_prompt(&self) -> String {
+        if self.style_hints.is_empty() {
+            return self.system_prompt.clone();
+        }
+        let hints: Vec<String> = self
+            .style_hints
+            .iter()
+            .map(|hint| format!("- {}", hint))
+            .collect();
+        format!("{}\n\nStyle:\n{}", self.system_prompt, hints.join("\n"))
+    }
+}
+
+/// Installs `persona` on the conversation: the first system message is
+/// replaced (or one is inserted at the front), and every Ai message after it
+/// that isn't already tagged gets the persona's name under
+/// [`PERSONA_KWARG`]. Messages tagged by an earlier persona keep their tag.
+pub fn apply_persona(conversation: &mut Conversation, persona: &Persona) {
+    let messages = conversation.messages_mut();
+    let prompt = persona.render_system_prompt();
+    let start = match messages
+        .iter()
+        .position(|message| message.message_type() == &MessageType::System)
+    {
+        Some(index) => {
+            messages[index].set_content(&prompt);
+            index
+        }
+        None => {
+            messages.insert(0, SystemMessage::new(&prompt).into());
+            0
+        }
+    };
+
+    for message in &mut messages[start + 1..] {
+        if let MessageEnum::Ai(ai) = message {
+            ai.base
+                .additional_kwargs
+                .entry(PERSONA_KWARG.to_string())
+                .or_insert_with(|| persona.name.clone().into());
+        }
+    }
+}
+
+/// The persona an Ai message was tagged with, if any.
+pub fn persona_of(message: &MessageEnum) -> Option<&str> {
+    match message {
+        MessageEnum::Ai(ai) => ai.base.additional_kwargs.get_str(PERSONA_KWARG),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AiMessage, HumanMessage};
+
+    #[test]
+    fn test_apply_persona_inserts_system_and_tags_ai() {
+        let mut conversation: Conversation = vec![
+            MessageEnum::from(HumanMessage::new("Hi")),
+            MessageEnum::from(AiMessage::new("Ahoy!")),
+        ]
+        .into_iter()
+        .collect();
+        let pirate = Persona::new("pirate", "You are a pirate.").with_style_hint("Say arr");
+
+        apply_persona(&mut conversation, &pirate);
+
+        assert_eq!(conversation.len(), 3);
+        assert_eq!(
+            conversation.messages()[0].content(),
+            "You are a pirate.\n\nStyle:\n- Say arr"
+        );
+        assert_eq!(persona_of(&conversation.messages()[2]), Some("pirate"));
+        assert_eq!(persona_of(&conversation.messages()[1]), None);
+    }
+
+    #[test]
+    fn test_switching_persona_keeps_earlier_tags() {
+        let mut conversation: Conversation = vec![
+            MessageEnum::from(SystemMessage::new("Be helpful.")),
+            MessageEnum::from(AiMessage::new("Ahoy!")),
+        ]
+        .into_iter()
+        .collect();
+
+        apply_persona(&mut conversation, &Persona::new("pirate", "Pirate."));
+        conversation.push(AiMessage::new("Good day."));
+        apply_persona(&mut conversation, &Persona::new("butler", "Butler."));
+
+        assert_eq!(conversation.len(), 3);
+        assert_eq!(conversation.messages()[0].content(), "Butler.");
+        let tags: Vec<Option<&str>> = conversation.iter().map(persona_of).collect();
+        assert_eq!(tags, vec![None, Some("pirate"), Some("butler")]);
+    }
+}
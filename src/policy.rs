@@ -0,0 +1,226 @@
+use std::collections::BTreeSet;
+use std::fmt;
+
+use crate::{BaseMessage, Conversation, MessageEnum, MessageType};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PolicyViolation {
+    TooManyAiTurns { count: usize, max: usize },
+    MissingSystemMessage,
+    ToolNotAllowed { index: usize, tool: Option<String> },
+}
+
+impl fmt::Display for PolicyViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PolicyViolation::TooManyAiTurns { count, max } => {
+                write!(f, "Conversation has {} ai turns, limit is {}", count, max)
+            }
+            PolicyViolation::MissingSystemMessage => {
+                write!(f, "Conversation must start with a system message")
+            }
+            PolicyViolation::ToolNotAllowed { index, tool } => match tool {
+                Some(tool) => write!(f, "message {}: tool '{}' is not allowed", index, tool),
+                None => write!(f, "message {}: unnamed tool result is not allowed", index),
+            },
+        }
+    }
+}
+
+impl std::error::Error for PolicyViolation {}
+
+/// Guardrails checked when appending and before sending a conversation.
+/// Tools are identified by the `name` of their [`crate::ToolMessage`]; an
+/// empty allow-list permits every tool.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ConversationPolicy {
+    pub max_ai_turns: Option<usize>,
+    pub require_system_message: bool,
+    pub allowed_tools: BTreeSet<String>,
+}
+
+impl ConversationPolicy {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_max_ai_turns(mut self, max: usize) -> Self {
+        self.max_ai_turns = Some(max);
+        self
+    }
+
+    pub fn with_required_system_message(mut self) -> Self {
+        self.require_system_message = true;
+        self
+    }
+
+    pub fn with_allowed_tool(mut self, tool: impl Into<String>) -> Self {
+        self.allowed_tools.insert(tool.into());
+        self
+    }
+
+    /// Checks the whole conversation, e.g. right before it is sent.
+    pub fn check(&self, conversation: &Conversation) -> Vec<PolicyViolation> {
+        let mut violations = Vec::new();
+        if self.require_system_message
+            && conversation
+                .messages()
+                .first()
+                .is_none_or(|message| message.message_type() != &MessageType::System)
+        {
+            violations.push(PolicyViolation::MissingSystemMessage);
+        }
+        if let Some(max) = self.max_ai_turns {
+            let count = ai_turns(conversation);
+            if count > max {
+                violations.push(PolicyViolation::TooManyAiTurns { count, max });
+            }
+        }
+        for (index, message) in conversation.iter().enumerate() {
+            if let Some(violation) = self.check_tool(index, message) {
+                violations.push(violation);
+            }
+        }
+        violations
+    }
+
+    /// Checks whether `message` may be appended to `conversation`.
+    pub fn check_append(
+        &self,
+        conversation: &Conversation,
+        message: &MessageEnum,
+    ) -> Result<(), PolicyViolation> {
+        if self.require_system_message
+            && conversation.is_empty()
+            && message.message_type() != &MessageType::System
+        {
+            return Err(PolicyViolation::MissingSystemMessage);
+        }
+        if let Some(max) = self.max_ai_turns {
+            if message.message_type() == &MessageType::Ai {
+                let count = ai_turns(conversation) + 1;
+                if count > max {
+                    return Err(PolicyViolation::TooManyAiTurns { count, max });
+                }
+            }
+        }
+        match self.check_tool(conversation.len(), message) {
+            Some(violation) => Err(violation),
+            None => Ok(()),
+        }
+    }
+
+    fn check_tool(&self, index: usize, message: &MessageEnum) -> Option<PolicyViolation> {
+        if self.allowed_tools.is_empty() || message.message_type() != &MessageType::Tool {
+            return None;
+        }
+        match message.name() {
+            Some(name) if self.allowed_tools.contains(name) => None,
+            name => Some(PolicyViolation::ToolNotAllowed {
+                index,
+                tool: name.map(str::to_string),
+            }),
+        }
+    }
+}
+
+fn ai_turns(conversation: &Conversation) -> usize {
+    conversation
+        .iter()
+        .filter(|message| message.message_type() == &MessageType::Ai)
+        .count()
+}
+
+impl Conversation {
+    /// Appends `message` if `policy` allows it.
+    pub fn push_with_policy(
+        &mut self,
+        message: impl Into<MessageEnum>,
+        policy: &ConversationPolicy,
+    ) -> Result<(), PolicyViolation> {
+        let message = message.into();
+        policy.check_append(self, &message)?;
+        self.push(message);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tool_message::ToolStatus;
+    use crate::{AiMessage, HumanMessage, SystemMessage, ToolMessage};
+
+    fn tool_result(name: Option<&str>) -> ToolMessage {
+        let mut tool = ToolMessage::new("ok", "call_1".to_string(), None, ToolStatus::Success);
+        tool.set_name(name.map(str::to_string));
+        tool
+    }
+
+    #[test]
+    fn test_push_with_policy_enforces_system_and_turns() {
+        let policy = ConversationPolicy::new()
+            .with_required_system_message()
+            .with_max_ai_turns(1);
+        let mut conversation = Conversation::new();
+
+        assert_eq!(
+            conversation.push_with_policy(HumanMessage::new("Hi"), &policy),
+            Err(PolicyViolation::MissingSystemMessage)
+        );
+        conversation
+            .push_with_policy(SystemMessage::new("Be brief."), &policy)
+            .unwrap();
+        conversation
+            .push_with_policy(AiMessage::new("Hello"), &policy)
+            .unwrap();
+        let error = conversation
+            .push_with_policy(AiMessage::new("Again"), &policy)
+            .unwrap_err();
+
+        assert_eq!(error, PolicyViolation::TooManyAiTurns { count: 2, max: 1 });
+        assert_eq!(error.to_string(), "Conversation has 2 ai turns, limit is 1");
+        assert_eq!(conversation.len(), 2);
+    }
+
+    #[test]
+    fn test_tool_allow_list() {
+        let policy = ConversationPolicy::new().with_allowed_tool("search");
+        let mut conversation = Conversation::new();
+
+        conversation
+            .push_with_policy(tool_result(Some("search")), &policy)
+            .unwrap();
+        assert_eq!(
+            conversation.push_with_policy(tool_result(Some("shell")), &policy),
+            Err(PolicyViolation::ToolNotAllowed {
+                index: 1,
+                tool: Some("shell".to_string())
+            })
+        );
+        assert!(conversation
+            .push_with_policy(tool_result(None), &policy)
+            .is_err());
+    }
+
+    #[test]
+    fn test_check_before_send_reports_everything() {
+        let conversation: Conversation = vec![
+            MessageEnum::from(AiMessage::new("a")),
+            MessageEnum::from(AiMessage::new("b")),
+            MessageEnum::from(tool_result(Some("shell"))),
+        ]
+        .into_iter()
+        .collect();
+        let policy = ConversationPolicy::new()
+            .with_required_system_message()
+            .with_max_ai_turns(1)
+            .with_allowed_tool("search");
+
+        let violations = policy.check(&conversation);
+
+        assert_eq!(violations.len(), 3);
+        assert_eq!(violations[0], PolicyViolation::MissingSystemMessage);
+        assert!(ConversationPolicy::new().check(&conversation).is_empty());
+    }
+}
@@ -1,14 +1,30 @@
 pub use crate::base_message::{BaseMessage, BaseMessageFields};
+pub use crate::content::{ContentBlock, MessageContent};
+pub use crate::extensions::Extensions;
+pub use crate::id_generator::generate_message_id;
 pub use crate::message_type::MessageType::*;
 pub use crate::message_type::{InvalidMessageTypeError, MessageType};
+pub use crate::metadata::Metadata;
+pub use crate::provenance::Provenance;
+pub use crate::serde_tag::{deserialize_tagged, serialize_tagged};
+pub use crate::tool_call::{InvalidToolCall, ToolCall};
+pub use crate::voice::VoiceMetadata;
 
 pub use serde::{Deserialize, Serialize};
 
+#[cfg(feature = "macros")]
 pub use crate::define_message;
+#[cfg(feature = "derive")]
 pub use crate::derive_base_message;
 
 pub use crate::ai_message::AiMessage;
 pub use crate::chat_message::ChatMessage;
 pub use crate::human_message::HumanMessage;
+pub use crate::remove_message::RemoveMessage;
 pub use crate::system_message::SystemMessage;
-// pub use crate::tool_message::ToolMessage;
+pub use crate::tool_message::{ToolMessage, ToolStatus};
+pub use crate::unknown_message::UnknownMessage;
+
+pub use crate::any_message::AnyMessage;
+pub use crate::conversation::Conversation;
+pub use crate::message_enum::MessageEnum;
@@ -0,0 +1,192 @@
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex, PoisonError};
+
+use crate::{BaseMessage, BaseMessageFields, MessageContent, MessageType, Metadata, SystemMessage};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PoolStats {
+    pub unique_prompts: usize,
+    pub unique_bytes: usize,
+    pub requests: usize,
+    pub bytes_saved: usize,
+}
+
+/// Deduplicates byte-identical prompts behind shared `Arc<str>` handles.
+#[derive(Debug, Default)]
+pub struct PromptPool {
+    inner: Mutex<PoolInner>,
+}
+
+#[derive(Debug, Default)]
+struct PoolInner {
+    prompts: HashSet<Arc<str>>,
+    stats: PoolStats,
+}
+
+impl PromptPool {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn intern(&self, prompt: &str) -> Arc<str> {
+        let mut inner = self.inner.lock().unwrap_or_else(PoisonError::into_inner);
+        inner.stats.requests += 1;
+        if let Some(existing) = inner.prompts.get(prompt) {
+            let existing = Arc::clone(existing);
+            inner.stats.bytes_saved += prompt.len();
+            return existing;
+        }
+
+        let pooled: Arc<str> = Arc::from(prompt);
+        inner.prompts.insert(Arc::clone(&pooled));
+        inner.stats.unique_prompts += 1;
+        inner.stats.unique_bytes += prompt.len();
+        pooled
+    }
+
+    pub fn stats(&self) -> PoolStats {
+        self.inner
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .stats
+    }
+
+    /// Drops prompts no longer referenced outside the pool.
+    pub fn purge_unused(&self) -> usize {
+        let mut inner = self.inner.lock().unwrap_or_else(PoisonError::into_inner);
+        let before = inner.prompts.len();
+        inner.prompts.retain(|prompt| Arc::strong_count(prompt) > 1);
+        let removed = before - inner.prompts.len();
+        inner.stats.unique_prompts = inner.prompts.len();
+        inner.stats.unique_bytes = inner.prompts.iter().map(|prompt| prompt.len()).sum();
+        removed
+    }
+}
+
+/// A system message whose content lives in a [`PromptPool`], for servers that
+/// keep many sessions with the same system prompt in memory. Convert to a
+/// [`SystemMessage`] when the owned form is needed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PooledSystemMessage {
+    content: Arc<str>,
+    base: BaseMessageFields,
+}
+
+impl PooledSystemMessage {
+    pub fn new(pool: &PromptPool, content: &str) -> Self {
+        PooledSystemMessage {
+            content: pool.intern(content),
+            base: BaseMessageFields::new(MessageContent::default(), MessageType::System),
+        }
+    }
+
+    pub fn from_system_message(pool: &PromptPool, message: SystemMessage) -> Self {
+        let mut base = message.base;
+        let content = pool.intern(&std::mem::take(&mut base.content).text());
+        PooledSystemMessage { content, base }
+    }
+
+    pub fn shared_content(&self) -> &Arc<str> {
+        &self.content
+    }
+
+    pub fn to_system_message(&self) -> SystemMessage {
+        let mut base = self.base.clone();
+        base.content = self.content.as_ref().into();
+        SystemMessage { base }
+    }
+}
+
+impl BaseMessage for PooledSystemMessage {
+    fn content(&self) -> &str {
+        &self.content
+    }
+
+    fn message_type(&self) -> &MessageType {
+        &self.base.message_type
+    }
+
+    fn role(&self) -> &str {
+        self.base.message_type.as_str()
+    }
+
+    fn name(&self) -> Option<&str> {
+        self.base.name.as_deref()
+    }
+
+    fn is_example(&self) -> bool {
+        self.base.example
+    }
+
+    fn additional_kwargs(&self) -> &Metadata {
+        &self.base.additional_kwargs
+    }
+
+    fn response_metadata(&self) -> &Metadata {
+        &self.base.response_metadata
+    }
+
+    fn id(&self) -> Option<&str> {
+        self.base.id.as_deref()
+    }
+}
+
+impl From<PooledSystemMessage> for SystemMessage {
+    fn from(message: PooledSystemMessage) -> Self {
+        message.to_system_message()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pool_deduplicates_identical_prompts() {
+        let pool = PromptPool::new();
+        let first = pool.intern("You are a helpful assistant.");
+        let second = pool.intern("You are a helpful assistant.");
+        let other = pool.intern("You are a pirate.");
+
+        assert!(Arc::ptr_eq(&first, &second));
+        assert!(!Arc::ptr_eq(&first, &other));
+        assert_eq!(
+            pool.stats(),
+            PoolStats {
+                unique_prompts: 2,
+                unique_bytes: 28 + 17,
+                requests: 3,
+                bytes_saved: 28,
+            }
+        );
+    }
+
+    #[test]
+    fn test_pooled_system_message_round_trip() {
+        let pool = PromptPool::new();
+        let mut original = SystemMessage::new("Be concise.");
+        original.set_id(Some("sys-1".to_string()));
+
+        let pooled = PooledSystemMessage::from_system_message(&pool, original.clone());
+        let sibling = PooledSystemMessage::new(&pool, "Be concise.");
+
+        assert!(Arc::ptr_eq(
+            pooled.shared_content(),
+            sibling.shared_content()
+        ));
+        assert_eq!(pooled.content(), "Be concise.");
+        assert_eq!(pooled.id(), Some("sys-1"));
+        assert_eq!(SystemMessage::from(pooled), original);
+    }
+
+    #[test]
+    fn test_purge_unused() {
+        let pool = PromptPool::new();
+        let kept = pool.intern("kept");
+        drop(pool.intern("dropped"));
+
+        assert_eq!(pool.purge_unused(), 1);
+        assert_eq!(pool.stats().unique_prompts, 1);
+        assert!(Arc::ptr_eq(&kept, &pool.intern("kept")));
+    }
+}
@@ -0,0 +1,467 @@
+use std::collections::{BTreeSet, HashMap};
+use std::fmt;
+
+use crate::{AiMessage, AnyMessage, HumanMessage, SystemMessage};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PromptError {
+    InvalidTemplate(String),
+    UnknownRole(String),
+    MissingVariable(String),
+    /// A placeholder was given text, or a text variable was given messages.
+    WrongValueType(String),
+}
+
+impl fmt::Display for PromptError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PromptError::InvalidTemplate(reason) => {
+                write!(f, "Invalid prompt template: {}", reason)
+            }
+            PromptError::UnknownRole(role) => write!(f, "Unknown prompt message role '{}'", role),
+            PromptError::MissingVariable(name) => {
+                write!(f, "Missing value for prompt variable '{}'", name)
+            }
+            PromptError::WrongValueType(name) => {
+                write!(f, "Prompt variable '{}' has the wrong kind of value", name)
+            }
+        }
+    }
+}
+
+impl std::error::Error for PromptError {}
+
+// Lets `from_messages` take ready-made templates, whose conversion cannot fail.
+impl From<std::convert::Infallible> for PromptError {
+    fn from(never: std::convert::Infallible) -> Self {
+        match never {}
+    }
+}
+
+/// A value bound to a template variable: text for `{name}` substitutions,
+/// messages for a [`MessagesPlaceholder`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum PromptValue {
+    Text(String),
+    Messages(Vec<AnyMessage>),
+}
+
+impl From<&str> for PromptValue {
+    fn from(text: &str) -> Self {
+        PromptValue::Text(text.to_string())
+    }
+}
+
+impl From<String> for PromptValue {
+    fn from(text: String) -> Self {
+        PromptValue::Text(text)
+    }
+}
+
+impl From<Vec<AnyMessage>> for PromptValue {
+    fn from(messages: Vec<AnyMessage>) -> Self {
+        PromptValue::Messages(messages)
+    }
+}
+
+pub type PromptVariables = HashMap<String, PromptValue>;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    Literal(String),
+    Variable(String),
+}
+
+/// A string with `{name}` variables; `{{` and `}}` are literal braces.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PromptTemplate {
+    segments: Vec<Segment>,
+}
+
+impl PromptTemplate {
+    pub fn parse(template: &str) -> Result<Self, PromptError> {
+        let mut segments = Vec::new();
+        let mut literal = String::new();
+        let mut chars = template.chars().peekable();
+        while let Some(c) = chars.next() {
+            match (c, chars.peek()) {
+                ('{', Some('{')) | ('}', Some('}')) => {
+                    literal.push(c);
+                    chars.next();
+                }
+                ('{', _) => {
+                    let name: String = chars.by_ref().take_while(|&c| c != '}').collect();
+                    let name = name.trim();
+                    if name.is_empty() || name.contains('{') {
+                        return Err(PromptError::InvalidTemplate(format!(
+                            "bad variable in {:?}",
+                            template
+                        )));
+                    }
+                    if !literal.is_empty() {
+                        segments.push(Segment::Literal(std::mem::take(&mut literal)));
+                    }
+                    segments.push(Segment::Variable(name.to_string()));
+                }
+                ('}', _) => {
+                    return Err(PromptError::InvalidTemplate(format!(
+                        "unmatched '}}' in {:?}",
+                        template
+                    )))
+                }
+                _ => literal.push(c),
+            }
+        }
+        if !literal.is_empty() {
+            segments.push(Segment::Literal(literal));
+        }
+        Ok(PromptTemplate { segments })
+    }
+
+    pub fn variables(&self) -> impl Iterator<Item = &str> {
+        self.segments.iter().filter_map(|segment| match segment {
+            Segment::Variable(name) => Some(name.as_str()),
+            Segment::Literal(_) => None,
+        })
+    }
+
+    pub fn format(&self, variables: &PromptVariables) -> Result<String, PromptError> {
+        self.bind(variables, BindMode::Strict)
+    }
+
+    /// Substitutes the variables that have values. In [`BindMode::Lenient`]
+    /// the rest are left as `{name}` and literal braces stay escaped, so
+    /// the result is still a template that can be bound again.
+    pub fn bind(&self, variables: &PromptVariables, mode: BindMode) -> Result<String, PromptError> {
+        let mut bound = String::new();
+        for segment in &self.segments {
+            match segment {
+                Segment::Literal(text) if mode == BindMode::Lenient => {
+                    bound.push_str(&text.replace('{', "{{").replace('}', "}}"))
+                }
+                Segment::Literal(text) => bound.push_str(text),
+                Segment::Variable(name) => match (variables.get(name), mode) {
+                    (Some(PromptValue::Text(value)), BindMode::Strict) => bound.push_str(value),
+                    (Some(PromptValue::Text(value)), BindMode::Lenient) => {
+                        bound.push_str(&value.replace('{', "{{").replace('}', "}}"))
+                    }
+                    (Some(PromptValue::Messages(_)), _) => {
+                        return Err(PromptError::WrongValueType(name.clone()))
+                    }
+                    (None, BindMode::Strict) => {
+                        return Err(PromptError::MissingVariable(name.clone()))
+                    }
+                    (None, BindMode::Lenient) => {
+                        bound.push('{');
+                        bound.push_str(name);
+                        bound.push('}');
+                    }
+                },
+            }
+        }
+        Ok(bound)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BindMode {
+    /// Every variable needs a text value; the result is plain text.
+    Strict,
+    /// Unbound variables are kept for a later bind.
+    Lenient,
+}
+
+/// Binds `{name}` variables in the text of stored messages, so reusable
+/// message sequences can be filled in at send time. Each text part is
+/// parsed with [`PromptTemplate`]; other fields are copied unchanged.
+pub fn bind_messages(
+    messages: &[AnyMessage],
+    variables: &PromptVariables,
+    mode: BindMode,
+) -> Result<Vec<AnyMessage>, PromptError> {
+    let mut bound = messages.to_vec();
+    for message in &mut bound {
+        for text in message.base_mut().content.texts_mut() {
+            *text = PromptTemplate::parse(text)?.bind(variables, mode)?;
+        }
+    }
+    Ok(bound)
+}
+
+/// Splices a list of messages, typically prior history, into the prompt.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MessagesPlaceholder {
+    pub variable_name: String,
+    /// When set, a missing variable splices in nothing instead of failing.
+    pub optional: bool,
+}
+
+impl MessagesPlaceholder {
+    pub fn new(variable_name: impl Into<String>) -> Self {
+        MessagesPlaceholder {
+            variable_name: variable_name.into(),
+            optional: false,
+        }
+    }
+
+    pub fn optional(mut self) -> Self {
+        self.optional = true;
+        self
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PromptRole {
+    System,
+    Human,
+    Ai,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MessageTemplate {
+    Message {
+        role: PromptRole,
+        template: PromptTemplate,
+    },
+    Placeholder(MessagesPlaceholder),
+}
+
+impl MessageTemplate {
+    /// Roles are `system`, `human`/`user` and `ai`/`assistant`;
+    /// `("placeholder", "{history}")` is shorthand for an optional
+    /// [`MessagesPlaceholder`].
+    pub fn new(role: &str, template: &str) -> Result<Self, PromptError> {
+        let role = match role {
+            "system" => PromptRole::System,
+            "human" | "user" => PromptRole::Human,
+            "ai" | "assistant" => PromptRole::Ai,
+            "placeholder" => {
+                let name = template
+                    .trim()
+                    .strip_prefix('{')
+                    .and_then(|rest| rest.strip_suffix('}'))
+                    .map(str::trim)
+                    .filter(|name| !name.is_empty())
+                    .ok_or_else(|| {
+                        PromptError::InvalidTemplate(format!(
+                            "placeholder must be a single variable, got {:?}",
+                            template
+                        ))
+                    })?;
+                return Ok(MessageTemplate::Placeholder(
+                    MessagesPlaceholder::new(name).optional(),
+                ));
+            }
+            other => return Err(PromptError::UnknownRole(other.to_string())),
+        };
+        Ok(MessageTemplate::Message {
+            role,
+            template: PromptTemplate::parse(template)?,
+        })
+    }
+}
+
+impl From<MessagesPlaceholder> for MessageTemplate {
+    fn from(placeholder: MessagesPlaceholder) -> Self {
+        MessageTemplate::Placeholder(placeholder)
+    }
+}
+
+impl TryFrom<(&str, &str)> for MessageTemplate {
+    type Error = PromptError;
+
+    fn try_from((role, template): (&str, &str)) -> Result<Self, PromptError> {
+        MessageTemplate::new(role, template)
+    }
+}
+
+/// A list of message templates and placeholders that formats into messages.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ChatPromptTemplate {
+    messages: Vec<MessageTemplate>,
+}
+
+impl ChatPromptTemplate {
+    pub fn from_messages<I, M>(messages: I) -> Result<Self, PromptError>
+    where
+        I: IntoIterator<Item = M>,
+        M: TryInto<MessageTemplate>,
+        PromptError: From<M::Error>,
+    {
+        let messages = messages
+            .into_iter()
+            .map(|message| message.try_into().map_err(PromptError::from))
+            .collect::<Result<_, _>>()?;
+        Ok(ChatPromptTemplate { messages })
+    }
+
+    pub fn push(&mut self, message: MessageTemplate) {
+        self.messages.push(message);
+    }
+
+    pub fn messages(&self) -> &[MessageTemplate] {
+        &self.messages
+    }
+
+    /// Every variable the template reads, sorted.
+    pub fn input_variables(&self) -> BTreeSet<&str> {
+        self.messages
+            .iter()
+            .flat_map(|message| -> Vec<&str> {
+                match message {
+                    MessageTemplate::Message { template, .. } => template.variables().collect(),
+                    MessageTemplate::Placeholder(placeholder) => {
+                        vec![placeholder.variable_name.as_str()]
+                    }
+                }
+            })
+            .collect()
+    }
+
+    pub fn format_messages(
+        &self,
+        variables: &PromptVariables,
+    ) -> Result<Vec<AnyMessage>, PromptError> {
+        let mut formatted = Vec::new();
+        for message in &self.messages {
+            match message {
+                MessageTemplate::Message { role, template } => {
+                    let content = template.format(variables)?;
+                    formatted.push(match role {
+                        PromptRole::System => SystemMessage::new(&content).into(),
+                        PromptRole::Human => HumanMessage::new(&content).into(),
+                        PromptRole::Ai => AiMessage::new(&content).into(),
+                    });
+                }
+                MessageTemplate::Placeholder(placeholder) => {
+                    match variables.get(&placeholder.variable_name) {
+                        Some(PromptValue::Messages(messages)) => {
+                            formatted.extend(messages.iter().cloned())
+                        }
+                        Some(PromptValue::Text(_)) => {
+                            return Err(PromptError::WrongValueType(
+                                placeholder.variable_name.clone(),
+                            ))
+                        }
+                        None if placeholder.optional => {}
+                        None => {
+                            return Err(PromptError::MissingVariable(
+                                placeholder.variable_name.clone(),
+                            ))
+                        }
+                    }
+                }
+            }
+        }
+        Ok(formatted)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::BaseMessage;
+
+    fn variables(pairs: Vec<(&str, PromptValue)>) -> PromptVariables {
+        pairs
+            .into_iter()
+            .map(|(name, value)| (name.to_string(), value))
+            .collect()
+    }
+
+    #[test]
+    fn test_format_messages_with_history() {
+        let prompt = ChatPromptTemplate::from_messages([
+            ("system", "You are {name}. Reply in {{json}}."),
+            ("placeholder", "{history}"),
+            ("human", "{input}"),
+        ])
+        .unwrap();
+        let history: Vec<AnyMessage> = vec![
+            HumanMessage::new("Hi").into(),
+            AiMessage::new("Hello!").into(),
+        ];
+
+        let messages = prompt
+            .format_messages(&variables(vec![
+                ("name", "Ada".into()),
+                ("input", "What's 2+2?".into()),
+                ("history", history.into()),
+            ]))
+            .unwrap();
+
+        let contents: Vec<&str> = messages.iter().map(|m| m.content()).collect();
+        assert_eq!(
+            contents,
+            vec![
+                "You are Ada. Reply in {json}.",
+                "Hi",
+                "Hello!",
+                "What's 2+2?"
+            ]
+        );
+        assert!(matches!(messages[0], AnyMessage::System(_)));
+        assert_eq!(
+            prompt.input_variables().into_iter().collect::<Vec<_>>(),
+            vec!["history", "input", "name"]
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        let prompt = ChatPromptTemplate::from_messages([
+            MessageTemplate::new("human", "{input}").unwrap(),
+            MessagesPlaceholder::new("history").into(),
+        ])
+        .unwrap();
+
+        let missing = prompt.format_messages(&variables(vec![("input", "x".into())]));
+        assert_eq!(
+            missing,
+            Err(PromptError::MissingVariable("history".to_string()))
+        );
+        let wrong = prompt.format_messages(&variables(vec![
+            ("input", "x".into()),
+            ("history", "not messages".into()),
+        ]));
+        assert_eq!(
+            wrong,
+            Err(PromptError::WrongValueType("history".to_string()))
+        );
+
+        assert!(matches!(
+            ChatPromptTemplate::from_messages([("critic", "x")]),
+            Err(PromptError::UnknownRole(_))
+        ));
+        assert!(PromptTemplate::parse("unclosed }").is_err());
+        assert!(PromptTemplate::parse("{}").is_err());
+    }
+
+    #[test]
+    fn test_bind_messages() {
+        let stored: Vec<AnyMessage> = vec![
+            SystemMessage::new("You answer in {language}. Use {{braces}} literally.").into(),
+            HumanMessage::new("Summarize {document}.").into(),
+        ];
+        let language = variables(vec![("language", "French".into())]);
+
+        let partial = bind_messages(&stored, &language, BindMode::Lenient).unwrap();
+        assert_eq!(
+            partial[0].content(),
+            "You answer in French. Use {{braces}} literally."
+        );
+        assert_eq!(partial[1].content(), "Summarize {document}.");
+        assert_eq!(
+            bind_messages(&partial, &language, BindMode::Strict),
+            Err(PromptError::MissingVariable("document".to_string()))
+        );
+
+        let document = variables(vec![("document", "the {draft}".into())]);
+        let bound = bind_messages(&partial, &document, BindMode::Strict).unwrap();
+        assert_eq!(
+            bound[0].content(),
+            "You answer in French. Use {braces} literally."
+        );
+        assert_eq!(bound[1].content(), "Summarize the {draft}.");
+    }
+}
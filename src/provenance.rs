@@ -0,0 +1,105 @@
+use serde::{Deserialize, Serialize};
+
+/// Where a message came from inside a multi-stage system: the component that
+/// emitted it and, optionally, the step, model, prompt template and build.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Provenance {
+    pub component: String,
+
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub step: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub model: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub template_id: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub git_sha: Option<String>,
+}
+
+impl Provenance {
+    pub fn new(component: impl Into<String>) -> Self {
+        Provenance {
+            component: component.into(),
+            ..Self::default()
+        }
+    }
+
+    pub fn with_step(mut self, step: impl Into<String>) -> Self {
+        self.step = Some(step.into());
+        self
+    }
+
+    pub fn with_model(mut self, model: impl Into<String>) -> Self {
+        self.model = Some(model.into());
+        self
+    }
+
+    pub fn with_template_id(mut self, template_id: impl Into<String>) -> Self {
+        self.template_id = Some(template_id.into());
+        self
+    }
+
+    pub fn with_git_sha(mut self, git_sha: impl Into<String>) -> Self {
+        self.git_sha = Some(git_sha.into());
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AiMessage, BaseMessage, HumanMessage, MessageEnum};
+
+    #[test]
+    fn test_provenance_builder() {
+        let provenance = Provenance::new("planner")
+            .with_step("draft")
+            .with_model("gpt-4o")
+            .with_template_id("plan-v2")
+            .with_git_sha("abc1234");
+
+        assert_eq!(provenance.component, "planner");
+        assert_eq!(provenance.step.as_deref(), Some("draft"));
+        assert_eq!(provenance.model.as_deref(), Some("gpt-4o"));
+        assert_eq!(provenance.template_id.as_deref(), Some("plan-v2"));
+        assert_eq!(provenance.git_sha.as_deref(), Some("abc1234"));
+    }
+
+    #[test]
+    fn test_message_provenance_round_trip() {
+        let message = AiMessage::new("Here is the plan.")
+            .with_provenance(Provenance::new("planner").with_step("draft"));
+        assert_eq!(message.provenance().unwrap().component, "planner");
+
+        let message: MessageEnum = message.into();
+        let serialized = serde_json::to_string(&message).unwrap();
+        assert!(serialized.contains(r#""provenance":{"component":"planner","step":"draft"}"#));
+
+        let deserialized: MessageEnum = serde_json::from_str(&serialized).unwrap();
+        assert_eq!(deserialized, message);
+        assert_eq!(
+            deserialized
+                .base()
+                .provenance
+                .as_ref()
+                .unwrap()
+                .step
+                .as_deref(),
+            Some("draft")
+        );
+    }
+
+    #[test]
+    fn test_provenance_omitted_when_absent() {
+        let message = HumanMessage::new("Hi");
+
+        assert!(message.provenance().is_none());
+        assert!(!serde_json::to_string(&message)
+            .unwrap()
+            .contains("provenance"));
+        assert_eq!(message.content(), "Hi");
+    }
+}
@@ -0,0 +1,489 @@
+use std::borrow::Cow;
+use std::fmt;
+
+use regex::Regex;
+use serde_json::Value;
+
+use crate::{BaseMessage, Conversation, MessageEnum};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueryError {
+    pub position: usize,
+    pub message: String,
+}
+
+impl QueryError {
+    fn new(position: usize, message: impl Into<String>) -> Self {
+        QueryError {
+            position,
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for QueryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid query at {}: {}", self.position, self.message)
+    }
+}
+
+impl std::error::Error for QueryError {}
+
+/// A message field addressed by a query. `additional_kwargs.<key>`,
+/// `response_metadata.<key>` and `provenance.<field>` reach into maps.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum QueryField {
+    Type,
+    Content,
+    Id,
+    Name,
+    Example,
+    Kwarg(String),
+    Metadata(String),
+    Provenance(String),
+}
+
+impl QueryField {
+    fn parse(name: &str, position: usize) -> Result<Self, QueryError> {
+        if let Some(key) = name.strip_prefix("additional_kwargs.") {
+            return Ok(QueryField::Kwarg(key.to_string()));
+        }
+        if let Some(key) = name.strip_prefix("response_metadata.") {
+            return Ok(QueryField::Metadata(key.to_string()));
+        }
+        if let Some(key) = name.strip_prefix("provenance.") {
+            return Ok(QueryField::Provenance(key.to_string()));
+        }
+        match name {
+            "type" | "role" => Ok(QueryField::Type),
+            "content" => Ok(QueryField::Content),
+            "id" => Ok(QueryField::Id),
+            "name" => Ok(QueryField::Name),
+            "example" => Ok(QueryField::Example),
+            _ => Err(QueryError::new(
+                position,
+                format!("unknown field '{}'", name),
+            )),
+        }
+    }
+
+    // Non-string kwarg and metadata values are compared as their JSON text.
+    fn value<'a>(&self, message: &'a MessageEnum) -> Option<Cow<'a, str>> {
+        let base = message.base();
+        let text = |value: &'a Value| match value {
+            Value::String(text) => Cow::Borrowed(text.as_str()),
+            other => Cow::Owned(other.to_string()),
+        };
+        let value = match self {
+            QueryField::Type => Some(message.role()),
+            QueryField::Content => Some(base.content.as_str()),
+            QueryField::Id => base.id.as_deref(),
+            QueryField::Name => base.name.as_deref(),
+            QueryField::Example => Some(if base.example { "true" } else { "false" }),
+            QueryField::Kwarg(key) => return base.additional_kwargs.get(key).map(text),
+            QueryField::Metadata(key) => return base.response_metadata.get(key).map(text),
+            QueryField::Provenance(key) => {
+                let provenance = base.provenance.as_ref()?;
+                let value = match key.as_str() {
+                    "component" => Some(&provenance.component),
+                    "step" => provenance.step.as_ref(),
+                    "model" => provenance.model.as_ref(),
+                    "template_id" => provenance.template_id.as_ref(),
+                    "git_sha" => provenance.git_sha.as_ref(),
+                    _ => None,
+                };
+                value.map(String::as_str)
+            }
+        };
+        value.map(Cow::Borrowed)
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Condition {
+    Equals(QueryField, String),
+    NotEquals(QueryField, String),
+    Matches(QueryField, Regex),
+    NotMatches(QueryField, Regex),
+}
+
+#[derive(Debug, Clone)]
+enum Expr {
+    Condition(Condition),
+    Not(Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+}
+
+impl Expr {
+    fn eval(&self, message: &MessageEnum) -> bool {
+        match self {
+            Expr::Condition(condition) => condition.eval(message),
+            Expr::Not(inner) => !inner.eval(message),
+            Expr::And(left, right) => left.eval(message) && right.eval(message),
+            Expr::Or(left, right) => left.eval(message) || right.eval(message),
+        }
+    }
+}
+
+impl Condition {
+    // A missing field only satisfies the negated operators.
+    fn eval(&self, message: &MessageEnum) -> bool {
+        match self {
+            Condition::Equals(field, expected) => {
+                field.value(message).is_some_and(|v| v == *expected)
+            }
+            Condition::NotEquals(field, expected) => {
+                field.value(message).is_none_or(|v| v != *expected)
+            }
+            Condition::Matches(field, regex) => {
+                field.value(message).is_some_and(|v| regex.is_match(&v))
+            }
+            Condition::NotMatches(field, regex) => {
+                field.value(message).is_none_or(|v| !regex.is_match(&v))
+            }
+        }
+    }
+}
+
+/// A parsed message query, e.g.
+/// `response_metadata.model =~ 'gpt-4.*' AND type = 'ai'`.
+///
+/// Conditions are `field = 'x'`, `field != 'x'`, `field =~ 'regex'` and
+/// `field !~ 'regex'`, combined with `AND`, `OR`, `NOT` and parentheses.
+/// Regexes are unanchored.
+#[derive(Debug, Clone)]
+pub struct Query {
+    source: String,
+    expr: Expr,
+}
+
+impl Query {
+    pub fn parse(source: &str) -> Result<Self, QueryError> {
+        let tokens = tokenize(source)?;
+        let mut parser = Parser {
+            tokens,
+            cursor: 0,
+            end: source.len(),
+        };
+        let expr = parser.or()?;
+        if let Some((position, token)) = parser.tokens.get(parser.cursor) {
+            return Err(QueryError::new(*position, format!("unexpected {}", token)));
+        }
+        Ok(Query {
+            source: source.to_string(),
+            expr,
+        })
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.source
+    }
+
+    pub fn matches(&self, message: &MessageEnum) -> bool {
+        self.expr.eval(message)
+    }
+
+    pub fn filter<'a>(
+        &self,
+        messages: impl IntoIterator<Item = &'a MessageEnum>,
+    ) -> Vec<&'a MessageEnum> {
+        messages
+            .into_iter()
+            .filter(|message| self.matches(message))
+            .collect()
+    }
+}
+
+pub fn query<'a>(
+    conversation: &'a Conversation,
+    source: &str,
+) -> Result<Vec<&'a MessageEnum>, QueryError> {
+    Ok(Query::parse(source)?.filter(conversation))
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Ident(String),
+    Literal(String),
+    Op(&'static str),
+    And,
+    Or,
+    Not,
+    Open,
+    Close,
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::Ident(name) => write!(f, "'{}'", name),
+            Token::Literal(value) => write!(f, "literal '{}'", value),
+            Token::Op(op) => write!(f, "'{}'", op),
+            Token::And => write!(f, "AND"),
+            Token::Or => write!(f, "OR"),
+            Token::Not => write!(f, "NOT"),
+            Token::Open => write!(f, "'('"),
+            Token::Close => write!(f, "')'"),
+        }
+    }
+}
+
+fn tokenize(source: &str) -> Result<Vec<(usize, Token)>, QueryError> {
+    let mut tokens = Vec::new();
+    let mut chars = source.char_indices().peekable();
+
+    while let Some(&(start, c)) = chars.peek() {
+        match c {
+            c if c.is_whitespace() => {
+                chars.next();
+            }
+            '(' | ')' => {
+                chars.next();
+                tokens.push((start, if c == '(' { Token::Open } else { Token::Close }));
+            }
+            '\'' | '"' => {
+                chars.next();
+                let mut value = String::new();
+                let mut closed = false;
+                while let Some((_, next)) = chars.next() {
+                    match next {
+                        '\\' => {
+                            if let Some((_, escaped)) = chars.next() {
+                                if escaped != c && escaped != '\\' {
+                                    value.push('\\');
+                                }
+                                value.push(escaped);
+                            }
+                        }
+                        next if next == c => {
+                            closed = true;
+                            break;
+                        }
+                        next => value.push(next),
+                    }
+                }
+                if !closed {
+                    return Err(QueryError::new(start, "unterminated string"));
+                }
+                tokens.push((start, Token::Literal(value)));
+            }
+            '=' | '!' => {
+                chars.next();
+                let op = match (c, chars.peek().map(|(_, next)| *next)) {
+                    ('=', Some('~')) => "=~",
+                    ('!', Some('~')) => "!~",
+                    ('!', Some('=')) => "!=",
+                    ('=', Some('=')) => "==",
+                    ('=', _) => "=",
+                    _ => return Err(QueryError::new(start, "expected '!=' or '!~'")),
+                };
+                if op.len() == 2 {
+                    chars.next();
+                }
+                tokens.push((start, Token::Op(if op == "==" { "=" } else { op })));
+            }
+            c if c.is_alphanumeric() || c == '_' => {
+                let mut ident = String::new();
+                while let Some(&(_, next)) = chars.peek() {
+                    if next.is_alphanumeric() || matches!(next, '_' | '.' | '-') {
+                        ident.push(next);
+                        chars.next();
+                    } else {
+                        break;
+                    }
+                }
+                let token = match ident.to_ascii_uppercase().as_str() {
+                    "AND" => Token::And,
+                    "OR" => Token::Or,
+                    "NOT" => Token::Not,
+                    _ => Token::Ident(ident),
+                };
+                tokens.push((start, token));
+            }
+            other => {
+                return Err(QueryError::new(
+                    start,
+                    format!("unexpected character '{}'", other),
+                ));
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<(usize, Token)>,
+    cursor: usize,
+    end: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.cursor).map(|(_, token)| token)
+    }
+
+    fn next(&mut self) -> Result<(usize, Token), QueryError> {
+        let token = self
+            .tokens
+            .get(self.cursor)
+            .cloned()
+            .ok_or_else(|| QueryError::new(self.end, "unexpected end of query"))?;
+        self.cursor += 1;
+        Ok(token)
+    }
+
+    fn or(&mut self) -> Result<Expr, QueryError> {
+        let mut expr = self.and()?;
+        while self.peek() == Some(&Token::Or) {
+            self.cursor += 1;
+            expr = Expr::Or(Box::new(expr), Box::new(self.and()?));
+        }
+        Ok(expr)
+    }
+
+    fn and(&mut self) -> Result<Expr, QueryError> {
+        let mut expr = self.not()?;
+        while self.peek() == Some(&Token::And) {
+            self.cursor += 1;
+            expr = Expr::And(Box::new(expr), Box::new(self.not()?));
+        }
+        Ok(expr)
+    }
+
+    fn not(&mut self) -> Result<Expr, QueryError> {
+        if self.peek() == Some(&Token::Not) {
+            self.cursor += 1;
+            return Ok(Expr::Not(Box::new(self.not()?)));
+        }
+        self.primary()
+    }
+
+    fn primary(&mut self) -> Result<Expr, QueryError> {
+        match self.next()? {
+            (_, Token::Open) => {
+                let expr = self.or()?;
+                match self.next()? {
+                    (_, Token::Close) => Ok(expr),
+                    (position, token) => Err(QueryError::new(
+                        position,
+                        format!("expected ')', found {}", token),
+                    )),
+                }
+            }
+            (position, Token::Ident(name)) => {
+                let field = QueryField::parse(&name, position)?;
+                let (op_position, op) = match self.next()? {
+                    (p, Token::Op(op)) => (p, op),
+                    (p, token) => {
+                        return Err(QueryError::new(
+                            p,
+                            format!("expected operator, found {}", token),
+                        ))
+                    }
+                };
+                let value = match self.next()? {
+                    (_, Token::Literal(value)) => value,
+                    (p, token) => {
+                        return Err(QueryError::new(
+                            p,
+                            format!("expected quoted value, found {}", token),
+                        ))
+                    }
+                };
+                let regex = || {
+                    Regex::new(&value).map_err(|err| {
+                        QueryError::new(op_position, format!("invalid regex: {}", err))
+                    })
+                };
+                let condition = match op {
+                    "=" => Condition::Equals(field, value.clone()),
+                    "!=" => Condition::NotEquals(field, value.clone()),
+                    "=~" => Condition::Matches(field, regex()?),
+                    _ => Condition::NotMatches(field, regex()?),
+                };
+                Ok(Expr::Condition(condition))
+            }
+            (position, token) => Err(QueryError::new(
+                position,
+                format!("expected field, found {}", token),
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AiMessage, HumanMessage, Provenance};
+
+    fn conversation() -> Conversation {
+        let mut first = AiMessage::new("Hello");
+        first
+            .base
+            .response_metadata
+            .insert("model".to_string(), "gpt-4o".to_string());
+        let mut second = AiMessage::new("Hi").with_provenance(Provenance::new("router"));
+        second
+            .base
+            .response_metadata
+            .insert("model".to_string(), "claude-3".to_string());
+        let mut question = HumanMessage::new("gpt-4 question");
+        question.set_id(Some("q1".to_string()));
+
+        let mut conversation = Conversation::new();
+        conversation.push(question);
+        conversation.push(first);
+        conversation.push(second);
+        conversation
+    }
+
+    fn contents(messages: &[&MessageEnum]) -> Vec<String> {
+        messages.iter().map(|m| m.content().to_string()).collect()
+    }
+
+    #[test]
+    fn test_query_regex_and_type() {
+        let conversation = conversation();
+
+        let found = query(
+            &conversation,
+            "response_metadata.model =~ 'gpt-4.*' AND type = 'ai'",
+        )
+        .unwrap();
+        assert_eq!(contents(&found), vec!["Hello"]);
+    }
+
+    #[test]
+    fn test_query_or_not_and_parentheses() {
+        let conversation = conversation();
+
+        let found = query(
+            &conversation,
+            "NOT (type = 'ai') OR provenance.component = \"router\"",
+        )
+        .unwrap();
+        assert_eq!(contents(&found), vec!["gpt-4 question", "Hi"]);
+
+        let found = query(&conversation, "id != 'q1' and content !~ '^H'").unwrap();
+        assert!(found.is_empty());
+    }
+
+    #[test]
+    fn test_query_errors() {
+        assert_eq!(Query::parse("colour = 'red'").unwrap_err().position, 0);
+        assert!(Query::parse("type = 'ai' AND")
+            .unwrap_err()
+            .message
+            .contains("end of query"));
+        assert!(Query::parse("content =~ '('")
+            .unwrap_err()
+            .message
+            .contains("invalid regex"));
+        assert!(Query::parse("type = 'ai")
+            .unwrap_err()
+            .message
+            .contains("unterminated"));
+        assert!(Query::parse("type 'ai'").is_err());
+    }
+}
@@ -0,0 +1,116 @@
+use std::io;
+use std::sync::{Mutex, PoisonError};
+use std::time::Duration;
+
+use redis::{Client, Commands, Connection};
+
+use crate::response_cache::{CachedResponse, ResponseCache};
+
+pub const DEFAULT_KEY_PREFIX: &str = "messageforge:response:";
+
+/// A [`ResponseCache`] in Redis, one JSON string per entry under
+/// `prefix` followed by the hex fingerprint, so several services can share
+/// a server. With an expiry Redis evicts entries itself; the
+/// [`crate::CachedChatModel`] TTL still applies on top.
+pub struct RedisResponseCache {
+    connection: Mutex<Connection>,
+    prefix: String,
+    expiry: Option<Duration>,
+}
+
+impl RedisResponseCache {
+    /// Connects to the server at `url`, e.g. `redis://127.0.0.1/`.
+    pub fn open(url: &str) -> io::Result<Self> {
+        let client = Client::open(url).map_err(redis_error)?;
+        Ok(Self::new(client.get_connection().map_err(redis_error)?))
+    }
+
+    pub fn new(connection: Connection) -> Self {
+        RedisResponseCache {
+            connection: Mutex::new(connection),
+            prefix: DEFAULT_KEY_PREFIX.to_string(),
+            expiry: None,
+        }
+    }
+
+    pub fn with_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.prefix = prefix.into();
+        self
+    }
+
+    /// Stores entries with `SET .. EX`, rounded up to whole seconds.
+    pub fn with_expiry(mut self, expiry: Duration) -> Self {
+        self.expiry = Some(expiry);
+        self
+    }
+
+    fn connection(&self) -> std::sync::MutexGuard<'_, Connection> {
+        self.connection
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl ResponseCache for RedisResponseCache {
+    fn get(&self, key: u64) -> io::Result<Option<CachedResponse>> {
+        let bytes: Option<Vec<u8>> = self
+            .connection()
+            .get(entry_key(&self.prefix, key))
+            .map_err(redis_error)?;
+        bytes
+            .map(|bytes| serde_json::from_slice(&bytes))
+            .transpose()
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+    }
+
+    fn put(&self, key: u64, response: &CachedResponse) -> io::Result<()> {
+        let bytes = serde_json::to_vec(response)?;
+        let key = entry_key(&self.prefix, key);
+        let mut connection = self.connection();
+        match self.expiry {
+            Some(expiry) => {
+                let seconds = expiry.as_millis().div_ceil(1000).max(1) as u64;
+                connection.set_ex(key, bytes, seconds)
+            }
+            None => connection.set(key, bytes),
+        }
+        .map_err(redis_error)
+    }
+
+    fn remove(&self, key: u64) -> io::Result<()> {
+        self.connection()
+            .del(entry_key(&self.prefix, key))
+            .map_err(redis_error)
+    }
+}
+
+fn entry_key(prefix: &str, key: u64) -> String {
+    format!("{}{:016x}", prefix, key)
+}
+
+fn redis_error(err: redis::RedisError) -> io::Error {
+    io::Error::other(err)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_entry_keys_are_prefixed_hex() {
+        assert_eq!(
+            entry_key(DEFAULT_KEY_PREFIX, 0xabc),
+            "messageforge:response:0000000000000abc"
+        );
+        assert_eq!(
+            entry_key("tenant-a:", u64::MAX),
+            "tenant-a:ffffffffffffffff"
+        );
+    }
+
+    #[test]
+    fn test_open_rejects_bad_urls() {
+        let error = RedisResponseCache::open("not a redis url").err().unwrap();
+        assert_eq!(error.kind(), io::ErrorKind::Other);
+    }
+}
@@ -0,0 +1,153 @@
+use std::borrow::Cow;
+
+use crate::prelude::*;
+use crate::AnyMessage;
+
+/// Target id that makes [`apply_message_ops`] clear the whole history.
+pub const REMOVE_ALL_MESSAGES: &str = "__remove_all__";
+
+/// A sentinel asking a history reducer to delete the message with the same
+/// id. It carries no content and is never kept in the history itself.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct RemoveMessage {
+    #[serde(flatten)]
+    pub base: BaseMessageFields,
+}
+
+impl RemoveMessage {
+    pub fn new(id: impl Into<String>) -> Self {
+        let mut base = BaseMessageFields::new("", MessageType::Remove);
+        base.id = Some(id.into());
+        RemoveMessage { base }
+    }
+
+    pub fn remove_all() -> Self {
+        Self::new(REMOVE_ALL_MESSAGES)
+    }
+
+    /// The id of the message to remove.
+    pub fn target_id(&self) -> &str {
+        self.base.id.as_deref().unwrap_or_default()
+    }
+}
+
+impl BaseMessage for RemoveMessage {
+    fn content(&self) -> &str {
+        self.base.content.as_str()
+    }
+
+    fn message_type(&self) -> &MessageType {
+        &self.base.message_type
+    }
+
+    fn role(&self) -> &str {
+        self.base.message_type.as_str()
+    }
+
+    fn name(&self) -> Option<&str> {
+        self.base.name.as_deref()
+    }
+
+    fn content_blocks(&self) -> Cow<'_, [ContentBlock]> {
+        self.base.content.blocks()
+    }
+
+    fn text(&self) -> Cow<'_, str> {
+        self.base.content.text()
+    }
+
+    fn is_example(&self) -> bool {
+        self.base.example
+    }
+
+    fn additional_kwargs(&self) -> &Metadata {
+        &self.base.additional_kwargs
+    }
+
+    fn response_metadata(&self) -> &Metadata {
+        &self.base.response_metadata
+    }
+
+    fn id(&self) -> Option<&str> {
+        self.base.id.as_deref()
+    }
+}
+
+/// Reduces `updates` into `history` the way LangGraph's `add_messages`
+/// does: a [`RemoveMessage`] deletes the message with its id (or, for
+/// [`REMOVE_ALL_MESSAGES`], everything before it), a message whose id is
+/// already present replaces it in place, and anything else is appended.
+/// Removals of ids that are not present are ignored.
+pub fn apply_message_ops(
+    history: &mut Vec<AnyMessage>,
+    updates: impl IntoIterator<Item = AnyMessage>,
+) {
+    for update in updates {
+        match update {
+            AnyMessage::Remove(remove) if remove.target_id() == REMOVE_ALL_MESSAGES => {
+                history.clear();
+            }
+            AnyMessage::Remove(remove) => {
+                history.retain(|message| message.id() != Some(remove.target_id()));
+            }
+            update => {
+                let existing = update
+                    .id()
+                    .and_then(|id| history.iter().position(|message| message.id() == Some(id)));
+                match existing {
+                    Some(index) => history[index] = update,
+                    None => history.push(update),
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(id: &str, content: &str) -> AnyMessage {
+        let mut message: AnyMessage = HumanMessage::new(content).into();
+        message.base_mut().id = Some(id.to_string());
+        message
+    }
+
+    #[test]
+    fn test_apply_message_ops() {
+        let mut history = vec![message("m1", "a"), message("m2", "b"), message("m3", "c")];
+
+        apply_message_ops(
+            &mut history,
+            vec![
+                RemoveMessage::new("m2").into(),
+                message("m3", "c2"),
+                AiMessage::new("new").into(),
+                RemoveMessage::new("missing").into(),
+            ],
+        );
+
+        let contents: Vec<&str> = history.iter().map(|message| message.content()).collect();
+        assert_eq!(contents, vec!["a", "c2", "new"]);
+
+        apply_message_ops(
+            &mut history,
+            vec![RemoveMessage::remove_all().into(), message("m4", "d")],
+        );
+        assert_eq!(history, vec![message("m4", "d")]);
+    }
+
+    #[test]
+    fn test_remove_message_serde() {
+        let remove: AnyMessage = RemoveMessage::new("m1").into();
+
+        let json = serde_json::to_string(&remove).unwrap();
+
+        assert_eq!(
+            json,
+            r#"{"role":"remove","content":"","example":false,"message_type":"Remove","id":"m1"}"#
+        );
+        assert_eq!(serde_json::from_str::<AnyMessage>(&json).unwrap(), remove);
+        assert_eq!(remove.role(), "remove");
+    }
+}
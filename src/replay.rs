@@ -0,0 +1,121 @@
+use std::sync::Arc;
+
+use crate::chat_model::{ChatModel, ChatModelError};
+use crate::clock::{Clock, SystemClock};
+use crate::fingerprint::{canonical_request, fingerprint_of, RequestOptions};
+use crate::response_cache::{CachedResponse, ResponseCache};
+use crate::{AiMessage, MessageEnum};
+
+/// Calls `model` for every request and stores each reply under the
+/// request's fingerprint. Unlike [`crate::CachedChatModel`] it never serves
+/// from the store, so re-recording refreshes every entry.
+pub struct RecordingChatModel<M, S> {
+    model: M,
+    store: S,
+    clock: Arc<dyn Clock>,
+}
+
+impl<M: ChatModel, S: ResponseCache> RecordingChatModel<M, S> {
+    pub fn new(model: M, store: S) -> Self {
+        RecordingChatModel {
+            model,
+            store,
+            clock: Arc::new(SystemClock),
+        }
+    }
+
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    pub fn store(&self) -> &S {
+        &self.store
+    }
+
+    pub fn into_store(self) -> S {
+        self.store
+    }
+}
+
+impl<M: ChatModel, S: ResponseCache> ChatModel for RecordingChatModel<M, S> {
+    fn invoke(
+        &self,
+        messages: &[MessageEnum],
+        options: &RequestOptions,
+    ) -> Result<AiMessage, ChatModelError> {
+        let message = self.model.invoke(messages, options)?;
+        let request = canonical_request(messages, options);
+        let key = fingerprint_of(&request);
+        let recorded = CachedResponse {
+            message,
+            stored_at_ms: self.clock.now_ms(),
+            request,
+        };
+        self.store.put(key, &recorded)?;
+        Ok(recorded.message)
+    }
+}
+
+/// Serves recorded replies without a model; a request that was never
+/// recorded is an error rather than a network call.
+pub struct ReplayChatModel<S> {
+    store: S,
+}
+
+impl<S: ResponseCache> ReplayChatModel<S> {
+    pub fn new(store: S) -> Self {
+        ReplayChatModel { store }
+    }
+}
+
+impl<S: ResponseCache> ChatModel for ReplayChatModel<S> {
+    fn invoke(
+        &self,
+        messages: &[MessageEnum],
+        options: &RequestOptions,
+    ) -> Result<AiMessage, ChatModelError> {
+        let request = canonical_request(messages, options);
+        let key = fingerprint_of(&request);
+        match self.store.get(key)? {
+            Some(recorded) if recorded.answers(&request) => Ok(recorded.message),
+            _ => Err(ChatModelError(format!(
+                "No recording for request {:016x}",
+                key
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::response_cache::FileResponseCache;
+    use crate::{BaseMessage, HumanMessage};
+
+    fn live(messages: &[MessageEnum], _: &RequestOptions) -> Result<AiMessage, ChatModelError> {
+        Ok(AiMessage::new(&format!("live reply to {}", messages.len())))
+    }
+
+    #[test]
+    fn test_record_then_replay() {
+        let dir = std::env::temp_dir().join(format!("messageforge-replay-{}", std::process::id()));
+        let options = RequestOptions::new("gpt-4o").with_param("temperature", 0.0);
+        let request: Vec<MessageEnum> = vec![HumanMessage::new("Hi").into()];
+
+        let recorder = RecordingChatModel::new(live, FileResponseCache::open(&dir).unwrap());
+        let recorded = recorder.invoke(&request, &options).unwrap();
+
+        let replay = ReplayChatModel::new(FileResponseCache::open(&dir).unwrap());
+        assert_eq!(replay.invoke(&request, &options).unwrap(), recorded);
+        assert_eq!(recorded.content(), "live reply to 1");
+
+        let unrecorded = replay.invoke(&request, &RequestOptions::new("gpt-4o"));
+        assert!(unrecorded
+            .unwrap_err()
+            .to_string()
+            .starts_with("Chat model error: No recording for request"));
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
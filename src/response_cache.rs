@@ -0,0 +1,400 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, PoisonError, RwLock};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::chat_model::{ChatModel, ChatModelError};
+use crate::clock::{Clock, SystemClock};
+use crate::fingerprint::{canonical_request, fingerprint_of, RequestOptions};
+use crate::{AiMessage, MessageEnum};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CachedResponse {
+    pub message: AiMessage,
+    pub stored_at_ms: u64,
+
+    /// The [`canonical_request`] this replies to; a hit whose request
+    /// differs is a fingerprint collision and is treated as a miss.
+    #[serde(default)]
+    pub request: String,
+}
+
+impl CachedResponse {
+    pub fn answers(&self, request: &str) -> bool {
+        self.request == request
+    }
+}
+
+/// Storage for replies keyed by [`crate::request_fingerprint`]. Backends
+/// decide nothing about freshness or collisions; [`CachedChatModel`]
+/// applies the TTL and checks the stored request.
+pub trait ResponseCache {
+    fn get(&self, key: u64) -> io::Result<Option<CachedResponse>>;
+
+    fn put(&self, key: u64, response: &CachedResponse) -> io::Result<()>;
+
+    fn remove(&self, key: u64) -> io::Result<()>;
+}
+
+#[derive(Debug, Default)]
+pub struct InMemoryResponseCache {
+    entries: RwLock<HashMap<u64, CachedResponse>>,
+}
+
+impl InMemoryResponseCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl ResponseCache for InMemoryResponseCache {
+    fn get(&self, key: u64) -> io::Result<Option<CachedResponse>> {
+        Ok(self
+            .entries
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(&key)
+            .cloned())
+    }
+
+    fn put(&self, key: u64, response: &CachedResponse) -> io::Result<()> {
+        self.entries
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(key, response.clone());
+        Ok(())
+    }
+
+    fn remove(&self, key: u64) -> io::Result<()> {
+        self.entries
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .remove(&key);
+        Ok(())
+    }
+}
+
+/// One JSON file per entry, named after the fingerprint.
+#[derive(Debug, Clone)]
+pub struct FileResponseCache {
+    dir: PathBuf,
+}
+
+impl FileResponseCache {
+    pub fn open(dir: impl AsRef<Path>) -> io::Result<Self> {
+        fs::create_dir_all(dir.as_ref())?;
+        Ok(FileResponseCache {
+            dir: dir.as_ref().to_path_buf(),
+        })
+    }
+
+    fn path(&self, key: u64) -> PathBuf {
+        self.dir.join(format!("{:016x}.json", key))
+    }
+}
+
+impl ResponseCache for FileResponseCache {
+    fn get(&self, key: u64) -> io::Result<Option<CachedResponse>> {
+        match fs::read(self.path(key)) {
+            Ok(bytes) => serde_json::from_slice(&bytes)
+                .map(Some)
+                .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err)),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+
+    fn put(&self, key: u64, response: &CachedResponse) -> io::Result<()> {
+        let bytes = serde_json::to_vec(response)?;
+        // Write then rename so readers never see a partial entry.
+        let temp = self.path(key).with_extension("tmp");
+        fs::write(&temp, bytes)?;
+        fs::rename(temp, self.path(key))
+    }
+
+    fn remove(&self, key: u64) -> io::Result<()> {
+        match fs::remove_file(self.path(key)) {
+            Err(err) if err.kind() != io::ErrorKind::NotFound => Err(err),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Serves identical requests from `cache` instead of calling `model`.
+/// Concurrent callers with the same request wait for the first one rather
+/// than all calling the model.
+pub struct CachedChatModel<M, C> {
+    model: M,
+    cache: C,
+    ttl: Option<Duration>,
+    clock: Arc<dyn Clock>,
+    in_flight: Mutex<HashMap<u64, Arc<Mutex<()>>>>,
+}
+
+impl<M: ChatModel, C: ResponseCache> CachedChatModel<M, C> {
+    pub fn new(model: M, cache: C) -> Self {
+        CachedChatModel {
+            model,
+            cache,
+            ttl: None,
+            clock: Arc::new(SystemClock),
+            in_flight: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Entries older than `ttl` are treated as misses and replaced.
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = Some(ttl);
+        self
+    }
+
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    pub fn model(&self) -> &M {
+        &self.model
+    }
+
+    pub fn cache(&self) -> &C {
+        &self.cache
+    }
+
+    fn fresh(&self, key: u64, request: &str) -> io::Result<Option<AiMessage>> {
+        let Some(cached) = self
+            .cache
+            .get(key)?
+            .filter(|cached| cached.answers(request))
+        else {
+            return Ok(None);
+        };
+        let expired = self.ttl.is_some_and(|ttl| {
+            self.clock.now_ms().saturating_sub(cached.stored_at_ms) >= ttl.as_millis() as u64
+        });
+        Ok((!expired).then_some(cached.message))
+    }
+
+    fn key_lock(&self, key: u64) -> Arc<Mutex<()>> {
+        let mut in_flight = self
+            .in_flight
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        in_flight.entry(key).or_default().clone()
+    }
+
+    fn release(&self, key: u64, lock: Arc<Mutex<()>>) {
+        let mut in_flight = self
+            .in_flight
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        // Only the map and this caller still hold it: nobody is waiting.
+        if Arc::strong_count(&lock) == 2 {
+            in_flight.remove(&key);
+        }
+    }
+}
+
+impl<M: ChatModel, C: ResponseCache> ChatModel for CachedChatModel<M, C> {
+    fn invoke(
+        &self,
+        messages: &[MessageEnum],
+        options: &RequestOptions,
+    ) -> Result<AiMessage, ChatModelError> {
+        let request = canonical_request(messages, options);
+        let key = fingerprint_of(&request);
+        if let Some(message) = self.fresh(key, &request)? {
+            return Ok(message);
+        }
+
+        let lock = self.key_lock(key);
+        let result = {
+            let _guard = lock.lock().unwrap_or_else(PoisonError::into_inner);
+            // Whoever held the lock before us may have filled the cache.
+            match self.fresh(key, &request) {
+                Ok(Some(message)) => Ok(message),
+                Ok(None) => self.model.invoke(messages, options).and_then(|message| {
+                    let cached = CachedResponse {
+                        message,
+                        stored_at_ms: self.clock.now_ms(),
+                        request,
+                    };
+                    self.cache.put(key, &cached)?;
+                    Ok(cached.message)
+                }),
+                Err(err) => Err(err.into()),
+            }
+        };
+        self.release(key, lock);
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::MockClock;
+    use crate::{BaseMessage, HumanMessage};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    struct CountingModel {
+        calls: AtomicUsize,
+    }
+
+    impl ChatModel for CountingModel {
+        fn invoke(
+            &self,
+            messages: &[MessageEnum],
+            _options: &RequestOptions,
+        ) -> Result<AiMessage, ChatModelError> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            std::thread::sleep(Duration::from_millis(20));
+            Ok(AiMessage::new(&format!("echo: {}", messages.len())))
+        }
+    }
+
+    fn counting() -> CountingModel {
+        CountingModel {
+            calls: AtomicUsize::new(0),
+        }
+    }
+
+    fn request() -> Vec<MessageEnum> {
+        vec![HumanMessage::new("Hi").into()]
+    }
+
+    #[test]
+    fn test_identical_requests_hit_cache() {
+        let cached = CachedChatModel::new(counting(), InMemoryResponseCache::new());
+        let options = RequestOptions::new("gpt-4o");
+
+        let first = cached.invoke(&request(), &options).unwrap();
+        let second = cached.invoke(&request(), &options).unwrap();
+        cached
+            .invoke(&request(), &RequestOptions::new("gpt-4o-mini"))
+            .unwrap();
+
+        assert_eq!(first, second);
+        assert_eq!(cached.model().calls.load(Ordering::SeqCst), 2);
+        assert_eq!(cached.cache().len(), 2);
+
+        let clock = Arc::new(MockClock::new(0));
+        let expiring = CachedChatModel::new(counting(), InMemoryResponseCache::new())
+            .with_ttl(Duration::from_secs(60))
+            .with_clock(clock.clone());
+        expiring.invoke(&request(), &options).unwrap();
+        clock.advance(Duration::from_secs(59));
+        expiring.invoke(&request(), &options).unwrap();
+        assert_eq!(expiring.model().calls.load(Ordering::SeqCst), 1);
+        clock.advance(Duration::from_secs(1));
+        expiring.invoke(&request(), &options).unwrap();
+        assert_eq!(expiring.model().calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn test_colliding_entries_are_misses() {
+        let cached = CachedChatModel::new(counting(), InMemoryResponseCache::new());
+        let options = RequestOptions::new("gpt-4o");
+        let key = crate::request_fingerprint(&request(), &options);
+        let other = CachedResponse {
+            message: AiMessage::new("Answer to another request"),
+            stored_at_ms: 0,
+            request: "{\"messages\":[]}".to_string(),
+        };
+        cached.cache().put(key, &other).unwrap();
+
+        let reply = cached.invoke(&request(), &options).unwrap();
+
+        assert_eq!(reply.content(), "echo: 1");
+        assert_eq!(cached.model().calls.load(Ordering::SeqCst), 1);
+        let stored = cached.cache().get(key).unwrap().unwrap();
+        assert!(stored.answers(&canonical_request(&request(), &options)));
+    }
+
+    #[test]
+    fn test_concurrent_callers_share_one_call() {
+        let cached = CachedChatModel::new(counting(), InMemoryResponseCache::new());
+        let options = RequestOptions::new("gpt-4o");
+
+        std::thread::scope(|scope| {
+            for _ in 0..8 {
+                scope.spawn(|| cached.invoke(&request(), &options).unwrap());
+            }
+        });
+
+        assert_eq!(cached.model().calls.load(Ordering::SeqCst), 1);
+        assert!(cached.in_flight.lock().unwrap().is_empty());
+    }
+
+    // Misses on the first lookup, then fails every lookup after it.
+    struct FlakyCache {
+        gets: AtomicUsize,
+    }
+
+    impl ResponseCache for FlakyCache {
+        fn get(&self, _key: u64) -> io::Result<Option<CachedResponse>> {
+            match self.gets.fetch_add(1, Ordering::SeqCst) {
+                0 => Ok(None),
+                _ => Err(io::Error::other("cache offline")),
+            }
+        }
+
+        fn put(&self, _key: u64, _response: &CachedResponse) -> io::Result<()> {
+            Ok(())
+        }
+
+        fn remove(&self, _key: u64) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_cache_errors_release_the_in_flight_entry() {
+        let cached = CachedChatModel::new(
+            counting(),
+            FlakyCache {
+                gets: AtomicUsize::new(0),
+            },
+        );
+
+        let result = cached.invoke(&request(), &RequestOptions::new("gpt-4o"));
+
+        assert!(result.is_err());
+        assert_eq!(cached.model().calls.load(Ordering::SeqCst), 0);
+        assert!(cached.in_flight.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_file_cache_round_trip() {
+        let dir = std::env::temp_dir().join(format!("messageforge-cache-{}", std::process::id()));
+        let cache = FileResponseCache::open(&dir).unwrap();
+        let response = CachedResponse {
+            message: AiMessage::new("Cached"),
+            stored_at_ms: 42,
+            request: "{}".to_string(),
+        };
+
+        cache.put(7, &response).unwrap();
+        assert_eq!(cache.get(7).unwrap(), Some(response));
+        cache.remove(7).unwrap();
+        cache.remove(7).unwrap();
+        assert_eq!(cache.get(7).unwrap(), None);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
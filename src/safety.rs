@@ -0,0 +1,374 @@
+use std::collections::{HashMap, HashSet};
+use std::ops::Range;
+
+use crate::transformer::{BlockedMessage, MessageTransformer};
+use crate::{BaseMessage, ContentBlock, MessageEnum};
+
+pub const SAFETY_FLAGS_KEY: &str = "safety_flags";
+pub const REDACTED: &str = "[REDACTED]";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FilterAction {
+    Block,
+    Mask,
+    Annotate,
+}
+
+#[derive(Debug, Clone)]
+pub struct ProfanityFilter {
+    words: HashSet<String>,
+    action: FilterAction,
+}
+
+impl ProfanityFilter {
+    pub fn new<I, S>(words: I, action: FilterAction) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        ProfanityFilter {
+            words: words
+                .into_iter()
+                .map(|word| word.as_ref().to_lowercase())
+                .collect(),
+            action,
+        }
+    }
+
+    pub fn find(&self, content: &str) -> Vec<Range<usize>> {
+        word_spans(content)
+            .into_iter()
+            .filter(|span| self.words.contains(&content[span.clone()].to_lowercase()))
+            .collect()
+    }
+}
+
+impl MessageTransformer for ProfanityFilter {
+    fn name(&self) -> &str {
+        "profanity"
+    }
+
+    fn transform(&self, message: MessageEnum) -> Result<MessageEnum, BlockedMessage> {
+        apply_action(
+            self.name(),
+            self.action,
+            message,
+            |text| self.find(text),
+            |matched| "*".repeat(matched.chars().count()),
+        )
+    }
+}
+
+/// Flags long, high-entropy tokens that look like API keys or passwords.
+#[derive(Debug, Clone)]
+pub struct SecretFilter {
+    min_length: usize,
+    min_entropy: f64,
+    action: FilterAction,
+}
+
+impl SecretFilter {
+    pub fn new(action: FilterAction) -> Self {
+        SecretFilter {
+            min_length: 20,
+            min_entropy: 3.5,
+            action,
+        }
+    }
+
+    pub fn with_min_length(mut self, min_length: usize) -> Self {
+        self.min_length = min_length;
+        self
+    }
+
+    pub fn with_min_entropy(mut self, min_entropy: f64) -> Self {
+        self.min_entropy = min_entropy;
+        self
+    }
+
+    pub fn find(&self, content: &str) -> Vec<Range<usize>> {
+        token_spans(content)
+            .into_iter()
+            .filter(|span| self.is_secret(&content[span.clone()]))
+            .collect()
+    }
+
+    fn is_secret(&self, token: &str) -> bool {
+        token.len() >= self.min_length
+            && token.chars().all(is_key_char)
+            && token.chars().any(|c| c.is_ascii_digit())
+            && token.chars().any(|c| c.is_ascii_alphabetic())
+            && shannon_entropy(token) >= self.min_entropy
+    }
+}
+
+impl MessageTransformer for SecretFilter {
+    fn name(&self) -> &str {
+        "secret"
+    }
+
+    fn transform(&self, message: MessageEnum) -> Result<MessageEnum, BlockedMessage> {
+        apply_action(
+            self.name(),
+            self.action,
+            message,
+            |text| self.find(text),
+            |_| REDACTED.to_string(),
+        )
+    }
+}
+
+pub fn shannon_entropy(value: &str) -> f64 {
+    let mut counts: HashMap<char, usize> = HashMap::new();
+    for c in value.chars() {
+        *counts.entry(c).or_default() += 1;
+    }
+    let total = value.chars().count() as f64;
+    counts
+        .values()
+        .map(|&count| {
+            let p = count as f64 / total;
+            -p * p.log2()
+        })
+        .sum()
+}
+
+fn is_key_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || matches!(c, '+' | '/' | '=' | '_' | '-' | '.')
+}
+
+fn word_spans(content: &str) -> Vec<Range<usize>> {
+    spans_where(content, char::is_alphanumeric)
+}
+
+pub(crate) fn token_spans(content: &str) -> Vec<Range<usize>> {
+    spans_where(content, |c| !c.is_whitespace())
+        .into_iter()
+        .filter_map(|span| {
+            let token = &content[span.clone()];
+            let trimmed = token.trim_matches(|c: char| {
+                matches!(
+                    c,
+                    '"' | '\''
+                        | '`'
+                        | '('
+                        | ')'
+                        | '['
+                        | ']'
+                        | '{'
+                        | '}'
+                        | '<'
+                        | '>'
+                        | ','
+                        | ';'
+                        | ':'
+                        | '.'
+                        | '!'
+                        | '?'
+                )
+            });
+            let start = span.start + token.find(trimmed)?;
+            (!trimmed.is_empty()).then(|| start..start + trimmed.len())
+        })
+        .collect()
+}
+
+fn spans_where(content: &str, predicate: impl Fn(char) -> bool) -> Vec<Range<usize>> {
+    let mut spans = Vec::new();
+    let mut start = None;
+    for (index, c) in content.char_indices() {
+        match (predicate(c), start) {
+            (true, None) => start = Some(index),
+            (false, Some(begin)) => {
+                spans.push(begin..index);
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    if let Some(begin) = start {
+        spans.push(begin..content.len());
+    }
+    spans
+}
+
+pub(crate) fn replace_spans(
+    content: &str,
+    spans: &[Range<usize>],
+    replacement: impl Fn(&str) -> String,
+) -> String {
+    let mut result = String::with_capacity(content.len());
+    let mut last = 0;
+    for span in spans {
+        result.push_str(&content[last..span.start]);
+        result.push_str(&replacement(&content[span.clone()]));
+        last = span.end;
+    }
+    result.push_str(&content[last..]);
+    result
+}
+
+/// Runs `find` over every text block, so media blocks are never touched
+/// and text after the first block is still checked.
+fn apply_action(
+    filter: &str,
+    action: FilterAction,
+    mut message: MessageEnum,
+    find: impl Fn(&str) -> Vec<Range<usize>>,
+    mask: impl Fn(&str) -> String,
+) -> Result<MessageEnum, BlockedMessage> {
+    let matches: usize = message
+        .content_blocks()
+        .iter()
+        .filter_map(ContentBlock::as_text)
+        .map(|text| find(text).len())
+        .sum();
+    if matches == 0 {
+        return Ok(message);
+    }
+
+    match action {
+        FilterAction::Block => Err(BlockedMessage::new(
+            filter,
+            format!("{} match(es) found", matches),
+        )),
+        FilterAction::Mask => {
+            for text in message.base_mut().content.texts_mut() {
+                let spans = find(text);
+                if !spans.is_empty() {
+                    *text = replace_spans(text, &spans, &mask);
+                }
+            }
+            Ok(message)
+        }
+        FilterAction::Annotate => {
+            let kwargs = &mut message.base_mut().additional_kwargs;
+            let mut flags = kwargs
+                .get_str(SAFETY_FLAGS_KEY)
+                .unwrap_or_default()
+                .to_string();
+            if !flags.split(',').any(|flag| flag == filter) {
+                if !flags.is_empty() {
+                    flags.push(',');
+                }
+                flags.push_str(filter);
+                kwargs.insert(SAFETY_FLAGS_KEY, flags);
+            }
+            Ok(message)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transformer::apply_transformers;
+    use crate::HumanMessage;
+
+    const API_KEY: &str = "sk-9fX2kQ7vLm3ZpR8tYw1BnC4d";
+
+    #[test]
+    fn test_profanity_filter_mask() {
+        let filter = ProfanityFilter::new(["darn", "heck"], FilterAction::Mask);
+        let message = HumanMessage::new("Darn it, what the heck? Heckle is fine.").into();
+
+        let result = filter.transform(message).unwrap();
+
+        assert_eq!(result.content(), "**** it, what the ****? Heckle is fine.");
+    }
+
+    #[test]
+    fn test_profanity_filter_block() {
+        let filter = ProfanityFilter::new(["darn"], FilterAction::Block);
+
+        let error = filter
+            .transform(HumanMessage::new("darn").into())
+            .unwrap_err();
+        assert_eq!(error.transformer, "profanity");
+
+        let clean = filter.transform(HumanMessage::new("hello").into());
+        assert!(clean.is_ok());
+    }
+
+    #[test]
+    fn test_secret_filter_detects_high_entropy_tokens() {
+        let filter = SecretFilter::new(FilterAction::Mask);
+        let content = format!("Use key \"{}\" for the staging API.", API_KEY);
+
+        let spans = filter.find(&content);
+
+        assert_eq!(spans.len(), 1);
+        assert_eq!(&content[spans[0].clone()], API_KEY);
+    }
+
+    #[test]
+    fn test_secret_filter_ignores_prose() {
+        let filter = SecretFilter::new(FilterAction::Mask);
+        let content = "Internationalization and responsibilities are long words, 2024 too.";
+
+        assert!(filter.find(content).is_empty());
+    }
+
+    #[test]
+    fn test_secret_filter_mask() {
+        let filter = SecretFilter::new(FilterAction::Mask);
+        let message = HumanMessage::new(&format!("token={} ok", API_KEY)).into();
+
+        let result = filter.transform(message).unwrap();
+
+        assert_eq!(result.content(), "[REDACTED] ok");
+    }
+
+    #[test]
+    fn test_annotate_adds_flags_once() {
+        let transformers: Vec<Box<dyn MessageTransformer>> = vec![
+            Box::new(ProfanityFilter::new(["darn"], FilterAction::Annotate)),
+            Box::new(SecretFilter::new(FilterAction::Annotate)),
+            Box::new(ProfanityFilter::new(["darn"], FilterAction::Annotate)),
+        ];
+        let message = HumanMessage::new(&format!("darn, {}", API_KEY)).into();
+
+        let result = apply_transformers(&transformers, message).unwrap();
+
+        assert_eq!(result.content(), format!("darn, {}", API_KEY));
+        assert_eq!(
+            result.additional_kwargs().get_str(SAFETY_FLAGS_KEY),
+            Some("profanity,secret")
+        );
+    }
+
+    #[test]
+    fn test_filters_check_every_text_block() {
+        let multimodal = || -> MessageEnum {
+            let mut message: MessageEnum = HumanMessage::new("").into();
+            message.base_mut().content = vec![
+                ContentBlock::text("Look at this."),
+                ContentBlock::image_url("https://example.com/cat.png"),
+                ContentBlock::text(format!("key: {}", API_KEY)),
+            ]
+            .into();
+            message
+        };
+
+        let masked = SecretFilter::new(FilterAction::Mask)
+            .transform(multimodal())
+            .unwrap();
+        let blocked = SecretFilter::new(FilterAction::Block).transform(multimodal());
+
+        assert_eq!(
+            masked.content_blocks().as_ref(),
+            [
+                ContentBlock::text("Look at this."),
+                ContentBlock::image_url("https://example.com/cat.png"),
+                ContentBlock::text("key: [REDACTED]"),
+            ]
+        );
+        assert!(blocked.is_err());
+    }
+
+    #[test]
+    fn test_shannon_entropy() {
+        assert_eq!(shannon_entropy("aaaa"), 0.0);
+        assert_eq!(shannon_entropy("abcd"), 2.0);
+    }
+}
@@ -0,0 +1,243 @@
+use std::collections::{BTreeSet, HashMap};
+use std::fmt;
+
+use serde_json::{json, Value};
+
+use crate::convert::Converter;
+use crate::AnyMessage;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SerdeRegistryError(pub String);
+
+impl fmt::Display for SerdeRegistryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Custom serialization failed: {}", self.0)
+    }
+}
+
+impl std::error::Error for SerdeRegistryError {}
+
+impl From<serde_json::Error> for SerdeRegistryError {
+    fn from(error: serde_json::Error) -> Self {
+        SerdeRegistryError(error.to_string())
+    }
+}
+
+/// The `type` tag of records written by a registered message serializer.
+pub const CUSTOM_RECORD_TYPE: &str = "custom";
+
+pub type SerializeHook =
+    Box<dyn Fn(&AnyMessage) -> Result<Value, SerdeRegistryError> + Send + Sync>;
+pub type DeserializeHook =
+    Box<dyn Fn(Value) -> Result<AnyMessage, SerdeRegistryError> + Send + Sync>;
+pub type KwargHook = Box<dyn Fn(&Value) -> Result<Value, SerdeRegistryError> + Send + Sync>;
+
+struct TypeHooks {
+    serialize: SerializeHook,
+    deserialize: DeserializeHook,
+}
+
+struct KwargHooks {
+    encode: KwargHook,
+    decode: KwargHook,
+}
+
+/// Runtime (de)serializers for proprietary message types and kwargs,
+/// layered over [`AnyMessage`]'s own JSON shape.
+///
+/// A message whose type is registered is written as
+/// `{"type": "custom", "message_type": .., "data": ..}` with `data` from
+/// its hook; other messages use the normal shape. Registered kwargs are
+/// encoded before either and decoded after reading.
+#[derive(Default)]
+pub struct SerdeRegistry {
+    types: HashMap<String, TypeHooks>,
+    kwargs: HashMap<String, KwargHooks>,
+}
+
+impl SerdeRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers hooks for messages whose `type_name()` is
+    /// `message_type`, e.g. `"critic"` for an unknown type or `"human"`.
+    pub fn register_type<S, D>(
+        &mut self,
+        message_type: impl Into<String>,
+        serialize: S,
+        deserialize: D,
+    ) -> &mut Self
+    where
+        S: Fn(&AnyMessage) -> Result<Value, SerdeRegistryError> + Send + Sync + 'static,
+        D: Fn(Value) -> Result<AnyMessage, SerdeRegistryError> + Send + Sync + 'static,
+    {
+        self.types.insert(
+            message_type.into(),
+            TypeHooks {
+                serialize: Box::new(serialize),
+                deserialize: Box::new(deserialize),
+            },
+        );
+        self
+    }
+
+    pub fn register_kwarg<E, D>(
+        &mut self,
+        key: impl Into<String>,
+        encode: E,
+        decode: D,
+    ) -> &mut Self
+    where
+        E: Fn(&Value) -> Result<Value, SerdeRegistryError> + Send + Sync + 'static,
+        D: Fn(&Value) -> Result<Value, SerdeRegistryError> + Send + Sync + 'static,
+    {
+        self.kwargs.insert(
+            key.into(),
+            KwargHooks {
+                encode: Box::new(encode),
+                decode: Box::new(decode),
+            },
+        );
+        self
+    }
+
+    pub fn to_value(&self, message: &AnyMessage) -> Result<Value, SerdeRegistryError> {
+        let mut message = message.clone();
+        self.map_kwargs(&mut message, |hooks| &hooks.encode)?;
+        let message_type = message.type_name();
+        match self.types.get(message_type) {
+            Some(hooks) => Ok(json!({
+                "type": CUSTOM_RECORD_TYPE,
+                "message_type": message_type,
+                "data": (hooks.serialize)(&message)?,
+            })),
+            None => Ok(serde_json::to_value(&message)?),
+        }
+    }
+
+    pub fn from_value(&self, mut value: Value) -> Result<AnyMessage, SerdeRegistryError> {
+        let is_custom = value.get("type").and_then(Value::as_str) == Some(CUSTOM_RECORD_TYPE);
+        let mut message = if is_custom {
+            let message_type = value
+                .get("message_type")
+                .and_then(Value::as_str)
+                .ok_or_else(|| SerdeRegistryError("record without message_type".to_string()))?;
+            let hooks = self.types.get(message_type).ok_or_else(|| {
+                SerdeRegistryError(format!("no deserializer for '{}'", message_type))
+            })?;
+            (hooks.deserialize)(value.get_mut("data").map(Value::take).unwrap_or_default())?
+        } else {
+            serde_json::from_value(value)?
+        };
+        self.map_kwargs(&mut message, |hooks| &hooks.decode)?;
+        Ok(message)
+    }
+
+    pub fn to_string(&self, message: &AnyMessage) -> Result<String, SerdeRegistryError> {
+        Ok(self.to_value(message)?.to_string())
+    }
+
+    pub fn from_str(&self, json: &str) -> Result<AnyMessage, SerdeRegistryError> {
+        self.from_value(serde_json::from_str(json)?)
+    }
+
+    fn map_kwargs(
+        &self,
+        message: &mut AnyMessage,
+        hook: impl Fn(&KwargHooks) -> &KwargHook,
+    ) -> Result<(), SerdeRegistryError> {
+        for (key, value) in message.base_mut().additional_kwargs.iter_mut() {
+            if let Some(hooks) = self.kwargs.get(key) {
+                *value = hook(hooks)(value)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Debug for SerdeRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SerdeRegistry")
+            .field("types", &self.types.keys().collect::<BTreeSet<_>>())
+            .field("kwargs", &self.kwargs.keys().collect::<BTreeSet<_>>())
+            .finish()
+    }
+}
+
+/// JSON records, e.g. JSONL rows, read through the registry.
+impl<'a> Converter<&'a str> for SerdeRegistry {
+    type Output = AnyMessage;
+    type Error = SerdeRegistryError;
+
+    fn convert(&self, input: &'a str) -> Result<AnyMessage, SerdeRegistryError> {
+        self.from_str(input)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::unknown_message::UnknownMessage;
+    use crate::{BaseMessage, HumanMessage};
+
+    fn registry() -> SerdeRegistry {
+        let mut registry = SerdeRegistry::new();
+        registry
+            .register_type(
+                "critic",
+                |message| Ok(json!({"verdict": message.content()})),
+                |data| {
+                    let verdict = data["verdict"].as_str().unwrap_or_default();
+                    Ok(UnknownMessage::new("critic", verdict).into())
+                },
+            )
+            .register_kwarg(
+                "score",
+                |value| Ok(json!(value.to_string())),
+                |value| {
+                    let text = value.as_str().unwrap_or_default();
+                    serde_json::from_str(text).map_err(SerdeRegistryError::from)
+                },
+            );
+        registry
+    }
+
+    #[test]
+    fn test_custom_type_round_trip() {
+        let registry = registry();
+        let mut critic = UnknownMessage::new("critic", "Looks good.");
+        critic.base.additional_kwargs.insert("score", 0.9);
+        let critic = AnyMessage::from(critic);
+
+        let value = registry.to_value(&critic).unwrap();
+
+        assert_eq!(
+            value,
+            json!({"type": "custom", "message_type": "critic", "data": {"verdict": "Looks good."}})
+        );
+        let parsed = registry.from_value(value).unwrap();
+        assert_eq!(parsed.content(), "Looks good.");
+        assert_eq!(parsed.role(), "critic");
+        assert!(SerdeRegistry::new()
+            .from_str(&registry.to_string(&critic).unwrap())
+            .is_err());
+    }
+
+    #[test]
+    fn test_kwarg_hooks_and_bulk_conversion() {
+        let registry = registry();
+        let mut human = HumanMessage::new("Rate this.");
+        human.base.additional_kwargs.insert("score", 3);
+        let human = AnyMessage::from(human);
+
+        let line = registry.to_string(&human).unwrap();
+        let value: Value = serde_json::from_str(&line).unwrap();
+        assert_eq!(value["role"], "human");
+        assert_eq!(value["additional_kwargs"]["score"], "3");
+
+        let result = registry.convert_all([line.as_str(), "{broken"]);
+        assert_eq!(result.ok, vec![human]);
+        assert_eq!(result.errors[0].0, 1);
+    }
+}
@@ -0,0 +1,55 @@
+use serde::de::{DeserializeOwned, Error as _};
+use serde::ser::Error as _;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::{Map, Value};
+
+use crate::MessageType;
+
+/// Backs `#[base_message(tag = "...")]`: writes `fields` with `tag: value`
+/// in place of the `message_type` key.
+pub fn serialize_tagged<T, S>(
+    fields: &T,
+    tag: &str,
+    value: &str,
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    T: Serialize,
+    S: Serializer,
+{
+    let mut object = match serde_json::to_value(fields).map_err(S::Error::custom)? {
+        Value::Object(object) => object,
+        _ => return Err(S::Error::custom("tagged message must serialize as a map")),
+    };
+    object.remove("message_type");
+    object.insert(tag.to_string(), Value::String(value.to_string()));
+    object.serialize(serializer)
+}
+
+/// Counterpart of [`serialize_tagged`]: checks and strips the tag, then
+/// restores `message_type` before deserializing `T`.
+pub fn deserialize_tagged<'de, T, D>(
+    deserializer: D,
+    tag: &str,
+    value: &str,
+    message_type: &MessageType,
+) -> Result<T, D::Error>
+where
+    T: DeserializeOwned,
+    D: Deserializer<'de>,
+{
+    let mut object = Map::deserialize(deserializer)?;
+    match object.remove(tag) {
+        Some(Value::String(found)) if found == value => {}
+        Some(found) => {
+            return Err(D::Error::custom(format!(
+                "invalid `{}`: expected \"{}\", found {}",
+                tag, value, found
+            )))
+        }
+        None => return Err(D::Error::custom(format!("missing field `{}`", tag))),
+    }
+    let message_type = serde_json::to_value(message_type).map_err(D::Error::custom)?;
+    object.insert("message_type".to_string(), message_type);
+    T::deserialize(Value::Object(object)).map_err(D::Error::custom)
+}
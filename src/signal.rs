@@ -0,0 +1,105 @@
+use serde::{Deserialize, Serialize};
+
+use crate::conversation_delta::{ConversationDelta, DeltaError};
+use crate::Conversation;
+
+/// Live status for UIs. Signals travel alongside deltas but are never
+/// stored in the conversation.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "signal", rename_all = "snake_case")]
+pub enum ConversationSignal {
+    Typing,
+    Thinking,
+    ToolRunning { name: String },
+    Error { message: String },
+}
+
+/// One frame of the sync protocol: either a change to the persisted history
+/// or an ephemeral signal.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ConversationUpdate {
+    Delta(ConversationDelta),
+    Signal(ConversationSignal),
+}
+
+impl ConversationUpdate {
+    pub fn is_ephemeral(&self) -> bool {
+        matches!(self, ConversationUpdate::Signal(_))
+    }
+
+    /// Applies a delta to `conversation`, or hands back the signal without
+    /// touching it.
+    pub fn apply_to(
+        &self,
+        conversation: &mut Conversation,
+    ) -> Result<Option<&ConversationSignal>, DeltaError> {
+        match self {
+            ConversationUpdate::Delta(delta) => {
+                let mut updated = delta.apply(conversation)?;
+                updated.session_id = conversation.session_id.take();
+                updated.forked_from = conversation.forked_from.take();
+                *conversation = updated;
+                Ok(None)
+            }
+            ConversationUpdate::Signal(signal) => Ok(Some(signal)),
+        }
+    }
+}
+
+impl From<ConversationDelta> for ConversationUpdate {
+    fn from(delta: ConversationDelta) -> Self {
+        ConversationUpdate::Delta(delta)
+    }
+}
+
+impl From<ConversationSignal> for ConversationUpdate {
+    fn from(signal: ConversationSignal) -> Self {
+        ConversationUpdate::Signal(signal)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AiMessage, HumanMessage};
+
+    #[test]
+    fn test_signal_wire_format() {
+        let update = ConversationUpdate::from(ConversationSignal::ToolRunning {
+            name: "search".to_string(),
+        });
+
+        let json = serde_json::to_value(&update).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({"kind": "signal", "signal": "tool_running", "name": "search"})
+        );
+        let parsed: ConversationUpdate = serde_json::from_value(json).unwrap();
+        assert_eq!(parsed, update);
+        assert!(parsed.is_ephemeral());
+    }
+
+    #[test]
+    fn test_signals_do_not_touch_history() {
+        let mut client = Conversation::with_session_id("s1");
+        client.push(HumanMessage::new("Hi"));
+        let mut server = client.clone();
+        server.push(AiMessage::new("Hello"));
+
+        let updates: Vec<ConversationUpdate> = vec![
+            ConversationSignal::Typing.into(),
+            ConversationDelta::between(&client, &server).into(),
+        ];
+        let json = serde_json::to_string(&updates).unwrap();
+        let updates: Vec<ConversationUpdate> = serde_json::from_str(&json).unwrap();
+
+        let signal = updates[0].apply_to(&mut client).unwrap();
+        assert_eq!(signal, Some(&ConversationSignal::Typing));
+        assert_eq!(client.len(), 1);
+
+        assert_eq!(updates[1].apply_to(&mut client).unwrap(), None);
+        assert_eq!(client.len(), 2);
+        assert_eq!(client.session_id(), Some("s1"));
+    }
+}
@@ -0,0 +1,179 @@
+use std::collections::HashMap;
+
+use crate::metadata::parse_u64;
+use crate::tool_pairs::requested_tool_call_ids;
+use crate::{BaseMessage, MessageEnum};
+
+/// `response_metadata` key holding a millisecond timestamp.
+pub const TIMESTAMP_KEY: &str = "timestamp";
+
+/// What [`sort_messages`] orders by. Values must parse as `u64`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MessageOrdering {
+    /// `response_metadata["timestamp"]`.
+    Timestamp,
+    /// A sequence number in `additional_kwargs` under this key.
+    SequenceKwarg(String),
+}
+
+impl MessageOrdering {
+    fn key(&self, message: &MessageEnum) -> Option<u64> {
+        let raw = match self {
+            MessageOrdering::Timestamp => message.response_metadata().get(TIMESTAMP_KEY),
+            MessageOrdering::SequenceKwarg(key) => message.additional_kwargs().get(key),
+        };
+        raw.and_then(parse_u64)
+    }
+}
+
+/// Problems noticed while sorting. Indices refer to the sorted order.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SortReport {
+    /// Messages that moved.
+    pub moved: usize,
+    /// Messages with no usable key; they are placed last, in input order.
+    pub missing_key: Vec<usize>,
+    /// Runs of messages sharing a key, so their order came from the tie-break.
+    pub ties: Vec<Vec<usize>>,
+    /// Tool results that sort before the Ai message requesting them.
+    pub results_before_call: Vec<usize>,
+}
+
+impl SortReport {
+    pub fn is_consistent(&self) -> bool {
+        self.missing_key.is_empty() && self.ties.is_empty() && self.results_before_call.is_empty()
+    }
+}
+
+/// Stable sort by `ordering`. Equal keys are ordered by message id, with
+/// messages lacking an id after those that have one, in input order.
+pub fn sort_messages(messages: &mut Vec<MessageEnum>, ordering: &MessageOrdering) -> SortReport {
+    let mut indexed: Vec<(usize, Option<u64>, MessageEnum)> = messages
+        .drain(..)
+        .enumerate()
+        .map(|(index, message)| (index, ordering.key(&message), message))
+        .collect();
+    indexed.sort_by(|(a_index, a_key, a), (b_index, b_key, b)| {
+        let a_key = a_key.unwrap_or(u64::MAX);
+        let b_key = b_key.unwrap_or(u64::MAX);
+        a_key
+            .cmp(&b_key)
+            .then_with(|| a.id().is_none().cmp(&b.id().is_none()))
+            .then_with(|| a.id().cmp(&b.id()))
+            .then_with(|| a_index.cmp(b_index))
+    });
+
+    let mut report = SortReport::default();
+    let mut run: Vec<usize> = Vec::new();
+    for (position, (original, key, _)) in indexed.iter().enumerate() {
+        if position != *original {
+            report.moved += 1;
+        }
+        let Some(key) = key else {
+            report.missing_key.push(position);
+            continue;
+        };
+        let continues_run = position > 0 && indexed[position - 1].1 == Some(*key);
+        if !continues_run {
+            if run.len() > 1 {
+                report.ties.push(std::mem::take(&mut run));
+            }
+            run.clear();
+        }
+        run.push(position);
+    }
+    if run.len() > 1 {
+        report.ties.push(run);
+    }
+
+    *messages = indexed.into_iter().map(|(_, _, message)| message).collect();
+
+    let called_at: HashMap<String, usize> = messages
+        .iter()
+        .enumerate()
+        .flat_map(|(position, message)| {
+            requested_tool_call_ids(message)
+                .into_iter()
+                .map(move |id| (id, position))
+        })
+        .collect();
+    for (position, message) in messages.iter().enumerate() {
+        if let Some(tool) = message.as_tool() {
+            if called_at
+                .get(tool.tool_call_id())
+                .is_some_and(|called| *called > position)
+            {
+                report.results_before_call.push(position);
+            }
+        }
+    }
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tool_message::ToolStatus;
+    use crate::{AiMessage, HumanMessage, ToolCall, ToolMessage};
+
+    fn stamped(mut message: MessageEnum, timestamp: u64, id: &str) -> MessageEnum {
+        let base = message.base_mut();
+        base.response_metadata
+            .insert(TIMESTAMP_KEY.to_string(), timestamp.to_string());
+        base.id = Some(id.to_string());
+        message
+    }
+
+    fn contents(messages: &[MessageEnum]) -> Vec<&str> {
+        messages.iter().map(|message| message.content()).collect()
+    }
+
+    #[test]
+    fn test_sort_by_timestamp_with_id_tie_break() {
+        let mut messages = vec![
+            stamped(AiMessage::new("third").into(), 30, "c"),
+            stamped(HumanMessage::new("second").into(), 20, "b"),
+            stamped(HumanMessage::new("first").into(), 20, "a"),
+            HumanMessage::new("unstamped").into(),
+        ];
+
+        let report = sort_messages(&mut messages, &MessageOrdering::Timestamp);
+
+        assert_eq!(
+            contents(&messages),
+            vec!["first", "second", "third", "unstamped"]
+        );
+        assert_eq!(report.moved, 2);
+        assert_eq!(report.ties, vec![vec![0, 1]]);
+        assert_eq!(report.missing_key, vec![3]);
+        assert!(!report.is_consistent());
+    }
+
+    #[test]
+    fn test_sequence_kwarg_and_causality() {
+        let seq = |mut message: MessageEnum, n: u64| {
+            message
+                .base_mut()
+                .additional_kwargs
+                .insert("seq".to_string(), n.to_string());
+            message
+        };
+        let call = AiMessage::new("").with_tool_calls(vec![ToolCall::new(
+            "call_1",
+            "lookup",
+            serde_json::Value::Null,
+        )]);
+        let result = ToolMessage::new("42", "call_1".to_string(), None, ToolStatus::Success);
+        let mut messages = vec![
+            seq(result.into(), 1),
+            seq(call.into(), 2),
+            seq(HumanMessage::new("q").into(), 0),
+        ];
+
+        let report = sort_messages(&mut messages, &MessageOrdering::SequenceKwarg("seq".into()));
+
+        assert_eq!(contents(&messages), vec!["q", "42", ""]);
+        assert_eq!(report.results_before_call, vec![1]);
+        assert!(report.ties.is_empty());
+    }
+}
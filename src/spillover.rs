@@ -0,0 +1,168 @@
+use std::io;
+
+use crate::debug_dump::elide;
+use crate::metadata::parse_u64;
+use crate::{BlobStore, Conversation, MessageEnum, ToolMessage};
+
+const SPILL_BLOB_KEY: &str = "spilled_blob";
+const SPILL_CHARS_KEY: &str = "spilled_chars";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SpilloverMode {
+    /// Cut the content down, discarding the rest.
+    Truncate,
+    /// Move the full content to a [`BlobStore`], keeping a preview inline.
+    #[default]
+    Spill,
+}
+
+/// Where an oversized tool output went, as recorded on the message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpilledToolOutput {
+    pub blob_key: String,
+    pub original_chars: usize,
+}
+
+/// Keeps tool outputs within `max_chars`. Oversized outputs are replaced by
+/// the first `preview_chars` characters and, in [`SpilloverMode::Spill`], a
+/// reference to the full output in `response_metadata`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ToolOutputPolicy {
+    pub max_chars: usize,
+    pub preview_chars: usize,
+    pub mode: SpilloverMode,
+}
+
+impl Default for ToolOutputPolicy {
+    fn default() -> Self {
+        ToolOutputPolicy {
+            max_chars: 16_000,
+            preview_chars: 2_000,
+            mode: SpilloverMode::Spill,
+        }
+    }
+}
+
+impl ToolOutputPolicy {
+    /// Returns whether the message was changed.
+    pub fn apply(&self, tool: &mut ToolMessage, store: &mut dyn BlobStore) -> io::Result<bool> {
+        let content = tool.base.content.text().into_owned();
+        let chars = content.chars().count();
+        if chars <= self.max_chars {
+            return Ok(false);
+        }
+
+        let preview = elide(&content, self.preview_chars.min(self.max_chars));
+        if self.mode == SpilloverMode::Spill {
+            let key = store.put(content.as_bytes())?;
+            let metadata = &mut tool.base.response_metadata;
+            metadata.insert(SPILL_BLOB_KEY.to_string(), key.clone());
+            metadata.insert(SPILL_CHARS_KEY, chars);
+            tool.base.content = format!("{}\n[full output: {}]", preview, key).into();
+        } else {
+            tool.base.content = preview.into();
+        }
+        Ok(true)
+    }
+
+    /// Applies the policy to every tool message, returning how many changed.
+    pub fn apply_to_conversation(
+        &self,
+        conversation: &mut Conversation,
+        store: &mut dyn BlobStore,
+    ) -> io::Result<usize> {
+        let mut changed = 0;
+        for message in conversation.messages_mut() {
+            if let MessageEnum::Tool(tool) = message {
+                if self.apply(tool, store)? {
+                    changed += 1;
+                }
+            }
+        }
+        Ok(changed)
+    }
+}
+
+impl ToolMessage {
+    pub fn spilled_output(&self) -> Option<SpilledToolOutput> {
+        let metadata = &self.base.response_metadata;
+        Some(SpilledToolOutput {
+            blob_key: metadata.get_str(SPILL_BLOB_KEY)?.to_string(),
+            original_chars: parse_u64(metadata.get(SPILL_CHARS_KEY)?)? as usize,
+        })
+    }
+
+    /// The full output, loaded back from `store` if it was spilled.
+    pub fn full_content(&self, store: &dyn BlobStore) -> io::Result<String> {
+        let Some(spilled) = self.spilled_output() else {
+            return Ok(self.base.content.text().into_owned());
+        };
+        let data = store.get(&spilled.blob_key)?.ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("Missing blob {}", spilled.blob_key),
+            )
+        })?;
+        String::from_utf8(data).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tool_message::ToolStatus;
+    use crate::{BaseMessage, InMemoryBlobStore};
+
+    fn tool_output(content: &str) -> ToolMessage {
+        ToolMessage::new(content, "call_1".to_string(), None, ToolStatus::Success)
+    }
+
+    #[test]
+    fn test_spill_keeps_preview_and_reference() {
+        let policy = ToolOutputPolicy {
+            max_chars: 10,
+            preview_chars: 4,
+            mode: SpilloverMode::Spill,
+        };
+        let mut store = InMemoryBlobStore::new();
+        let mut tool = tool_output("abcdefghijklmnop");
+
+        assert!(policy.apply(&mut tool, &mut store).unwrap());
+
+        let spilled = tool.spilled_output().unwrap();
+        assert_eq!(spilled.original_chars, 16);
+        assert_eq!(
+            tool.content(),
+            format!("abcd… [+12 chars]\n[full output: {}]", spilled.blob_key)
+        );
+        assert_eq!(tool.full_content(&store).unwrap(), "abcdefghijklmnop");
+    }
+
+    #[test]
+    fn test_truncate_and_small_outputs() {
+        let policy = ToolOutputPolicy {
+            max_chars: 10,
+            preview_chars: 4,
+            mode: SpilloverMode::Truncate,
+        };
+        let mut store = InMemoryBlobStore::new();
+        let mut conversation: Conversation =
+            vec![tool_output("short"), tool_output("abcdefghijklmnop")]
+                .into_iter()
+                .collect();
+
+        let changed = policy
+            .apply_to_conversation(&mut conversation, &mut store)
+            .unwrap();
+
+        assert_eq!(changed, 1);
+        assert_eq!(conversation.messages()[0].content(), "short");
+        assert_eq!(conversation.messages()[1].content(), "abcd… [+12 chars]");
+        assert!(store.is_empty());
+        assert!(conversation.messages()[1]
+            .as_tool()
+            .unwrap()
+            .spilled_output()
+            .is_none());
+    }
+}
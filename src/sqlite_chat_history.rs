@@ -0,0 +1,337 @@
+use std::io;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+use rusqlite::{params, Connection, OptionalExtension};
+
+use crate::chat_history::{ChatHistory, DEFAULT_TRASH_RETENTION};
+use crate::clock::{Clock, SystemClock};
+use crate::{AnyMessage, BaseMessage};
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS messageforge_messages (
+        seq INTEGER PRIMARY KEY AUTOINCREMENT,
+        session_id TEXT NOT NULL,
+        message_id TEXT,
+        message TEXT NOT NULL,
+        deleted_at_ms INTEGER
+    );
+    CREATE INDEX IF NOT EXISTS messageforge_messages_session
+        ON messageforge_messages (session_id, seq);
+";
+
+/// A [`ChatHistory`] persisted in SQLite, one row per message in the
+/// crate's JSON format. Many sessions can share a database; each history
+/// reads and writes only its own. Soft-deleted rows are kept in place and
+/// purged once their retention passes.
+#[derive(Debug)]
+pub struct SqliteChatHistory {
+    connection: Connection,
+    session_id: String,
+    retention: Duration,
+    clock: Arc<dyn Clock>,
+}
+
+impl SqliteChatHistory {
+    /// Opens or creates the database at `path`.
+    pub fn open(path: impl AsRef<Path>, session_id: impl Into<String>) -> io::Result<Self> {
+        Self::new(Connection::open(path).map_err(sql_error)?, session_id)
+    }
+
+    pub fn open_in_memory(session_id: impl Into<String>) -> io::Result<Self> {
+        Self::new(Connection::open_in_memory().map_err(sql_error)?, session_id)
+    }
+
+    /// Uses an existing connection, creating the table if it is missing.
+    pub fn new(connection: Connection, session_id: impl Into<String>) -> io::Result<Self> {
+        connection.execute_batch(SCHEMA).map_err(sql_error)?;
+        Ok(SqliteChatHistory {
+            connection,
+            session_id: session_id.into(),
+            retention: DEFAULT_TRASH_RETENTION,
+            clock: Arc::new(SystemClock),
+        })
+    }
+
+    pub fn with_trash_retention(mut self, retention: Duration) -> Self {
+        self.retention = retention;
+        self
+    }
+
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    pub fn session_id(&self) -> &str {
+        &self.session_id
+    }
+
+    /// Every session with stored messages, in order of first use.
+    pub fn sessions(&self) -> io::Result<Vec<String>> {
+        let mut statement = self
+            .connection
+            .prepare(
+                "SELECT session_id FROM messageforge_messages
+                 GROUP BY session_id ORDER BY MIN(seq)",
+            )
+            .map_err(sql_error)?;
+        let rows = statement
+            .query_map([], |row| row.get(0))
+            .map_err(sql_error)?;
+        rows.collect::<Result<_, _>>().map_err(sql_error)
+    }
+
+    fn purge_expired(&self) -> io::Result<()> {
+        let Some(cutoff) = self
+            .clock
+            .now_ms()
+            .checked_sub(self.retention.as_millis() as u64)
+        else {
+            return Ok(());
+        };
+        self.connection
+            .execute(
+                "DELETE FROM messageforge_messages
+                 WHERE session_id = ?1 AND deleted_at_ms <= ?2",
+                params![self.session_id, cutoff as i64],
+            )
+            .map_err(sql_error)?;
+        Ok(())
+    }
+
+    fn write_batch(&mut self, messages: Vec<AnyMessage>, replace: bool) -> io::Result<()> {
+        let transaction = self.connection.transaction().map_err(sql_error)?;
+        if replace {
+            transaction
+                .execute(
+                    "DELETE FROM messageforge_messages
+                     WHERE session_id = ?1 AND deleted_at_ms IS NULL",
+                    [&self.session_id],
+                )
+                .map_err(sql_error)?;
+        }
+        {
+            let mut statement = transaction
+                .prepare(
+                    "INSERT INTO messageforge_messages (session_id, message_id, message)
+                     VALUES (?1, ?2, ?3)",
+                )
+                .map_err(sql_error)?;
+            for message in &messages {
+                let json = serde_json::to_string(message)?;
+                statement
+                    .execute(params![self.session_id, message.id(), json])
+                    .map_err(sql_error)?;
+            }
+        }
+        transaction.commit().map_err(sql_error)
+    }
+}
+
+impl ChatHistory for SqliteChatHistory {
+    fn add_message(&mut self, message: AnyMessage) -> io::Result<()> {
+        let json = serde_json::to_string(&message)?;
+        self.connection
+            .execute(
+                "INSERT INTO messageforge_messages (session_id, message_id, message)
+                 VALUES (?1, ?2, ?3)",
+                params![self.session_id, message.id(), json],
+            )
+            .map_err(sql_error)?;
+        Ok(())
+    }
+
+    /// Inserts the batch in one transaction.
+    fn add_messages(&mut self, messages: Vec<AnyMessage>) -> io::Result<()> {
+        self.write_batch(messages, false)
+    }
+
+    /// Swaps the live rows in one transaction; soft-deleted rows stay.
+    fn replace_messages(&mut self, messages: Vec<AnyMessage>) -> io::Result<()> {
+        self.write_batch(messages, true)
+    }
+
+    fn messages(&self) -> io::Result<Vec<AnyMessage>> {
+        let mut statement = self
+            .connection
+            .prepare(
+                "SELECT message FROM messageforge_messages
+                 WHERE session_id = ?1 AND deleted_at_ms IS NULL ORDER BY seq",
+            )
+            .map_err(sql_error)?;
+        let rows = statement
+            .query_map([&self.session_id], |row| row.get::<_, String>(0))
+            .map_err(sql_error)?;
+        rows.map(|json| Ok(serde_json::from_str(&json.map_err(sql_error)?)?))
+            .collect()
+    }
+
+    fn clear(&mut self) -> io::Result<()> {
+        self.connection
+            .execute(
+                "DELETE FROM messageforge_messages WHERE session_id = ?1",
+                [&self.session_id],
+            )
+            .map_err(sql_error)?;
+        Ok(())
+    }
+
+    fn len(&self) -> io::Result<usize> {
+        self.connection
+            .query_row(
+                "SELECT COUNT(*) FROM messageforge_messages
+                 WHERE session_id = ?1 AND deleted_at_ms IS NULL",
+                [&self.session_id],
+                |row| row.get(0),
+            )
+            .map_err(sql_error)
+    }
+
+    fn soft_delete(&mut self, id: &str) -> io::Result<bool> {
+        self.purge_expired()?;
+        let changed = self
+            .connection
+            .execute(
+                "UPDATE messageforge_messages SET deleted_at_ms = ?3
+                 WHERE seq = (
+                     SELECT MIN(seq) FROM messageforge_messages
+                     WHERE session_id = ?1 AND message_id = ?2 AND deleted_at_ms IS NULL
+                 )",
+                params![self.session_id, id, self.clock.now_ms() as i64],
+            )
+            .map_err(sql_error)?;
+        Ok(changed > 0)
+    }
+
+    fn restore(&mut self, id: &str) -> io::Result<bool> {
+        self.purge_expired()?;
+        let seq: Option<i64> = self
+            .connection
+            .query_row(
+                "SELECT seq FROM messageforge_messages
+                 WHERE session_id = ?1 AND message_id = ?2 AND deleted_at_ms IS NOT NULL
+                 ORDER BY deleted_at_ms DESC, seq DESC LIMIT 1",
+                params![self.session_id, id],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(sql_error)?;
+        let Some(seq) = seq else {
+            return Ok(false);
+        };
+        self.connection
+            .execute(
+                "UPDATE messageforge_messages SET deleted_at_ms = NULL WHERE seq = ?1",
+                [seq],
+            )
+            .map_err(sql_error)?;
+        Ok(true)
+    }
+}
+
+fn sql_error(err: rusqlite::Error) -> io::Error {
+    io::Error::other(err)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::MockClock;
+    use crate::{AiMessage, HumanMessage, ToolCall};
+
+    #[test]
+    fn test_persists_per_session() {
+        let path = std::env::temp_dir().join(format!(
+            "messageforge-history-{}.sqlite",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_file(&path);
+        {
+            let mut history = SqliteChatHistory::open(&path, "s1").unwrap();
+            history.add_message(HumanMessage::new("Hi").into()).unwrap();
+            history
+                .add_messages(vec![
+                    AiMessage::new("")
+                        .with_tool_calls(vec![ToolCall::new(
+                            "call_1",
+                            "search",
+                            serde_json::json!({"q": "rust"}),
+                        )])
+                        .into(),
+                    AiMessage::new("Done").into(),
+                ])
+                .unwrap();
+            let mut other = SqliteChatHistory::open(&path, "s2").unwrap();
+            other
+                .add_message(HumanMessage::new("Other").into())
+                .unwrap();
+        }
+
+        let mut history = SqliteChatHistory::open(&path, "s1").unwrap();
+        let messages = history.messages().unwrap();
+        assert_eq!(history.len().unwrap(), 3);
+        assert_eq!(messages[0].content(), "Hi");
+        assert_eq!(messages[1].tool_calls()[0].args["q"], "rust");
+        assert_eq!(history.sessions().unwrap(), vec!["s1", "s2"]);
+
+        history.clear().unwrap();
+        assert!(history.is_empty().unwrap());
+        let other = SqliteChatHistory::open(&path, "s2").unwrap();
+        assert_eq!(other.len().unwrap(), 1);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_soft_delete_within_retention() {
+        let clock = Arc::new(MockClock::new(0));
+        let mut history = SqliteChatHistory::open_in_memory("s1")
+            .unwrap()
+            .with_trash_retention(Duration::from_secs(60))
+            .with_clock(clock.clone());
+        for id in ["m1", "m2", "m3"] {
+            let mut message: AnyMessage = HumanMessage::new(id).into();
+            message.base_mut().id = Some(id.to_string());
+            history.add_message(message).unwrap();
+        }
+
+        assert!(history.soft_delete("m2").unwrap());
+        assert!(!history.soft_delete("m2").unwrap());
+        assert_eq!(history.len().unwrap(), 2);
+        assert!(history.restore("m2").unwrap());
+        let contents: Vec<String> = history
+            .messages()
+            .unwrap()
+            .iter()
+            .map(|message| message.content().to_string())
+            .collect();
+        assert_eq!(contents, vec!["m1", "m2", "m3"]);
+
+        history.soft_delete("m3").unwrap();
+        clock.advance(Duration::from_secs(60));
+        assert!(!history.restore("m3").unwrap());
+        assert_eq!(history.len().unwrap(), 2);
+    }
+
+    #[test]
+    fn test_replace_messages_keeps_trash() {
+        let mut history = SqliteChatHistory::open_in_memory("s1").unwrap();
+        for id in ["m1", "m2"] {
+            let mut message: AnyMessage = HumanMessage::new(id).into();
+            message.base_mut().id = Some(id.to_string());
+            history.add_message(message).unwrap();
+        }
+        history.soft_delete("m2").unwrap();
+
+        history
+            .replace_messages(vec![AiMessage::new("Replaced").into()])
+            .unwrap();
+
+        let messages = history.messages().unwrap();
+        assert_eq!(messages.len(), 1);
+        assert_eq!(messages[0].content(), "Replaced");
+        assert!(history.restore("m2").unwrap());
+        assert_eq!(history.len().unwrap(), 2);
+    }
+}
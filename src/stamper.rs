@@ -0,0 +1,127 @@
+use std::sync::Arc;
+
+use crate::transformer::{BlockedMessage, MessageTransformer};
+use crate::{BaseMessage, MessageEnum};
+
+type ContextValue = Arc<dyn Fn() -> Option<String> + Send + Sync>;
+
+#[derive(Clone)]
+enum Stamp {
+    Fixed(String),
+    Context(ContextValue),
+}
+
+/// Fills configured `additional_kwargs` on every message it transforms:
+/// fixed values such as a tenant id, environment or app version, and values
+/// read from the current context at append time, such as a request id.
+/// Keys the message already sets are left alone unless overwriting is on.
+#[derive(Clone, Default)]
+pub struct Stamper {
+    stamps: Vec<(String, Stamp)>,
+    overwrite: bool,
+}
+
+impl Stamper {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_field(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.stamps.push((key.into(), Stamp::Fixed(value.into())));
+        self
+    }
+
+    /// Reads the value when each message is stamped; `None` skips the key.
+    pub fn with_context_field(
+        mut self,
+        key: impl Into<String>,
+        value: impl Fn() -> Option<String> + Send + Sync + 'static,
+    ) -> Self {
+        self.stamps
+            .push((key.into(), Stamp::Context(Arc::new(value))));
+        self
+    }
+
+    pub fn with_overwrite(mut self) -> Self {
+        self.overwrite = true;
+        self
+    }
+
+    pub fn stamp(&self, message: &mut MessageEnum) {
+        for (key, stamp) in &self.stamps {
+            if !self.overwrite && message.additional_kwargs().contains_key(key) {
+                continue;
+            }
+            let value = match stamp {
+                Stamp::Fixed(value) => Some(value.clone()),
+                Stamp::Context(value) => value(),
+            };
+            if let Some(value) = value {
+                message
+                    .base_mut()
+                    .additional_kwargs
+                    .insert(key.clone(), value);
+            }
+        }
+    }
+}
+
+impl MessageTransformer for Stamper {
+    fn name(&self) -> &str {
+        "stamper"
+    }
+
+    fn transform(&self, mut message: MessageEnum) -> Result<MessageEnum, BlockedMessage> {
+        self.stamp(&mut message);
+        Ok(message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transformer::apply_transformers;
+    use crate::HumanMessage;
+    use std::sync::Mutex;
+
+    #[test]
+    fn test_fills_fixed_and_context_fields() {
+        let request_id = Arc::new(Mutex::new(Some("req-1".to_string())));
+        let current = request_id.clone();
+        let stamper = Stamper::new()
+            .with_field("tenant_id", "acme")
+            .with_field("environment", "prod")
+            .with_context_field("request_id", move || current.lock().unwrap().clone());
+        let transformers: Vec<Box<dyn MessageTransformer>> = vec![Box::new(stamper)];
+
+        let first = apply_transformers(&transformers, HumanMessage::new("Hi").into()).unwrap();
+        *request_id.lock().unwrap() = None;
+        let second = apply_transformers(&transformers, HumanMessage::new("Bye").into()).unwrap();
+
+        let kwargs = first.additional_kwargs();
+        assert_eq!(kwargs["tenant_id"], "acme");
+        assert_eq!(kwargs["environment"], "prod");
+        assert_eq!(kwargs["request_id"], "req-1");
+        assert!(!second.additional_kwargs().contains_key("request_id"));
+    }
+
+    #[test]
+    fn test_existing_keys_win_unless_overwriting() {
+        let mut message: MessageEnum = HumanMessage::new("Hi").into();
+        message
+            .base_mut()
+            .additional_kwargs
+            .insert("environment".to_string(), "staging".to_string());
+
+        Stamper::new()
+            .with_field("environment", "prod")
+            .stamp(&mut message);
+        assert_eq!(message.additional_kwargs()["environment"], "staging");
+
+        Stamper::new()
+            .with_field("environment", "prod")
+            .with_overwrite()
+            .stamp(&mut message);
+        assert_eq!(message.additional_kwargs()["environment"], "prod");
+    }
+}
@@ -0,0 +1,101 @@
+use crate::window::message_groups;
+use crate::{BaseMessage, MessageEnum, MessageType, SystemMessage};
+
+pub const SUMMARY_PREFIX: &str = "Summary of the earlier conversation:";
+
+/// Sliding summarization: keeps leading system messages, pinned messages and
+/// at least the last `verbatim_tail` messages as they are, and replaces the
+/// rest with one system message holding `summarize`'s output. The tail is
+/// widened rather than split if it would cut a tool call off its results.
+/// `summarize` is not called when there is nothing to fold.
+pub fn summarize_history(
+    messages: &[MessageEnum],
+    verbatim_tail: usize,
+    summarize: impl FnOnce(&[MessageEnum]) -> String,
+) -> Vec<MessageEnum> {
+    let system_end = messages
+        .iter()
+        .take_while(|message| message.message_type() == &MessageType::System)
+        .count();
+    let (system, rest) = messages.split_at(system_end);
+
+    let wanted = rest.len().saturating_sub(verbatim_tail);
+    let tail_start = message_groups(rest)
+        .into_iter()
+        .map(|group| group.start)
+        .filter(|start| *start <= wanted)
+        .max()
+        .unwrap_or(0);
+    let (older, tail) = rest.split_at(tail_start);
+
+    let (pinned, folded): (Vec<&MessageEnum>, Vec<&MessageEnum>) =
+        older.iter().partition(|message| message.is_pinned());
+    if folded.is_empty() {
+        return messages.to_vec();
+    }
+    let folded: Vec<MessageEnum> = folded.into_iter().cloned().collect();
+    let summary = SystemMessage::new(&format!("{}\n{}", SUMMARY_PREFIX, summarize(&folded)));
+
+    system
+        .iter()
+        .cloned()
+        .chain(std::iter::once(summary.into()))
+        .chain(pinned.into_iter().cloned())
+        .chain(tail.iter().cloned())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AiMessage, HumanMessage};
+
+    fn join_contents(messages: &[MessageEnum]) -> String {
+        messages
+            .iter()
+            .map(|message| message.content())
+            .collect::<Vec<_>>()
+            .join(" | ")
+    }
+
+    #[test]
+    fn test_summarize_keeps_system_pins_and_tail() {
+        let mut constraint: MessageEnum = HumanMessage::new("Budget is $500").into();
+        constraint.set_pinned(true);
+        let messages = vec![
+            SystemMessage::new("Be brief.").into(),
+            HumanMessage::new("Plan a trip").into(),
+            constraint,
+            AiMessage::new("Where to?").into(),
+            HumanMessage::new("Lisbon").into(),
+            AiMessage::new("Great choice").into(),
+        ];
+
+        let summarized = summarize_history(&messages, 2, join_contents);
+
+        let contents: Vec<&str> = summarized.iter().map(|m| m.content()).collect();
+        assert_eq!(
+            contents,
+            vec![
+                "Be brief.",
+                "Summary of the earlier conversation:\nPlan a trip | Where to?",
+                "Budget is $500",
+                "Lisbon",
+                "Great choice",
+            ]
+        );
+        assert!(summarized[2].is_pinned());
+    }
+
+    #[test]
+    fn test_nothing_to_fold() {
+        let messages: Vec<MessageEnum> = vec![
+            HumanMessage::new("Hi").into(),
+            AiMessage::new("Hello").into(),
+        ];
+
+        let summarized = summarize_history(&messages, 5, |_| panic!("nothing to summarize"));
+
+        assert_eq!(summarized, messages);
+    }
+}
@@ -23,6 +23,13 @@ mod tests {
         assert_eq!(system_message.content(), "new_content");
     }
 
+    #[test]
+    fn test_systemmessage_new_with_id() {
+        let system_message = SystemMessage::new_with_id("Has an id.");
+        assert!(system_message.id().is_some());
+        assert_eq!(system_message.content(), "Has an id.");
+    }
+
     #[test]
     fn test_systemmessage_example() {
         let mut system_message = SystemMessage::new("Example system message.");
@@ -40,6 +47,36 @@ mod tests {
         assert_eq!(system_message.name(), Some("System Admin"));
     }
 
+    #[test]
+    fn test_systemmessage_with_provenance() {
+        let mut system_message = SystemMessage::new("Traced message.")
+            .with_provenance(Provenance::new("router").with_step("classify"));
+        assert_eq!(system_message.provenance().unwrap().component, "router");
+
+        system_message.set_provenance(None);
+        assert!(system_message.provenance().is_none());
+    }
+
+    #[test]
+    fn test_systemmessage_with_voice() {
+        let mut system_message =
+            SystemMessage::new("Transcribed message.").with_voice(VoiceMetadata::new(1_500));
+        assert_eq!(system_message.voice().unwrap().duration_ms, 1_500);
+
+        system_message.set_voice(None);
+        assert!(system_message.voice().is_none());
+    }
+
+    #[test]
+    fn test_systemmessage_with_content_blocks() {
+        let system_message = SystemMessage::new("").with_content(vec![
+            ContentBlock::text("Describe this."),
+            ContentBlock::image_url("https://example.com/chart.png"),
+        ]);
+        assert_eq!(system_message.content(), "Describe this.");
+        assert_eq!(system_message.content_blocks().len(), 2);
+    }
+
     #[test]
     fn test_systemmessage_with_additional_kwargs() {
         let mut system_message = SystemMessage::new("This is a system message.");
@@ -49,8 +86,8 @@ mod tests {
             .insert("key".to_string(), "value".to_string());
 
         assert_eq!(
-            system_message.additional_kwargs().get("key"),
-            Some(&"value".to_string())
+            system_message.additional_kwargs().get_str("key"),
+            Some("value")
         );
     }
 
@@ -63,8 +100,8 @@ mod tests {
             .insert("source".to_string(), "System Process".to_string());
 
         assert_eq!(
-            system_message.response_metadata().get("source"),
-            Some(&"System Process".to_string())
+            system_message.response_metadata().get_str("source"),
+            Some("System Process")
         );
     }
 
@@ -98,7 +135,7 @@ mod tests {
     fn test_systemmessage_debug_format() {
         let system_message = SystemMessage::new("Debug system message.");
         let debug_output = format!("{:?}", system_message);
-        let expected_debug_output = r#"SystemMessage { base: BaseMessageFields { content: "Debug system message.", example: false, message_type: System, additional_kwargs: {}, response_metadata: {}, id: None, name: None } }"#;
+        let expected_debug_output = r#"SystemMessage { base: BaseMessageFields { content: Text("Debug system message."), example: false, message_type: System, additional_kwargs: {}, response_metadata: {}, id: None, name: None, provenance: None, voice: None, pinned: false, extensions: Extensions { .. } } }"#;
         assert_eq!(debug_output, expected_debug_output);
     }
 
@@ -119,12 +156,12 @@ mod tests {
         assert_eq!(system_message.id(), Some("SYS123"));
         assert_eq!(system_message.name(), Some("System Bot"));
         assert_eq!(
-            system_message.additional_kwargs().get("task"),
-            Some(&"system monitoring".to_string())
+            system_message.additional_kwargs().get_str("task"),
+            Some("system monitoring")
         );
         assert_eq!(
-            system_message.response_metadata().get("process"),
-            Some(&"systemd".to_string())
+            system_message.response_metadata().get_str("process"),
+            Some("systemd")
         );
 
         let expected_json = json!({
@@ -144,4 +181,24 @@ mod tests {
         let serialized: Value = serde_json::to_value(&system_message).unwrap();
         assert_eq!(serialized, expected_json);
     }
+
+    #[test]
+    fn test_systemmessage_builder() {
+        let system_message = SystemMessage::builder()
+            .content("Be brief.")
+            .name("policy")
+            .id("s1")
+            .kwarg("version", 3)
+            .example(true)
+            .build();
+
+        assert_eq!(system_message.content(), "Be brief.");
+        assert_eq!(system_message.name(), Some("policy"));
+        assert_eq!(system_message.id(), Some("s1"));
+        assert_eq!(
+            system_message.additional_kwargs().get_u64("version"),
+            Some(3)
+        );
+        assert!(system_message.is_example());
+    }
 }
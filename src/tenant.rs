@@ -0,0 +1,231 @@
+use std::collections::HashMap;
+use std::io;
+use std::sync::{PoisonError, RwLock};
+
+use crate::{AnyMessage, BaseMessage, ChatHistory};
+
+/// `additional_kwargs` key recording which tenant wrote a message.
+pub const TENANT_ID_KEY: &str = "tenant_id";
+
+const SEPARATOR: char = ':';
+
+/// Per-tenant counters, for quotas and billing dashboards.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TenantUsage {
+    pub sessions: usize,
+    pub messages_written: usize,
+    pub messages_read: usize,
+}
+
+/// Keeps each tenant's sessions apart over any [`ChatHistory`] backend.
+/// Every session lives under the key `tenant:session`, handed to `open` the
+/// first time it is written, so backends that persist by key never share
+/// storage between tenants. Writes stamp [`TENANT_ID_KEY`], and reads fail
+/// with `PermissionDenied` if a message from another tenant shows up.
+pub struct TenantScopedHistory<H, F> {
+    histories: HashMap<String, H>,
+    open: F,
+    usage: RwLock<HashMap<String, TenantUsage>>,
+}
+
+impl<H, F> TenantScopedHistory<H, F>
+where
+    H: ChatHistory,
+    F: Fn(&str) -> io::Result<H>,
+{
+    pub fn new(open: F) -> Self {
+        TenantScopedHistory {
+            histories: HashMap::new(),
+            open,
+            usage: RwLock::new(HashMap::new()),
+        }
+    }
+
+    pub fn add_message(
+        &mut self,
+        tenant: &str,
+        session: &str,
+        mut message: AnyMessage,
+    ) -> io::Result<()> {
+        let key = scoped_key(tenant, session)?;
+        message
+            .base_mut()
+            .additional_kwargs
+            .insert(TENANT_ID_KEY.to_string(), tenant.to_string());
+        if !self.histories.contains_key(&key) {
+            let history = (self.open)(&key)?;
+            self.histories.insert(key.clone(), history);
+            self.update_usage(tenant, |usage| usage.sessions += 1);
+        }
+        self.histories
+            .get_mut(&key)
+            .expect("opened above")
+            .add_message(message)?;
+        self.update_usage(tenant, |usage| usage.messages_written += 1);
+        Ok(())
+    }
+
+    /// The session's messages; empty if the tenant never wrote to it.
+    pub fn messages(&self, tenant: &str, session: &str) -> io::Result<Vec<AnyMessage>> {
+        let key = scoped_key(tenant, session)?;
+        let Some(history) = self.histories.get(&key) else {
+            return Ok(Vec::new());
+        };
+        let messages = history.messages()?;
+        if let Some(foreign) = messages
+            .iter()
+            .find(|message| message.additional_kwargs().get_str(TENANT_ID_KEY) != Some(tenant))
+        {
+            return Err(io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                format!(
+                    "history of tenant '{}' holds a message from tenant {:?}",
+                    tenant,
+                    foreign.additional_kwargs().get(TENANT_ID_KEY)
+                ),
+            ));
+        }
+        self.update_usage(tenant, |usage| usage.messages_read += messages.len());
+        Ok(messages)
+    }
+
+    pub fn clear(&mut self, tenant: &str, session: &str) -> io::Result<()> {
+        let key = scoped_key(tenant, session)?;
+        match self.histories.get_mut(&key) {
+            Some(history) => history.clear(),
+            None => Ok(()),
+        }
+    }
+
+    pub fn soft_delete(&mut self, tenant: &str, session: &str, id: &str) -> io::Result<bool> {
+        let key = scoped_key(tenant, session)?;
+        match self.histories.get_mut(&key) {
+            Some(history) => history.soft_delete(id),
+            None => Ok(false),
+        }
+    }
+
+    pub fn restore(&mut self, tenant: &str, session: &str, id: &str) -> io::Result<bool> {
+        let key = scoped_key(tenant, session)?;
+        match self.histories.get_mut(&key) {
+            Some(history) => history.restore(id),
+            None => Ok(false),
+        }
+    }
+
+    /// The tenant's session ids, sorted.
+    pub fn sessions(&self, tenant: &str) -> Vec<&str> {
+        let mut sessions: Vec<&str> = self
+            .histories
+            .keys()
+            .filter_map(|key| key.split_once(SEPARATOR))
+            .filter(|(owner, _)| *owner == tenant)
+            .map(|(_, session)| session)
+            .collect();
+        sessions.sort_unstable();
+        sessions
+    }
+
+    pub fn usage(&self, tenant: &str) -> TenantUsage {
+        self.usage
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(tenant)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    fn update_usage(&self, tenant: &str, update: impl FnOnce(&mut TenantUsage)) {
+        update(
+            self.usage
+                .write()
+                .unwrap_or_else(PoisonError::into_inner)
+                .entry(tenant.to_string())
+                .or_default(),
+        );
+    }
+}
+
+// Ids containing the separator could make two different pairs share a key.
+fn scoped_key(tenant: &str, session: &str) -> io::Result<String> {
+    if tenant.is_empty() || tenant.contains(SEPARATOR) || session.contains(SEPARATOR) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "tenant and session ids must be non-empty and free of '{}'",
+                SEPARATOR
+            ),
+        ));
+    }
+    Ok(format!("{}{}{}", tenant, SEPARATOR, session))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AiMessage, HumanMessage, InMemoryChatHistory};
+
+    fn in_memory(_: &str) -> io::Result<InMemoryChatHistory> {
+        Ok(InMemoryChatHistory::new())
+    }
+
+    #[test]
+    fn test_tenants_are_isolated() {
+        let mut history = TenantScopedHistory::new(in_memory);
+        history
+            .add_message("acme", "s1", HumanMessage::new("Hi").into())
+            .unwrap();
+        history
+            .add_message("acme", "s1", AiMessage::new("Hello").into())
+            .unwrap();
+        history
+            .add_message("globex", "s1", HumanMessage::new("Secret").into())
+            .unwrap();
+
+        let messages = history.messages("acme", "s1").unwrap();
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[0].additional_kwargs()[TENANT_ID_KEY], "acme");
+        assert_eq!(
+            history.messages("globex", "s1").unwrap()[0].content(),
+            "Secret"
+        );
+        assert!(history.messages("initech", "s1").unwrap().is_empty());
+        assert_eq!(history.sessions("acme"), vec!["s1"]);
+        assert!(history
+            .add_message("acme", "s1:x", HumanMessage::new("x").into())
+            .is_err());
+
+        assert_eq!(
+            history.usage("acme"),
+            TenantUsage {
+                sessions: 1,
+                messages_written: 2,
+                messages_read: 2,
+            }
+        );
+    }
+
+    #[test]
+    fn test_foreign_messages_are_rejected_on_read() {
+        let mut history = TenantScopedHistory::new(in_memory);
+        history
+            .add_message("acme", "s1", HumanMessage::new("Hi").into())
+            .unwrap();
+        let mut leaked: AnyMessage = HumanMessage::new("Not yours").into();
+        leaked
+            .base_mut()
+            .additional_kwargs
+            .insert(TENANT_ID_KEY.to_string(), "globex".to_string());
+        history
+            .histories
+            .get_mut("acme:s1")
+            .unwrap()
+            .add_message(leaked)
+            .unwrap();
+
+        let err = history.messages("acme", "s1").unwrap_err();
+
+        assert_eq!(err.kind(), io::ErrorKind::PermissionDenied);
+        assert_eq!(history.usage("acme").messages_read, 0);
+    }
+}
@@ -0,0 +1,164 @@
+use std::collections::HashMap;
+
+use crate::{Conversation, MessageEnum, TokenCounter};
+
+/// Per-message token counts, one set per counter key, kept in a
+/// conversation's extensions. `counts` lines up with the messages; `None`
+/// marks a message that is new or was edited since it was last counted.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct TokenCountCache {
+    counters: HashMap<String, CachedCounts>,
+}
+
+#[derive(Debug, Clone, Default)]
+struct CachedCounts {
+    counts: Vec<Option<usize>>,
+    total: usize,
+}
+
+impl CachedCounts {
+    fn take(&mut self, index: usize) -> Option<usize> {
+        let count = self.counts.get_mut(index)?.take()?;
+        self.total -= count;
+        Some(count)
+    }
+
+    fn refresh(&mut self, messages: &[MessageEnum], counter: &dyn TokenCounter) {
+        if self.counts.len() > messages.len() {
+            self.counts.truncate(messages.len());
+            self.total = self.counts.iter().flatten().sum();
+        }
+        self.counts.resize(messages.len(), None);
+        for (slot, message) in self.counts.iter_mut().zip(messages) {
+            if slot.is_none() {
+                let count = counter.count_tokens(message);
+                self.total += count;
+                *slot = Some(count);
+            }
+        }
+    }
+}
+
+impl TokenCountCache {
+    pub(crate) fn invalidate(&mut self, index: usize) {
+        for counts in self.counters.values_mut() {
+            counts.take(index);
+        }
+    }
+
+    pub(crate) fn remove(&mut self, index: usize) {
+        for counts in self.counters.values_mut() {
+            counts.take(index);
+            if index < counts.counts.len() {
+                counts.counts.remove(index);
+            }
+        }
+    }
+
+    pub(crate) fn insert(&mut self, index: usize) {
+        for counts in self.counters.values_mut() {
+            if index <= counts.counts.len() {
+                counts.counts.insert(index, None);
+            }
+        }
+    }
+}
+
+impl Conversation {
+    /// Total tokens under `counter`, cached per `key` (typically the model
+    /// name). Only messages pushed, restored or edited through
+    /// [`Conversation::message_mut`] since the last call are counted again;
+    /// [`Conversation::messages_mut`] drops every cached count.
+    pub fn token_count(&mut self, key: &str, counter: &dyn TokenCounter) -> usize {
+        self.cached_counts(key, counter).total
+    }
+
+    /// Each message's token count under `counter`, cached like
+    /// [`Conversation::token_count`].
+    pub fn message_token_counts(&mut self, key: &str, counter: &dyn TokenCounter) -> Vec<usize> {
+        self.cached_counts(key, counter)
+            .counts
+            .iter()
+            .flatten()
+            .copied()
+            .collect()
+    }
+
+    fn cached_counts(&mut self, key: &str, counter: &dyn TokenCounter) -> &CachedCounts {
+        let counts = self
+            .extensions
+            .get_or_insert_default::<TokenCountCache>()
+            .counters
+            .entry(key.to_string())
+            .or_default();
+        counts.refresh(&self.messages, counter);
+        counts
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use super::*;
+    use crate::{AiMessage, ApproximateTokenCounter, BaseMessage, HumanMessage};
+
+    #[test]
+    fn test_counts_only_changed_messages() {
+        let calls = Cell::new(0);
+        let counter = |message: &MessageEnum| {
+            calls.set(calls.get() + 1);
+            message.content().len()
+        };
+        let mut conversation = Conversation::new();
+        conversation.push(HumanMessage::new("four"));
+        conversation.push(AiMessage::new("sixsix"));
+
+        assert_eq!(conversation.token_count("bytes", &counter), 10);
+        assert_eq!(conversation.token_count("bytes", &counter), 10);
+        assert_eq!(calls.get(), 2);
+
+        conversation.push(HumanMessage::new("two"));
+        conversation.message_mut(0).unwrap().base_mut().content = "1".into();
+        assert_eq!(
+            conversation.message_token_counts("bytes", &counter),
+            vec![1, 6, 3]
+        );
+        assert_eq!(calls.get(), 4);
+        assert_eq!(
+            conversation.token_count("chars/4", &ApproximateTokenCounter),
+            4
+        );
+
+        conversation.messages_mut().pop();
+        assert_eq!(conversation.token_count("bytes", &counter), 7);
+        assert_eq!(calls.get(), 6);
+    }
+
+    #[test]
+    fn test_soft_delete_and_restore_keep_counts_aligned() {
+        let calls = Cell::new(0);
+        let counter = |message: &MessageEnum| {
+            calls.set(calls.get() + 1);
+            message.content().len()
+        };
+        let mut conversation = Conversation::new();
+        for (id, content) in [("m1", "a"), ("m2", "bb"), ("m3", "ccc")] {
+            let mut message = HumanMessage::new(content);
+            message.set_id(Some(id.to_string()));
+            conversation.push(message);
+        }
+        assert_eq!(conversation.token_count("bytes", &counter), 6);
+
+        conversation.soft_delete("m2");
+        assert_eq!(conversation.token_count("bytes", &counter), 4);
+        assert_eq!(calls.get(), 3);
+
+        conversation.restore("m2");
+        assert_eq!(
+            conversation.message_token_counts("bytes", &counter),
+            vec![1, 2, 3]
+        );
+        assert_eq!(calls.get(), 4);
+    }
+}
@@ -0,0 +1,264 @@
+use serde::Serialize;
+
+use crate::{BaseMessage, MessageEnum, TokenCounter};
+
+/// Rough token count for budgeting without a tokenizer: about four
+/// characters per token, rounded up.
+pub fn approximate_tokens(text: &str) -> usize {
+    text.chars().count().div_ceil(4)
+}
+
+pub fn approximate_message_tokens(messages: &[MessageEnum]) -> usize {
+    messages
+        .iter()
+        .map(|message| approximate_tokens(message.content()))
+        .sum()
+}
+
+/// Character classes that tokenize at clearly different rates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Script {
+    /// ASCII and the Latin blocks, with their whitespace and punctuation.
+    Latin,
+    Cyrillic,
+    /// Han, kana and Hangul, including fullwidth forms.
+    Cjk,
+    /// Anything inside a fenced code block.
+    Code,
+    /// Every other script, symbols and emoji.
+    Other,
+}
+
+impl Script {
+    /// The script of `c` outside code blocks.
+    pub fn of(c: char) -> Script {
+        match c as u32 {
+            0x0000..=0x024F | 0x1E00..=0x1EFF | 0x2000..=0x206F => Script::Latin,
+            0x0400..=0x052F => Script::Cyrillic,
+            0x1100..=0x11FF
+            | 0x3000..=0x30FF
+            | 0x3400..=0x4DBF
+            | 0x4E00..=0x9FFF
+            | 0xAC00..=0xD7AF
+            | 0xF900..=0xFAFF
+            | 0xFF00..=0xFFEF
+            | 0x20000..=0x2FFFF => Script::Cjk,
+            _ => Script::Other,
+        }
+    }
+}
+
+/// How many characters of one script make a token, and how far real counts
+/// stray from that, as a fraction of the estimate.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ScriptCalibration {
+    pub chars_per_token: f64,
+    pub relative_error: f64,
+}
+
+impl ScriptCalibration {
+    pub const fn new(chars_per_token: f64, relative_error: f64) -> Self {
+        ScriptCalibration {
+            chars_per_token,
+            relative_error,
+        }
+    }
+
+    /// Fits a calibration to `(text, actual token count)` samples from a real
+    /// tokenizer: the pooled ratio, with the worst sample's error as the
+    /// bound. `None` without any tokens to fit to.
+    pub fn fit<S: AsRef<str>>(samples: &[(S, usize)]) -> Option<Self> {
+        let chars: usize = samples
+            .iter()
+            .map(|(text, _)| text.as_ref().chars().count())
+            .sum();
+        let tokens: usize = samples.iter().map(|(_, tokens)| tokens).sum();
+        if tokens == 0 || chars == 0 {
+            return None;
+        }
+        let chars_per_token = chars as f64 / tokens as f64;
+        let relative_error = samples
+            .iter()
+            .filter(|(_, actual)| *actual > 0)
+            .map(|(text, actual)| {
+                let estimate = text.as_ref().chars().count() as f64 / chars_per_token;
+                (estimate - *actual as f64).abs() / estimate
+            })
+            .fold(0.0, f64::max);
+        Some(ScriptCalibration::new(chars_per_token, relative_error))
+    }
+}
+
+/// Per-script calibrations. The default is tuned for current BPE tokenizers
+/// of the cl100k/o200k family.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CalibrationTable {
+    pub latin: ScriptCalibration,
+    pub cyrillic: ScriptCalibration,
+    pub cjk: ScriptCalibration,
+    pub code: ScriptCalibration,
+    pub other: ScriptCalibration,
+}
+
+impl Default for CalibrationTable {
+    fn default() -> Self {
+        CalibrationTable {
+            latin: ScriptCalibration::new(4.0, 0.15),
+            cyrillic: ScriptCalibration::new(2.5, 0.25),
+            cjk: ScriptCalibration::new(0.8, 0.3),
+            code: ScriptCalibration::new(3.2, 0.2),
+            other: ScriptCalibration::new(1.0, 0.5),
+        }
+    }
+}
+
+impl CalibrationTable {
+    pub fn get(&self, script: Script) -> ScriptCalibration {
+        match script {
+            Script::Latin => self.latin,
+            Script::Cyrillic => self.cyrillic,
+            Script::Cjk => self.cjk,
+            Script::Code => self.code,
+            Script::Other => self.other,
+        }
+    }
+
+    pub fn with(mut self, script: Script, calibration: ScriptCalibration) -> Self {
+        *match script {
+            Script::Latin => &mut self.latin,
+            Script::Cyrillic => &mut self.cyrillic,
+            Script::Cjk => &mut self.cjk,
+            Script::Code => &mut self.code,
+            Script::Other => &mut self.other,
+        } = calibration;
+        self
+    }
+
+    pub fn estimate(&self, text: &str) -> TokenEstimate {
+        let (mut tokens, mut low, mut high) = (0.0, 0.0, 0.0);
+        for (script, chars) in script_counts(text) {
+            let calibration = self.get(script);
+            let estimate = chars as f64 / calibration.chars_per_token;
+            tokens += estimate;
+            low += estimate * (1.0 - calibration.relative_error).max(0.0);
+            high += estimate * (1.0 + calibration.relative_error);
+        }
+        TokenEstimate {
+            tokens: tokens.ceil() as usize,
+            low: low.floor() as usize,
+            high: high.ceil() as usize,
+        }
+    }
+}
+
+/// An estimated token count and the range the real count should fall in.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct TokenEstimate {
+    pub tokens: usize,
+    pub low: usize,
+    pub high: usize,
+}
+
+/// Estimates `text` with the default [`CalibrationTable`].
+pub fn estimate_tokens(text: &str) -> TokenEstimate {
+    CalibrationTable::default().estimate(text)
+}
+
+/// Characters per script, indexed by discriminant, with fenced code blocks
+/// (fence lines included) counted as [`Script::Code`].
+fn script_counts(text: &str) -> [(Script, usize); 5] {
+    let mut counts = [
+        (Script::Latin, 0),
+        (Script::Cyrillic, 0),
+        (Script::Cjk, 0),
+        (Script::Code, 0),
+        (Script::Other, 0),
+    ];
+    let mut in_code = false;
+    for line in text.split_inclusive('\n') {
+        let fence = line.trim_start().starts_with("```");
+        if in_code || fence {
+            counts[Script::Code as usize].1 += line.chars().count();
+        } else {
+            for c in line.chars() {
+                counts[Script::of(c) as usize].1 += 1;
+            }
+        }
+        in_code ^= fence;
+    }
+    counts
+}
+
+/// Counts message text with a [`CalibrationTable`], taking the point
+/// estimate.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct CalibratedTokenCounter {
+    pub table: CalibrationTable,
+}
+
+impl CalibratedTokenCounter {
+    pub fn new(table: CalibrationTable) -> Self {
+        CalibratedTokenCounter { table }
+    }
+}
+
+impl TokenCounter for CalibratedTokenCounter {
+    fn count_tokens(&self, message: &MessageEnum) -> usize {
+        self.table.estimate(&message.text()).tokens
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::HumanMessage;
+
+    #[test]
+    fn test_approximate_tokens() {
+        assert_eq!(approximate_tokens(""), 0);
+        assert_eq!(approximate_tokens("abcd"), 1);
+        assert_eq!(approximate_tokens("abcde"), 2);
+
+        let messages: Vec<MessageEnum> = vec![
+            HumanMessage::new("abcd").into(),
+            HumanMessage::new("ab").into(),
+        ];
+        assert_eq!(approximate_message_tokens(&messages), 2);
+    }
+
+    #[test]
+    fn test_estimate_by_script() {
+        assert_eq!(estimate_tokens(""), TokenEstimate::default());
+
+        let english = estimate_tokens("The quick brown fox jumps.");
+        assert_eq!(english.tokens, 7);
+        assert!(english.low < english.tokens && english.tokens < english.high);
+
+        let russian = estimate_tokens("Привет, как дела?");
+        assert!(russian.tokens > approximate_tokens("Привет, как дела?"));
+        assert_eq!(estimate_tokens("你好世界").tokens, 5);
+
+        let code = "Run:\n```rust\nfn main() {}\n```\n";
+        let table =
+            CalibrationTable::default().with(Script::Code, ScriptCalibration::new(1.0, 0.0));
+        assert_eq!(table.estimate(code).tokens, 27);
+    }
+
+    #[test]
+    fn test_fit_calibration() {
+        let calibration = ScriptCalibration::fit(&[("abcdefgh", 2), ("abcdefghijkl", 3)]).unwrap();
+        assert_eq!(calibration.chars_per_token, 4.0);
+        assert_eq!(calibration.relative_error, 0.0);
+
+        let loose = ScriptCalibration::fit(&[("abcd", 2), ("abcdefgh", 1)]).unwrap();
+        assert_eq!(loose.chars_per_token, 4.0);
+        assert_eq!(loose.relative_error, 1.0);
+        assert!(ScriptCalibration::fit::<&str>(&[]).is_none());
+
+        let counter = CalibratedTokenCounter::new(CalibrationTable::default());
+        assert_eq!(
+            counter.count_tokens(&HumanMessage::new("abcdefgh").into()),
+            2
+        );
+    }
+}
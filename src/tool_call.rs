@@ -0,0 +1,83 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// A structured tool call requested by the model.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ToolCall {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub args: Value,
+}
+
+impl ToolCall {
+    pub fn new(id: impl Into<String>, name: impl Into<String>, args: Value) -> Self {
+        ToolCall {
+            id: id.into(),
+            name: name.into(),
+            args,
+        }
+    }
+}
+
+/// A tool call the provider returned but that could not be parsed, kept
+/// with the raw arguments so it can be reported back to the model.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InvalidToolCall {
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub id: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub name: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub args: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub error: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AiMessage, BaseMessage, MessageEnum};
+    use serde_json::json;
+
+    #[test]
+    fn test_tool_calls_round_trip() {
+        let message = AiMessage::new("")
+            .with_tool_calls(vec![ToolCall::new(
+                "call_1",
+                "get_weather",
+                json!({"city": "Paris"}),
+            )])
+            .with_invalid_tool_calls(vec![InvalidToolCall {
+                id: Some("call_2".to_string()),
+                args: Some("{\"city\": ".to_string()),
+                error: Some("EOF while parsing".to_string()),
+                ..InvalidToolCall::default()
+            }]);
+        let message: MessageEnum = message.into();
+
+        let value = serde_json::to_value(&message).unwrap();
+        assert_eq!(
+            value["tool_calls"],
+            json!([{"id": "call_1", "name": "get_weather", "args": {"city": "Paris"}}])
+        );
+        let parsed: MessageEnum = serde_json::from_value(value).unwrap();
+
+        assert_eq!(parsed, message);
+        assert_eq!(parsed.tool_calls()[0].args["city"], "Paris");
+        assert_eq!(parsed.invalid_tool_calls()[0].id.as_deref(), Some("call_2"));
+    }
+
+    #[test]
+    fn test_messages_without_calls_omit_fields() {
+        let message: MessageEnum = AiMessage::new("Hi").into();
+
+        let json = serde_json::to_string(&message).unwrap();
+
+        assert!(!json.contains("tool_calls"));
+        assert!(message.tool_calls().is_empty());
+    }
+}
@@ -1,5 +1,4 @@
 use crate::prelude::*;
-use derive_base_message::BaseMessage;
 
 #[derive(Debug, PartialEq, Serialize, Deserialize, Clone)]
 pub enum ToolStatus {
@@ -7,15 +6,12 @@ pub enum ToolStatus {
     Error,
 }
 
-#[derive(BaseMessage, Debug, Serialize, Deserialize, Clone, PartialEq)]
-pub struct ToolMessage {
+define_message!(Tool {
     tool_call_id: String,
     #[serde(skip_serializing_if = "Option::is_none", default)]
     artifact: Option<String>,
     status: ToolStatus,
-    #[serde(flatten)]
-    base: BaseMessageFields,
-}
+});
 
 impl ToolMessage {
     pub fn new_with_base(
@@ -32,6 +28,22 @@ impl ToolMessage {
         }
     }
 
+    /// A result answering `call`, named after the tool that was called.
+    pub fn for_call(call: &ToolCall, content: &str, status: ToolStatus) -> Self {
+        let mut message = ToolMessage::new(content, call.id.clone(), None, status);
+        message.base.name = Some(call.name.clone());
+        message
+    }
+
+    pub fn with_artifact(mut self, artifact: impl Into<String>) -> Self {
+        self.artifact = Some(artifact.into());
+        self
+    }
+
+    pub fn is_error(&self) -> bool {
+        self.status == ToolStatus::Error
+    }
+
     pub fn tool_call_id(&self) -> &str {
         &self.tool_call_id
     }
@@ -40,16 +52,36 @@ impl ToolMessage {
         &self.artifact
     }
 
+    pub fn set_artifact(&mut self, artifact: Option<String>) {
+        self.artifact = artifact;
+    }
+
     pub fn status(&self) -> &ToolStatus {
         &self.status
     }
+
+    pub fn set_status(&mut self, status: ToolStatus) {
+        self.status = status;
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use serde_json;
-    use std::collections::HashMap;
+
+    #[test]
+    fn test_tool_message_for_call() {
+        let call = ToolCall::new("call_7", "get_weather", serde_json::json!({"city": "Oslo"}));
+
+        let message = ToolMessage::for_call(&call, "Timed out", ToolStatus::Error)
+            .with_artifact("{\"retry_after\": 30}");
+
+        assert_eq!(message.tool_call_id(), "call_7");
+        assert_eq!(message.name(), Some("get_weather"));
+        assert_eq!(message.artifact().as_deref(), Some("{\"retry_after\": 30}"));
+        assert!(message.is_error());
+    }
 
     #[test]
     fn test_tool_message_serialization_with_empty_fields() {
@@ -68,10 +100,10 @@ mod tests {
 
     #[test]
     fn test_tool_message_serialization_with_values() {
-        let mut additional_kwargs = HashMap::new();
+        let mut additional_kwargs = Metadata::new();
         additional_kwargs.insert("key1".to_string(), "value1".to_string());
 
-        let mut response_metadata = HashMap::new();
+        let mut response_metadata = Metadata::new();
         response_metadata.insert("meta_key".to_string(), "meta_value".to_string());
 
         let mut tool_message = ToolMessage::new(
@@ -138,7 +170,7 @@ mod tests {
 
     #[test]
     fn test_tool_message_serialization_with_partial_values() {
-        let mut additional_kwargs = HashMap::new();
+        let mut additional_kwargs = Metadata::new();
         additional_kwargs.insert("key2".to_string(), "value2".to_string());
 
         let mut tool_message = ToolMessage::new(
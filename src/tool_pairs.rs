@@ -0,0 +1,185 @@
+use std::collections::HashSet;
+
+use crate::tool_message::ToolStatus;
+use crate::{BaseMessage, MessageEnum, ToolMessage};
+
+const STUB_TOOL_RESULT: &str = "Tool call did not complete.";
+
+/// Ids of the tool calls an Ai message requested.
+pub(crate) fn requested_tool_call_ids(message: &MessageEnum) -> Vec<String> {
+    match message.as_ai() {
+        Some(ai) => ai.tool_calls().iter().map(|call| call.id.clone()).collect(),
+        None => Vec::new(),
+    }
+}
+
+/// What to do with tool calls that never got a result.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum UnansweredCalls {
+    /// Remove the call from the Ai message, and the message itself if it is
+    /// left with no content and no calls.
+    Drop,
+    /// Answer the call with an error [`ToolMessage`].
+    #[default]
+    Stub,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RepairReport {
+    pub stubbed_calls: Vec<String>,
+    pub dropped_calls: Vec<String>,
+    pub dropped_results: Vec<String>,
+}
+
+impl RepairReport {
+    pub fn is_clean(&self) -> bool {
+        self.stubbed_calls.is_empty()
+            && self.dropped_calls.is_empty()
+            && self.dropped_results.is_empty()
+    }
+}
+
+/// Makes every tool call answered and every tool result answer a call, as
+/// strict providers require. A result pairs with a call when it follows the
+/// Ai message that requested it with only other results in between. Orphaned
+/// results are always dropped; unanswered calls are handled per `mode`.
+pub fn repair_tool_pairs(messages: &mut Vec<MessageEnum>, mode: UnansweredCalls) -> RepairReport {
+    let mut report = RepairReport::default();
+    let mut repaired = Vec::with_capacity(messages.len());
+    let mut open: Option<(usize, Vec<String>)> = None;
+
+    for message in messages.drain(..) {
+        if let Some(tool) = message.as_tool() {
+            let call_id = tool.tool_call_id().to_string();
+            match open.as_mut() {
+                Some((_, pending)) if pending.contains(&call_id) => {
+                    pending.retain(|id| *id != call_id);
+                    repaired.push(message);
+                }
+                _ => report.dropped_results.push(call_id),
+            }
+            continue;
+        }
+
+        if let Some((ai_index, pending)) = open.take() {
+            close_group(&mut repaired, ai_index, pending, mode, &mut report);
+        }
+        let calls = requested_tool_call_ids(&message);
+        repaired.push(message);
+        if !calls.is_empty() {
+            open = Some((repaired.len() - 1, calls));
+        }
+    }
+    if let Some((ai_index, pending)) = open {
+        close_group(&mut repaired, ai_index, pending, mode, &mut report);
+    }
+
+    *messages = repaired;
+    report
+}
+
+fn close_group(
+    messages: &mut Vec<MessageEnum>,
+    ai_index: usize,
+    pending: Vec<String>,
+    mode: UnansweredCalls,
+    report: &mut RepairReport,
+) {
+    if pending.is_empty() {
+        return;
+    }
+    match mode {
+        UnansweredCalls::Stub => {
+            for call_id in pending {
+                messages.push(
+                    ToolMessage::new(STUB_TOOL_RESULT, call_id.clone(), None, ToolStatus::Error)
+                        .into(),
+                );
+                report.stubbed_calls.push(call_id);
+            }
+        }
+        UnansweredCalls::Drop => {
+            let unanswered: HashSet<&str> = pending.iter().map(String::as_str).collect();
+            if let Some(ai) = messages[ai_index].as_ai_mut() {
+                ai.tool_calls
+                    .retain(|call| !unanswered.contains(call.id.as_str()));
+            }
+            if requested_tool_call_ids(&messages[ai_index]).is_empty()
+                && messages[ai_index].content().trim().is_empty()
+            {
+                messages.remove(ai_index);
+            }
+            report.dropped_calls.extend(pending);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AiMessage, HumanMessage, ToolCall};
+    use serde_json::Value;
+
+    fn ai_calling(content: &str, ids: &[&str]) -> MessageEnum {
+        let calls = ids
+            .iter()
+            .map(|id| ToolCall::new(*id, "lookup", Value::Null))
+            .collect();
+        AiMessage::new(content).with_tool_calls(calls).into()
+    }
+
+    fn result(call_id: &str) -> MessageEnum {
+        ToolMessage::new("ok", call_id.to_string(), None, ToolStatus::Success).into()
+    }
+
+    #[test]
+    fn test_stub_unanswered_and_drop_orphans() {
+        let mut messages = vec![
+            HumanMessage::new("Weather?").into(),
+            result("call_0"),
+            ai_calling("", &["call_1", "call_2"]),
+            result("call_1"),
+            HumanMessage::new("Still there?").into(),
+            result("call_2"),
+        ];
+
+        let report = repair_tool_pairs(&mut messages, UnansweredCalls::Stub);
+
+        assert_eq!(report.stubbed_calls, vec!["call_2"]);
+        assert_eq!(report.dropped_results, vec!["call_0", "call_2"]);
+        assert_eq!(messages.len(), 5);
+        let stub = messages[3].as_tool().unwrap();
+        assert_eq!(stub.tool_call_id(), "call_2");
+        assert_eq!(stub.status(), &ToolStatus::Error);
+        assert_eq!(messages[4].content(), "Still there?");
+    }
+
+    #[test]
+    fn test_drop_unanswered_calls() {
+        let mut messages = vec![
+            ai_calling("Checking.", &["call_1", "call_2"]),
+            result("call_1"),
+            ai_calling("", &["call_3"]),
+        ];
+
+        let report = repair_tool_pairs(&mut messages, UnansweredCalls::Drop);
+
+        assert_eq!(report.dropped_calls, vec!["call_2", "call_3"]);
+        assert_eq!(messages.len(), 2);
+        assert_eq!(requested_tool_call_ids(&messages[0]), vec!["call_1"]);
+    }
+
+    #[test]
+    fn test_well_formed_history_is_untouched() {
+        let mut messages = vec![
+            HumanMessage::new("Hi").into(),
+            ai_calling("", &["call_1"]),
+            result("call_1"),
+            AiMessage::new("Done").into(),
+        ];
+        let original = messages.clone();
+
+        assert!(repair_tool_pairs(&mut messages, UnansweredCalls::Stub).is_clean());
+        assert_eq!(messages, original);
+    }
+}
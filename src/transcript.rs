@@ -0,0 +1,185 @@
+use crate::{AnyMessage, BaseMessage};
+
+const RESET: &str = "\x1b[0m";
+const DIM: &str = "\x1b[2m";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TranscriptStyle {
+    /// Colors the role column with ANSI escapes, one color per role.
+    pub color: bool,
+    pub show_tool_calls: bool,
+    /// Longest text shown per message, in characters; the rest is elided.
+    pub max_content_chars: Option<usize>,
+}
+
+impl Default for TranscriptStyle {
+    fn default() -> Self {
+        TranscriptStyle {
+            color: false,
+            show_tool_calls: true,
+            max_content_chars: None,
+        }
+    }
+}
+
+impl TranscriptStyle {
+    pub fn colored() -> Self {
+        TranscriptStyle {
+            color: true,
+            ..Self::default()
+        }
+    }
+}
+
+/// Renders `messages` behind a role column padded to the widest role, so
+/// the text lines up; speaker names follow the role:
+///
+/// ```text
+/// human      │ What's the weather?
+/// ai         │ → weather {"city":"Paris"}
+/// tool       │ [call_1] Sunny
+/// ai:planner │ It is sunny.
+/// ```
+pub fn render_transcript(messages: &[AnyMessage], style: TranscriptStyle) -> String {
+    let labels: Vec<String> = messages
+        .iter()
+        .map(|message| match message.name() {
+            Some(name) => format!("{}:{}", message.role(), name),
+            None => message.role().to_string(),
+        })
+        .collect();
+    let width = labels
+        .iter()
+        .map(|label| label.chars().count())
+        .max()
+        .unwrap_or_default();
+
+    let mut out = String::new();
+    for (message, label) in messages.iter().zip(&labels) {
+        let mut lines: Vec<String> = body(message, style).lines().map(str::to_string).collect();
+        if style.show_tool_calls {
+            lines.extend(
+                message
+                    .tool_calls()
+                    .iter()
+                    .map(|call| format!("→ {} {}", call.name, call.args)),
+            );
+        }
+        if lines.is_empty() {
+            lines.push(String::new());
+        }
+        for (index, line) in lines.iter().enumerate() {
+            let label = if index == 0 { label.as_str() } else { "" };
+            let padded = format!("{:<width$}", label, width = width);
+            if style.color {
+                out.push_str(&format!(
+                    "{}{}{} {}│{} {}",
+                    role_color(message.role()),
+                    padded,
+                    RESET,
+                    DIM,
+                    RESET,
+                    line
+                ));
+            } else {
+                out.push_str(&format!("{} │ {}", padded, line));
+            }
+            out.push('\n');
+        }
+    }
+    out
+}
+
+fn body(message: &AnyMessage, style: TranscriptStyle) -> String {
+    let text = message.text();
+    let mut body = match style.max_content_chars {
+        Some(max) if text.chars().count() > max => {
+            format!("{}...", text.chars().take(max).collect::<String>())
+        }
+        _ => text.into_owned(),
+    };
+    if let AnyMessage::Tool(tool) = message {
+        body = format!("[{}] {}", tool.tool_call_id(), body);
+    }
+    body
+}
+
+fn role_color(role: &str) -> &'static str {
+    match role {
+        "human" | "user" => "\x1b[32m",
+        "ai" | "assistant" => "\x1b[34m",
+        "system" => "\x1b[33m",
+        "tool" => "\x1b[35m",
+        _ => "\x1b[36m",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tool_message::ToolStatus;
+    use crate::{AiMessage, HumanMessage, ToolCall, ToolMessage};
+    use serde_json::json;
+
+    fn run() -> Vec<AnyMessage> {
+        let mut answer = AiMessage::new("It is sunny.\nEnjoy!");
+        answer.set_name(Some("planner".to_string()));
+        vec![
+            HumanMessage::new("What's the weather?").into(),
+            AiMessage::new("")
+                .with_tool_calls(vec![ToolCall::new(
+                    "call_1",
+                    "weather",
+                    json!({"city": "Paris"}),
+                )])
+                .into(),
+            ToolMessage::new("Sunny", "call_1".to_string(), None, ToolStatus::Success).into(),
+            answer.into(),
+        ]
+    }
+
+    #[test]
+    fn test_render_transcript() {
+        assert_eq!(
+            render_transcript(&run(), TranscriptStyle::default()),
+            "human      │ What's the weather?\n\
+             ai         │ → weather {\"city\":\"Paris\"}\n\
+             tool       │ [call_1] Sunny\n\
+             ai:planner │ It is sunny.\n\
+             \x20          │ Enjoy!\n"
+        );
+
+        let colored = render_transcript(&run()[..1], TranscriptStyle::colored());
+        assert_eq!(
+            colored,
+            "\x1b[32mhuman\x1b[0m \x1b[2m│\x1b[0m What's the weather?\n"
+        );
+        let short = TranscriptStyle {
+            max_content_chars: Some(4),
+            show_tool_calls: false,
+            ..TranscriptStyle::default()
+        };
+        assert!(
+            render_transcript(&run(), short).starts_with("human      │ What...\nai         │ \n")
+        );
+    }
+
+    #[test]
+    fn test_pretty_repr() {
+        let messages = run();
+
+        assert_eq!(
+            messages[0].pretty_repr(),
+            format!(
+                "{}\n\nWhat's the weather?",
+                "=".repeat(32) + " Human Message " + &"=".repeat(33)
+            )
+        );
+        let call = messages[1].pretty_repr();
+        assert!(call.starts_with(&format!("{} Ai Message ", "=".repeat(34))));
+        assert!(call.ends_with("Tool Calls:\n  weather (call_1)\n    Args: {\"city\":\"Paris\"}"));
+        assert!(messages[3]
+            .pretty_repr()
+            .contains("\nName: planner\n\nIt is sunny."));
+    }
+}
@@ -0,0 +1,108 @@
+use std::fmt;
+
+use crate::MessageEnum;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlockedMessage {
+    pub transformer: String,
+    pub reason: String,
+}
+
+impl BlockedMessage {
+    pub fn new(transformer: impl Into<String>, reason: impl Into<String>) -> Self {
+        BlockedMessage {
+            transformer: transformer.into(),
+            reason: reason.into(),
+        }
+    }
+}
+
+impl fmt::Display for BlockedMessage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Message blocked by {}: {}",
+            self.transformer, self.reason
+        )
+    }
+}
+
+impl std::error::Error for BlockedMessage {}
+
+/// A step that inspects a message before it is stored or sent and either
+/// passes it on (possibly rewritten) or blocks it.
+pub trait MessageTransformer {
+    fn name(&self) -> &str;
+    fn transform(&self, message: MessageEnum) -> Result<MessageEnum, BlockedMessage>;
+}
+
+pub fn apply_transformers(
+    transformers: &[Box<dyn MessageTransformer>],
+    message: MessageEnum,
+) -> Result<MessageEnum, BlockedMessage> {
+    transformers
+        .iter()
+        .try_fold(message, |message, transformer| {
+            transformer.transform(message)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BaseMessage, HumanMessage};
+
+    struct Uppercase;
+
+    impl MessageTransformer for Uppercase {
+        fn name(&self) -> &str {
+            "uppercase"
+        }
+
+        fn transform(&self, mut message: MessageEnum) -> Result<MessageEnum, BlockedMessage> {
+            let upper = message.content().to_uppercase();
+            message.set_content(&upper);
+            Ok(message)
+        }
+    }
+
+    struct RejectEmpty;
+
+    impl MessageTransformer for RejectEmpty {
+        fn name(&self) -> &str {
+            "reject_empty"
+        }
+
+        fn transform(&self, message: MessageEnum) -> Result<MessageEnum, BlockedMessage> {
+            if message.content().is_empty() {
+                Err(BlockedMessage::new(self.name(), "empty content"))
+            } else {
+                Ok(message)
+            }
+        }
+    }
+
+    #[test]
+    fn test_apply_transformers_in_order() {
+        let transformers: Vec<Box<dyn MessageTransformer>> =
+            vec![Box::new(RejectEmpty), Box::new(Uppercase)];
+
+        let result = apply_transformers(&transformers, HumanMessage::new("hello").into());
+
+        assert_eq!(result.unwrap().content(), "HELLO");
+    }
+
+    #[test]
+    fn test_apply_transformers_blocked() {
+        let transformers: Vec<Box<dyn MessageTransformer>> =
+            vec![Box::new(Uppercase), Box::new(RejectEmpty)];
+
+        let error = apply_transformers(&transformers, HumanMessage::new("").into()).unwrap_err();
+
+        assert_eq!(error, BlockedMessage::new("reject_empty", "empty content"));
+        assert_eq!(
+            error.to_string(),
+            "Message blocked by reject_empty: empty content"
+        );
+    }
+}
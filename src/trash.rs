@@ -0,0 +1,65 @@
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::BaseMessage;
+
+/// A soft-deleted message, kept with the position it was removed from so a
+/// restore puts it back in place.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TrashedMessage<M> {
+    pub message: M,
+    pub index: usize,
+    pub deleted_at_ms: u64,
+}
+
+impl<M> TrashedMessage<M> {
+    pub fn is_expired(&self, retention: Duration, now_ms: u64) -> bool {
+        now_ms.saturating_sub(self.deleted_at_ms) >= retention.as_millis() as u64
+    }
+}
+
+pub(crate) fn soft_delete<M: BaseMessage>(
+    messages: &mut Vec<M>,
+    trash: &mut Vec<TrashedMessage<M>>,
+    id: &str,
+    now_ms: u64,
+) -> bool {
+    let Some(index) = messages.iter().position(|message| message.id() == Some(id)) else {
+        return false;
+    };
+    trash.push(TrashedMessage {
+        message: messages.remove(index),
+        index,
+        deleted_at_ms: now_ms,
+    });
+    true
+}
+
+// Messages added since the delete can shift the original slot; restoring
+// clamps to the end rather than failing. Returns where it was put back.
+pub(crate) fn restore<M: BaseMessage>(
+    messages: &mut Vec<M>,
+    trash: &mut Vec<TrashedMessage<M>>,
+    id: &str,
+) -> Option<usize> {
+    let position = trash
+        .iter()
+        .rposition(|trashed| trashed.message.id() == Some(id))?;
+    let trashed = trash.remove(position);
+    let index = trashed.index.min(messages.len());
+    messages.insert(index, trashed.message);
+    Some(index)
+}
+
+pub(crate) fn purge<M>(
+    trash: &mut Vec<TrashedMessage<M>>,
+    retention: Duration,
+    now_ms: u64,
+) -> Vec<M> {
+    let (expired, kept): (Vec<_>, Vec<_>) = std::mem::take(trash)
+        .into_iter()
+        .partition(|trashed| trashed.is_expired(retention, now_ms));
+    *trash = kept;
+    expired.into_iter().map(|trashed| trashed.message).collect()
+}
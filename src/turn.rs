@@ -0,0 +1,274 @@
+use std::fmt;
+
+use crate::tool_pairs::requested_tool_call_ids;
+use crate::{BaseMessage, Conversation, MessageEnum, MessageType};
+
+/// What the conversation is waiting for next.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TurnState {
+    AwaitingUser,
+    ReadyForModel,
+    AwaitingToolResults { pending: Vec<String> },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TurnError {
+    pub state: TurnState,
+    pub got: MessageType,
+}
+
+impl fmt::Display for TurnError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.state {
+            TurnState::AwaitingUser => {
+                write!(f, "Expected a human message, got {:?}", self.got)
+            }
+            TurnState::ReadyForModel => {
+                write!(f, "Expected an ai message, got {:?}", self.got)
+            }
+            TurnState::AwaitingToolResults { pending } => write!(
+                f,
+                "Expected tool results for [{}], got {:?}",
+                pending.join(", "),
+                self.got
+            ),
+        }
+    }
+}
+
+impl std::error::Error for TurnError {}
+
+/// Encodes the chat protocol: system prompts come first, the model answers
+/// after the user or after every requested tool result is in, and tool
+/// results only answer calls that are still pending. Chat and unknown
+/// messages pass through without changing the state. An Ai message's
+/// `tool_calls` become the pending results; callers can also announce calls
+/// with [`TurnStateMachine::expect_tool_results`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TurnStateMachine {
+    state: TurnState,
+    started: bool,
+}
+
+impl Default for TurnStateMachine {
+    fn default() -> Self {
+        TurnStateMachine {
+            state: TurnState::AwaitingUser,
+            started: false,
+        }
+    }
+}
+
+impl TurnStateMachine {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Picks up the state an existing conversation left off in. History is
+    /// trusted, so nothing is rejected while replaying.
+    pub fn from_conversation(conversation: &Conversation) -> Self {
+        let mut machine = Self::new();
+        for message in conversation {
+            machine.advance(message);
+        }
+        machine
+    }
+
+    pub fn state(&self) -> &TurnState {
+        &self.state
+    }
+
+    pub fn check(&self, message: &MessageEnum) -> Result<(), TurnError> {
+        let message_type = message.message_type();
+        let allowed = match (&self.state, message_type) {
+            (_, MessageType::Chat | MessageType::Remove | MessageType::Unknown) => true,
+            (_, MessageType::System) => !self.started,
+            (TurnState::AwaitingToolResults { pending }, MessageType::Tool) => message
+                .as_tool()
+                .is_some_and(|tool| pending.iter().any(|id| id == tool.tool_call_id())),
+            (TurnState::AwaitingToolResults { .. }, _) => false,
+            (_, MessageType::Tool) => false,
+            (_, MessageType::Human) => true,
+            (TurnState::ReadyForModel, MessageType::Ai) => true,
+            (TurnState::AwaitingUser, MessageType::Ai) => false,
+        };
+        if allowed {
+            Ok(())
+        } else {
+            Err(TurnError {
+                state: self.state.clone(),
+                got: *message_type,
+            })
+        }
+    }
+
+    /// Validates `message` against the protocol and, if it fits, appends it.
+    pub fn append(
+        &mut self,
+        conversation: &mut Conversation,
+        message: impl Into<MessageEnum>,
+    ) -> Result<(), TurnError> {
+        let message = message.into();
+        self.check(&message)?;
+        self.advance(&message);
+        conversation.push(message);
+        Ok(())
+    }
+
+    /// Records that the last Ai message requested these tool calls.
+    pub fn expect_tool_results<I, S>(&mut self, call_ids: I)
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let pending: Vec<String> = call_ids.into_iter().map(Into::into).collect();
+        if !pending.is_empty() {
+            self.state = TurnState::AwaitingToolResults { pending };
+        }
+    }
+
+    fn advance(&mut self, message: &MessageEnum) {
+        match message.message_type() {
+            MessageType::System
+            | MessageType::Chat
+            | MessageType::Remove
+            | MessageType::Unknown => return,
+            MessageType::Human => self.state = TurnState::ReadyForModel,
+            MessageType::Ai => {
+                self.state = TurnState::AwaitingUser;
+                self.expect_tool_results(requested_tool_call_ids(message));
+            }
+            MessageType::Tool => {
+                let next = match (&self.state, message.as_tool()) {
+                    (TurnState::AwaitingToolResults { pending }, Some(tool)) => {
+                        let pending: Vec<String> = pending
+                            .iter()
+                            .filter(|id| *id != tool.tool_call_id())
+                            .cloned()
+                            .collect();
+                        if pending.is_empty() {
+                            TurnState::ReadyForModel
+                        } else {
+                            TurnState::AwaitingToolResults { pending }
+                        }
+                    }
+                    _ => TurnState::ReadyForModel,
+                };
+                self.state = next;
+            }
+        }
+        self.started = true;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tool_message::ToolStatus;
+    use crate::{AiMessage, HumanMessage, SystemMessage, ToolCall, ToolMessage};
+
+    fn tool_result(call_id: &str) -> ToolMessage {
+        ToolMessage::new("42", call_id.to_string(), None, ToolStatus::Success)
+    }
+
+    #[test]
+    fn test_basic_turns() {
+        let mut conversation = Conversation::new();
+        let mut machine = TurnStateMachine::new();
+
+        machine
+            .append(&mut conversation, SystemMessage::new("Be brief."))
+            .unwrap();
+        assert_eq!(machine.state(), &TurnState::AwaitingUser);
+        assert!(machine
+            .append(&mut conversation, AiMessage::new("Hi"))
+            .is_err());
+
+        machine
+            .append(&mut conversation, HumanMessage::new("Hello"))
+            .unwrap();
+        assert_eq!(machine.state(), &TurnState::ReadyForModel);
+        machine
+            .append(&mut conversation, AiMessage::new("Hi!"))
+            .unwrap();
+        assert_eq!(machine.state(), &TurnState::AwaitingUser);
+
+        let error = machine
+            .append(&mut conversation, SystemMessage::new("Late"))
+            .unwrap_err();
+        assert_eq!(error.got, MessageType::System);
+        assert_eq!(conversation.len(), 3);
+    }
+
+    #[test]
+    fn test_tool_results_must_match_pending_calls() {
+        let mut conversation = Conversation::new();
+        let mut machine = TurnStateMachine::new();
+        machine
+            .append(&mut conversation, HumanMessage::new("Weather?"))
+            .unwrap();
+        machine
+            .append(&mut conversation, AiMessage::new(""))
+            .unwrap();
+        machine.expect_tool_results(["call_1", "call_2"]);
+
+        let error = machine
+            .append(&mut conversation, HumanMessage::new("Well?"))
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Expected tool results for [call_1, call_2], got Human"
+        );
+        assert!(machine
+            .append(&mut conversation, tool_result("call_9"))
+            .is_err());
+
+        machine
+            .append(&mut conversation, tool_result("call_2"))
+            .unwrap();
+        assert_eq!(
+            machine.state(),
+            &TurnState::AwaitingToolResults {
+                pending: vec!["call_1".to_string()]
+            }
+        );
+        machine
+            .append(&mut conversation, tool_result("call_1"))
+            .unwrap();
+        assert_eq!(machine.state(), &TurnState::ReadyForModel);
+    }
+
+    #[test]
+    fn test_from_conversation_resumes_state() {
+        let conversation: Conversation = vec![
+            MessageEnum::from(SystemMessage::new("Be brief.")),
+            MessageEnum::from(HumanMessage::new("Hi")),
+        ]
+        .into_iter()
+        .collect();
+
+        let machine = TurnStateMachine::from_conversation(&conversation);
+
+        assert_eq!(machine.state(), &TurnState::ReadyForModel);
+        assert!(machine
+            .check(&SystemMessage::new("Another").into())
+            .is_err());
+        assert_eq!(
+            TurnStateMachine::from_conversation(&Conversation::new()).state(),
+            &TurnState::AwaitingUser
+        );
+
+        let mut conversation = conversation;
+        conversation.push(AiMessage::new("").with_tool_calls(vec![ToolCall::new(
+            "call_1",
+            "weather",
+            serde_json::Value::Null,
+        )]));
+        assert_eq!(
+            TurnStateMachine::from_conversation(&conversation).state(),
+            &TurnState::AwaitingToolResults {
+                pending: vec!["call_1".to_string()]
+            }
+        );
+    }
+}
@@ -0,0 +1,136 @@
+use std::borrow::Cow;
+
+use serde::{Deserializer, Serializer};
+
+use crate::prelude::*;
+
+/// A message whose type this version of the crate does not recognise.
+/// `type_name` holds the original name so it is written back out as
+/// `message_type`; `base.message_type` is `MessageType::Unknown`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnknownMessage {
+    pub type_name: String,
+    pub base: BaseMessageFields,
+}
+
+impl UnknownMessage {
+    pub fn new(message_type: &str, content: &str) -> Self {
+        UnknownMessage {
+            type_name: message_type.to_string(),
+            base: BaseMessageFields::new(content, MessageType::Unknown),
+        }
+    }
+
+    pub fn type_name(&self) -> &str {
+        &self.type_name
+    }
+}
+
+impl Serialize for UnknownMessage {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        // `base` leaves out its `message_type` while it is unknown.
+        #[derive(Serialize)]
+        struct Fields<'a> {
+            #[serde(flatten)]
+            base: &'a BaseMessageFields,
+            message_type: &'a str,
+        }
+
+        Fields {
+            base: &self.base,
+            message_type: &self.type_name,
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for UnknownMessage {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let value = serde_json::Value::deserialize(deserializer)?;
+        let type_name = value
+            .get("message_type")
+            .and_then(serde_json::Value::as_str)
+            .ok_or_else(|| serde::de::Error::missing_field("message_type"))?
+            .to_string();
+        let mut base = BaseMessageFields::deserialize(value).map_err(serde::de::Error::custom)?;
+        base.message_type = MessageType::Unknown;
+        Ok(UnknownMessage { type_name, base })
+    }
+}
+
+impl BaseMessage for UnknownMessage {
+    fn content(&self) -> &str {
+        self.base.content.as_str()
+    }
+
+    fn message_type(&self) -> &MessageType {
+        &self.base.message_type
+    }
+
+    fn role(&self) -> &str {
+        &self.type_name
+    }
+
+    fn name(&self) -> Option<&str> {
+        self.base.name.as_deref()
+    }
+
+    fn content_blocks(&self) -> Cow<'_, [ContentBlock]> {
+        self.base.content.blocks()
+    }
+
+    fn text(&self) -> Cow<'_, str> {
+        self.base.content.text()
+    }
+
+    fn is_example(&self) -> bool {
+        self.base.example
+    }
+
+    fn additional_kwargs(&self) -> &Metadata {
+        &self.base.additional_kwargs
+    }
+
+    fn response_metadata(&self) -> &Metadata {
+        &self.base.response_metadata
+    }
+
+    fn id(&self) -> Option<&str> {
+        self.base.id.as_deref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unknown_message_creation() {
+        let message = UnknownMessage::new("critic", "Needs more detail.");
+
+        assert_eq!(message.content(), "Needs more detail.");
+        assert_eq!(message.role(), "critic");
+        assert_eq!(message.message_type(), &MessageType::Unknown);
+        assert_eq!(message.type_name(), "critic");
+    }
+
+    #[test]
+    fn test_unknown_message_serialization() {
+        let message = UnknownMessage::new("critic", "Needs more detail.");
+
+        let serialized = serde_json::to_string(&message).unwrap();
+
+        assert_eq!(
+            serialized,
+            r#"{"content":"Needs more detail.","example":false,"message_type":"critic"}"#
+        );
+        let deserialized: UnknownMessage = serde_json::from_str(&serialized).unwrap();
+        assert_eq!(deserialized, message);
+    }
+}
@@ -0,0 +1,144 @@
+use std::collections::BTreeMap;
+use std::ops::{Add, AddAssign};
+
+use serde::{Deserialize, Serialize};
+
+/// Token counts for one model call. The details break the totals down by
+/// provider-specific kind, e.g. `cache_read` or `audio` input tokens and
+/// `reasoning` output tokens.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UsageMetadata {
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub total_tokens: u64,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty", default)]
+    pub input_token_details: BTreeMap<String, u64>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty", default)]
+    pub output_token_details: BTreeMap<String, u64>,
+}
+
+impl UsageMetadata {
+    pub fn new(input_tokens: u64, output_tokens: u64) -> Self {
+        UsageMetadata {
+            input_tokens,
+            output_tokens,
+            total_tokens: input_tokens + output_tokens,
+            ..Self::default()
+        }
+    }
+
+    pub fn with_input_detail(mut self, kind: impl Into<String>, tokens: u64) -> Self {
+        self.input_token_details.insert(kind.into(), tokens);
+        self
+    }
+
+    pub fn with_output_detail(mut self, kind: impl Into<String>, tokens: u64) -> Self {
+        self.output_token_details.insert(kind.into(), tokens);
+        self
+    }
+
+    /// Field-wise sum, for totalling calls or joining streamed chunks.
+    pub fn add_usage(&self, other: &UsageMetadata) -> UsageMetadata {
+        UsageMetadata {
+            input_tokens: self.input_tokens + other.input_tokens,
+            output_tokens: self.output_tokens + other.output_tokens,
+            total_tokens: self.total_tokens + other.total_tokens,
+            input_token_details: combine(
+                &self.input_token_details,
+                &other.input_token_details,
+                u64::saturating_add,
+            ),
+            output_token_details: combine(
+                &self.output_token_details,
+                &other.output_token_details,
+                u64::saturating_add,
+            ),
+        }
+    }
+
+    /// Field-wise difference, saturating at zero; details that reach zero
+    /// are dropped.
+    pub fn subtract_usage(&self, other: &UsageMetadata) -> UsageMetadata {
+        UsageMetadata {
+            input_tokens: self.input_tokens.saturating_sub(other.input_tokens),
+            output_tokens: self.output_tokens.saturating_sub(other.output_tokens),
+            total_tokens: self.total_tokens.saturating_sub(other.total_tokens),
+            input_token_details: combine(
+                &self.input_token_details,
+                &other.input_token_details,
+                u64::saturating_sub,
+            ),
+            output_token_details: combine(
+                &self.output_token_details,
+                &other.output_token_details,
+                u64::saturating_sub,
+            ),
+        }
+    }
+}
+
+fn combine(
+    left: &BTreeMap<String, u64>,
+    right: &BTreeMap<String, u64>,
+    op: fn(u64, u64) -> u64,
+) -> BTreeMap<String, u64> {
+    left.keys()
+        .chain(right.keys())
+        .map(|kind| {
+            let value = op(
+                left.get(kind).copied().unwrap_or_default(),
+                right.get(kind).copied().unwrap_or_default(),
+            );
+            (kind.clone(), value)
+        })
+        .filter(|(_, value)| *value > 0)
+        .collect()
+}
+
+impl Add for UsageMetadata {
+    type Output = UsageMetadata;
+
+    fn add(self, other: UsageMetadata) -> UsageMetadata {
+        self.add_usage(&other)
+    }
+}
+
+impl AddAssign for UsageMetadata {
+    fn add_assign(&mut self, other: UsageMetadata) {
+        *self = self.add_usage(&other);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_add_and_subtract_usage() {
+        let first = UsageMetadata::new(10, 2).with_input_detail("cache_read", 4);
+        let second = UsageMetadata::new(0, 3).with_output_detail("reasoning", 1);
+
+        let total = first.clone() + second.clone();
+
+        assert_eq!(total.input_tokens, 10);
+        assert_eq!(total.output_tokens, 5);
+        assert_eq!(total.total_tokens, 15);
+        assert_eq!(total.input_token_details["cache_read"], 4);
+        assert_eq!(total.output_token_details["reasoning"], 1);
+        assert_eq!(total.subtract_usage(&second), first);
+        assert_eq!(first.subtract_usage(&total), UsageMetadata::default());
+    }
+
+    #[test]
+    fn test_serde_skips_empty_details() {
+        let usage = UsageMetadata::new(3, 4);
+
+        let json = serde_json::to_string(&usage).unwrap();
+
+        assert_eq!(
+            json,
+            r#"{"input_tokens":3,"output_tokens":4,"total_tokens":7}"#
+        );
+        assert_eq!(serde_json::from_str::<UsageMetadata>(&json).unwrap(), usage);
+    }
+}
@@ -0,0 +1,576 @@
+use std::ops::Range;
+
+use crate::tokens::approximate_tokens;
+use crate::window::message_groups;
+use crate::{AnyMessage, BaseMessage, ContentBlock, MessageContent, MessageEnum, MessageType};
+
+/// Counts the tokens a message costs in a prompt. Implement it over a real
+/// tokenizer such as tiktoken; closures work too.
+pub trait TokenCounter {
+    fn count_tokens(&self, message: &MessageEnum) -> usize;
+}
+
+impl<F: Fn(&MessageEnum) -> usize> TokenCounter for F {
+    fn count_tokens(&self, message: &MessageEnum) -> usize {
+        self(message)
+    }
+}
+
+/// The [`approximate_tokens`] heuristic over a message's text.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ApproximateTokenCounter;
+
+impl TokenCounter for ApproximateTokenCounter {
+    fn count_tokens(&self, message: &MessageEnum) -> usize {
+        approximate_tokens(&message.text())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrimStrategy {
+    /// Keep the earliest messages that fit.
+    First,
+    /// Keep the latest messages that fit, plus a leading system message
+    /// when `include_system` is set.
+    Last { include_system: bool },
+}
+
+/// [`trim_messages_with`] using the [`ApproximateTokenCounter`].
+pub fn trim_messages(
+    messages: &[MessageEnum],
+    max_tokens: usize,
+    strategy: TrimStrategy,
+) -> Vec<MessageEnum> {
+    trim_messages_with(messages, max_tokens, strategy, &ApproximateTokenCounter)
+}
+
+/// Drops whole messages from the end (`First`) or the start (`Last`) until
+/// the rest fit in `max_tokens`. An Ai message is never separated from the
+/// tool results answering it. A kept system message and pinned messages are
+/// paid for first and always kept, even if they alone are over budget.
+pub fn trim_messages_with(
+    messages: &[MessageEnum],
+    max_tokens: usize,
+    strategy: TrimStrategy,
+    counter: &dyn TokenCounter,
+) -> Vec<MessageEnum> {
+    let keep_system = matches!(
+        strategy,
+        TrimStrategy::Last {
+            include_system: true
+        }
+    ) && messages
+        .first()
+        .is_some_and(|message| message.message_type() == &MessageType::System);
+    let (system, rest) = messages.split_at(usize::from(keep_system));
+
+    let cost = |group: &Range<usize>| -> usize {
+        rest[group.clone()]
+            .iter()
+            .map(|m| counter.count_tokens(m))
+            .sum()
+    };
+    let (mut kept, mut groups): (Vec<_>, Vec<_>) = message_groups(rest)
+        .into_iter()
+        .partition(|group| rest[group.clone()].iter().any(MessageEnum::is_pinned));
+    let fixed = system
+        .iter()
+        .map(|m| counter.count_tokens(m))
+        .sum::<usize>()
+        + kept.iter().map(cost).sum::<usize>();
+    let mut remaining = max_tokens.saturating_sub(fixed);
+    if let TrimStrategy::Last { .. } = strategy {
+        groups.reverse();
+    }
+    for group in groups {
+        let cost = cost(&group);
+        if cost > remaining {
+            break;
+        }
+        remaining -= cost;
+        kept.push(group);
+    }
+    kept.sort_by_key(|group| group.start);
+
+    system
+        .iter()
+        .chain(kept.into_iter().flat_map(|group| &rest[group]))
+        .cloned()
+        .collect()
+}
+
+/// [`merge_message_runs_with`] joining content with a newline.
+pub fn merge_message_runs(messages: Vec<AnyMessage>) -> Vec<AnyMessage> {
+    merge_message_runs_with(messages, "\n")
+}
+
+/// Collapses consecutive messages with the same role into one, for
+/// providers that reject back-to-back turns from the same side. Content is
+/// joined with `separator`, tool calls are concatenated, and kwargs and
+/// metadata are combined with the first message's values winning on
+/// conflicts; the first message's id and name are kept. Tool messages
+/// answer different calls and remove sentinels target different ids, so
+/// neither is ever merged.
+pub fn merge_message_runs_with(messages: Vec<AnyMessage>, separator: &str) -> Vec<AnyMessage> {
+    let mut merged: Vec<AnyMessage> = Vec::with_capacity(messages.len());
+    for message in messages {
+        match merged.last_mut() {
+            Some(last)
+                if !matches!(message, AnyMessage::Tool(_) | AnyMessage::Remove(_))
+                    && last.role() == message.role() =>
+            {
+                merge_into(last, message, separator)
+            }
+            _ => merged.push(message),
+        }
+    }
+    merged
+}
+
+fn merge_into(target: &mut AnyMessage, message: AnyMessage, separator: &str) {
+    if let (Some(target), Some(source)) = (target.as_ai_mut(), message.as_ai()) {
+        target.tool_calls.extend_from_slice(&source.tool_calls);
+        target
+            .invalid_tool_calls
+            .extend_from_slice(&source.invalid_tool_calls);
+    }
+    let mut source = message.base().clone();
+    let base = target.base_mut();
+    if !base.content.is_empty() && !source.content.is_empty() {
+        base.content
+            .append(MessageContent::Text(separator.to_string()));
+    }
+    base.content.append(std::mem::take(&mut source.content));
+    for (key, value) in source.additional_kwargs {
+        base.additional_kwargs.entry(key).or_insert(value);
+    }
+    for (key, value) in source.response_metadata {
+        base.response_metadata.entry(key).or_insert(value);
+    }
+    base.pinned |= source.pinned;
+}
+
+/// Selects messages for [`filter_messages`]. A message is kept when it
+/// matches any `include_*` criterion (or none are set) and no `exclude_*`
+/// criterion.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MessageFilter {
+    include_types: Vec<MessageType>,
+    exclude_types: Vec<MessageType>,
+    include_names: Vec<String>,
+    exclude_names: Vec<String>,
+    include_ids: Vec<String>,
+    exclude_ids: Vec<String>,
+}
+
+impl MessageFilter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn include_types(mut self, types: impl IntoIterator<Item = MessageType>) -> Self {
+        self.include_types.extend(types);
+        self
+    }
+
+    pub fn exclude_types(mut self, types: impl IntoIterator<Item = MessageType>) -> Self {
+        self.exclude_types.extend(types);
+        self
+    }
+
+    pub fn include_names<S: Into<String>>(mut self, names: impl IntoIterator<Item = S>) -> Self {
+        self.include_names.extend(names.into_iter().map(Into::into));
+        self
+    }
+
+    pub fn exclude_names<S: Into<String>>(mut self, names: impl IntoIterator<Item = S>) -> Self {
+        self.exclude_names.extend(names.into_iter().map(Into::into));
+        self
+    }
+
+    pub fn include_ids<S: Into<String>>(mut self, ids: impl IntoIterator<Item = S>) -> Self {
+        self.include_ids.extend(ids.into_iter().map(Into::into));
+        self
+    }
+
+    pub fn exclude_ids<S: Into<String>>(mut self, ids: impl IntoIterator<Item = S>) -> Self {
+        self.exclude_ids.extend(ids.into_iter().map(Into::into));
+        self
+    }
+
+    pub fn matches(&self, message: &impl BaseMessage) -> bool {
+        let in_list = |list: &[String], value: Option<&str>| {
+            value.is_some_and(|value| list.iter().any(|item| item == value))
+        };
+        let message_type = message.message_type();
+        let no_includes = self.include_types.is_empty()
+            && self.include_names.is_empty()
+            && self.include_ids.is_empty();
+        let included = no_includes
+            || self.include_types.contains(message_type)
+            || in_list(&self.include_names, message.name())
+            || in_list(&self.include_ids, message.id());
+        let excluded = self.exclude_types.contains(message_type)
+            || in_list(&self.exclude_names, message.name())
+            || in_list(&self.exclude_ids, message.id());
+        included && !excluded
+    }
+}
+
+/// The messages `filter` matches, in their original order.
+pub fn filter_messages<M: BaseMessage + Clone>(messages: &[M], filter: MessageFilter) -> Vec<M> {
+    messages
+        .iter()
+        .filter(|message| filter.matches(*message))
+        .cloned()
+        .collect()
+}
+
+/// Kwarg holding a split part's 1-based position.
+pub const SPLIT_PART_KEY: &str = "split_part";
+/// Kwarg holding how many parts a message was split into.
+pub const SPLIT_TOTAL_KEY: &str = "split_total";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SplitStrategy {
+    /// Cut anywhere, repeating about `overlap` tokens of each part at the
+    /// start of the next.
+    Chars { overlap: usize },
+    /// Cut between words, repeating the trailing words worth up to `overlap`
+    /// tokens. A word longer than the budget is cut like `Chars`.
+    Words { overlap: usize },
+}
+
+/// Splits a message whose text is over `max_tokens` (by
+/// [`approximate_tokens`]) into continuation messages of the same type,
+/// marked with [`SPLIT_PART_KEY`] and [`SPLIT_TOTAL_KEY`]. Overlap is capped
+/// at half the budget. Non-text blocks and tool calls stay on the first
+/// and last part respectively, and ids get a `-{part}` suffix. A message
+/// that fits is returned unchanged.
+pub fn split_long_message(
+    message: &AnyMessage,
+    max_tokens: usize,
+    strategy: SplitStrategy,
+) -> Vec<AnyMessage> {
+    let text = message.base().content.text();
+    let pieces = split_text(&text, max_tokens, strategy);
+    if pieces.len() <= 1 {
+        return vec![message.clone()];
+    }
+
+    let total = pieces.len();
+    let other_blocks: Vec<ContentBlock> = match &message.base().content {
+        MessageContent::Text(_) => Vec::new(),
+        MessageContent::Blocks(blocks) => blocks
+            .iter()
+            .filter(|block| !matches!(block, ContentBlock::Text { .. }))
+            .cloned()
+            .collect(),
+    };
+    pieces
+        .into_iter()
+        .enumerate()
+        .map(|(index, piece)| {
+            let mut part = message.clone();
+            if let Some(ai) = part.as_ai_mut().filter(|_| index + 1 < total) {
+                ai.tool_calls.clear();
+                ai.invalid_tool_calls.clear();
+            }
+            let base = part.base_mut();
+            base.content = if index == 0 && !other_blocks.is_empty() {
+                let mut blocks = other_blocks.clone();
+                blocks.push(ContentBlock::text(piece));
+                MessageContent::Blocks(blocks)
+            } else {
+                MessageContent::Text(piece.to_string())
+            };
+            base.id = base.id.take().map(|id| format!("{}-{}", id, index + 1));
+            base.additional_kwargs.insert(SPLIT_PART_KEY, index + 1);
+            base.additional_kwargs.insert(SPLIT_TOTAL_KEY, total);
+            part
+        })
+        .collect()
+}
+
+fn split_text(text: &str, max_tokens: usize, strategy: SplitStrategy) -> Vec<&str> {
+    // Approximate tokens are four characters, so budgets are in characters.
+    let limit = max_tokens.max(1) * 4;
+    let bounds: Vec<usize> = text
+        .char_indices()
+        .map(|(index, _)| index)
+        .chain([text.len()])
+        .collect();
+    let chars = bounds.len() - 1;
+    let (cuts, overlap): (Vec<usize>, usize) = match strategy {
+        SplitStrategy::Chars { overlap } => ((0..=chars).collect(), overlap),
+        SplitStrategy::Words { overlap } => {
+            let mut previous = None;
+            let starts = text.chars().enumerate().filter_map(|(index, c)| {
+                let start = !c.is_whitespace() && previous.is_none_or(char::is_whitespace);
+                previous = Some(c);
+                start.then_some(index)
+            });
+            (starts.chain([chars]).collect(), overlap)
+        }
+    };
+    let overlap = (overlap * 4).min(limit / 2);
+    let trim = matches!(strategy, SplitStrategy::Words { .. });
+
+    let mut pieces = Vec::new();
+    let mut start = 0;
+    let mut previous_end = 0;
+    loop {
+        let end = if chars - start <= limit {
+            chars
+        } else {
+            cuts.iter()
+                .rev()
+                .copied()
+                .find(|&cut| cut > start && cut - start <= limit)
+                .unwrap_or(start + limit)
+        };
+        // A part that would only repeat the overlap drops it instead.
+        if end <= previous_end {
+            start = previous_end;
+            continue;
+        }
+        previous_end = end;
+        let piece = &text[bounds[start]..bounds[end]];
+        pieces.push(if trim { piece.trim_end() } else { piece });
+        if end == chars {
+            return pieces;
+        }
+        start = cuts
+            .iter()
+            .copied()
+            .find(|&cut| cut > start && cut <= end && end - cut <= overlap)
+            .unwrap_or(end);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tool_message::ToolStatus;
+    use crate::{AiMessage, HumanMessage, RemoveMessage, SystemMessage, ToolCall, ToolMessage};
+    use serde_json::json;
+
+    fn contents(messages: &[MessageEnum]) -> Vec<&str> {
+        messages.iter().map(|message| message.content()).collect()
+    }
+
+    fn words(message: &MessageEnum) -> usize {
+        message.content().split_whitespace().count()
+    }
+
+    fn chat() -> Vec<MessageEnum> {
+        vec![
+            SystemMessage::new("be very brief").into(),
+            HumanMessage::new("one two").into(),
+            AiMessage::new("three four").into(),
+            HumanMessage::new("five").into(),
+        ]
+    }
+
+    #[test]
+    fn test_trim_first_and_last() {
+        let first = trim_messages_with(&chat(), 6, TrimStrategy::First, &words);
+        assert_eq!(contents(&first), vec!["be very brief", "one two"]);
+
+        let last = trim_messages_with(
+            &chat(),
+            4,
+            TrimStrategy::Last {
+                include_system: false,
+            },
+            &words,
+        );
+        assert_eq!(contents(&last), vec!["three four", "five"]);
+
+        let with_system = trim_messages_with(
+            &chat(),
+            6,
+            TrimStrategy::Last {
+                include_system: true,
+            },
+            &words,
+        );
+        assert_eq!(
+            contents(&with_system),
+            vec!["be very brief", "three four", "five"]
+        );
+    }
+
+    #[test]
+    fn test_trim_keeps_pinned_messages() {
+        let mut messages = chat();
+        messages[1].set_pinned(true);
+
+        let last = trim_messages_with(
+            &messages,
+            4,
+            TrimStrategy::Last {
+                include_system: true,
+            },
+            &words,
+        );
+        let first = trim_messages_with(&messages[1..], 3, TrimStrategy::First, &words);
+
+        assert_eq!(contents(&last), vec!["be very brief", "one two"]);
+        assert_eq!(contents(&first), vec!["one two"]);
+        let roomy = trim_messages_with(
+            &messages[1..],
+            3,
+            TrimStrategy::Last {
+                include_system: false,
+            },
+            &words,
+        );
+        assert_eq!(contents(&roomy), vec!["one two", "five"]);
+    }
+
+    #[test]
+    fn test_merge_message_runs_keeps_removals_apart() {
+        let messages: Vec<AnyMessage> = vec![
+            RemoveMessage::new("m1").into(),
+            RemoveMessage::new("m2").into(),
+        ];
+
+        let merged = merge_message_runs(messages.clone());
+
+        assert_eq!(merged, messages);
+    }
+
+    #[test]
+    fn test_merge_message_runs() {
+        let mut first = HumanMessage::new("Hi");
+        first.set_id(Some("h1".to_string()));
+        first.base.additional_kwargs.insert("channel", "web");
+        let mut second = HumanMessage::new("Are you there?");
+        second.set_id(Some("h2".to_string()));
+        second.base.additional_kwargs.insert("channel", "sms");
+        second.base.additional_kwargs.insert("locale", "en");
+        let messages: Vec<AnyMessage> = vec![
+            SystemMessage::new("Be brief.").into(),
+            first.into(),
+            second.into(),
+            AiMessage::new("")
+                .with_tool_calls(vec![ToolCall::new("c1", "ping", json!({}))])
+                .into(),
+            AiMessage::new("")
+                .with_tool_calls(vec![ToolCall::new("c2", "ping", json!({}))])
+                .into(),
+            ToolMessage::new("pong", "c1".to_string(), None, ToolStatus::Success).into(),
+            ToolMessage::new("pong", "c2".to_string(), None, ToolStatus::Success).into(),
+        ];
+
+        let merged = merge_message_runs_with(messages, " ");
+
+        assert_eq!(merged.len(), 5);
+        assert_eq!(merged[1].content(), "Hi Are you there?");
+        assert_eq!(merged[1].id(), Some("h1"));
+        assert_eq!(merged[1].additional_kwargs()["channel"], "web");
+        assert_eq!(merged[1].additional_kwargs()["locale"], "en");
+        assert_eq!(merged[2].content(), "");
+        assert_eq!(merged[2].tool_calls().len(), 2);
+        assert!(matches!(merged[4], AnyMessage::Tool(_)));
+        assert_eq!(merge_message_runs(Vec::new()), Vec::new());
+    }
+
+    #[test]
+    fn test_filter_messages() {
+        let mut debug = HumanMessage::new("dump state");
+        debug.set_name(Some("debug".to_string()));
+        let mut pinned = AiMessage::new("Paris.");
+        pinned.set_id(Some("a1".to_string()));
+        let messages: Vec<MessageEnum> = vec![
+            SystemMessage::new("Be brief.").into(),
+            HumanMessage::new("Capital of France?").into(),
+            debug.into(),
+            pinned.into(),
+            AiMessage::new("Anything else?").into(),
+        ];
+
+        let filtered = filter_messages(
+            &messages,
+            MessageFilter::new()
+                .include_types([MessageType::Human])
+                .exclude_names(["debug"])
+                .include_ids(["a1"]),
+        );
+        assert_eq!(contents(&filtered), vec!["Capital of France?", "Paris."]);
+
+        let filtered = filter_messages(
+            &messages,
+            MessageFilter::new().exclude_types([MessageType::System, MessageType::Ai]),
+        );
+        assert_eq!(
+            contents(&filtered),
+            vec!["Capital of France?", "dump state"]
+        );
+        assert_eq!(filter_messages(&messages, MessageFilter::new()).len(), 5);
+    }
+
+    #[test]
+    fn test_split_long_message() {
+        let mut pasted = HumanMessage::new("alpha beta gamma delta epsilon");
+        pasted.set_id(Some("h1".to_string()));
+        let pasted = AnyMessage::from(pasted);
+
+        let parts = split_long_message(&pasted, 3, SplitStrategy::Words { overlap: 0 });
+        let texts: Vec<&str> = parts.iter().map(|part| part.content()).collect();
+        assert_eq!(texts, vec!["alpha beta", "gamma delta", "epsilon"]);
+        assert_eq!(parts[1].id(), Some("h1-2"));
+        assert_eq!(
+            parts[1].additional_kwargs().get_u64(SPLIT_PART_KEY),
+            Some(2)
+        );
+        assert_eq!(
+            parts[1].additional_kwargs().get_u64(SPLIT_TOTAL_KEY),
+            Some(3)
+        );
+
+        let parts = split_long_message(&pasted, 3, SplitStrategy::Words { overlap: 2 });
+        let texts: Vec<&str> = parts.iter().map(|part| part.content()).collect();
+        assert_eq!(
+            texts,
+            vec!["alpha beta", "beta gamma", "gamma delta", "epsilon"]
+        );
+
+        let parts = split_long_message(&pasted, 2, SplitStrategy::Chars { overlap: 1 });
+        assert_eq!(parts[0].content(), "alpha be");
+        assert_eq!(parts[1].content(), "a beta g");
+
+        assert_eq!(
+            split_long_message(&pasted, 100, SplitStrategy::Chars { overlap: 0 }),
+            vec![pasted]
+        );
+    }
+
+    #[test]
+    fn test_tool_results_stay_with_their_call() {
+        let messages: Vec<MessageEnum> = vec![
+            HumanMessage::new("Weather?").into(),
+            AiMessage::new("")
+                .with_tool_calls(vec![ToolCall::new("c1", "weather", json!({}))])
+                .into(),
+            ToolMessage::new(
+                "Sunny and warm",
+                "c1".to_string(),
+                None,
+                ToolStatus::Success,
+            )
+            .into(),
+            AiMessage::new("Sunny.").into(),
+        ];
+        let last = TrimStrategy::Last {
+            include_system: true,
+        };
+
+        let trimmed = trim_messages_with(&messages, 2, last, &words);
+        assert_eq!(contents(&trimmed), vec!["Sunny."]);
+        let trimmed = trim_messages_with(&messages, 4, last, &words);
+        assert_eq!(contents(&trimmed), vec!["", "Sunny and warm", "Sunny."]);
+        assert_eq!(trim_messages(&messages, 100, last).len(), 4);
+    }
+}
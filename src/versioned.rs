@@ -0,0 +1,202 @@
+use serde::{Deserialize, Serialize};
+
+use crate::clock::{Clock, SystemClock};
+use crate::{Conversation, MessageEnum};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum ConversationOp {
+    Push { message: MessageEnum },
+    Replace { index: usize, message: MessageEnum },
+    Remove { index: usize },
+    Truncate { len: usize },
+}
+
+impl ConversationOp {
+    fn apply(&self, conversation: &mut Conversation) {
+        let messages = conversation.messages_mut();
+        match self {
+            ConversationOp::Push { message } => messages.push(message.clone()),
+            ConversationOp::Replace { index, message } => {
+                if let Some(slot) = messages.get_mut(*index) {
+                    *slot = message.clone();
+                }
+            }
+            ConversationOp::Remove { index } => {
+                if *index < messages.len() {
+                    messages.remove(*index);
+                }
+            }
+            ConversationOp::Truncate { len } => messages.truncate(*len),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ConversationEvent {
+    pub version: u64,
+    pub timestamp_ms: u64,
+    #[serde(flatten)]
+    pub op: ConversationOp,
+}
+
+/// A conversation plus the log of every change made to it, so any earlier
+/// version can be rebuilt: "what did the model see when it answered?".
+/// Version 0 is the starting conversation; each event bumps it by one.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct VersionedConversation {
+    initial: Conversation,
+    #[serde(default)]
+    events: Vec<ConversationEvent>,
+    #[serde(skip)]
+    current: Option<Conversation>,
+}
+
+impl VersionedConversation {
+    pub fn new(initial: Conversation) -> Self {
+        VersionedConversation {
+            current: Some(initial.clone()),
+            initial,
+            events: Vec::new(),
+        }
+    }
+
+    pub fn version(&self) -> u64 {
+        self.events.len() as u64
+    }
+
+    pub fn events(&self) -> &[ConversationEvent] {
+        &self.events
+    }
+
+    /// The latest state.
+    pub fn current(&mut self) -> &Conversation {
+        if self.current.is_none() {
+            self.current = Some(self.replay(self.events.len()));
+        }
+        self.current.get_or_insert_with(Conversation::new)
+    }
+
+    pub fn record(&mut self, op: ConversationOp) -> u64 {
+        self.record_with(op, &SystemClock)
+    }
+
+    pub fn record_with(&mut self, op: ConversationOp, clock: &dyn Clock) -> u64 {
+        self.record_at(op, clock.now_ms())
+    }
+
+    /// Like [`VersionedConversation::record`] with an explicit timestamp, for
+    /// importing history or tests.
+    pub fn record_at(&mut self, op: ConversationOp, timestamp_ms: u64) -> u64 {
+        self.current();
+        if let Some(current) = self.current.as_mut() {
+            op.apply(current);
+        }
+        let version = self.version() + 1;
+        self.events.push(ConversationEvent {
+            version,
+            timestamp_ms,
+            op,
+        });
+        version
+    }
+
+    pub fn push(&mut self, message: impl Into<MessageEnum>) -> u64 {
+        self.record(ConversationOp::Push {
+            message: message.into(),
+        })
+    }
+
+    /// The conversation as it was right after `version` was recorded.
+    pub fn at(&self, version: u64) -> Option<Conversation> {
+        (version <= self.version()).then(|| self.replay(version as usize))
+    }
+
+    /// The conversation as it was at `timestamp_ms`, including every event
+    /// recorded at or before it.
+    pub fn at_time(&self, timestamp_ms: u64) -> Conversation {
+        let applied = self
+            .events
+            .iter()
+            .take_while(|event| event.timestamp_ms <= timestamp_ms)
+            .count();
+        self.replay(applied)
+    }
+
+    fn replay(&self, events: usize) -> Conversation {
+        let mut conversation = self.initial.clone();
+        for event in &self.events[..events] {
+            event.op.apply(&mut conversation);
+        }
+        conversation
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::MockClock;
+    use crate::{AiMessage, BaseMessage, HumanMessage, SystemMessage};
+
+    fn history() -> VersionedConversation {
+        let initial: Conversation = vec![SystemMessage::new("Be brief.")].into_iter().collect();
+        let mut versioned = VersionedConversation::new(initial);
+        versioned.record_at(
+            ConversationOp::Push {
+                message: HumanMessage::new("Hi").into(),
+            },
+            100,
+        );
+        versioned.record_at(
+            ConversationOp::Push {
+                message: AiMessage::new("Hello!").into(),
+            },
+            200,
+        );
+        versioned.record_at(
+            ConversationOp::Replace {
+                index: 2,
+                message: AiMessage::new("Hello, how can I help?").into(),
+            },
+            300,
+        );
+        versioned
+    }
+
+    #[test]
+    fn test_at_version() {
+        let mut versioned = history();
+
+        assert_eq!(versioned.version(), 3);
+        assert_eq!(versioned.at(0).unwrap().len(), 1);
+        assert_eq!(versioned.at(2).unwrap().messages()[2].content(), "Hello!");
+        assert_eq!(versioned.at(3).unwrap(), versioned.current().clone());
+        assert!(versioned.at(4).is_none());
+    }
+
+    #[test]
+    fn test_at_time() {
+        let versioned = history();
+
+        assert_eq!(versioned.at_time(50).len(), 1);
+        assert_eq!(versioned.at_time(250).messages()[2].content(), "Hello!");
+        assert_eq!(
+            versioned.at_time(u64::MAX).messages()[2].content(),
+            "Hello, how can I help?"
+        );
+    }
+
+    #[test]
+    fn test_serialized_log_rebuilds_current() {
+        let mut versioned = history();
+        versioned.record_with(ConversationOp::Truncate { len: 2 }, &MockClock::new(400));
+        assert_eq!(versioned.events()[3].timestamp_ms, 400);
+
+        let json = serde_json::to_string(&versioned).unwrap();
+        let mut restored: VersionedConversation = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(restored.current().len(), 2);
+        assert_eq!(restored.at(3), versioned.at(3));
+        assert!(json.contains(r#""op":"truncate""#));
+    }
+}
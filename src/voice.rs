@@ -0,0 +1,146 @@
+use std::io;
+
+use serde::{Deserialize, Serialize};
+
+use crate::blob::BlobStore;
+
+/// Audio provenance for a transcribed or synthesized message. The message
+/// content holds the transcript; the audio itself lives in a [`BlobStore`].
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct VoiceMetadata {
+    pub duration_ms: u64,
+
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub audio_blob: Option<String>,
+
+    /// Speech-to-text confidence, from 0.0 to 1.0.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub transcript_confidence: Option<f32>,
+
+    /// Diarization: who spoke when, in order.
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub segments: Vec<SpeechSegment>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SpeechSegment {
+    pub speaker: String,
+    pub start_ms: u64,
+    pub end_ms: u64,
+
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub text: Option<String>,
+}
+
+impl SpeechSegment {
+    pub fn new(speaker: impl Into<String>, start_ms: u64, end_ms: u64) -> Self {
+        SpeechSegment {
+            speaker: speaker.into(),
+            start_ms,
+            end_ms,
+            text: None,
+        }
+    }
+
+    pub fn with_text(mut self, text: impl Into<String>) -> Self {
+        self.text = Some(text.into());
+        self
+    }
+
+    pub fn duration_ms(&self) -> u64 {
+        self.end_ms.saturating_sub(self.start_ms)
+    }
+}
+
+impl VoiceMetadata {
+    pub fn new(duration_ms: u64) -> Self {
+        VoiceMetadata {
+            duration_ms,
+            ..Self::default()
+        }
+    }
+
+    pub fn with_audio_blob(mut self, key: impl Into<String>) -> Self {
+        self.audio_blob = Some(key.into());
+        self
+    }
+
+    pub fn with_transcript_confidence(mut self, confidence: f32) -> Self {
+        self.transcript_confidence = Some(confidence.clamp(0.0, 1.0));
+        self
+    }
+
+    pub fn with_segment(mut self, segment: SpeechSegment) -> Self {
+        self.segments.push(segment);
+        self
+    }
+
+    /// Distinct speakers in order of first appearance.
+    pub fn speakers(&self) -> Vec<&str> {
+        let mut speakers: Vec<&str> = Vec::new();
+        for segment in &self.segments {
+            if !speakers.contains(&segment.speaker.as_str()) {
+                speakers.push(&segment.speaker);
+            }
+        }
+        speakers
+    }
+
+    /// Total speaking time attributed to `speaker`.
+    pub fn speaking_time_ms(&self, speaker: &str) -> u64 {
+        self.segments
+            .iter()
+            .filter(|segment| segment.speaker == speaker)
+            .map(SpeechSegment::duration_ms)
+            .sum()
+    }
+
+    /// Loads the audio, or `None` when there is no blob or it is missing.
+    pub fn audio(&self, store: &dyn BlobStore) -> io::Result<Option<Vec<u8>>> {
+        match &self.audio_blob {
+            Some(key) => store.get(key),
+            None => Ok(None),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blob::InMemoryBlobStore;
+    use crate::{BaseMessage, HumanMessage, MessageEnum};
+
+    #[test]
+    fn test_diarization_helpers() {
+        let voice = VoiceMetadata::new(9_000)
+            .with_transcript_confidence(1.4)
+            .with_segment(SpeechSegment::new("alice", 0, 4_000))
+            .with_segment(SpeechSegment::new("bob", 4_000, 6_500))
+            .with_segment(SpeechSegment::new("alice", 6_500, 9_000).with_text("Thanks"));
+
+        assert_eq!(voice.transcript_confidence, Some(1.0));
+        assert_eq!(voice.speakers(), vec!["alice", "bob"]);
+        assert_eq!(voice.speaking_time_ms("alice"), 6_500);
+        assert_eq!(voice.speaking_time_ms("carol"), 0);
+    }
+
+    #[test]
+    fn test_voice_message_round_trip() {
+        let mut store = InMemoryBlobStore::new();
+        let key = store.put(b"RIFF....WAVE").unwrap();
+        let message = HumanMessage::new("Book a table for two")
+            .with_voice(VoiceMetadata::new(2_300).with_audio_blob(key));
+
+        let message: MessageEnum = message.into();
+        let json = serde_json::to_value(&message).unwrap();
+        assert_eq!(json["voice"]["duration_ms"], 2_300);
+        assert!(json["voice"].get("segments").is_none());
+
+        let parsed: MessageEnum = serde_json::from_value(json).unwrap();
+        assert_eq!(parsed, message);
+        assert_eq!(parsed.content(), "Book a table for two");
+        let voice = parsed.voice().unwrap();
+        assert_eq!(voice.audio(&store).unwrap().unwrap(), b"RIFF....WAVE");
+        assert_eq!(VoiceMetadata::new(1).audio(&store).unwrap(), None);
+    }
+}
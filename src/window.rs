@@ -0,0 +1,176 @@
+use std::ops::Range;
+
+use crate::tool_pairs::requested_tool_call_ids;
+use crate::{BaseMessage, MessageEnum, MessageType};
+
+/// Splits `messages` into the units a trimmer may keep or drop: an Ai message
+/// that requested tool calls together with the results that answer it, and
+/// every other message on its own.
+pub fn message_groups(messages: &[MessageEnum]) -> Vec<Range<usize>> {
+    let mut groups = Vec::new();
+    let mut index = 0;
+    while index < messages.len() {
+        let mut pending = requested_tool_call_ids(&messages[index]);
+        let start = index;
+        index += 1;
+        while !pending.is_empty() {
+            let Some(tool) = messages.get(index).and_then(MessageEnum::as_tool) else {
+                break;
+            };
+            let Some(position) = pending.iter().position(|id| id == tool.tool_call_id()) else {
+                break;
+            };
+            pending.remove(position);
+            index += 1;
+        }
+        groups.push(start..index);
+    }
+    groups
+}
+
+/// The most recent messages whose content fits in `budget` characters,
+/// keeping leading system messages and pinned messages and never splitting a
+/// tool call from its results.
+pub fn window_messages(messages: &[MessageEnum], budget: usize) -> Vec<MessageEnum> {
+    window_messages_by(messages, budget, |message| {
+        message.content().chars().count()
+    })
+}
+
+/// Like [`window_messages`] with a caller-supplied cost per message, e.g. a
+/// token count. Leading system messages and pinned groups are paid for first,
+/// even if that alone exceeds the budget.
+pub fn window_messages_by(
+    messages: &[MessageEnum],
+    budget: usize,
+    cost: impl Fn(&MessageEnum) -> usize,
+) -> Vec<MessageEnum> {
+    let system_end = messages
+        .iter()
+        .take_while(|message| message.message_type() == &MessageType::System)
+        .count();
+    let (system, rest) = messages.split_at(system_end);
+    let groups = message_groups(rest);
+    let pinned = |group: &Range<usize>| rest[group.clone()].iter().any(MessageEnum::is_pinned);
+
+    let fixed: usize = system.iter().map(&cost).sum::<usize>()
+        + groups
+            .iter()
+            .filter(|group| pinned(group))
+            .flat_map(|group| &rest[group.clone()])
+            .map(&cost)
+            .sum::<usize>();
+    let mut remaining = budget.saturating_sub(fixed);
+    let mut start = rest.len();
+    for group in groups.iter().rev() {
+        if pinned(group) {
+            continue;
+        }
+        let group_cost: usize = rest[group.clone()].iter().map(&cost).sum();
+        if group_cost > remaining {
+            break;
+        }
+        remaining -= group_cost;
+        start = group.start;
+    }
+
+    let kept = groups
+        .iter()
+        .filter(|group| group.start >= start || pinned(group))
+        .flat_map(|group| &rest[group.clone()]);
+    system.iter().chain(kept).cloned().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tool_message::ToolStatus;
+    use crate::{AiMessage, HumanMessage, SystemMessage, ToolCall, ToolMessage};
+
+    fn ai_calling(ids: &[&str]) -> MessageEnum {
+        let calls = ids
+            .iter()
+            .map(|id| ToolCall::new(*id, "lookup", serde_json::Value::Null))
+            .collect();
+        AiMessage::new("").with_tool_calls(calls).into()
+    }
+
+    fn result(call_id: &str, content: &str) -> MessageEnum {
+        ToolMessage::new(content, call_id.to_string(), None, ToolStatus::Success).into()
+    }
+
+    #[test]
+    fn test_message_groups() {
+        let messages = vec![
+            HumanMessage::new("q").into(),
+            ai_calling(&["a", "b"]),
+            result("b", "1"),
+            result("a", "2"),
+            result("z", "orphan"),
+            AiMessage::new("done").into(),
+        ];
+
+        assert_eq!(message_groups(&messages), vec![0..1, 1..4, 4..5, 5..6]);
+    }
+
+    #[test]
+    fn test_window_never_splits_tool_group() {
+        let messages = vec![
+            SystemMessage::new("sys").into(),
+            HumanMessage::new("question").into(),
+            ai_calling(&["a"]),
+            result("a", "0123456789"),
+            AiMessage::new("answer").into(),
+        ];
+
+        // 3 (system) + 6 (answer) + 10 (tool result) fits; the human message
+        // does not, and the tool result can't be kept without its call.
+        let window = window_messages(&messages, 20);
+        let types: Vec<&MessageType> = window.iter().map(|m| m.message_type()).collect();
+        assert_eq!(
+            types,
+            vec![
+                &MessageType::System,
+                &MessageType::Ai,
+                &MessageType::Tool,
+                &MessageType::Ai
+            ]
+        );
+
+        let window = window_messages(&messages, 18);
+        assert_eq!(window.len(), 2);
+        assert_eq!(window[1].content(), "answer");
+    }
+
+    #[test]
+    fn test_window_by_message_count() {
+        let messages: Vec<MessageEnum> = ["a", "b", "c"]
+            .into_iter()
+            .map(|content| HumanMessage::new(content).into())
+            .collect();
+
+        let window = window_messages_by(&messages, 2, |_| 1);
+        let contents: Vec<&str> = window.iter().map(|m| m.content()).collect();
+        assert_eq!(contents, vec!["b", "c"]);
+    }
+
+    #[test]
+    fn test_window_keeps_pinned_messages() {
+        let mut preference: MessageEnum = HumanMessage::new("I am vegetarian").into();
+        preference.set_pinned(true);
+        let messages = vec![
+            preference,
+            HumanMessage::new("old").into(),
+            HumanMessage::new("mid").into(),
+            HumanMessage::new("new").into(),
+        ];
+
+        let window = window_messages_by(&messages, 2, |_| 1);
+        let contents: Vec<&str> = window.iter().map(|m| m.content()).collect();
+        assert_eq!(contents, vec!["I am vegetarian", "new"]);
+
+        let window = window_messages_by(&messages, 0, |_| 1);
+        assert_eq!(window.len(), 1);
+        assert!(window[0].is_pinned());
+    }
+}
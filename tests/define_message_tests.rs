@@ -49,19 +49,13 @@ mod tests {
             .insert("key".to_string(), "value".to_string());
         msg.base
             .response_metadata
-            .insert("token_count".to_string(), "42".to_string());
+            .insert("token_count".to_string(), 42);
         msg.base.id = Some("12345".to_string());
         msg.base.name = Some("User".to_string());
 
         assert!(msg.base.example);
-        assert_eq!(
-            msg.base.additional_kwargs.get("key"),
-            Some(&"value".to_string())
-        );
-        assert_eq!(
-            msg.base.response_metadata.get("token_count"),
-            Some(&"42".to_string())
-        );
+        assert_eq!(msg.base.additional_kwargs.get_str("key"), Some("value"));
+        assert_eq!(msg.base.response_metadata.get_i64("token_count"), Some(42));
         assert_eq!(msg.base.id, Some("12345".to_string()));
         assert_eq!(msg.base.name, Some("User".to_string()));
     }
@@ -101,8 +95,8 @@ mod tests {
             .insert("test_key".to_string(), "test_value".to_string());
 
         assert_eq!(
-            msg.base.additional_kwargs.get("test_key"),
-            Some(&"test_value".to_string())
+            msg.base.additional_kwargs.get_str("test_key"),
+            Some("test_value")
         );
     }
 
@@ -123,6 +117,63 @@ mod tests {
         assert_eq!(msg.message_type(), &MessageType::Human);
     }
 
+    #[test]
+    fn test_extra_fields_and_role_field() {
+        define_message!(
+            Chat {
+                role: String,
+                #[serde(skip_serializing_if = "Option::is_none", default)]
+                pub mood: Option<String>,
+            },
+            role = role
+        );
+
+        let message = ChatMessage::builder("critic".to_string(), None)
+            .content("Too long.")
+            .build();
+
+        assert_eq!(message.role(), "critic");
+        assert_eq!(message.message_type(), &MessageType::Chat);
+        assert_eq!(
+            message,
+            ChatMessage::new("Too long.", "critic".to_string(), None)
+        );
+        assert_eq!(
+            serde_json::to_value(&message).unwrap(),
+            serde_json::json!({
+                "role": "critic",
+                "content": "Too long.",
+                "example": false,
+                "message_type": "Chat"
+            })
+        );
+    }
+
+    #[test]
+    fn test_default_fields_and_tool_calls() {
+        define_message!(
+            Ai {},
+            defaults {
+                #[serde(skip_serializing_if = "Vec::is_empty", default)]
+                pub tool_calls: Vec<ToolCall>,
+                #[serde(skip_serializing_if = "Vec::is_empty", default)]
+                pub invalid_tool_calls: Vec<InvalidToolCall>,
+            },
+            tool_calls = tool_calls,
+            invalid_tool_calls = invalid_tool_calls
+        );
+
+        let mut message = AiMessage::new("Looking it up.");
+        assert!(message.tool_calls().is_empty());
+
+        message.tool_calls = vec![ToolCall::new("call_1", "search", serde_json::json!({}))];
+        assert_eq!(message.tool_calls()[0].name, "search");
+        let value = serde_json::to_value(&message).unwrap();
+        assert_eq!(value["tool_calls"][0]["id"], "call_1");
+        assert_eq!(serde_json::from_value::<AiMessage>(value).unwrap(), message);
+        assert!(HumanMessage::new("Hi.").tool_calls().is_empty());
+    }
+
     #[test]
     fn test_fully_qualified_human_message_getters() {
         define_message!(MessageType::Human);
@@ -150,12 +201,12 @@ mod tests {
 
         assert!(human_message.is_example());
         assert_eq!(
-            human_message.additional_kwargs().get("key"),
-            Some(&"value".to_string())
+            human_message.additional_kwargs().get_str("key"),
+            Some("value")
         );
         assert_eq!(
-            human_message.response_metadata().get("metadata_key"),
-            Some(&"metadata_value".to_string())
+            human_message.response_metadata().get_str("metadata_key"),
+            Some("metadata_value")
         );
         assert_eq!(human_message.id(), Some("12345"));
         assert_eq!(human_message.name(), Some("Test User"));
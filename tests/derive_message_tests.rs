@@ -3,12 +3,71 @@ mod tests {
     use derive_base_message::BaseMessage;
     use messageforge::prelude::*;
 
-    #[derive(BaseMessage, Deserialize)]
+    #[derive(BaseMessage, Debug, Clone, PartialEq, Deserialize)]
     pub struct ChatMessage {
         pub role: String,
         pub base: BaseMessageFields,
     }
 
+    #[derive(BaseMessage, Debug, Clone, PartialEq, Serialize, Deserialize)]
+    #[base_message(gen_tests)]
+    pub struct ToolMessage {
+        pub tool_call_id: String,
+        pub attempt: u32,
+        #[serde(flatten)]
+        pub base: BaseMessageFields,
+    }
+
+    #[derive(BaseMessage, Debug, Clone, PartialEq, Serialize, Deserialize)]
+    #[base_message(message_type = "Ai", gen_tests)]
+    pub struct AssistantReply {
+        #[serde(flatten)]
+        pub base: BaseMessageFields,
+    }
+
+    #[derive(BaseMessage, Debug, Clone, PartialEq)]
+    #[base_message(message_type = "Human", tag = "role", rename = "user", gen_tests)]
+    pub struct UserTurn {
+        #[serde(rename = "userId")]
+        pub user_id: String,
+        pub base: BaseMessageFields,
+    }
+
+    #[derive(BaseMessage, Debug, Clone, PartialEq)]
+    #[base_message(message_type = "Ai", tag = "role", rename = "assistant")]
+    pub struct Structured<T: Serialize>
+    where
+        T: PartialEq,
+    {
+        pub payload: T,
+        pub base: BaseMessageFields,
+    }
+
+    #[derive(BaseMessage, Debug, Clone, PartialEq)]
+    #[base_message(message_type = "Ai", tag = "role", rename = "assistant")]
+    pub struct Annotated<T: Serialize> {
+        pub payload: T,
+        #[serde(skip_serializing_if = "Option::is_none", default)]
+        pub note: Option<String>,
+        pub base: BaseMessageFields,
+    }
+
+    // Serializable but not `Clone`.
+    #[derive(Debug, PartialEq, Serialize)]
+    pub struct Score(u32);
+
+    #[derive(BaseMessage, Debug, Clone, PartialEq, Serialize, Deserialize)]
+    #[base_message(message_type = "Tool", gen_tests)]
+    pub struct ToolRetryMessage {
+        pub tool_call_id: String,
+        #[base_message(default = "3")]
+        pub max_attempts: u32,
+        #[base_message(default = "vec![\"retry\".to_string()]")]
+        pub tags: Vec<String>,
+        #[serde(flatten)]
+        pub base: BaseMessageFields,
+    }
+
     #[test]
     fn test_human_message_new_method() {
         let msg = ChatMessage::new("Hello, world!", "Admin".to_string());
@@ -28,4 +87,104 @@ mod tests {
         assert_eq!(msg.content(), "Hello, world!");
         assert_eq!(msg.message_type(), &MessageType::Chat);
     }
+
+    #[test]
+    fn test_field_accessors() {
+        let mut tool = ToolMessage::new("Done", "call_1".to_string(), 1);
+        tool.set_attempt(2);
+        tool.set_tool_call_id("call_2".to_string());
+
+        assert_eq!(tool.attempt(), &2);
+        assert_eq!(tool.tool_call_id(), "call_2");
+
+        let mut chat = ChatMessage::new("Hi", "user".to_string());
+        chat.set_role("admin".to_string());
+        assert_eq!(chat.role(), "admin");
+    }
+
+    #[test]
+    fn test_tagged_serde() {
+        let turn = UserTurn::new("Hi", "u1".to_string());
+
+        let value = serde_json::to_value(&turn).unwrap();
+        assert_eq!(
+            value,
+            serde_json::json!({"role": "user", "userId": "u1", "content": "Hi", "example": false})
+        );
+        let parsed: UserTurn = serde_json::from_value(value).unwrap();
+        assert_eq!(parsed, turn);
+        assert_eq!(parsed.message_type(), &MessageType::Human);
+
+        let wrong = serde_json::json!({"role": "assistant", "userId": "u1", "content": "Hi"});
+        let error = serde_json::from_value::<UserTurn>(wrong).unwrap_err();
+        assert!(error.to_string().contains("expected \"user\""));
+    }
+
+    #[test]
+    fn test_message_type_attribute() {
+        let reply = AssistantReply::new("Done.");
+        assert_eq!(reply.message_type(), &MessageType::Ai);
+        assert_eq!(reply.role(), "ai");
+    }
+
+    #[test]
+    fn test_builder() {
+        let tool = ToolMessage::builder("call_1".to_string(), 3)
+            .content("Sunny")
+            .kwarg("cached", true)
+            .example(true)
+            .build();
+
+        assert_eq!(tool.content(), "Sunny");
+        assert_eq!(tool.tool_call_id(), "call_1");
+        assert_eq!(tool.attempt(), &3);
+        assert_eq!(tool.additional_kwargs().get_bool("cached"), Some(true));
+        assert!(tool.is_example());
+
+        let draft = ToolMessage::builder("call_2".to_string(), 1).content("Rainy");
+        assert_eq!(draft.clone(), draft);
+        assert!(format!("{:?}", draft).starts_with("ToolMessageBuilder"));
+        assert_eq!(draft.clone().build(), draft.build());
+    }
+
+    #[test]
+    fn test_generic_struct() {
+        let reply = Structured::builder(vec![1, 2])
+            .content("Two numbers.")
+            .build();
+        assert_eq!(reply.payload(), &vec![1, 2]);
+        assert_eq!(reply.role(), "ai");
+
+        let value = serde_json::to_value(&reply).unwrap();
+        assert_eq!(value["role"], "assistant");
+        assert_eq!(value["payload"], serde_json::json!([1, 2]));
+        let parsed: Structured<Vec<u32>> = serde_json::from_value(value).unwrap();
+        assert_eq!(parsed, reply);
+    }
+
+    #[test]
+    fn test_tagged_serde_borrows_fields() {
+        let mut reply = Annotated::new("Scored.", Score(7), None);
+
+        let value = serde_json::to_value(&reply).unwrap();
+        assert_eq!(
+            value,
+            serde_json::json!({"role": "assistant", "payload": 7, "content": "Scored.", "example": false})
+        );
+
+        reply.set_note(Some("generous".to_string()));
+        let value = serde_json::to_value(&reply).unwrap();
+        assert_eq!(value["note"], "generous");
+    }
+
+    #[test]
+    fn test_field_defaults() {
+        let message = ToolRetryMessage::new("Failed", "call_1".to_string());
+        assert_eq!(message.tool_call_id(), "call_1");
+        assert_eq!(message.max_attempts(), &3);
+        assert_eq!(message.tags(), &vec!["retry".to_string()]);
+
+        let built = ToolRetryMessage::builder("call_2".to_string()).build();
+        assert_eq!(built.max_attempts(), &3);
+    }
 }
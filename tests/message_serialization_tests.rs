@@ -49,12 +49,12 @@ mod tests {
         assert_eq!(deserialized.content(), "Hello, world!");
         assert!(!deserialized.is_example());
         assert_eq!(
-            deserialized.additional_kwargs().get("key1"),
-            Some(&"value1".to_string())
+            deserialized.additional_kwargs().get_str("key1"),
+            Some("value1")
         );
         assert_eq!(
-            deserialized.response_metadata().get("meta1"),
-            Some(&"metadata1".to_string())
+            deserialized.response_metadata().get_str("meta1"),
+            Some("metadata1")
         );
         assert_eq!(deserialized.id(), Some("12345"));
         assert_eq!(deserialized.name(), Some("John Doe"));
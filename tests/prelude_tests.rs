@@ -0,0 +1,24 @@
+use messageforge::prelude::*;
+
+#[test]
+fn test_prelude_covers_common_surface() {
+    let mut conversation = Conversation::new();
+    conversation.push(SystemMessage::new("You are terse."));
+    conversation.push(HumanMessage::new("Weather?"));
+    conversation.push(AiMessage::new("Sunny."));
+    conversation.push(ToolMessage::new(
+        "72F",
+        "call_1".to_string(),
+        None,
+        ToolStatus::Success,
+    ));
+
+    let roles: Vec<&str> = conversation.iter().map(|message| message.role()).collect();
+    assert_eq!(roles, vec!["system", "human", "ai", "tool"]);
+
+    let message: &MessageEnum = &conversation.messages()[2];
+    assert_eq!(message.message_type(), &MessageType::Ai);
+
+    let chat = ChatMessage::new("Hi", "moderator".to_string());
+    assert_eq!(chat.role(), "moderator");
+}